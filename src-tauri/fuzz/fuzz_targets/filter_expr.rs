@@ -0,0 +1,44 @@
+//! 过滤表达式解析器模糊测试
+//!
+//! 运行：`cargo +nightly fuzz run filter_expr`（在 src-tauri 目录下）
+//!
+//! 性质：
+//! - 任意输入都不会 panic，错误位置可以正常渲染
+//! - 解析成功的表达式格式化后重新解析，得到相同的语法树
+//! - 求值不会 panic
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use port_detection_lib::core::query;
+use port_detection_lib::models::{ConnectionState, PortInfo, ProcessInfo, Protocol};
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(source) = std::str::from_utf8(data) else {
+        return;
+    };
+
+    let expr = match query::parse(source) {
+        Ok(expr) => expr,
+        Err(e) => {
+            let _ = e.render(source);
+            return;
+        }
+    };
+
+    let printed = expr.to_string();
+    match query::parse(&printed) {
+        Ok(reparsed) => assert_eq!(reparsed, expr, "round trip changed AST: {}", printed),
+        Err(e) => panic!("failed to reparse {:?}: {}", printed, e.render(&printed)),
+    }
+
+    let mut port = PortInfo::new(3000, Protocol::TCP, "127.0.0.1".to_string(), 42);
+    port.state = ConnectionState::Listen;
+    port.process = Some(ProcessInfo::new(42, "node".to_string()));
+    let _ = expr.matches(&port);
+
+    let mut port = PortInfo::new(53, Protocol::UDP, "::".to_string(), 7);
+    port.remote_addr = Some("fe80::1%eth0".to_string());
+    port.remote_port = Some(53);
+    let _ = expr.matches(&port);
+});
@@ -0,0 +1,819 @@
+//! Port Detection 命令行客户端
+//!
+//! 通过本地 IPC 访问守护进程
+
+use std::path::PathBuf;
+
+use clap::{Parser, Subcommand};
+use port_detection_lib::core::exporter::{export_ports, ExportFormat};
+use port_detection_lib::core::probe::ProbeConfig;
+use port_detection_lib::core::PortFilter;
+use port_detection_lib::daemon::DaemonClient;
+use port_detection_lib::models::{
+    AppError, AppResult, BaselineStatus, ComplianceReport, ConnectionGroup, ConnectionSummary,
+    DriftEvent, ExposureReport, Finding,
+    ListenerPolicy, PortInfo, Protocol,
+};
+use port_detection_lib::utils::time::now_millis;
+use serde::Serialize;
+
+#[derive(Debug, Parser)]
+#[command(name = "port-detection-cli", version, about = "Port Detection 命令行客户端")]
+struct Cli {
+    /// 守护进程 IPC 地址，默认读取 PORT_DETECTION_SOCKET 环境变量
+    #[arg(long, global = true)]
+    socket: Option<String>,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// 显示守护进程信息
+    Status,
+    /// 列出端口
+    Ports {
+        /// 只显示监听端口
+        #[arg(long)]
+        listen: bool,
+        /// 端口号
+        #[arg(long)]
+        port: Option<u16>,
+        /// 协议（tcp / udp）
+        #[arg(long, value_parser = parse_protocol)]
+        protocol: Option<Protocol>,
+        /// 进程 ID
+        #[arg(long)]
+        pid: Option<u32>,
+        /// 进程名（子串匹配）
+        #[arg(long)]
+        process: Option<String>,
+        /// 只显示可执行文件不属于任何软件包的端口（Linux）
+        #[arg(long)]
+        unpackaged: bool,
+        /// 过滤表达式，如 'port in 3000..4000 and state = listen'，不能与其他过滤选项同时使用
+        #[arg(long, short = 'w', conflicts_with_all = ["listen", "port", "protocol", "pid", "process", "unpackaged"])]
+        filter: Option<String>,
+        /// 输出格式：table、csv、json、ndjson、markdown
+        #[arg(long, default_value = "table")]
+        format: String,
+    },
+    /// 主动探测 TCP 监听端口上的服务，未指定端口时探测全部
+    Probe {
+        ports: Vec<u16>,
+        /// 以 JSON 输出
+        #[arg(long)]
+        json: bool,
+    },
+    /// 探测端口上的 HTTP(S) 服务
+    Http {
+        port: u16,
+        /// 以 JSON 输出
+        #[arg(long)]
+        json: bool,
+    },
+    /// 检查端口上的 TLS 证书
+    Tls {
+        port: u16,
+        /// 以 JSON 输出（包含完整证书链）
+        #[arg(long)]
+        json: bool,
+    },
+    /// 按进程显示监听端口的网络暴露面和风险
+    Exposure {
+        /// 以 JSON 输出
+        #[arg(long)]
+        json: bool,
+    },
+    /// 判断监听端口的入站连接是否被防火墙放行（仅 Linux）
+    Firewall {
+        /// 以 JSON 输出
+        #[arg(long)]
+        json: bool,
+    },
+    /// 按 TOML 策略文件检查监听端口，存在违规时退出码为 3
+    Check {
+        policy: PathBuf,
+        /// 以 JSON 输出
+        #[arg(long)]
+        json: bool,
+    },
+    /// 检测可疑行为（临时目录程序监听、shell 持有套接字、连接扇出等）
+    Detect {
+        /// 以 JSON 输出
+        #[arg(long)]
+        json: bool,
+    },
+    /// 按远程地址、远程端口、网段和进程汇总已建立的连接
+    Connections {
+        /// 每个维度显示的分组数
+        #[arg(long, default_value_t = 10)]
+        limit: usize,
+        /// 以 JSON 输出
+        #[arg(long)]
+        json: bool,
+    },
+    /// 显示进程信息
+    Process {
+        pid: u32,
+        /// 检查可执行文件（大小、修改时间、SHA-256 和所属软件包）
+        #[arg(long)]
+        exe: bool,
+    },
+    /// 终止进程
+    Kill {
+        pid: u32,
+        /// 进程创建时间（毫秒），用于防止 PID 复用
+        #[arg(long)]
+        create_time: Option<u64>,
+    },
+    /// 以 NDJSON 格式持续输出监控事件
+    Events,
+    /// 重新发送基线快照
+    Resync,
+    /// 开始跟踪端口
+    Watch {
+        port: u16,
+        #[arg(value_parser = parse_protocol)]
+        protocol: Protocol,
+    },
+    /// 停止跟踪端口
+    Unwatch {
+        port: u16,
+        #[arg(value_parser = parse_protocol)]
+        protocol: Protocol,
+    },
+    /// 显示端口时间线，未指定端口时显示所有跟踪中的端口
+    Timeline {
+        port: Option<u16>,
+        #[arg(value_parser = parse_protocol)]
+        protocol: Option<Protocol>,
+    },
+    /// 行为基线与偏离事件
+    Baseline {
+        #[command(subcommand)]
+        action: BaselineAction,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum BaselineAction {
+    /// 开始学习基线，学习期间不检测偏离
+    Train {
+        /// 学习时长，如 90s、30m、24h、7d
+        #[arg(long, default_value = "1h", value_parser = parse_duration)]
+        duration: u64,
+    },
+    /// 显示基线状态
+    Status,
+    /// 以 JSON 输出当前基线
+    Show,
+    /// 列出待处理的偏离事件
+    Drifts {
+        /// 以 JSON 输出
+        #[arg(long)]
+        json: bool,
+    },
+    /// 将偏离并入基线
+    Approve {
+        #[arg(required_unless_present = "all")]
+        ids: Vec<u64>,
+        /// 处理全部待处理的偏离
+        #[arg(long, conflicts_with = "ids")]
+        all: bool,
+    },
+    /// 忽略偏离
+    Dismiss {
+        #[arg(required_unless_present = "all")]
+        ids: Vec<u64>,
+        /// 处理全部待处理的偏离
+        #[arg(long, conflicts_with = "ids")]
+        all: bool,
+    },
+}
+
+fn parse_protocol(value: &str) -> Result<Protocol, String> {
+    Protocol::from_name(value).ok_or_else(|| format!("未知协议: {}", value))
+}
+
+/// 解析时长（毫秒），单位为 s、m、h、d，省略单位时为秒
+fn parse_duration(value: &str) -> Result<u64, String> {
+    let value = value.trim();
+    let (number, unit) = match value.find(|c: char| !c.is_ascii_digit()) {
+        Some(i) => value.split_at(i),
+        None => (value, "s"),
+    };
+    let secs = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        _ => return Err(format!("无效的时长: {}", value)),
+    };
+    let number: u64 = number.parse().map_err(|_| format!("无效的时长: {}", value))?;
+    match number.saturating_mul(secs * 1000) {
+        0 => Err("时长必须大于 0".to_string()),
+        ms => Ok(ms),
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    let cli = Cli::parse();
+
+    if let Err(e) = run(cli).await {
+        eprintln!("port-detection-cli: {}", e);
+        std::process::exit(1);
+    }
+}
+
+async fn run(cli: Cli) -> AppResult<()> {
+    let mut client = match &cli.socket {
+        Some(endpoint) => DaemonClient::connect(endpoint).await,
+        None => DaemonClient::connect_default().await,
+    }
+    .map_err(|e| AppError::Ipc(format!("无法连接守护进程，请确认 port-detection-daemon 正在运行（{}）", e)))?;
+
+    match cli.command {
+        Command::Status => print_json(client.info())?,
+        Command::Ports {
+            listen,
+            port,
+            protocol,
+            pid,
+            process,
+            unpackaged,
+            filter,
+            format,
+        } => {
+            let ports = match filter {
+                Some(expr) => client.query_ports(&expr).await?,
+                None => {
+                    let filter = PortFilter {
+                        listen_only: Some(listen),
+                        port,
+                        protocol,
+                        pid,
+                        process_name: process,
+                        unpackaged: Some(unpackaged),
+                    };
+                    client.scan_ports(filter).await?
+                }
+            };
+
+            if format == "table" {
+                print_table(&ports);
+            } else {
+                let format = ExportFormat::from_name(&format)
+                    .ok_or_else(|| AppError::InvalidArgument(format!("未知格式: {}", format)))?;
+                print!("{}", export_ports(&ports, format, &[])?);
+            }
+        }
+        Command::Probe { ports, json } => {
+            let only = (!ports.is_empty()).then_some(ports);
+            let ports = client.probe_services(only, ProbeConfig::default()).await?;
+            if json {
+                print_json(&ports)?;
+            } else {
+                print_probe_table(&ports);
+            }
+        }
+        Command::Http { port, json } => {
+            let ports = client.probe_http(port).await?;
+            if json {
+                print_json(&ports)?;
+            } else {
+                print_http_table(&ports);
+            }
+        }
+        Command::Tls { port, json } => {
+            let ports = client.inspect_tls(port).await?;
+            if json {
+                print_json(&ports)?;
+            } else {
+                print_tls_table(&ports);
+            }
+        }
+        Command::Exposure { json } => {
+            let report = client.get_exposure_report().await?;
+            if json {
+                print_json(&report)?;
+            } else {
+                print_exposure(&report);
+            }
+        }
+        Command::Firewall { json } => {
+            let ports = client.get_firewall_status().await?;
+            if json {
+                print_json(&ports)?;
+            } else {
+                print_firewall_table(&ports);
+            }
+        }
+        Command::Check { policy, json } => {
+            let policy = ListenerPolicy::load(&policy)?;
+            let report = client.check_compliance(policy).await?;
+            if json {
+                print_json(&report)?;
+            } else {
+                print_compliance(&report);
+            }
+            if !report.is_compliant() {
+                std::process::exit(3);
+            }
+        }
+        Command::Detect { json } => {
+            let findings = client.detect_suspicious().await?;
+            if json {
+                print_json(&findings)?;
+            } else {
+                print_findings(&findings);
+            }
+        }
+        Command::Connections { limit, json } => {
+            let summary = client.get_connection_summary(Some(limit)).await?;
+            if json {
+                print_json(&summary)?;
+            } else {
+                print_connection_summary(&summary);
+            }
+        }
+        Command::Process { pid, exe } => {
+            let info = if exe {
+                client.get_process_executable(pid).await?
+            } else {
+                client.get_process_info(pid).await?
+            };
+            match info {
+                Some(info) => print_json(&info)?,
+                None => return Err(AppError::ProcessNotFound(pid)),
+            }
+        }
+        Command::Kill { pid, create_time } => {
+            client.kill_process(pid, create_time).await?;
+            println!("已终止进程 {}", pid);
+        }
+        Command::Events => {
+            let mut events = client.subscribe().await?;
+            while let Some(event) = events.next().await? {
+                let line = serde_json::to_string(&event).map_err(|e| AppError::Internal(e.to_string()))?;
+                println!("{}", line);
+            }
+        }
+        Command::Resync => client.resync().await?,
+        Command::Watch { port, protocol } => print_json(&client.watch_port(port, protocol).await?)?,
+        Command::Unwatch { port, protocol } => {
+            if !client.unwatch_port(port, protocol).await? {
+                return Err(AppError::InvalidArgument(format!("端口未在跟踪中: {}/{:?}", port, protocol)));
+            }
+        }
+        Command::Timeline { port, protocol } => match port {
+            Some(port) => {
+                let protocol = protocol.unwrap_or(Protocol::TCP);
+                let timeline = client
+                    .get_port_timeline(port, protocol)
+                    .await?
+                    .ok_or_else(|| AppError::InvalidArgument(format!("端口未在跟踪中: {}/{:?}", port, protocol)))?;
+                print_json(&timeline)?;
+            }
+            None => print_json(&client.list_watches().await?)?,
+        },
+        Command::Baseline { action } => match action {
+            BaselineAction::Train { duration } => {
+                print_baseline_status(&client.start_baseline_training(duration).await?)
+            }
+            BaselineAction::Status => print_baseline_status(&client.get_baseline_status().await?),
+            BaselineAction::Show => match client.get_baseline().await? {
+                Some(baseline) => print_json(&baseline)?,
+                None => return Err(AppError::InvalidArgument("尚未建立基线".to_string())),
+            },
+            BaselineAction::Drifts { json } => {
+                let drifts = client.list_drifts().await?;
+                if json {
+                    print_json(&drifts)?;
+                } else {
+                    print_drift_table(&drifts);
+                }
+            }
+            BaselineAction::Approve { ids, all } => {
+                let ids = if all { all_drift_ids(&mut client).await? } else { ids };
+                let count = client.approve_drifts(ids).await?;
+                println!("已将 {} 个偏离并入基线", count);
+            }
+            BaselineAction::Dismiss { ids, all } => {
+                let ids = if all { all_drift_ids(&mut client).await? } else { ids };
+                let count = client.dismiss_drifts(ids).await?;
+                println!("已忽略 {} 个偏离", count);
+            }
+        },
+    }
+
+    Ok(())
+}
+
+fn print_json<T: Serialize>(value: &T) -> AppResult<()> {
+    let json = serde_json::to_string_pretty(value).map_err(|e| AppError::Internal(e.to_string()))?;
+    println!("{}", json);
+    Ok(())
+}
+
+/// 以对齐的文本表格输出端口
+fn print_table(ports: &[PortInfo]) {
+    let header = ["PROTO", "LOCAL", "REMOTE", "STATE", "SERVICE", "PID", "PROCESS"];
+    let rows: Vec<[String; 7]> = ports
+        .iter()
+        .map(|p| {
+            let remote = match (&p.remote_addr, p.remote_port, &p.remote_hostname) {
+                (Some(addr), Some(port), Some(host)) => format!("{}:{} ({})", addr, port, host),
+                (Some(addr), Some(port), None) => format!("{}:{}", addr, port),
+                _ => "-".to_string(),
+            };
+            [
+                format!("{:?}", p.protocol),
+                format!("{}:{}", p.local_addr, p.port),
+                remote,
+                format!("{:?}", p.state),
+                p.service_name.clone().unwrap_or_else(|| "-".to_string()),
+                p.pid.to_string(),
+                p.process
+                    .as_ref()
+                    .map(|proc| proc.name.clone())
+                    .unwrap_or_else(|| "-".to_string()),
+            ]
+        })
+        .collect();
+
+    print_aligned(&header, &rows);
+}
+
+/// 以对齐的文本表格输出探测结果
+fn print_probe_table(ports: &[PortInfo]) {
+    let header = ["LOCAL", "PROTOCOL", "PRODUCT", "BANNER", "PID", "PROCESS"];
+    let rows: Vec<[String; 6]> = ports
+        .iter()
+        .map(|p| {
+            let fingerprint = p.fingerprint.as_ref();
+            [
+                format!("{}:{}", p.local_addr, p.port),
+                fingerprint
+                    .map(|f| f.protocol.name().to_string())
+                    .unwrap_or_else(|| "-".to_string()),
+                fingerprint
+                    .and_then(|f| f.product.clone())
+                    .unwrap_or_else(|| "-".to_string()),
+                fingerprint
+                    .and_then(|f| f.banner.clone())
+                    .unwrap_or_else(|| "-".to_string()),
+                p.pid.to_string(),
+                p.process
+                    .as_ref()
+                    .map(|proc| proc.name.clone())
+                    .unwrap_or_else(|| "-".to_string()),
+            ]
+        })
+        .collect();
+
+    print_aligned(&header, &rows);
+}
+
+/// 以对齐的文本表格输出 HTTP 探测结果
+fn print_http_table(ports: &[PortInfo]) {
+    let header = ["LOCAL", "URL", "STATUS", "SERVER", "TITLE", "LATENCY", "PID"];
+    let rows: Vec<[String; 7]> = ports
+        .iter()
+        .map(|p| {
+            let local = format!("{}:{}", p.local_addr, p.port);
+            match &p.http {
+                Some(http) => [
+                    local,
+                    http.url.clone(),
+                    http.status.to_string(),
+                    http.server.clone().unwrap_or_else(|| "-".to_string()),
+                    http.title.clone().unwrap_or_else(|| "-".to_string()),
+                    format!("{}ms", http.latency_ms),
+                    p.pid.to_string(),
+                ],
+                None => [
+                    local,
+                    "-".to_string(),
+                    "-".to_string(),
+                    "-".to_string(),
+                    "-".to_string(),
+                    "-".to_string(),
+                    p.pid.to_string(),
+                ],
+            }
+        })
+        .collect();
+
+    print_aligned(&header, &rows);
+}
+
+/// 以对齐的文本表格输出 TLS 检查结果
+fn print_tls_table(ports: &[PortInfo]) {
+    let header = ["LOCAL", "VERSION", "CIPHER", "SUBJECT", "EXPIRES", "STATUS", "PID"];
+    let rows: Vec<[String; 7]> = ports
+        .iter()
+        .map(|p| {
+            let local = format!("{}:{}", p.local_addr, p.port);
+            match &p.tls {
+                Some(tls) => [
+                    local,
+                    tls.protocol_version.clone(),
+                    tls.cipher_suite.clone(),
+                    tls.leaf()
+                        .map(|cert| cert.subject.clone())
+                        .unwrap_or_else(|| "-".to_string()),
+                    tls.expires_in_days
+                        .map(|days| format!("{}d", days))
+                        .unwrap_or_else(|| "-".to_string()),
+                    tls.status.name().to_string(),
+                    p.pid.to_string(),
+                ],
+                None => [
+                    local,
+                    "-".to_string(),
+                    "-".to_string(),
+                    "-".to_string(),
+                    "-".to_string(),
+                    "-".to_string(),
+                    p.pid.to_string(),
+                ],
+            }
+        })
+        .collect();
+
+    print_aligned(&header, &rows);
+}
+
+/// 以对齐的文本表格输出防火墙判断结果，回环监听端口显示为 `-`
+fn print_firewall_table(ports: &[PortInfo]) {
+    let header = ["PROTO", "LOCAL", "VERDICT", "CHAIN", "RULE", "PID", "PROCESS"];
+    let rows: Vec<[String; 7]> = ports
+        .iter()
+        .map(|p| {
+            let (verdict, chain, rule) = match &p.firewall {
+                Some(status) => match &status.rule {
+                    Some(rule) => (status.verdict.name(), rule.chain.clone(), rule.rule.clone()),
+                    None => (status.verdict.name(), "-".to_string(), "-".to_string()),
+                },
+                None => ("-", "-".to_string(), "-".to_string()),
+            };
+            [
+                format!("{:?}", p.protocol),
+                format!("{}:{}", p.local_addr, p.port),
+                verdict.to_string(),
+                chain,
+                rule,
+                p.pid.to_string(),
+                p.process
+                    .as_ref()
+                    .map(|proc| proc.name.clone())
+                    .unwrap_or_else(|| "-".to_string()),
+            ]
+        })
+        .collect();
+
+    print_aligned(&header, &rows);
+}
+
+/// 以对齐的文本表格输出违规项
+fn print_compliance(report: &ComplianceReport) {
+    if report.is_compliant() {
+        println!("已检查 {} 个监听端口，未发现违规", report.checked);
+        return;
+    }
+
+    let header = ["KIND", "RULE", "LOCAL", "PID", "PROCESS", "MESSAGE"];
+    let rows: Vec<[String; 6]> = report
+        .violations
+        .iter()
+        .map(|v| {
+            let local = match (&v.local_addr, v.protocol) {
+                (Some(addr), Some(protocol)) => format!("{:?} {}:{}", protocol, addr, v.port),
+                (None, Some(protocol)) => format!("{:?} {}", protocol, v.port),
+                _ => v.port.to_string(),
+            };
+            [
+                v.kind.name().to_string(),
+                v.rule.clone().unwrap_or_else(|| "-".to_string()),
+                local,
+                v.pid.map(|pid| pid.to_string()).unwrap_or_else(|| "-".to_string()),
+                v.process_name.clone().unwrap_or_else(|| "-".to_string()),
+                v.message.clone(),
+            ]
+        })
+        .collect();
+
+    print_aligned(&header, &rows);
+    println!();
+    println!(
+        "已检查 {} 个监听端口，发现 {} 项违规",
+        report.checked,
+        report.violations.len()
+    );
+}
+
+/// 逐条输出检测发现及其依据
+fn print_findings(findings: &[Finding]) {
+    if findings.is_empty() {
+        println!("未发现可疑行为");
+        return;
+    }
+
+    for (i, finding) in findings.iter().enumerate() {
+        if i > 0 {
+            println!();
+        }
+        println!(
+            "[{}] {}  {} (PID {})",
+            finding.severity.name(),
+            finding.detector,
+            finding.process.as_deref().unwrap_or("-"),
+            finding.pid
+        );
+        println!("  {}", finding.message);
+        for evidence in &finding.evidence {
+            println!("    - {}", evidence);
+        }
+    }
+}
+
+/// 按维度输出连接汇总
+fn print_connection_summary(summary: &ConnectionSummary) {
+    match summary.tracking_since {
+        Some(since) => println!(
+            "统计时长: {}，累计 {} 个连接，当前 {} 个",
+            elapsed(since, summary.generated_at),
+            summary.total,
+            summary.active
+        ),
+        None => println!("监控未运行，仅汇总当前 {} 个连接", summary.active),
+    }
+
+    let sections = [
+        ("远程地址", &summary.by_host),
+        ("远程端口", &summary.by_port),
+        ("网段", &summary.by_subnet),
+        ("进程", &summary.by_process),
+    ];
+    for (title, groups) in sections {
+        println!();
+        println!("{}:", title);
+        print_connection_groups(groups, summary.generated_at);
+    }
+}
+
+fn print_connection_groups(groups: &[ConnectionGroup], now: u64) {
+    let header = ["KEY", "TOTAL", "ACTIVE", "FIRST", "LAST", "PROCESSES"];
+    let rows: Vec<[String; 6]> = groups
+        .iter()
+        .map(|g| {
+            [
+                g.key.clone(),
+                g.connections.to_string(),
+                g.active.to_string(),
+                format!("{} ago", elapsed(g.first_seen, now)),
+                if g.active > 0 {
+                    "now".to_string()
+                } else {
+                    format!("{} ago", elapsed(g.last_seen, now))
+                },
+                if g.processes.is_empty() {
+                    "-".to_string()
+                } else {
+                    g.processes.join(",")
+                },
+            ]
+        })
+        .collect();
+
+    print_aligned(&header, &rows);
+}
+
+/// 两个时间戳之间的时长，如 `42s`、`5m`、`3h`
+fn elapsed(from: u64, to: u64) -> String {
+    let secs = to.saturating_sub(from) / 1000;
+    match secs {
+        0..=119 => format!("{}s", secs),
+        120..=7199 => format!("{}m", secs / 60),
+        _ => format!("{}h", secs / 3600),
+    }
+}
+
+/// 输出基线状态
+fn print_baseline_status(status: &BaselineStatus) {
+    println!("状态: {}", status.mode.name());
+    if let Some(ends_at) = status.training_ends_at {
+        let remaining = ends_at.saturating_sub(now_millis()) / 1000;
+        println!("学习剩余: {}m{}s", remaining / 60, remaining % 60);
+    }
+    if status.trained_until.is_some() {
+        println!(
+            "基线: {} 个监听端口，{} 个有出站连接的进程",
+            status.listeners, status.processes
+        );
+    }
+    println!("待处理偏离: {}", status.pending_drifts);
+}
+
+/// 以对齐的文本表格输出偏离事件
+fn print_drift_table(drifts: &[DriftEvent]) {
+    let header = ["ID", "KIND", "CONFIDENCE", "TARGET", "PID", "PROCESS", "SEEN", "MESSAGE"];
+    let rows: Vec<[String; 8]> = drifts
+        .iter()
+        .map(|d| {
+            [
+                d.id.to_string(),
+                d.kind.name().to_string(),
+                format!("{:.2}", d.confidence),
+                format!("{:?} {}:{}", d.protocol, d.addr, d.port),
+                d.pid.to_string(),
+                d.process.clone().unwrap_or_else(|| "-".to_string()),
+                d.occurrences.to_string(),
+                d.message.clone(),
+            ]
+        })
+        .collect();
+
+    print_aligned(&header, &rows);
+}
+
+/// 全部待处理偏离的 ID
+async fn all_drift_ids(client: &mut DaemonClient) -> AppResult<Vec<u64>> {
+    Ok(client.list_drifts().await?.iter().map(|d| d.id).collect())
+}
+
+/// 按进程分组输出暴露面报告
+fn print_exposure(report: &ExposureReport) {
+    let interfaces: Vec<String> = report
+        .interfaces
+        .iter()
+        .map(|iface| format!("{} {}/{}", iface.name, iface.addr, iface.prefix_len))
+        .collect();
+    if interfaces.is_empty() {
+        println!("接口: -");
+    } else {
+        println!("接口: {}", interfaces.join(", "));
+    }
+    let summary = &report.summary;
+    println!(
+        "监听端口: loopback {}  link_local {}  private_lan {}  public {}",
+        summary.loopback, summary.link_local, summary.private_lan, summary.public
+    );
+
+    for process in &report.processes {
+        println!();
+        println!(
+            "[{}] {} (PID {})  {}",
+            process.risk.name(),
+            process.process_name.as_deref().unwrap_or("-"),
+            process.pid,
+            process.exposure.name()
+        );
+        for service in &process.services {
+            let mut line = format!(
+                "  {:?} {}:{}  {}  {}",
+                service.protocol,
+                service.local_addr,
+                service.port,
+                service.service_name.as_deref().unwrap_or("-"),
+                service.exposure.name()
+            );
+            if !service.reachable_via.is_empty() {
+                line.push_str(&format!("  via {}", service.reachable_via.join(", ")));
+            }
+            println!("{}", line);
+            for risk in &service.risks {
+                println!("    - [{}] {}", risk.level.name(), risk.message);
+            }
+        }
+    }
+}
+
+fn print_aligned<const N: usize>(header: &[&str; N], rows: &[[String; N]]) {
+    let mut widths = header.map(str::len);
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let format_row = |cells: &[&str]| {
+        cells
+            .iter()
+            .zip(widths)
+            .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+            .collect::<Vec<_>>()
+            .join("  ")
+            .trim_end()
+            .to_string()
+    };
+
+    println!("{}", format_row(header));
+    for row in rows {
+        let cells: Vec<&str> = row.iter().map(String::as_str).collect();
+        println!("{}", format_row(&cells));
+    }
+}
@@ -0,0 +1,134 @@
+//! Port Detection 守护进程
+//!
+//! 以 root / 管理员身份常驻运行，通过本地 IPC 为 GUI 和 CLI 提供扫描、监控和历史记录
+
+use std::path::PathBuf;
+
+use clap::Parser;
+use port_detection_lib::core::monitor::MonitorConfig;
+use port_detection_lib::core::resolver::ResolverConfig;
+use port_detection_lib::daemon::{self, DaemonConfig, ListenOptions};
+use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+
+#[derive(Debug, Parser)]
+#[command(name = "port-detection-daemon", version, about = "Port Detection 守护进程")]
+struct Args {
+    /// IPC 地址（Unix 套接字路径或 Windows 命名管道名）
+    #[arg(long)]
+    socket: Option<String>,
+
+    /// 套接字文件权限（八进制，仅 Unix）
+    #[arg(long, default_value = "660", value_parser = parse_mode)]
+    socket_mode: u32,
+
+    /// 套接字文件所属组，该组用户可以连接（仅 Linux）
+    #[arg(long)]
+    socket_group: Option<String>,
+
+    /// 管理组：跟踪端口、保存快照、修改服务名和基线等请求只允许该组用户调用，
+    /// 不指定时所有能连接的用户都可以调用（仅 Linux）
+    #[arg(long)]
+    admin_group: Option<String>,
+
+    /// 数据目录（快照等）
+    #[arg(long)]
+    data_dir: Option<PathBuf>,
+
+    /// 采样间隔（毫秒）
+    #[arg(long, default_value_t = 2000)]
+    interval_ms: u64,
+
+    /// 只监控监听端口
+    #[arg(long)]
+    listen_only: bool,
+
+    /// 禁用平台事件触发源，仅定时采样
+    #[arg(long)]
+    no_event_driven: bool,
+
+    /// 不对远程地址做反向 DNS 查询（仍使用 hosts 文件），适用于隔离网络
+    #[arg(long)]
+    no_reverse_dns: bool,
+}
+
+fn parse_mode(value: &str) -> Result<u32, String> {
+    u32::from_str_radix(value.trim_start_matches("0o"), 8).map_err(|e| e.to_string())
+}
+
+/// 默认数据目录
+fn default_data_dir() -> PathBuf {
+    #[cfg(unix)]
+    {
+        PathBuf::from("/var/lib/port-detection")
+    }
+
+    #[cfg(windows)]
+    {
+        std::env::var_os("ProgramData")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from(r"C:\ProgramData"))
+            .join("PortDetection")
+    }
+}
+
+/// 等待退出信号（Ctrl+C，Unix 上还包括 SIGTERM）
+async fn shutdown_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        match signal(SignalKind::terminate()) {
+            Ok(mut term) => {
+                tokio::select! {
+                    _ = tokio::signal::ctrl_c() => {}
+                    _ = term.recv() => {}
+                }
+            }
+            Err(_) => {
+                let _ = tokio::signal::ctrl_c().await;
+            }
+        }
+    }
+
+    #[cfg(not(unix))]
+    {
+        let _ = tokio::signal::ctrl_c().await;
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    tracing_subscriber::registry()
+        .with(tracing_subscriber::fmt::layer())
+        .with(tracing_subscriber::EnvFilter::from_default_env())
+        .init();
+
+    let args = Args::parse();
+
+    let config = DaemonConfig {
+        endpoint: args.socket.unwrap_or_else(daemon::default_endpoint),
+        listen: ListenOptions {
+            mode: args.socket_mode,
+            group: args.socket_group,
+            admin_group: args.admin_group,
+        },
+        monitor: MonitorConfig {
+            interval_ms: args.interval_ms,
+            listen_only: args.listen_only,
+            event_driven: !args.no_event_driven,
+            ..MonitorConfig::default()
+        },
+        data_dir: args.data_dir.unwrap_or_else(default_data_dir),
+        resolver: ResolverConfig {
+            reverse_dns: !args.no_reverse_dns,
+            ..ResolverConfig::default()
+        },
+    };
+
+    tracing::info!("Starting Port Detection daemon");
+
+    if let Err(e) = daemon::run(config, shutdown_signal()).await {
+        eprintln!("port-detection-daemon: {}", e);
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,31 @@
+//! 连接汇总命令
+
+use crate::core::analytics::{connection_summary, DEFAULT_SUMMARY_LIMIT};
+use crate::core::PortScanner;
+use crate::models::ConnectionSummary;
+
+use super::daemon::daemon_client;
+use super::monitor::MonitorState;
+
+/// 按远程地址、远程端口、网段和进程汇总已建立的连接，每个维度默认返回 20 个分组
+///
+/// 累计次数和首次、最后出现时间来自监控事件，监控未运行时只汇总当前连接
+#[tauri::command]
+pub async fn get_connection_summary(
+    limit: Option<usize>,
+    state: tauri::State<'_, MonitorState>,
+) -> Result<ConnectionSummary, String> {
+    if let Some(mut client) = daemon_client().await {
+        return client
+            .get_connection_summary(limit)
+            .await
+            .map_err(|e| e.to_string());
+    }
+
+    connection_summary(
+        &PortScanner::new(),
+        &state.connections,
+        limit.unwrap_or(DEFAULT_SUMMARY_LIMIT),
+    )
+    .map_err(|e| e.to_string())
+}
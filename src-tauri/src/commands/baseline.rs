@@ -0,0 +1,121 @@
+//! 行为基线命令
+//!
+//! 基线由监控事件驱动，未连接守护进程时需要先启动本进程内的监控
+
+use tauri::{AppHandle, Manager};
+
+use crate::core::baseline::DEFAULT_TRAINING_MS;
+use crate::models::{Baseline, BaselineStatus, DriftEvent};
+use crate::utils::time::now_millis;
+
+use super::daemon::daemon_client;
+use super::monitor::MonitorState;
+
+/// 启动时加载应用数据目录下的基线
+pub(crate) fn load_baseline(app: &AppHandle) -> Result<(), String> {
+    let dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    app.state::<MonitorState>()
+        .baseline
+        .lock()
+        .map_err(|e| e.to_string())?
+        .load(&dir.join("baseline.json"))
+        .map_err(|e| e.to_string())
+}
+
+/// 开始学习行为基线，未指定时长时学习 1 小时
+#[tauri::command]
+pub async fn start_baseline_training(
+    duration_secs: Option<u64>,
+    state: tauri::State<'_, MonitorState>,
+) -> Result<BaselineStatus, String> {
+    let duration_ms = duration_secs
+        .map(|secs| secs.saturating_mul(1000))
+        .unwrap_or(DEFAULT_TRAINING_MS);
+
+    if let Some(mut client) = daemon_client().await {
+        return client
+            .start_baseline_training(duration_ms)
+            .await
+            .map_err(|e| e.to_string());
+    }
+
+    state
+        .baseline
+        .lock()
+        .unwrap()
+        .start_training(duration_ms, now_millis())
+        .map_err(|e| e.to_string())
+}
+
+/// 获取基线状态
+#[tauri::command]
+pub async fn get_baseline_status(
+    state: tauri::State<'_, MonitorState>,
+) -> Result<BaselineStatus, String> {
+    if let Some(mut client) = daemon_client().await {
+        return client
+            .get_baseline_status()
+            .await
+            .map_err(|e| e.to_string());
+    }
+
+    Ok(state.baseline.lock().unwrap().status(now_millis()))
+}
+
+/// 获取当前基线，尚未建立时为 None
+#[tauri::command]
+pub async fn get_baseline(
+    state: tauri::State<'_, MonitorState>,
+) -> Result<Option<Baseline>, String> {
+    if let Some(mut client) = daemon_client().await {
+        return client.get_baseline().await.map_err(|e| e.to_string());
+    }
+
+    Ok(state.baseline.lock().unwrap().baseline().cloned())
+}
+
+/// 列出待处理的偏离事件
+#[tauri::command]
+pub async fn list_drifts(state: tauri::State<'_, MonitorState>) -> Result<Vec<DriftEvent>, String> {
+    if let Some(mut client) = daemon_client().await {
+        return client.list_drifts().await.map_err(|e| e.to_string());
+    }
+
+    Ok(state.baseline.lock().unwrap().drifts().to_vec())
+}
+
+/// 将偏离并入基线，返回处理的数量
+#[tauri::command]
+pub async fn approve_drifts(
+    ids: Vec<u64>,
+    state: tauri::State<'_, MonitorState>,
+) -> Result<usize, String> {
+    if let Some(mut client) = daemon_client().await {
+        return client.approve_drifts(ids).await.map_err(|e| e.to_string());
+    }
+
+    state
+        .baseline
+        .lock()
+        .unwrap()
+        .approve(&ids)
+        .map_err(|e| e.to_string())
+}
+
+/// 忽略偏离，返回处理的数量
+#[tauri::command]
+pub async fn dismiss_drifts(
+    ids: Vec<u64>,
+    state: tauri::State<'_, MonitorState>,
+) -> Result<usize, String> {
+    if let Some(mut client) = daemon_client().await {
+        return client.dismiss_drifts(ids).await.map_err(|e| e.to_string());
+    }
+
+    state
+        .baseline
+        .lock()
+        .unwrap()
+        .dismiss(&ids)
+        .map_err(|e| e.to_string())
+}
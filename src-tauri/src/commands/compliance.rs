@@ -0,0 +1,24 @@
+//! 监听策略合规检查命令
+
+use std::path::Path;
+
+use crate::core::compliance::scan_compliance;
+use crate::core::PortScanner;
+use crate::models::{ComplianceReport, ListenerPolicy};
+
+use super::daemon::daemon_client;
+
+/// 读取 TOML 策略文件并检查当前监听端口
+#[tauri::command]
+pub async fn check_compliance(path: String) -> Result<ComplianceReport, String> {
+    let policy = ListenerPolicy::load(Path::new(&path)).map_err(|e| e.to_string())?;
+
+    if let Some(mut client) = daemon_client().await {
+        return client
+            .check_compliance(policy)
+            .await
+            .map_err(|e| e.to_string());
+    }
+
+    scan_compliance(&PortScanner::new(), &policy).map_err(|e| e.to_string())
+}
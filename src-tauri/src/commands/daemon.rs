@@ -0,0 +1,22 @@
+//! 守护进程命令
+//!
+//! 守护进程运行时，端口、进程、监控和跟踪命令转发给守护进程处理，否则在本进程内执行
+
+use crate::daemon::{DaemonClient, DaemonInfo};
+
+/// 连接守护进程，未运行或无法连接时返回 None
+pub(crate) async fn daemon_client() -> Option<DaemonClient> {
+    match DaemonClient::connect_default().await {
+        Ok(client) => Some(client),
+        Err(e) => {
+            tracing::debug!("Daemon unavailable, using in-process backend: {}", e);
+            None
+        }
+    }
+}
+
+/// 获取守护进程信息，未运行时返回 None
+#[tauri::command]
+pub async fn get_daemon_info() -> Result<Option<DaemonInfo>, String> {
+    Ok(daemon_client().await.map(|client| client.info().clone()))
+}
@@ -0,0 +1,20 @@
+//! 可疑行为检测命令
+
+use crate::core::detection::detect_suspicious;
+use crate::core::PortScanner;
+use crate::models::Finding;
+
+use super::daemon::daemon_client;
+use super::monitor::MonitorState;
+
+/// 运行可疑行为检测，连接扇出检测依赖监控期间积累的连接记录
+#[tauri::command]
+pub async fn detect_suspicious_activity(
+    state: tauri::State<'_, MonitorState>,
+) -> Result<Vec<Finding>, String> {
+    if let Some(mut client) = daemon_client().await {
+        return client.detect_suspicious().await.map_err(|e| e.to_string());
+    }
+
+    detect_suspicious(&PortScanner::new(), &state.history).map_err(|e| e.to_string())
+}
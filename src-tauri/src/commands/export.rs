@@ -0,0 +1,35 @@
+//! 导出命令
+
+use crate::core::exporter::{export_ports as render_ports, ExportColumn, ExportFormat};
+use crate::core::{PortFilter, PortScanner};
+use crate::models::AppError;
+
+use super::daemon::daemon_client;
+
+/// 扫描端口并导出到文件，返回导出的条目数
+#[tauri::command]
+pub async fn export_ports(
+    format: String,
+    filter: Option<PortFilter>,
+    path: String,
+    columns: Option<Vec<ExportColumn>>,
+) -> Result<usize, String> {
+    let format =
+        ExportFormat::from_name(&format).ok_or_else(|| format!("未知导出格式: {}", format))?;
+
+    let filter = filter.unwrap_or_default();
+    let ports = match daemon_client().await {
+        Some(mut client) => client.scan_ports(filter).await,
+        None => PortScanner::new().scan_filtered(&filter),
+    }
+    .map_err(|e| e.to_string())?;
+
+    let content =
+        render_ports(&ports, format, &columns.unwrap_or_default()).map_err(|e| e.to_string())?;
+
+    std::fs::write(&path, content).map_err(|e| AppError::Io(e.to_string()).to_string())?;
+
+    tracing::info!("Exported {} ports to {}", ports.len(), path);
+
+    Ok(ports.len())
+}
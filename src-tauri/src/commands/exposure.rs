@@ -0,0 +1,20 @@
+//! 网络暴露面命令
+
+use crate::core::exposure::exposure_report;
+use crate::core::PortScanner;
+use crate::models::ExposureReport;
+
+use super::daemon::daemon_client;
+
+/// 生成网络暴露面报告：按进程汇总监听端口的可达范围和风险
+#[tauri::command]
+pub async fn get_exposure_report() -> Result<ExposureReport, String> {
+    if let Some(mut client) = daemon_client().await {
+        return client
+            .get_exposure_report()
+            .await
+            .map_err(|e| e.to_string());
+    }
+
+    exposure_report(&PortScanner::new()).map_err(|e| e.to_string())
+}
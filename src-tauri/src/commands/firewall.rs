@@ -0,0 +1,20 @@
+//! 防火墙关联命令
+
+use crate::core::firewall::scan_firewall;
+use crate::core::PortScanner;
+use crate::models::PortInfo;
+
+use super::daemon::daemon_client;
+
+/// 扫描监听端口并判断入站连接是否被防火墙放行（读取规则通常需要 root，优先通过守护进程）
+#[tauri::command]
+pub async fn get_firewall_status() -> Result<Vec<PortInfo>, String> {
+    if let Some(mut client) = daemon_client().await {
+        return client
+            .get_firewall_status()
+            .await
+            .map_err(|e| e.to_string());
+    }
+
+    scan_firewall(&PortScanner::new()).map_err(|e| e.to_string())
+}
@@ -3,3 +3,19 @@
 pub mod port;
 pub mod process;
 pub mod monitor;
+pub mod daemon;
+pub mod watch;
+pub mod export;
+pub mod snapshot;
+pub mod probe;
+pub mod exposure;
+pub mod analytics;
+pub mod firewall;
+pub mod compliance;
+pub mod baseline;
+pub mod detection;
+pub mod service;
+pub mod resolver;
+pub mod sink;
+#[cfg(feature = "api-server")]
+pub mod server;
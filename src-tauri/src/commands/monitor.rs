@@ -1,22 +1,72 @@
 //! 监控命令
 
 use std::sync::Arc;
-use tokio::sync::{mpsc, Mutex};
+use tokio::sync::{broadcast, mpsc, Mutex};
 use tauri::{AppHandle, Emitter};
 
+use crate::core::analytics::ConnectionTracker;
+use crate::core::baseline::BaselineRecorder;
+use crate::core::detection::ConnectionHistory;
 use crate::core::monitor::{MonitorConfig, MonitorHandle, start_monitor_service};
+use crate::core::sink::{EventSink, SinkHub};
+use crate::core::trigger::platform_trigger_sources;
+use crate::core::watch::WatchRegistry;
+use crate::models::{AppResult, MonitorEvent};
+
+use super::daemon::daemon_client;
+
+/// 正在运行的监控
+enum ActiveMonitor {
+    /// 本进程内的监控服务
+    Local(MonitorHandle),
+    /// 订阅守护进程的监控事件
+    Daemon(tokio::task::JoinHandle<()>),
+}
 
 /// 监控状态
 pub struct MonitorState {
-    handle: Arc<Mutex<Option<MonitorHandle>>>,
+    handle: Arc<Mutex<Option<ActiveMonitor>>>,
+    /// 端口跟踪注册表，跨监控启停保留
+    pub(crate) watches: Arc<Mutex<WatchRegistry>>,
+    /// 行为基线，由本进程内的监控事件驱动
+    pub(crate) baseline: Arc<std::sync::Mutex<BaselineRecorder>>,
+    /// 可疑行为检测使用的连接记录，由本进程内的监控事件驱动
+    pub(crate) history: Arc<std::sync::Mutex<ConnectionHistory>>,
+    /// 已建立连接的汇总，由本进程内的监控事件驱动
+    pub(crate) connections: Arc<std::sync::Mutex<ConnectionTracker>>,
+    /// 监控事件广播，供 Tauri 之外的订阅者（如 API 服务）使用
+    pub(crate) events: broadcast::Sender<MonitorEvent>,
+    /// 事件输出端，跨监控启停保留
+    pub(crate) sinks: Arc<std::sync::Mutex<SinkHub>>,
 }
 
 impl MonitorState {
     pub fn new() -> Self {
+        let (events, _) = broadcast::channel(64);
         Self {
             handle: Arc::new(Mutex::new(None)),
+            watches: Arc::new(Mutex::new(WatchRegistry::new())),
+            baseline: Arc::new(std::sync::Mutex::new(BaselineRecorder::new())),
+            history: Arc::new(std::sync::Mutex::new(ConnectionHistory::new())),
+            connections: Arc::new(std::sync::Mutex::new(ConnectionTracker::new())),
+            events,
+            sinks: Arc::new(std::sync::Mutex::new(SinkHub::new())),
         }
     }
+
+    /// 接入 Tauri 前端输出端
+    pub fn attach_tauri_sink(&self, app: AppHandle) -> AppResult<()> {
+        self.sinks
+            .lock()
+            .unwrap()
+            .attach(Box::new(TauriSink { app }))?;
+        Ok(())
+    }
+
+    /// 订阅监控事件
+    pub fn subscribe(&self) -> broadcast::Receiver<MonitorEvent> {
+        self.events.subscribe()
+    }
 }
 
 impl Default for MonitorState {
@@ -25,18 +75,60 @@ impl Default for MonitorState {
     }
 }
 
+/// Tauri 前端输出端，发送 `port-snapshot` 和 `port-change` 事件
+struct TauriSink {
+    app: AppHandle,
+}
+
+impl EventSink for TauriSink {
+    fn kind(&self) -> &'static str {
+        "tauri"
+    }
+
+    fn describe(&self) -> String {
+        "port-snapshot / port-change".to_string()
+    }
+
+    fn start(self: Box<Self>, mut rx: mpsc::Receiver<MonitorEvent>) -> AppResult<()> {
+        let app = self.app;
+        // 应用初始化时接入，此时不一定处于 Tokio 运行时上下文
+        tauri::async_runtime::spawn(async move {
+            while let Some(event) = rx.recv().await {
+                let result = match &event {
+                    MonitorEvent::Snapshot(snapshot) => app.emit("port-snapshot", snapshot),
+                    MonitorEvent::Change(changes) => app.emit("port-change", changes),
+                };
+                if let Err(e) = result {
+                    tracing::error!("Failed to emit monitor event: {}", e);
+                }
+            }
+        });
+        Ok(())
+    }
+}
+
 /// 启动端口监控
+///
+/// 守护进程运行时改为订阅守护进程的监控事件，此时采样参数由守护进程决定
 #[tauri::command]
 pub async fn start_monitor(
-    app: AppHandle,
     interval_ms: Option<u64>,
     listen_only: Option<bool>,
+    min_lifetime_ms: Option<u64>,
+    flap_window_ms: Option<u64>,
+    batch_window_ms: Option<u64>,
+    event_driven: Option<bool>,
     state: tauri::State<'_, MonitorState>,
 ) -> Result<(), String> {
+    let defaults = MonitorConfig::default();
     let config = MonitorConfig {
         interval_ms: interval_ms.unwrap_or(2000),
         listen_only: listen_only.unwrap_or(false),
-        ..Default::default()
+        min_lifetime_ms: min_lifetime_ms.unwrap_or(defaults.min_lifetime_ms),
+        flap_window_ms: flap_window_ms.unwrap_or(defaults.flap_window_ms),
+        batch_window_ms: batch_window_ms.unwrap_or(defaults.batch_window_ms),
+        event_driven: event_driven.unwrap_or(defaults.event_driven),
+        ..defaults
     };
 
     let mut guard = state.handle.lock().await;
@@ -44,19 +136,56 @@ pub async fn start_monitor(
         return Err("监控已在运行".to_string());
     }
 
+    let events = state.events.clone();
+    let sinks = state.sinks.clone();
+
+    if let Some(client) = daemon_client().await {
+        let mut stream = client.subscribe().await.map_err(|e| e.to_string())?;
+        let task = tokio::spawn(async move {
+            loop {
+                match stream.next().await {
+                    Ok(Some(event)) => {
+                        let _ = events.send(event.clone());
+                        sinks.lock().unwrap().dispatch(event);
+                    }
+                    Ok(None) => break,
+                    Err(e) => {
+                        tracing::error!("Daemon event stream failed: {}", e);
+                        break;
+                    }
+                }
+            }
+            tracing::warn!("Daemon event stream closed");
+        });
+        *guard = Some(ActiveMonitor::Daemon(task));
+        tracing::info!("Monitor attached to daemon");
+        return Ok(());
+    }
+
     let (tx, mut rx) = mpsc::channel(32);
 
-    let handle = start_monitor_service(config, tx).await;
-    *guard = Some(handle);
+    let triggers = if config.event_driven {
+        platform_trigger_sources()
+    } else {
+        Vec::new()
+    };
+
+    let handle = start_monitor_service(config, tx, state.watches.clone(), triggers).await;
+    *guard = Some(ActiveMonitor::Local(handle));
     drop(guard);
 
     // 启动事件转发任务
-    let app_clone = app.clone();
+    let baseline = state.baseline.clone();
+    let history = state.history.clone();
+    let connections = state.connections.clone();
     tokio::spawn(async move {
-        while let Some(changes) = rx.recv().await {
-            if let Err(e) = app_clone.emit("port-change", &changes) {
-                tracing::error!("Failed to emit port-change event: {}", e);
-            }
+        while let Some(event) = rx.recv().await {
+            baseline.lock().unwrap().observe(&event);
+            history.lock().unwrap().observe(&event);
+            connections.lock().unwrap().observe(&event);
+            // 没有其他订阅者时发送失败，忽略即可
+            let _ = events.send(event.clone());
+            sinks.lock().unwrap().dispatch(event);
         }
     });
 
@@ -69,10 +198,29 @@ pub async fn start_monitor(
 #[tauri::command]
 pub async fn stop_monitor(state: tauri::State<'_, MonitorState>) -> Result<(), String> {
     let mut guard = state.handle.lock().await;
-    if let Some(handle) = guard.take() {
-        handle.stop().await;
-        tracing::info!("Monitor stopped");
-        return Ok(());
+    match guard.take() {
+        Some(ActiveMonitor::Local(handle)) => handle.stop().await,
+        // 守护进程的监控继续运行，只停止接收事件
+        Some(ActiveMonitor::Daemon(task)) => task.abort(),
+        None => return Err("监控未运行".to_string()),
+    }
+    tracing::info!("Monitor stopped");
+    Ok(())
+}
+
+/// 重新发送基线快照（`port-snapshot` 事件）
+#[tauri::command]
+pub async fn resync(state: tauri::State<'_, MonitorState>) -> Result<(), String> {
+    let guard = state.handle.lock().await;
+    match guard.as_ref() {
+        Some(ActiveMonitor::Local(handle)) => {
+            handle.resync().await;
+            Ok(())
+        }
+        Some(ActiveMonitor::Daemon(_)) => match daemon_client().await {
+            Some(mut client) => client.resync().await.map_err(|e| e.to_string()),
+            None => Err("守护进程未运行".to_string()),
+        },
+        None => Err("监控未运行".to_string()),
     }
-    Err("监控未运行".to_string())
 }
@@ -1,14 +1,26 @@
 //! 端口查询命令
 
-use crate::core::PortScanner;
+use crate::core::probe::{scan_page, scan_query, HttpProber, TlsInspector};
+use crate::core::query::{PageQuery, PortPage, PortQuery, ScanCache};
+use crate::core::{PortFilter, PortScanner};
 use crate::models::{PortInfo, Protocol};
 
+use super::daemon::daemon_client;
+
 #[cfg(target_os = "windows")]
 use crate::platform::windows::{ReservedPortRange, get_reserved_tcp_ports, get_reserved_udp_ports, is_tcp_port_reserved};
 
 /// 扫描所有端口
 #[tauri::command]
 pub async fn scan_all_ports(listen_only: Option<bool>) -> Result<Vec<PortInfo>, String> {
+    if let Some(mut client) = daemon_client().await {
+        let filter = PortFilter {
+            listen_only,
+            ..PortFilter::default()
+        };
+        return client.scan_ports(filter).await.map_err(|e| e.to_string());
+    }
+
     let scanner = PortScanner::new();
 
     let result = if listen_only.unwrap_or(false) {
@@ -20,19 +32,56 @@ pub async fn scan_all_ports(listen_only: Option<bool>) -> Result<Vec<PortInfo>,
     result.map_err(|e| e.to_string())
 }
 
+/// 按过滤表达式扫描端口，如 `port in 3000..4000 and state = listen`
+#[tauri::command]
+pub async fn scan_ports(
+    inspector: tauri::State<'_, TlsInspector>,
+    filter: String,
+) -> Result<Vec<PortInfo>, String> {
+    if let Some(mut client) = daemon_client().await {
+        return client.query_ports(&filter).await.map_err(|e| e.to_string());
+    }
+
+    let query = PortQuery::parse(&filter).map_err(|e| e.to_string())?;
+    scan_query(&PortScanner::new(), &inspector, &query)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 分页扫描端口，支持排序和分组，翻页时传回 `scan_id` 复用同一次扫描
+#[tauri::command]
+pub async fn scan_ports_page(
+    cache: tauri::State<'_, ScanCache>,
+    prober: tauri::State<'_, HttpProber>,
+    inspector: tauri::State<'_, TlsInspector>,
+    query: PageQuery,
+) -> Result<PortPage, String> {
+    if let Some(mut client) = daemon_client().await {
+        return client.scan_ports_page(query).await.map_err(|e| e.to_string());
+    }
+
+    let mut page = scan_page(&cache, &PortScanner::new(), &inspector, &query)
+        .await
+        .map_err(|e| e.to_string())?;
+    if query.http {
+        prober.enrich(&mut page.items).await;
+    }
+    Ok(page)
+}
+
 /// 查询指定端口
 #[tauri::command]
 pub async fn query_port(
     port: u16,
     protocol: Option<String>,
 ) -> Result<Vec<PortInfo>, String> {
-    let scanner = PortScanner::new();
+    let proto = protocol.and_then(|p| Protocol::from_name(&p));
+
+    if let Some(mut client) = daemon_client().await {
+        return client.query_port(port, proto).await.map_err(|e| e.to_string());
+    }
 
-    let proto = protocol.and_then(|p| match p.to_uppercase().as_str() {
-        "TCP" => Some(Protocol::TCP),
-        "UDP" => Some(Protocol::UDP),
-        _ => None,
-    });
+    let scanner = PortScanner::new();
 
     scanner.query_port(port, proto).map_err(|e| e.to_string())
 }
@@ -76,6 +125,20 @@ pub async fn check_port_reserved(port: u16) -> Result<PortReservedInfo, String>
     })
 }
 
+/// 获取系统保留端口范围（仅 Windows 支持）
+#[tauri::command]
+#[cfg(not(target_os = "windows"))]
+pub async fn get_reserved_ports() -> Result<(), String> {
+    Err("当前平台不支持保留端口查询".to_string())
+}
+
+/// 检查端口是否被系统保留（仅 Windows 支持）
+#[tauri::command]
+#[cfg(not(target_os = "windows"))]
+pub async fn check_port_reserved(port: u16) -> Result<(), String> {
+    Err(format!("当前平台不支持保留端口查询: {}", port))
+}
+
 #[cfg(target_os = "windows")]
 use serde::{Deserialize, Serialize};
 
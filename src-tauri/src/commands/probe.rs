@@ -0,0 +1,59 @@
+//! 主动探测命令
+
+use crate::core::probe::{probe_listening, HttpProber, ProbeConfig, TlsInspector};
+use crate::core::PortScanner;
+use crate::models::PortInfo;
+
+use super::daemon::daemon_client;
+
+/// 探测 TCP 监听端口上的服务，`ports` 为空时探测全部监听端口
+#[tauri::command]
+pub async fn probe_services(
+    ports: Option<Vec<u16>>,
+    config: Option<ProbeConfig>,
+) -> Result<Vec<PortInfo>, String> {
+    let config = config.unwrap_or_default();
+
+    if let Some(mut client) = daemon_client().await {
+        return client
+            .probe_services(ports, config)
+            .await
+            .map_err(|e| e.to_string());
+    }
+
+    probe_listening(&PortScanner::new(), ports.as_deref(), &config)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 探测端口上的 HTTP(S) 服务，返回该端口的 TCP 监听及探测结果（按 PID 和端口缓存）
+#[tauri::command]
+pub async fn probe_http(
+    prober: tauri::State<'_, HttpProber>,
+    port: u16,
+) -> Result<Vec<PortInfo>, String> {
+    if let Some(mut client) = daemon_client().await {
+        return client.probe_http(port).await.map_err(|e| e.to_string());
+    }
+
+    prober
+        .probe_port(&PortScanner::new(), port)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 检查端口上的 TLS 证书链，返回该端口的 TCP 监听及检查结果（按 PID 和端口缓存）
+#[tauri::command]
+pub async fn inspect_tls(
+    inspector: tauri::State<'_, TlsInspector>,
+    port: u16,
+) -> Result<Vec<PortInfo>, String> {
+    if let Some(mut client) = daemon_client().await {
+        return client.inspect_tls(port).await.map_err(|e| e.to_string());
+    }
+
+    inspector
+        .inspect_port(&PortScanner::new(), port)
+        .await
+        .map_err(|e| e.to_string())
+}
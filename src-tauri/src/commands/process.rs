@@ -1,16 +1,43 @@
 //! 进程操作命令
 
+use crate::core::executable::inspect_process;
+use crate::core::metrics;
 use crate::models::ProcessInfo;
-use crate::platform::windows::{win_get_process_info, win_kill_process};
+use crate::platform::{traits::ProcessProvider, NativePlatform};
+
+use super::daemon::daemon_client;
 
 /// 获取进程信息
 #[tauri::command]
 pub async fn get_process_info(pid: u32) -> Result<Option<ProcessInfo>, String> {
-    win_get_process_info(pid).map_err(|e| e.to_string())
+    if let Some(mut client) = daemon_client().await {
+        return client.get_process_info(pid).await.map_err(|e| e.to_string());
+    }
+
+    NativePlatform::new().get_process_info(pid).map_err(|e| e.to_string())
 }
 
-/// 终止进程
+/// 获取进程信息并检查其可执行文件（大小、修改时间、SHA-256 和所属软件包）
+#[tauri::command]
+pub async fn get_process_executable(pid: u32) -> Result<Option<ProcessInfo>, String> {
+    if let Some(mut client) = daemon_client().await {
+        return client
+            .get_process_executable(pid)
+            .await
+            .map_err(|e| e.to_string());
+    }
+
+    inspect_process(&NativePlatform::new(), pid).map_err(|e| e.to_string())
+}
+
+/// 终止进程（守护进程运行时由守护进程执行并校验权限）
 #[tauri::command]
 pub async fn kill_process(pid: u32, create_time: Option<u64>) -> Result<(), String> {
-    win_kill_process(pid, create_time).map_err(|e| e.to_string())
+    if let Some(mut client) = daemon_client().await {
+        return client.kill_process(pid, create_time).await.map_err(|e| e.to_string());
+    }
+
+    let result = NativePlatform::new().kill_process(pid, create_time);
+    metrics::counters().record_kill(result.is_ok());
+    result.map_err(|e| e.to_string())
 }
@@ -0,0 +1,34 @@
+//! 主机名解析命令
+
+use crate::core::resolver::hostname_resolver;
+use crate::utils::net::parse_addr;
+
+use super::daemon::daemon_client;
+
+/// 开启或关闭远程地址的反向 DNS 查询，关闭后只使用 hosts 文件
+#[tauri::command]
+pub async fn set_reverse_dns(enabled: bool) -> Result<(), String> {
+    if let Some(mut client) = daemon_client().await {
+        return client
+            .set_reverse_dns(enabled)
+            .await
+            .map_err(|e| e.to_string());
+    }
+
+    hostname_resolver().set_reverse_dns(enabled);
+    Ok(())
+}
+
+/// 解析单个地址的主机名，等待查询完成
+#[tauri::command]
+pub async fn resolve_hostname(addr: String) -> Result<Option<String>, String> {
+    if let Some(mut client) = daemon_client().await {
+        return client
+            .resolve_hostname(addr)
+            .await
+            .map_err(|e| e.to_string());
+    }
+
+    let ip = parse_addr(&addr).ok_or_else(|| format!("无效的地址: {}", addr))?;
+    Ok(hostname_resolver().resolve(ip).await)
+}
@@ -0,0 +1,65 @@
+//! 本地 API 服务命令
+
+use tokio::sync::Mutex;
+
+use crate::core::PortScanner;
+use crate::daemon::default_endpoint;
+use crate::server::{ApiServer, ApiServerInfo};
+
+use super::monitor::MonitorState;
+
+/// API 服务状态
+#[derive(Default)]
+pub struct ApiServerState {
+    server: Mutex<Option<ApiServer>>,
+}
+
+/// 启动本地 API 服务（仅监听 127.0.0.1）
+///
+/// 与其他命令一样，守护进程运行时请求转发给守护进程处理
+#[tauri::command]
+pub async fn start_api_server(
+    port: Option<u16>,
+    token: Option<String>,
+    state: tauri::State<'_, ApiServerState>,
+    monitor: tauri::State<'_, MonitorState>,
+) -> Result<ApiServerInfo, String> {
+    let mut guard = state.server.lock().await;
+    if guard.is_some() {
+        return Err("API 服务已在运行".to_string());
+    }
+
+    let server = ApiServer::start(
+        PortScanner::new(),
+        monitor.events.clone(),
+        Some(&default_endpoint()),
+        port.unwrap_or(0),
+        token,
+    )
+    .await
+    .map_err(|e| e.to_string())?;
+
+    let info = server.info();
+    *guard = Some(server);
+    Ok(info)
+}
+
+/// 停止本地 API 服务
+#[tauri::command]
+pub async fn stop_api_server(state: tauri::State<'_, ApiServerState>) -> Result<(), String> {
+    match state.server.lock().await.take() {
+        Some(server) => {
+            server.stop();
+            Ok(())
+        }
+        None => Err("API 服务未运行".to_string()),
+    }
+}
+
+/// 获取本地 API 服务信息，未运行时返回 None
+#[tauri::command]
+pub async fn get_api_server_info(
+    state: tauri::State<'_, ApiServerState>,
+) -> Result<Option<ApiServerInfo>, String> {
+    Ok(state.server.lock().await.as_ref().map(|s| s.info()))
+}
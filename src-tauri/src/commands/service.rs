@@ -0,0 +1,58 @@
+//! 服务名命令
+
+use std::path::PathBuf;
+
+use tauri::{AppHandle, Manager};
+
+use crate::models::{service_registry, ServiceEntry};
+
+use super::daemon::daemon_client;
+
+/// 应用数据目录下的服务名覆盖文件
+pub(crate) fn overrides_path(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    Ok(dir.join("service_overrides.json"))
+}
+
+/// 启动时加载用户覆盖
+pub(crate) fn load_service_overrides(app: &AppHandle) -> Result<(), String> {
+    let path = overrides_path(app)?;
+    service_registry()
+        .write()
+        .map_err(|e| e.to_string())?
+        .load_overrides(&path)
+        .map_err(|e| e.to_string())
+}
+
+/// 获取用户自定义的服务名
+#[tauri::command]
+pub async fn get_service_overrides() -> Result<Vec<ServiceEntry>, String> {
+    if let Some(mut client) = daemon_client().await {
+        return client
+            .list_service_overrides()
+            .await
+            .map_err(|e| e.to_string());
+    }
+
+    let registry = service_registry().read().map_err(|e| e.to_string())?;
+    Ok(registry.overrides())
+}
+
+/// 替换用户自定义的服务名并保存
+#[tauri::command]
+pub async fn set_service_overrides(
+    app: AppHandle,
+    entries: Vec<ServiceEntry>,
+) -> Result<(), String> {
+    if let Some(mut client) = daemon_client().await {
+        return client
+            .set_service_overrides(entries)
+            .await
+            .map_err(|e| e.to_string());
+    }
+
+    let path = overrides_path(&app)?;
+    let mut registry = service_registry().write().map_err(|e| e.to_string())?;
+    registry.set_overrides(entries);
+    registry.save_overrides(&path).map_err(|e| e.to_string())
+}
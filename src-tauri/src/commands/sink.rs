@@ -0,0 +1,80 @@
+//! 事件输出端命令
+
+use serde::Deserialize;
+
+use crate::core::sink::{EventSink, LocalSocketSink, NdjsonFileSink, SinkInfo};
+
+use super::monitor::MonitorState;
+
+/// 输出端配置
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SinkConfig {
+    /// 追加写入 NDJSON 文件
+    NdjsonFile { path: String },
+    /// Unix 域套接字或 Windows 命名管道，未指定路径时使用默认路径
+    LocalSocket { path: Option<String> },
+    /// 回环地址上的 WebSocket 服务
+    #[cfg(feature = "websocket")]
+    #[serde(rename = "websocket")]
+    WebSocket {
+        port: Option<u16>,
+        token: Option<String>,
+    },
+}
+
+impl SinkConfig {
+    fn build(self) -> Result<Box<dyn EventSink>, String> {
+        let sink: Box<dyn EventSink> = match self {
+            SinkConfig::NdjsonFile { path } => {
+                Box::new(NdjsonFileSink::open(path).map_err(|e| e.to_string())?)
+            }
+            SinkConfig::LocalSocket { path } => {
+                let path = path.unwrap_or_else(LocalSocketSink::default_path);
+                Box::new(LocalSocketSink::bind(&path).map_err(|e| e.to_string())?)
+            }
+            #[cfg(feature = "websocket")]
+            SinkConfig::WebSocket { port, token } => Box::new(
+                crate::server::WebSocketSink::bind(port.unwrap_or(0), token)
+                    .map_err(|e| e.to_string())?,
+            ),
+        };
+        Ok(sink)
+    }
+}
+
+/// 接入事件输出端
+#[tauri::command]
+pub async fn attach_event_sink(
+    config: SinkConfig,
+    state: tauri::State<'_, MonitorState>,
+) -> Result<SinkInfo, String> {
+    let sink = config.build()?;
+    state
+        .sinks
+        .lock()
+        .unwrap()
+        .attach(sink)
+        .map_err(|e| e.to_string())
+}
+
+/// 移除事件输出端
+#[tauri::command]
+pub async fn detach_event_sink(
+    id: u64,
+    state: tauri::State<'_, MonitorState>,
+) -> Result<(), String> {
+    if state.sinks.lock().unwrap().detach(id) {
+        Ok(())
+    } else {
+        Err(format!("输出端不存在: {}", id))
+    }
+}
+
+/// 列出已接入的事件输出端
+#[tauri::command]
+pub async fn list_event_sinks(
+    state: tauri::State<'_, MonitorState>,
+) -> Result<Vec<SinkInfo>, String> {
+    Ok(state.sinks.lock().unwrap().list())
+}
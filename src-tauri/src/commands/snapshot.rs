@@ -0,0 +1,137 @@
+//! 快照命令
+//!
+//! 守护进程运行时快照保存在守护进程的数据目录中，否则保存在应用数据目录
+
+use tauri::{AppHandle, Manager};
+
+use crate::core::snapshot_store::{
+    SavedSnapshot, SnapshotDiff, SnapshotStore, SnapshotSummary, LIVE_SNAPSHOT_NAME,
+};
+use crate::core::{PortFilter, PortScanner};
+use crate::models::{AppResult, PortInfo};
+
+use super::daemon::daemon_client;
+
+/// 应用数据目录下的快照存储
+fn snapshot_store(app: &AppHandle) -> Result<SnapshotStore, String> {
+    let dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    Ok(SnapshotStore::new(dir.join("snapshots")))
+}
+
+/// 在本进程内扫描端口
+fn scan_local(listen_only: bool) -> AppResult<Vec<PortInfo>> {
+    let scanner = PortScanner::new();
+    if listen_only {
+        scanner.scan_listening()
+    } else {
+        scanner.scan_all()
+    }
+}
+
+/// 扫描当前端口并保存为命名快照
+#[tauri::command]
+pub async fn save_snapshot(
+    app: AppHandle,
+    name: String,
+    listen_only: Option<bool>,
+) -> Result<SnapshotSummary, String> {
+    let listen_only = listen_only.unwrap_or(false);
+    if let Some(mut client) = daemon_client().await {
+        return client
+            .save_snapshot(&name, listen_only)
+            .await
+            .map_err(|e| e.to_string());
+    }
+
+    let ports = scan_local(listen_only).map_err(|e| e.to_string())?;
+    snapshot_store(&app)?
+        .save(&name, ports, listen_only)
+        .map_err(|e| e.to_string())
+}
+
+/// 列出已保存的快照
+#[tauri::command]
+pub async fn list_snapshots(app: AppHandle) -> Result<Vec<SnapshotSummary>, String> {
+    if let Some(mut client) = daemon_client().await {
+        return client.list_snapshots().await.map_err(|e| e.to_string());
+    }
+    snapshot_store(&app)?.list().map_err(|e| e.to_string())
+}
+
+/// 加载快照
+#[tauri::command]
+pub async fn load_snapshot(app: AppHandle, name: String) -> Result<SavedSnapshot, String> {
+    if let Some(mut client) = daemon_client().await {
+        return client.load_snapshot(&name).await.map_err(|e| e.to_string());
+    }
+    snapshot_store(&app)?.load(&name).map_err(|e| e.to_string())
+}
+
+/// 删除快照
+#[tauri::command]
+pub async fn delete_snapshot(app: AppHandle, name: String) -> Result<(), String> {
+    if let Some(mut client) = daemon_client().await {
+        return client
+            .delete_snapshot(&name)
+            .await
+            .map_err(|e| e.to_string());
+    }
+    snapshot_store(&app)?
+        .delete(&name)
+        .map_err(|e| e.to_string())
+}
+
+/// 比较两个快照，`b` 为空时与当前实时状态比较
+#[tauri::command]
+pub async fn diff_snapshots(
+    app: AppHandle,
+    a: String,
+    b: Option<String>,
+) -> Result<SnapshotDiff, String> {
+    let (from, (to_name, to_ports)) = match daemon_client().await {
+        Some(mut client) => {
+            let from = client.load_snapshot(&a).await.map_err(|e| e.to_string())?;
+            let to = match b {
+                Some(name) => {
+                    let to = client
+                        .load_snapshot(&name)
+                        .await
+                        .map_err(|e| e.to_string())?;
+                    (to.name, to.ports)
+                }
+                None => {
+                    // 与快照保持一致的扫描范围
+                    let filter = PortFilter {
+                        listen_only: Some(from.listen_only),
+                        ..Default::default()
+                    };
+                    let ports = client.scan_ports(filter).await.map_err(|e| e.to_string())?;
+                    (LIVE_SNAPSHOT_NAME.to_string(), ports)
+                }
+            };
+            (from, to)
+        }
+        None => {
+            let store = snapshot_store(&app)?;
+            let from = store.load(&a).map_err(|e| e.to_string())?;
+            let to = match b {
+                Some(name) => {
+                    let to = store.load(&name).map_err(|e| e.to_string())?;
+                    (to.name, to.ports)
+                }
+                None => {
+                    let ports = scan_local(from.listen_only).map_err(|e| e.to_string())?;
+                    (LIVE_SNAPSHOT_NAME.to_string(), ports)
+                }
+            };
+            (from, to)
+        }
+    };
+
+    Ok(SnapshotDiff::between(
+        from.name,
+        &from.ports,
+        to_name,
+        &to_ports,
+    ))
+}
@@ -0,0 +1,113 @@
+//! 端口跟踪命令
+
+use crate::core::monitor::PortKey;
+use crate::core::watch::{port_occupant, PortTimeline};
+use crate::core::PortScanner;
+use crate::models::{AppError, Protocol};
+use crate::utils::time::now_millis;
+
+use super::daemon::daemon_client;
+use super::monitor::MonitorState;
+
+/// 解析端口与协议
+fn parse_key(port: u16, protocol: &str) -> Result<PortKey, String> {
+    let proto = Protocol::from_name(protocol).ok_or_else(|| format!("未知协议: {}", protocol))?;
+    Ok((port, proto))
+}
+
+/// 开始跟踪端口（需要监控运行时才会记录变化）
+#[tauri::command]
+pub async fn watch_port(
+    port: u16,
+    protocol: String,
+    state: tauri::State<'_, MonitorState>,
+) -> Result<PortTimeline, String> {
+    let key = parse_key(port, &protocol)?;
+
+    if let Some(mut client) = daemon_client().await {
+        return client.watch_port(key.0, key.1).await.map_err(|e| e.to_string());
+    }
+
+    // 以当前占用情况作为时间线起点，端口上有监听时以监听进程为占用者
+    let sockets = PortScanner::new()
+        .query_port(key.0, Some(key.1))
+        .map_err(|e| e.to_string())?;
+
+    let now = now_millis();
+    let mut watches = state.watches.lock().await;
+    watches.watch(key, port_occupant(&sockets, key), now);
+
+    watches
+        .timeline(&key, now)
+        .ok_or_else(|| AppError::PortNotFound(port).to_string())
+}
+
+/// 停止跟踪端口
+#[tauri::command]
+pub async fn unwatch_port(
+    port: u16,
+    protocol: String,
+    state: tauri::State<'_, MonitorState>,
+) -> Result<bool, String> {
+    let key = parse_key(port, &protocol)?;
+
+    if let Some(mut client) = daemon_client().await {
+        return client.unwatch_port(key.0, key.1).await.map_err(|e| e.to_string());
+    }
+
+    Ok(state.watches.lock().await.unwatch(&key))
+}
+
+/// 获取所有正在跟踪的端口时间线
+#[tauri::command]
+pub async fn list_watches(
+    state: tauri::State<'_, MonitorState>,
+) -> Result<Vec<PortTimeline>, String> {
+    if let Some(mut client) = daemon_client().await {
+        return client.list_watches().await.map_err(|e| e.to_string());
+    }
+
+    let now = now_millis();
+    let watches = state.watches.lock().await;
+
+    Ok(watches
+        .watched()
+        .iter()
+        .filter_map(|key| watches.timeline(key, now))
+        .collect())
+}
+
+/// 获取端口时间线
+#[tauri::command]
+pub async fn get_port_timeline(
+    port: u16,
+    protocol: String,
+    state: tauri::State<'_, MonitorState>,
+) -> Result<PortTimeline, String> {
+    let key = parse_key(port, &protocol)?;
+
+    let timeline = match daemon_client().await {
+        Some(mut client) => client
+            .get_port_timeline(key.0, key.1)
+            .await
+            .map_err(|e| e.to_string())?,
+        None => state.watches.lock().await.timeline(&key, now_millis()),
+    };
+
+    timeline
+        .ok_or_else(|| format!("端口未在跟踪中: {}/{:?}", port, key.1))
+}
+
+/// 将端口时间线导出为 JSON 文件
+#[tauri::command]
+pub async fn export_port_timeline(
+    port: u16,
+    protocol: String,
+    path: String,
+    state: tauri::State<'_, MonitorState>,
+) -> Result<(), String> {
+    let timeline = get_port_timeline(port, protocol, state).await?;
+
+    let json = serde_json::to_string_pretty(&timeline).map_err(|e| e.to_string())?;
+    std::fs::write(&path, json).map_err(|e| AppError::Io(e.to_string()).to_string())
+}
@@ -0,0 +1,517 @@
+//! 已建立连接的汇总
+//!
+//! 根据监控事件跟踪已建立的 TCP 连接，按远程地址、远程端口、网段和进程累计
+//! 连接数与首次、最后出现时间。每个维度最多保留 `MAX_GROUPS` 个分组，超出时
+//! 丢弃最久没有活动的分组
+
+use std::cmp::Reverse;
+use std::collections::{BTreeSet, HashMap};
+use std::hash::Hash;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::sync::Mutex;
+
+use crate::core::PortScanner;
+use crate::models::{
+    AppResult, ChangeKind, ConnectionGroup, ConnectionState, ConnectionSummary, MonitorEvent,
+    PortInfo, Protocol,
+};
+use crate::utils::net::parse_addr;
+use crate::utils::time::now_millis;
+
+/// 每个维度默认返回的分组数
+pub const DEFAULT_SUMMARY_LIMIT: usize = 20;
+
+/// 每个维度最多保留的分组数
+const MAX_GROUPS: usize = 4096;
+
+/// 连接的键：本地地址、本地端口、远程地址、远程端口
+type ConnectionKey = (String, u16, IpAddr, u16);
+
+/// 单条已建立连接
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Connection {
+    pid: u32,
+    local_addr: String,
+    local_port: u16,
+    remote: IpAddr,
+    remote_port: u16,
+    process: Option<String>,
+}
+
+impl Connection {
+    fn from_port(info: &PortInfo) -> Option<Self> {
+        if info.state != ConnectionState::Established {
+            return None;
+        }
+        let (local_addr, local_port, remote, remote_port) = connection_key(info)?;
+        if remote.is_unspecified() {
+            return None;
+        }
+        Some(Self {
+            pid: info.pid,
+            local_addr,
+            local_port,
+            remote,
+            remote_port,
+            process: info.process.as_ref().map(|p| p.name.clone()),
+        })
+    }
+
+    fn key(&self) -> ConnectionKey {
+        (
+            self.local_addr.clone(),
+            self.local_port,
+            self.remote,
+            self.remote_port,
+        )
+    }
+
+    fn process_label(&self) -> String {
+        self.process
+            .clone()
+            .unwrap_or_else(|| "未知进程".to_string())
+    }
+}
+
+#[derive(Debug, Default)]
+struct Group {
+    connections: u64,
+    active: usize,
+    first_seen: u64,
+    last_seen: u64,
+    processes: BTreeSet<String>,
+}
+
+/// 单个维度的分组
+#[derive(Debug)]
+struct Dimension<K> {
+    groups: HashMap<K, Group>,
+    /// 是否记录参与的进程
+    track_processes: bool,
+}
+
+impl<K: Hash + Eq + Clone + ToString> Dimension<K> {
+    fn new(track_processes: bool) -> Self {
+        Self {
+            groups: HashMap::new(),
+            track_processes,
+        }
+    }
+
+    fn open(&mut self, key: K, conn: &Connection, now: u64) {
+        let group = self.groups.entry(key).or_insert_with(|| Group {
+            first_seen: now,
+            ..Group::default()
+        });
+        group.connections += 1;
+        group.active += 1;
+        group.last_seen = group.last_seen.max(now);
+        if self.track_processes {
+            group.processes.insert(conn.process_label());
+        }
+    }
+
+    fn close(&mut self, key: &K, now: u64) {
+        if let Some(group) = self.groups.get_mut(key) {
+            group.active = group.active.saturating_sub(1);
+            group.last_seen = group.last_seen.max(now);
+        }
+    }
+
+    /// 分组过多时丢弃最久没有活动的非活动分组
+    fn prune(&mut self) {
+        if self.groups.len() <= MAX_GROUPS {
+            return;
+        }
+        let mut idle: Vec<(u64, K)> = self
+            .groups
+            .iter()
+            .filter(|(_, g)| g.active == 0)
+            .map(|(k, g)| (g.last_seen, k.clone()))
+            .collect();
+        idle.sort_by_key(|(last_seen, _)| *last_seen);
+        let excess = self.groups.len() - MAX_GROUPS;
+        for (_, key) in idle.into_iter().take(excess) {
+            self.groups.remove(&key);
+        }
+    }
+
+    fn summary(&self, limit: usize, now: u64) -> Vec<ConnectionGroup> {
+        let mut groups: Vec<ConnectionGroup> = self
+            .groups
+            .iter()
+            .map(|(key, g)| ConnectionGroup {
+                key: key.to_string(),
+                connections: g.connections,
+                active: g.active,
+                first_seen: g.first_seen,
+                last_seen: if g.active > 0 { now } else { g.last_seen },
+                processes: g.processes.iter().cloned().collect(),
+            })
+            .collect();
+        groups.sort_by(|a, b| {
+            (Reverse(a.connections), Reverse(a.active), &a.key).cmp(&(
+                Reverse(b.connections),
+                Reverse(b.active),
+                &b.key,
+            ))
+        });
+        groups.truncate(limit);
+        groups
+    }
+}
+
+/// 已建立连接的跟踪与汇总，由监控事件驱动
+#[derive(Debug)]
+pub struct ConnectionTracker {
+    since: Option<u64>,
+    total: u64,
+    /// 按监控事件维护的当前连接
+    open: HashMap<ConnectionKey, Connection>,
+    by_host: Dimension<IpAddr>,
+    by_port: Dimension<u16>,
+    by_subnet: Dimension<String>,
+    by_process: Dimension<String>,
+}
+
+impl Default for ConnectionTracker {
+    fn default() -> Self {
+        Self {
+            since: None,
+            total: 0,
+            open: HashMap::new(),
+            by_host: Dimension::new(true),
+            by_port: Dimension::new(true),
+            by_subnet: Dimension::new(true),
+            by_process: Dimension::new(false),
+        }
+    }
+}
+
+impl ConnectionTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 是否已收到监控事件
+    pub fn is_tracking(&self) -> bool {
+        self.since.is_some()
+    }
+
+    /// 应用一个监控事件
+    pub fn observe(&mut self, event: &MonitorEvent) {
+        match event {
+            MonitorEvent::Snapshot(snapshot) => {
+                self.since.get_or_insert(snapshot.timestamp);
+                self.apply_snapshot(&snapshot.ports, snapshot.timestamp);
+            }
+            MonitorEvent::Change(change) => {
+                self.since.get_or_insert(change.timestamp);
+                for entry in &change.entries {
+                    let Some(key) = connection_key(&entry.info) else {
+                        continue;
+                    };
+                    // 监控按 socket 汇报变化，移除只关闭这一条连接，
+                    // 同一本地端口上的其他连接各自有变化记录
+                    let conn = match entry.kind {
+                        ChangeKind::Removed => None,
+                        _ => Connection::from_port(&entry.info),
+                    };
+                    self.replace(key, conn, change.timestamp);
+                }
+            }
+        }
+        self.prune();
+    }
+
+    /// 生成汇总，每个维度最多返回 `limit` 个分组
+    pub fn summary(&self, limit: usize, now: u64) -> ConnectionSummary {
+        ConnectionSummary {
+            tracking_since: self.since,
+            generated_at: now,
+            total: self.total,
+            active: self.open.len(),
+            by_host: self.by_host.summary(limit, now),
+            by_port: self.by_port.summary(limit, now),
+            by_subnet: self.by_subnet.summary(limit, now),
+            by_process: self.by_process.summary(limit, now),
+        }
+    }
+
+    /// 用完整端口列表替换当前连接
+    fn apply_snapshot(&mut self, ports: &[PortInfo], now: u64) {
+        let current: HashMap<ConnectionKey, Connection> = ports
+            .iter()
+            .filter_map(Connection::from_port)
+            .map(|conn| (conn.key(), conn))
+            .collect();
+
+        let stale: Vec<ConnectionKey> = self
+            .open
+            .keys()
+            .filter(|key| !current.contains_key(*key))
+            .cloned()
+            .collect();
+        for key in stale {
+            self.replace(key, None, now);
+        }
+        for (key, conn) in current {
+            self.replace(key, Some(conn), now);
+        }
+    }
+
+    /// 更新一条连接，连接不变时不计数
+    fn replace(&mut self, key: ConnectionKey, conn: Option<Connection>, now: u64) {
+        if self.open.get(&key) == conn.as_ref() {
+            return;
+        }
+        if let Some(old) = self.open.remove(&key) {
+            self.by_host.close(&old.remote, now);
+            self.by_port.close(&old.remote_port, now);
+            self.by_subnet.close(&subnet(old.remote), now);
+            self.by_process.close(&old.process_label(), now);
+        }
+        if let Some(conn) = conn {
+            self.total += 1;
+            self.by_host.open(conn.remote, &conn, now);
+            self.by_port.open(conn.remote_port, &conn, now);
+            self.by_subnet.open(subnet(conn.remote), &conn, now);
+            self.by_process.open(conn.process_label(), &conn, now);
+            self.open.insert(key, conn);
+        }
+    }
+
+    fn prune(&mut self) {
+        self.by_host.prune();
+        self.by_port.prune();
+        self.by_subnet.prune();
+        self.by_process.prune();
+    }
+}
+
+/// TCP 连接的四元组，不要求处于已建立状态
+fn connection_key(info: &PortInfo) -> Option<ConnectionKey> {
+    if info.protocol != Protocol::TCP {
+        return None;
+    }
+    let remote = parse_addr(info.remote_addr.as_deref()?)?.to_canonical();
+    Some((
+        info.local_addr.clone(),
+        info.port,
+        remote,
+        info.remote_port?,
+    ))
+}
+
+/// IPv4 取 /24，IPv6 取 /64
+fn subnet(ip: IpAddr) -> String {
+    match ip {
+        IpAddr::V4(v4) => format!("{}/24", Ipv4Addr::from(u32::from(v4) & !0xff)),
+        IpAddr::V6(v6) => format!(
+            "{}/64",
+            Ipv6Addr::from(u128::from(v6) & !(u64::MAX as u128))
+        ),
+    }
+}
+
+/// 获取连接汇总；尚未收到监控事件时只汇总当前扫描到的连接
+pub fn connection_summary(
+    scanner: &PortScanner,
+    tracker: &Mutex<ConnectionTracker>,
+    limit: usize,
+) -> AppResult<ConnectionSummary> {
+    {
+        let tracker = tracker.lock().unwrap_or_else(|e| e.into_inner());
+        if tracker.is_tracking() {
+            return Ok(tracker.summary(limit, now_millis()));
+        }
+    }
+
+    let ports = scanner.scan_all()?;
+    let now = now_millis();
+    let mut snapshot = ConnectionTracker::new();
+    snapshot.apply_snapshot(&ports, now);
+    Ok(snapshot.summary(limit, now))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{ChangeEntry, PortChange, PortSnapshot};
+
+    fn connection(local_port: u16, remote: &str, remote_port: u16) -> PortInfo {
+        let mut info = PortInfo::new(local_port, Protocol::TCP, "10.0.0.2".to_string(), 42);
+        info.state = ConnectionState::Established;
+        info.remote_addr = Some(remote.to_string());
+        info.remote_port = Some(remote_port);
+        info
+    }
+
+    fn snapshot(timestamp: u64, ports: Vec<PortInfo>) -> MonitorEvent {
+        MonitorEvent::Snapshot(PortSnapshot {
+            seq: 1,
+            timestamp,
+            snapshot_version: 1,
+            ports,
+        })
+    }
+
+    fn change(timestamp: u64, entries: Vec<ChangeEntry>) -> MonitorEvent {
+        MonitorEvent::Change(PortChange {
+            seq: 2,
+            timestamp,
+            snapshot_version: 2,
+            entries,
+        })
+    }
+
+    fn hosts(tracker: &ConnectionTracker) -> Vec<(String, usize)> {
+        tracker
+            .summary(10, 0)
+            .by_host
+            .into_iter()
+            .map(|g| (g.key, g.active))
+            .collect()
+    }
+
+    #[test]
+    fn test_inbound_connections_tracked_separately() {
+        let mut tracker = ConnectionTracker::new();
+        tracker.observe(&snapshot(
+            1000,
+            vec![
+                connection(443, "198.51.100.1", 50001),
+                connection(443, "198.51.100.1", 50002),
+                connection(443, "198.51.100.2", 50001),
+            ],
+        ));
+
+        let summary = tracker.summary(10, 1000);
+        assert_eq!((summary.total, summary.active), (3, 3));
+        assert_eq!(
+            hosts(&tracker),
+            vec![
+                ("198.51.100.1".to_string(), 2),
+                ("198.51.100.2".to_string(), 1)
+            ]
+        );
+
+        // 快照中消失的连接被关闭，未变化的连接不重复计数
+        tracker.observe(&snapshot(
+            2000,
+            vec![connection(443, "198.51.100.1", 50001)],
+        ));
+        let summary = tracker.summary(10, 2000);
+        assert_eq!((summary.total, summary.active), (3, 1));
+    }
+
+    #[test]
+    fn test_change_events() {
+        let mut tracker = ConnectionTracker::new();
+        tracker.observe(&snapshot(1000, Vec::new()));
+        tracker.observe(&change(
+            2000,
+            vec![
+                ChangeEntry::added(connection(443, "198.51.100.1", 50001)),
+                ChangeEntry::added(connection(443, "198.51.100.2", 50001)),
+                ChangeEntry::added(connection(40000, "203.0.113.9", 443)),
+            ],
+        ));
+        assert_eq!(tracker.summary(10, 2000).active, 3);
+
+        // 连接进入 TIME_WAIT
+        let mut closing = connection(443, "198.51.100.2", 50001);
+        closing.state = ConnectionState::TimeWait;
+        tracker.observe(&change(
+            3000,
+            vec![ChangeEntry {
+                kind: ChangeKind::StateChanged {
+                    from: ConnectionState::Established,
+                    to: ConnectionState::TimeWait,
+                },
+                info: closing,
+            }],
+        ));
+        assert_eq!(tracker.summary(10, 3000).active, 2);
+
+        // 连接被移除
+        tracker.observe(&change(
+            4000,
+            vec![ChangeEntry::removed(connection(443, "198.51.100.1", 50001))],
+        ));
+        let summary = tracker.summary(10, 4000);
+        assert_eq!((summary.total, summary.active), (3, 1));
+        let active: Vec<&str> = summary
+            .by_host
+            .iter()
+            .filter(|g| g.active > 0)
+            .map(|g| g.key.as_str())
+            .collect();
+        assert_eq!(active, vec!["203.0.113.9"]);
+    }
+
+    #[test]
+    fn test_removed_socket_closes_only_its_connection() {
+        let mut tracker = ConnectionTracker::new();
+        let mut listener = PortInfo::new(443, Protocol::TCP, "0.0.0.0".to_string(), 42);
+        listener.state = ConnectionState::Listen;
+        tracker.observe(&snapshot(
+            1000,
+            vec![
+                listener.clone(),
+                connection(443, "198.51.100.1", 50001),
+                connection(443, "198.51.100.2", 50002),
+                connection(443, "198.51.100.3", 50003),
+            ],
+        ));
+        assert_eq!(tracker.summary(10, 1000).active, 3);
+
+        // 同一批变化中一条连接关闭、另一条连接建立，两种顺序结果相同
+        let swap = [
+            ChangeEntry::removed(connection(443, "198.51.100.1", 50001)),
+            ChangeEntry::added(connection(443, "198.51.100.4", 50004)),
+        ];
+        for entries in [swap.to_vec(), swap.iter().rev().cloned().collect()] {
+            let mut tracker = ConnectionTracker::new();
+            tracker.observe(&snapshot(
+                1000,
+                vec![
+                    connection(443, "198.51.100.1", 50001),
+                    connection(443, "198.51.100.2", 50002),
+                ],
+            ));
+            tracker.observe(&change(2000, entries));
+            let summary = tracker.summary(10, 2000);
+            assert_eq!((summary.total, summary.active), (3, 2));
+            let mut active: Vec<(String, usize)> = hosts(&tracker)
+                .into_iter()
+                .filter(|(_, active)| *active > 0)
+                .collect();
+            active.sort();
+            assert_eq!(
+                active,
+                vec![
+                    ("198.51.100.2".to_string(), 1),
+                    ("198.51.100.4".to_string(), 1)
+                ]
+            );
+        }
+
+        // 监听 socket 被移除不影响已建立的连接
+        tracker.observe(&change(3000, vec![ChangeEntry::removed(listener)]));
+        assert_eq!(tracker.summary(10, 3000).active, 3);
+
+        tracker.observe(&change(
+            4000,
+            vec![ChangeEntry::removed(connection(443, "198.51.100.2", 50002))],
+        ));
+        let summary = tracker.summary(10, 4000);
+        assert_eq!(summary.active, 2);
+        let closed = summary
+            .by_host
+            .iter()
+            .find(|g| g.key == "198.51.100.2")
+            .unwrap();
+        assert_eq!((closed.active, closed.last_seen), (0, 4000));
+    }
+}
@@ -0,0 +1,922 @@
+//! 行为基线学习与偏离检测
+//!
+//! 学习期内根据监控事件记录稳定的监听端口和各进程的出站目标，学习结束后把
+//! 基线之外的监听、出站连接和端口占用变化记为偏离事件。基线和待处理的偏离
+//! 保存在同一个 JSON 文件中，偏离审批后并入基线
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::core::monitor::{snapshot_map, socket_key, PortKey, SocketKey};
+use crate::models::{
+    AppError, AppResult, Baseline, BaselineDestination, BaselineListener, BaselineMode,
+    BaselineProcess, BaselineStatus, ChangeKind, ConnectionState, DriftEvent, DriftKind,
+    MonitorEvent, PortInfo, Protocol,
+};
+use crate::utils::net::parse_addr;
+
+/// 当前基线文件格式版本
+pub const BASELINE_FORMAT_VERSION: u32 = 1;
+
+/// 默认学习时长（毫秒）
+pub const DEFAULT_TRAINING_MS: u64 = 60 * 60 * 1000;
+
+/// 学习时长达到该值后，置信度不再因学习时间不足而折减
+const FULL_TRAINING_MS: u64 = 24 * 60 * 60 * 1000;
+
+/// 在线比例低于该值的监听端口不计入基线
+const STABLE_PRESENCE: f64 = 0.2;
+
+/// 最多保留的待处理偏离事件，超出时丢弃最早的
+const MAX_PENDING_DRIFTS: usize = 500;
+
+/// 基线文件内容
+#[derive(Debug, Serialize, Deserialize)]
+struct SavedBaseline {
+    format_version: u32,
+    baseline: Option<Baseline>,
+    drifts: Vec<DriftEvent>,
+    next_drift_id: u64,
+    /// 进行中的学习，重启后继续
+    #[serde(default)]
+    training: Option<SavedTraining>,
+}
+
+/// 学习状态的保存形式，未结束的在线区间按最后更新时间计入
+#[derive(Debug, Serialize, Deserialize)]
+struct SavedTraining {
+    started_at: u64,
+    ends_at: u64,
+    listeners: Vec<SavedPresence>,
+    outbound: Vec<SavedOutbound>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SavedPresence {
+    port: u16,
+    protocol: Protocol,
+    process: Option<String>,
+    /// 累计在线时长（毫秒）
+    online_ms: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SavedOutbound {
+    process: String,
+    addr: String,
+    port: u16,
+    seen: u32,
+}
+
+/// 学习期内监听端口的键：端口、协议和进程名
+type ListenerKey = (u16, Protocol, Option<String>);
+
+/// 学习中的状态
+#[derive(Debug)]
+struct Training {
+    started_at: u64,
+    ends_at: u64,
+    /// 最后一次更新在线区间的时间
+    updated_at: u64,
+    /// 监听端口 -> (本次上线时间, 累计在线时长)
+    listeners: HashMap<ListenerKey, (Option<u64>, u64)>,
+    /// 进程名 -> 出站目标 -> 观察次数
+    processes: HashMap<String, HashMap<(String, u16), u32>>,
+}
+
+impl Training {
+    fn new(started_at: u64, ends_at: u64) -> Self {
+        Self {
+            started_at,
+            ends_at,
+            updated_at: started_at,
+            listeners: HashMap::new(),
+            processes: HashMap::new(),
+        }
+    }
+
+    /// 按当前监听端口更新在线区间，返回是否有区间开始或结束
+    fn track_presence(&mut self, present: &HashSet<ListenerKey>, now: u64) -> bool {
+        let mut changed = false;
+        self.updated_at = self.updated_at.max(now);
+        for key in present {
+            let entry = self.listeners.entry(key.clone()).or_insert((None, 0));
+            if entry.0.is_none() {
+                entry.0 = Some(now);
+                changed = true;
+            }
+        }
+        for (key, (since, total)) in self.listeners.iter_mut() {
+            if present.contains(key) {
+                continue;
+            }
+            if let Some(since) = since.take() {
+                *total += now.saturating_sub(since);
+                changed = true;
+            }
+        }
+        changed
+    }
+
+    /// 记录一次出站连接，返回是否为新目标
+    fn record_outbound(&mut self, process: String, target: (String, u16)) -> bool {
+        let seen = self
+            .processes
+            .entry(process)
+            .or_default()
+            .entry(target)
+            .or_insert(0);
+        *seen += 1;
+        *seen == 1
+    }
+
+    fn to_saved(&self) -> SavedTraining {
+        let listeners = self
+            .listeners
+            .iter()
+            .map(
+                |((port, protocol, process), (since, total))| SavedPresence {
+                    port: *port,
+                    protocol: *protocol,
+                    process: process.clone(),
+                    online_ms: total
+                        + since.map_or(0, |since| self.updated_at.saturating_sub(since)),
+                },
+            )
+            .collect();
+        let outbound = self
+            .processes
+            .iter()
+            .flat_map(|(process, targets)| {
+                targets.iter().map(|((addr, port), seen)| SavedOutbound {
+                    process: process.clone(),
+                    addr: addr.clone(),
+                    port: *port,
+                    seen: *seen,
+                })
+            })
+            .collect();
+        SavedTraining {
+            started_at: self.started_at,
+            ends_at: self.ends_at,
+            listeners,
+            outbound,
+        }
+    }
+
+    /// 从保存的状态恢复，所有在线区间都已结束，等收到快照后重新开始
+    fn from_saved(saved: SavedTraining) -> Self {
+        let mut training = Self::new(saved.started_at, saved.ends_at);
+        for l in saved.listeners {
+            training
+                .listeners
+                .insert((l.port, l.protocol, l.process), (None, l.online_ms));
+        }
+        for o in saved.outbound {
+            training
+                .processes
+                .entry(o.process)
+                .or_default()
+                .insert((o.addr, o.port), o.seen);
+        }
+        training
+    }
+
+    /// 结束学习，生成基线
+    fn finish(mut self, now: u64) -> Baseline {
+        self.track_presence(&HashSet::new(), now);
+        let span = now.saturating_sub(self.started_at).max(1);
+
+        let mut listeners: Vec<BaselineListener> = self
+            .listeners
+            .into_iter()
+            .map(|((port, protocol, process), (_, total))| BaselineListener {
+                port,
+                protocol,
+                process,
+                presence: (total as f64 / span as f64).min(1.0),
+            })
+            .filter(|l| l.presence >= STABLE_PRESENCE)
+            .collect();
+        listeners.sort_by(|a, b| {
+            (a.port, a.protocol as u8, &a.process).cmp(&(b.port, b.protocol as u8, &b.process))
+        });
+
+        let mut processes: Vec<BaselineProcess> = self
+            .processes
+            .into_iter()
+            .map(|(name, targets)| {
+                let mut destinations: Vec<BaselineDestination> = targets
+                    .into_iter()
+                    .map(|((addr, port), seen)| BaselineDestination { addr, port, seen })
+                    .collect();
+                destinations.sort_by(|a, b| (&a.addr, a.port).cmp(&(&b.addr, b.port)));
+                BaselineProcess { name, destinations }
+            })
+            .collect();
+        processes.sort_by(|a, b| a.name.cmp(&b.name));
+
+        Baseline {
+            trained_from: self.started_at,
+            trained_until: now,
+            listeners,
+            processes,
+        }
+    }
+}
+
+/// 基线学习与偏离检测，由监控事件驱动
+#[derive(Debug, Default)]
+pub struct BaselineRecorder {
+    /// 基线文件，为 None 时不保存
+    path: Option<PathBuf>,
+    baseline: Option<Baseline>,
+    training: Option<Training>,
+    /// 按监控事件维护的当前端口
+    current: HashMap<SocketKey, PortInfo>,
+    /// 已收到过快照，`current` 反映了实际端口
+    synced: bool,
+    drifts: Vec<DriftEvent>,
+    next_drift_id: u64,
+}
+
+impl BaselineRecorder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 从基线文件加载，之后的修改都保存到该文件，文件不存在时从空基线开始
+    pub fn load(&mut self, path: &Path) -> AppResult<()> {
+        self.path = Some(path.to_path_buf());
+
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(AppError::Io(e.to_string())),
+        };
+        let saved: SavedBaseline = serde_json::from_str(&content)
+            .map_err(|e| AppError::Internal(format!("基线格式错误 {}: {}", path.display(), e)))?;
+
+        if saved.format_version > BASELINE_FORMAT_VERSION {
+            return Err(AppError::Internal(format!(
+                "基线文件的格式版本 {} 高于当前支持的版本 {}",
+                saved.format_version, BASELINE_FORMAT_VERSION
+            )));
+        }
+
+        self.baseline = saved.baseline;
+        self.drifts = saved.drifts;
+        self.next_drift_id = saved.next_drift_id;
+        self.training = saved.training.map(Training::from_saved);
+        Ok(())
+    }
+
+    /// 当前状态，学习到期时先生成基线
+    pub fn status(&mut self, now: u64) -> BaselineStatus {
+        self.advance(now);
+
+        let mode = if self.training.is_some() {
+            BaselineMode::Training
+        } else if self.baseline.is_some() {
+            BaselineMode::Active
+        } else {
+            BaselineMode::Empty
+        };
+
+        BaselineStatus {
+            mode,
+            training_started_at: self.training.as_ref().map(|t| t.started_at),
+            training_ends_at: self.training.as_ref().map(|t| t.ends_at),
+            trained_until: self.baseline.as_ref().map(|b| b.trained_until),
+            listeners: self.baseline.as_ref().map_or(0, |b| b.listeners.len()),
+            processes: self.baseline.as_ref().map_or(0, |b| b.processes.len()),
+            pending_drifts: self.drifts.len(),
+        }
+    }
+
+    /// 当前基线
+    pub fn baseline(&self) -> Option<&Baseline> {
+        self.baseline.as_ref()
+    }
+
+    /// 待处理的偏离事件，按出现顺序排列
+    pub fn drifts(&self) -> &[DriftEvent] {
+        &self.drifts
+    }
+
+    /// 开始学习，`duration_ms` 后用学习结果替换基线
+    ///
+    /// 学习期间不检测偏离，原有的待处理偏离被清空。需要先收到监控快照
+    pub fn start_training(&mut self, duration_ms: u64, now: u64) -> AppResult<BaselineStatus> {
+        if duration_ms == 0 {
+            return Err(AppError::InvalidArgument("学习时长必须大于 0".to_string()));
+        }
+        if !self.synced {
+            return Err(AppError::MonitorNotRunning);
+        }
+
+        let listening = self.listening_ports();
+        let mut training = Training::new(now, now.saturating_add(duration_ms));
+        training.track_presence(&listener_keys(&self.current), now);
+        for info in self.current.values() {
+            if let (Some(process), Some(target)) =
+                (process_name(info), outbound_target(info, &listening))
+            {
+                training.record_outbound(process, target);
+            }
+        }
+
+        self.training = Some(training);
+        self.drifts.clear();
+        self.save()?;
+        Ok(self.status(now))
+    }
+
+    /// 处理一个监控事件，返回新产生的偏离事件
+    pub fn observe(&mut self, event: &MonitorEvent) -> Vec<DriftEvent> {
+        let now = match event {
+            MonitorEvent::Snapshot(snapshot) => snapshot.timestamp,
+            MonitorEvent::Change(change) => change.timestamp,
+        };
+        self.advance(now);
+
+        let touched: Vec<PortInfo> = match event {
+            MonitorEvent::Snapshot(snapshot) => {
+                self.current = snapshot_map(snapshot.ports.clone());
+                self.synced = true;
+                snapshot.ports.clone()
+            }
+            MonitorEvent::Change(change) => {
+                let mut touched = Vec::new();
+                for entry in &change.entries {
+                    let key = socket_key(&entry.info);
+                    if let ChangeKind::Removed = entry.kind {
+                        self.current.remove(&key);
+                    } else {
+                        self.current.insert(key, entry.info.clone());
+                        touched.push(entry.info.clone());
+                    }
+                }
+                touched
+            }
+        };
+
+        let listening = self.listening_ports();
+
+        if let Some(training) = self.training.as_mut() {
+            let mut changed = training.track_presence(&listener_keys(&self.current), now);
+            for info in &touched {
+                if let (Some(process), Some(target)) =
+                    (process_name(info), outbound_target(info, &listening))
+                {
+                    changed |= training.record_outbound(process, target);
+                }
+            }
+            if changed {
+                self.persist();
+            }
+            return Vec::new();
+        }
+
+        let Some(baseline) = self.baseline.as_ref() else {
+            return Vec::new();
+        };
+        let maturity = maturity(baseline);
+        let candidates: Vec<DriftEvent> = touched
+            .iter()
+            .filter_map(|info| {
+                if info.state.is_listening() {
+                    check_listener(baseline, info, maturity, now)
+                } else {
+                    let target = outbound_target(info, &listening)?;
+                    check_outbound(baseline, info, target, maturity, now)
+                }
+            })
+            .collect();
+
+        let created: Vec<DriftEvent> = candidates
+            .into_iter()
+            .filter_map(|drift| self.record(drift))
+            .collect();
+        if !created.is_empty() {
+            self.persist();
+        }
+        created
+    }
+
+    /// 将偏离并入基线并从待处理列表移除，返回处理的数量
+    pub fn approve(&mut self, ids: &[u64]) -> AppResult<usize> {
+        let Some(baseline) = self.baseline.as_mut() else {
+            return Ok(0);
+        };
+
+        let (approved, pending): (Vec<DriftEvent>, Vec<DriftEvent>) =
+            std::mem::take(&mut self.drifts)
+                .into_iter()
+                .partition(|d| ids.contains(&d.id));
+        self.drifts = pending;
+        for drift in &approved {
+            merge(baseline, drift);
+        }
+
+        self.save()?;
+        Ok(approved.len())
+    }
+
+    /// 忽略偏离（不并入基线），返回处理的数量
+    pub fn dismiss(&mut self, ids: &[u64]) -> AppResult<usize> {
+        let before = self.drifts.len();
+        self.drifts.retain(|d| !ids.contains(&d.id));
+        self.save()?;
+        Ok(before - self.drifts.len())
+    }
+
+    /// 学习时间到期时生成基线
+    fn advance(&mut self, now: u64) {
+        match &self.training {
+            Some(training) if now >= training.ends_at => {}
+            _ => return,
+        }
+        let Some(training) = self.training.take() else {
+            return;
+        };
+
+        let ends_at = training.ends_at;
+        let baseline = training.finish(ends_at);
+        tracing::info!(
+            "Baseline trained: {} listeners, {} outbound processes",
+            baseline.listeners.len(),
+            baseline.processes.len()
+        );
+        self.baseline = Some(baseline);
+        self.persist();
+    }
+
+    /// 记录偏离，同一对象已有待处理的偏离时只更新次数，返回新建的偏离
+    fn record(&mut self, drift: DriftEvent) -> Option<DriftEvent> {
+        if let Some(existing) = self.drifts.iter_mut().find(|d| same_subject(d, &drift)) {
+            existing.pid = drift.pid;
+            existing.last_seen = drift.last_seen;
+            existing.occurrences += 1;
+            return None;
+        }
+
+        self.next_drift_id += 1;
+        let drift = DriftEvent {
+            id: self.next_drift_id,
+            ..drift
+        };
+        tracing::warn!(
+            "Baseline drift #{} ({:.2}): {}",
+            drift.id,
+            drift.confidence,
+            drift.message
+        );
+
+        self.drifts.push(drift.clone());
+        if self.drifts.len() > MAX_PENDING_DRIFTS {
+            self.drifts.remove(0);
+        }
+        Some(drift)
+    }
+
+    /// 当前、学习中和基线中的监听端口，用于区分入站与出站连接
+    fn listening_ports(&self) -> HashSet<PortKey> {
+        let mut ports: HashSet<PortKey> = self
+            .current
+            .values()
+            .filter(|p| p.state.is_listening())
+            .map(|p| (p.port, p.protocol))
+            .collect();
+        if let Some(training) = &self.training {
+            ports.extend(
+                training
+                    .listeners
+                    .keys()
+                    .map(|(port, protocol, _)| (*port, *protocol)),
+            );
+        }
+        if let Some(baseline) = &self.baseline {
+            ports.extend(baseline.listeners.iter().map(|l| (l.port, l.protocol)));
+        }
+        ports
+    }
+
+    fn save(&self) -> AppResult<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|e| AppError::Io(e.to_string()))?;
+        }
+
+        let saved = SavedBaseline {
+            format_version: BASELINE_FORMAT_VERSION,
+            baseline: self.baseline.clone(),
+            drifts: self.drifts.clone(),
+            next_drift_id: self.next_drift_id,
+            training: self.training.as_ref().map(Training::to_saved),
+        };
+        let json =
+            serde_json::to_string_pretty(&saved).map_err(|e| AppError::Internal(e.to_string()))?;
+        fs::write(path, json).map_err(|e| AppError::Io(e.to_string()))
+    }
+
+    /// 保存失败只记录日志，不影响事件处理
+    fn persist(&self) {
+        if let Err(e) = self.save() {
+            tracing::warn!("Failed to save baseline: {}", e);
+        }
+    }
+}
+
+fn process_name(info: &PortInfo) -> Option<String> {
+    info.process.as_ref().map(|p| p.name.clone())
+}
+
+fn process_label(process: &Option<String>) -> String {
+    process.clone().unwrap_or_else(|| "未知进程".to_string())
+}
+
+fn listener_keys(current: &HashMap<SocketKey, PortInfo>) -> HashSet<ListenerKey> {
+    current
+        .values()
+        .filter(|p| p.state.is_listening())
+        .map(|p| (p.port, p.protocol, process_name(p)))
+        .collect()
+}
+
+/// 出站连接的目标：本地端口不是监听端口、远程地址不是本机的已建立 TCP 连接
+fn outbound_target(info: &PortInfo, listening: &HashSet<PortKey>) -> Option<(String, u16)> {
+    if info.protocol != Protocol::TCP
+        || info.state != ConnectionState::Established
+        || listening.contains(&(info.port, info.protocol))
+    {
+        return None;
+    }
+
+    let (addr, port) = (info.remote_addr.as_ref()?, info.remote_port?);
+    let ip = parse_addr(addr)?.to_canonical();
+    if ip.is_loopback() || ip.is_unspecified() {
+        return None;
+    }
+    Some((addr.clone(), port))
+}
+
+/// 学习时长带来的置信度系数（0.5~1）
+fn maturity(baseline: &Baseline) -> f64 {
+    let span = baseline.trained_until.saturating_sub(baseline.trained_from);
+    0.5 + 0.5 * (span as f64 / FULL_TRAINING_MS as f64).min(1.0)
+}
+
+fn check_listener(
+    baseline: &Baseline,
+    info: &PortInfo,
+    maturity: f64,
+    now: u64,
+) -> Option<DriftEvent> {
+    let process = process_name(info);
+    let owners: Vec<&BaselineListener> = baseline
+        .listeners
+        .iter()
+        .filter(|l| l.port == info.port && l.protocol == info.protocol)
+        .collect();
+
+    if owners.is_empty() {
+        let message = format!(
+            "新的监听端口 {}/{:?}（{}）",
+            info.port,
+            info.protocol,
+            process_label(&process)
+        );
+        return Some(new_drift(
+            DriftKind::NewListener,
+            info,
+            (info.local_addr.clone(), info.port),
+            0.9 * maturity,
+            message,
+            now,
+        ));
+    }
+    if owners.iter().any(|l| l.process == process) {
+        return None;
+    }
+
+    // 基线中的占用进程越稳定，占用变化越可疑
+    let presence = owners.iter().map(|l| l.presence).fold(0.0, f64::max);
+    let expected: Vec<String> = owners.iter().map(|l| process_label(&l.process)).collect();
+    let message = format!(
+        "端口 {}/{:?} 由 {} 监听，基线中为 {}",
+        info.port,
+        info.protocol,
+        process_label(&process),
+        expected.join("、")
+    );
+    Some(DriftEvent {
+        expected,
+        ..new_drift(
+            DriftKind::OwnerChanged,
+            info,
+            (info.local_addr.clone(), info.port),
+            (0.5 + 0.45 * presence) * maturity,
+            message,
+            now,
+        )
+    })
+}
+
+fn check_outbound(
+    baseline: &Baseline,
+    info: &PortInfo,
+    target: (String, u16),
+    maturity: f64,
+    now: u64,
+) -> Option<DriftEvent> {
+    let name = process_name(info)?;
+    let Some(process) = baseline.processes.iter().find(|p| p.name == name) else {
+        let message = format!("{} 开始连接外部地址 {}:{}", name, target.0, target.1);
+        return Some(new_drift(
+            DriftKind::NewOutboundProcess,
+            info,
+            target,
+            0.8 * maturity,
+            message,
+            now,
+        ));
+    };
+
+    let (addr, port) = &target;
+    if process
+        .destinations
+        .iter()
+        .any(|d| d.addr == *addr && d.port == *port)
+    {
+        return None;
+    }
+
+    // 同一服务端口换了地址（如 CDN）不太可疑，目标本来就多的进程也不太可疑
+    let known_port = process.destinations.iter().any(|d| d.port == *port);
+    let base = if known_port { 0.3 } else { 0.6 };
+    let spread = 1.0 + process.destinations.len() as f64 / 10.0;
+    let message = format!("{} 连接了基线之外的目标 {}:{}", name, addr, port);
+    Some(new_drift(
+        DriftKind::NewDestination,
+        info,
+        target,
+        base / spread * maturity,
+        message,
+        now,
+    ))
+}
+
+fn new_drift(
+    kind: DriftKind,
+    info: &PortInfo,
+    (addr, port): (String, u16),
+    confidence: f64,
+    message: String,
+    now: u64,
+) -> DriftEvent {
+    DriftEvent {
+        id: 0,
+        kind,
+        protocol: info.protocol,
+        port,
+        addr,
+        pid: info.pid,
+        process: process_name(info),
+        expected: Vec::new(),
+        confidence: (confidence * 100.0).round() / 100.0,
+        message,
+        first_seen: now,
+        last_seen: now,
+        occurrences: 1,
+    }
+}
+
+/// 同一对象的偏离：监听类按端口和进程，出站类按进程（和目标）
+fn same_subject(a: &DriftEvent, b: &DriftEvent) -> bool {
+    a.kind == b.kind
+        && a.process == b.process
+        && match a.kind {
+            DriftKind::NewListener | DriftKind::OwnerChanged => {
+                (a.port, a.protocol) == (b.port, b.protocol)
+            }
+            DriftKind::NewOutboundProcess => true,
+            DriftKind::NewDestination => (&a.addr, a.port) == (&b.addr, b.port),
+        }
+}
+
+/// 将偏离对应的监听或出站目标加入基线
+fn merge(baseline: &mut Baseline, drift: &DriftEvent) {
+    match drift.kind {
+        DriftKind::NewListener | DriftKind::OwnerChanged => {
+            let known = baseline.listeners.iter().any(|l| {
+                l.port == drift.port && l.protocol == drift.protocol && l.process == drift.process
+            });
+            if !known {
+                baseline.listeners.push(BaselineListener {
+                    port: drift.port,
+                    protocol: drift.protocol,
+                    process: drift.process.clone(),
+                    presence: 1.0,
+                });
+            }
+        }
+        DriftKind::NewOutboundProcess | DriftKind::NewDestination => {
+            let Some(name) = &drift.process else {
+                return;
+            };
+            let index = match baseline.processes.iter().position(|p| p.name == *name) {
+                Some(index) => index,
+                None => {
+                    baseline.processes.push(BaselineProcess {
+                        name: name.clone(),
+                        destinations: Vec::new(),
+                    });
+                    baseline.processes.len() - 1
+                }
+            };
+            let process = &mut baseline.processes[index];
+            if !process
+                .destinations
+                .iter()
+                .any(|d| d.addr == drift.addr && d.port == drift.port)
+            {
+                process.destinations.push(BaselineDestination {
+                    addr: drift.addr.clone(),
+                    port: drift.port,
+                    seen: drift.occurrences,
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{ChangeEntry, PortChange, PortSnapshot, ProcessInfo};
+
+    const HOUR: u64 = 60 * 60 * 1000;
+
+    fn listener(port: u16, process: &str) -> PortInfo {
+        let mut info = PortInfo::new(port, Protocol::TCP, "0.0.0.0".to_string(), 100);
+        info.state = ConnectionState::Listen;
+        info.process = Some(ProcessInfo::new(100, process.to_string()));
+        info
+    }
+
+    fn snapshot(timestamp: u64, ports: Vec<PortInfo>) -> MonitorEvent {
+        MonitorEvent::Snapshot(PortSnapshot {
+            seq: 1,
+            timestamp,
+            snapshot_version: 1,
+            ports,
+        })
+    }
+
+    fn added(timestamp: u64, info: PortInfo) -> MonitorEvent {
+        MonitorEvent::Change(PortChange {
+            seq: 2,
+            timestamp,
+            snapshot_version: 2,
+            entries: vec![ChangeEntry::added(info)],
+        })
+    }
+
+    fn temp_path(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "port-detection-baseline-{}-{}.json",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_file(&path);
+        path
+    }
+
+    #[test]
+    fn test_training_requires_snapshot() {
+        let mut recorder = BaselineRecorder::new();
+        assert!(matches!(
+            recorder.start_training(HOUR, 0),
+            Err(AppError::MonitorNotRunning)
+        ));
+        assert!(matches!(
+            recorder.start_training(0, 0),
+            Err(AppError::InvalidArgument(_))
+        ));
+        assert_eq!(recorder.status(0).mode, BaselineMode::Empty);
+
+        // 没有端口的快照也算已同步
+        recorder.observe(&snapshot(0, Vec::new()));
+        assert!(recorder.start_training(HOUR, 0).is_ok());
+    }
+
+    #[test]
+    fn test_state_machine() {
+        let mut recorder = BaselineRecorder::new();
+        recorder.observe(&snapshot(0, vec![listener(22, "sshd")]));
+
+        let status = recorder.start_training(HOUR, 0).unwrap();
+        assert_eq!(status.mode, BaselineMode::Training);
+        assert_eq!(status.training_ends_at, Some(HOUR));
+
+        // 学习期间不产生偏离
+        assert!(recorder
+            .observe(&added(1000, listener(80, "nginx")))
+            .is_empty());
+
+        let status = recorder.status(HOUR);
+        assert_eq!(status.mode, BaselineMode::Active);
+        assert_eq!(status.listeners, 2);
+        assert_eq!(status.trained_until, Some(HOUR));
+
+        let drifts = recorder.observe(&added(HOUR + 1, listener(8080, "node")));
+        assert_eq!(drifts.len(), 1);
+        assert_eq!(drifts[0].kind, DriftKind::NewListener);
+        assert!(recorder
+            .observe(&added(HOUR + 2, listener(22, "sshd")))
+            .is_empty());
+        let owner = recorder.observe(&added(HOUR + 3, listener(80, "python")));
+        assert_eq!(owner[0].kind, DriftKind::OwnerChanged);
+
+        // 同一对象只累计次数
+        assert!(recorder
+            .observe(&added(HOUR + 4, listener(8080, "node")))
+            .is_empty());
+        assert_eq!(recorder.drifts()[0].occurrences, 2);
+
+        assert_eq!(recorder.approve(&[drifts[0].id]).unwrap(), 1);
+        assert_eq!(recorder.dismiss(&[owner[0].id]).unwrap(), 1);
+        let status = recorder.status(HOUR + 5);
+        assert_eq!((status.listeners, status.pending_drifts), (3, 0));
+        assert!(recorder
+            .observe(&added(HOUR + 6, listener(8080, "node")))
+            .is_empty());
+
+        // 重新学习清空待处理的偏离
+        recorder.observe(&added(HOUR + 7, listener(9000, "php")));
+        assert_eq!(recorder.drifts().len(), 1);
+        let status = recorder.start_training(HOUR, HOUR + 8).unwrap();
+        assert_eq!(
+            (status.mode, status.pending_drifts),
+            (BaselineMode::Training, 0)
+        );
+    }
+
+    #[test]
+    fn test_unstable_listener_excluded() {
+        let mut recorder = BaselineRecorder::new();
+        recorder.observe(&snapshot(0, vec![listener(22, "sshd")]));
+        recorder.start_training(100 * 1000, 0).unwrap();
+        recorder.observe(&added(1000, listener(8080, "node")));
+        recorder.observe(&MonitorEvent::Change(PortChange {
+            seq: 3,
+            timestamp: 2000,
+            snapshot_version: 3,
+            entries: vec![ChangeEntry::removed(listener(8080, "node"))],
+        }));
+
+        recorder.status(100 * 1000);
+        let ports: Vec<u16> = recorder
+            .baseline()
+            .unwrap()
+            .listeners
+            .iter()
+            .map(|l| l.port)
+            .collect();
+        assert_eq!(ports, vec![22]);
+    }
+
+    #[test]
+    fn test_training_survives_restart() {
+        let path = temp_path("restart");
+        let mut recorder = BaselineRecorder::new();
+        recorder.load(&path).unwrap();
+        recorder.observe(&snapshot(0, vec![listener(22, "sshd")]));
+        recorder.start_training(10 * HOUR, 0).unwrap();
+        recorder.observe(&added(HOUR, listener(80, "nginx")));
+        recorder.observe(&added(4 * HOUR, listener(443, "nginx")));
+        drop(recorder);
+
+        let mut restored = BaselineRecorder::new();
+        restored.load(&path).unwrap();
+        let status = restored.status(5 * HOUR);
+        assert_eq!(status.mode, BaselineMode::Training);
+        assert_eq!(status.training_started_at, Some(0));
+        assert_eq!(status.training_ends_at, Some(10 * HOUR));
+
+        // 停机期间不计入在线时长：80 在停机前在线 3 小时，重启后不再出现
+        restored.observe(&snapshot(6 * HOUR, vec![listener(22, "sshd")]));
+        restored.status(10 * HOUR);
+        let listeners = &restored.baseline().unwrap().listeners;
+        let presence: Vec<(u16, f64)> = listeners.iter().map(|l| (l.port, l.presence)).collect();
+        assert_eq!(presence, vec![(22, 0.8), (80, 0.3)]);
+
+        let _ = fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,294 @@
+//! 变化事件合并
+//!
+//! 位于 `PortMonitor::poll` 与事件发送之间，过滤短连接噪声、合并抖动并按窗口批量发送
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use crate::core::monitor::{socket_key, MonitorConfig, SocketKey};
+use crate::models::{ChangeEntry, ChangeKind, PortInfo};
+
+/// 变化事件合并器
+///
+/// 同时维护订阅者已知的状态和最新采样状态，刷新时按阈值比较两者并生成变化
+pub struct ChangeCoalescer {
+    min_lifetime: Duration,
+    flap_window: Duration,
+    /// 订阅者已知的 socket 状态
+    reported: HashMap<SocketKey, PortInfo>,
+    /// 最新采样得到的 socket 状态
+    current: HashMap<SocketKey, PortInfo>,
+    /// 有未发送变化的 socket，值为最近一次出现或消失的时间
+    dirty: HashMap<SocketKey, Instant>,
+    /// 被过滤的短连接数量（仅用于日志）
+    suppressed: u64,
+}
+
+impl ChangeCoalescer {
+    pub fn new(config: &MonitorConfig) -> Self {
+        Self {
+            min_lifetime: Duration::from_millis(config.min_lifetime_ms),
+            flap_window: Duration::from_millis(config.flap_window_ms),
+            reported: HashMap::new(),
+            current: HashMap::new(),
+            dirty: HashMap::new(),
+            suppressed: 0,
+        }
+    }
+
+    /// 合并一次采样得到的变化
+    pub fn push(&mut self, entries: Vec<ChangeEntry>, now: Instant) {
+        for entry in entries {
+            let key = socket_key(&entry.info);
+            let was_present = self.current.contains_key(&key);
+
+            if entry.kind == ChangeKind::Removed {
+                self.current.remove(&key);
+            } else {
+                self.current.insert(key.clone(), entry.info);
+            }
+
+            if was_present != self.current.contains_key(&key) {
+                self.dirty.insert(key, now);
+            } else {
+                self.dirty.entry(key).or_insert(now);
+            }
+        }
+    }
+
+    /// 取出已满足条件的变化，没有可发送内容时返回 None
+    pub fn flush(&mut self, now: Instant) -> Option<Vec<ChangeEntry>> {
+        let mut ready = Vec::new();
+
+        for (key, since) in &self.dirty {
+            let age = now.duration_since(*since);
+            let is_ready = match (
+                self.reported.contains_key(key),
+                self.current.contains_key(key),
+            ) {
+                (false, true) => age >= self.min_lifetime,
+                (true, false) => age >= self.flap_window,
+                _ => true,
+            };
+            if is_ready {
+                ready.push(key.clone());
+            }
+        }
+
+        ready.sort();
+
+        let mut entries = Vec::new();
+
+        for key in ready {
+            self.dirty.remove(&key);
+
+            match (self.reported.get(&key), self.current.get(&key)) {
+                (None, Some(new)) => {
+                    entries.push(ChangeEntry::added(new.clone()));
+                    self.reported.insert(key, new.clone());
+                }
+                (Some(_), None) => {
+                    if let Some(old) = self.reported.remove(&key) {
+                        entries.push(ChangeEntry::removed(old));
+                    }
+                }
+                // 抖动后恢复原状时不产生任何变化
+                (Some(old), Some(new)) => {
+                    entries.extend(ChangeEntry::between(old, new));
+                    self.reported.insert(key, new.clone());
+                }
+                // 尚未上报就已消失：存活时间不足，直接丢弃
+                (None, None) => self.suppressed += 1,
+            }
+        }
+
+        if self.suppressed > 0 {
+            tracing::debug!("Suppressed {} short-lived sockets", self.suppressed);
+            self.suppressed = 0;
+        }
+
+        if entries.is_empty() {
+            None
+        } else {
+            Some(entries)
+        }
+    }
+
+    /// 以新的基线重置状态，丢弃所有未发送的变化
+    pub fn reset(&mut self, baseline: &HashMap<SocketKey, PortInfo>) {
+        self.reported = baseline.clone();
+        self.current = baseline.clone();
+        self.dirty.clear();
+        self.suppressed = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::monitor::snapshot_map;
+    use crate::models::{ConnectionState, Protocol};
+
+    fn config() -> MonitorConfig {
+        MonitorConfig {
+            min_lifetime_ms: 3000,
+            flap_window_ms: 3000,
+            ..MonitorConfig::default()
+        }
+    }
+
+    fn port(port: u16, pid: u32) -> PortInfo {
+        let mut info = PortInfo::new(port, Protocol::TCP, "0.0.0.0".to_string(), pid);
+        info.state = ConnectionState::Listen;
+        info
+    }
+
+    fn ms(start: Instant, millis: u64) -> Instant {
+        start + Duration::from_millis(millis)
+    }
+
+    /// 将变化简化为 (类型, 端口, PID) 便于比较
+    fn flushed(entries: Option<Vec<ChangeEntry>>) -> Vec<(ChangeKind, u16, u32)> {
+        entries
+            .unwrap_or_default()
+            .into_iter()
+            .map(|e| (e.kind, e.info.port, e.info.pid))
+            .collect()
+    }
+
+    #[test]
+    fn test_short_lived_socket_is_suppressed() {
+        let start = Instant::now();
+        let mut coalescer = ChangeCoalescer::new(&config());
+
+        coalescer.push(vec![ChangeEntry::added(port(8080, 1))], start);
+        assert!(coalescer.flush(ms(start, 1000)).is_none());
+
+        coalescer.push(vec![ChangeEntry::removed(port(8080, 1))], ms(start, 2000));
+        assert!(coalescer.flush(ms(start, 2000)).is_none());
+        assert!(coalescer.flush(ms(start, 10_000)).is_none());
+    }
+
+    #[test]
+    fn test_added_after_min_lifetime() {
+        let start = Instant::now();
+        let mut coalescer = ChangeCoalescer::new(&config());
+
+        coalescer.push(vec![ChangeEntry::added(port(8080, 1))], start);
+        assert!(coalescer.flush(ms(start, 2999)).is_none());
+        assert_eq!(
+            flushed(coalescer.flush(ms(start, 3000))),
+            vec![(ChangeKind::Added, 8080, 1)]
+        );
+        assert!(coalescer.flush(ms(start, 5000)).is_none());
+    }
+
+    #[test]
+    fn test_flap_is_merged() {
+        let start = Instant::now();
+        let mut coalescer = ChangeCoalescer::new(&config());
+        coalescer.reset(&snapshot_map(vec![port(80, 1)]));
+
+        coalescer.push(vec![ChangeEntry::removed(port(80, 1))], start);
+        assert!(coalescer.flush(ms(start, 1000)).is_none());
+        coalescer.push(vec![ChangeEntry::added(port(80, 1))], ms(start, 1500));
+        assert!(coalescer.flush(ms(start, 1500)).is_none());
+        assert!(coalescer.flush(ms(start, 10_000)).is_none());
+    }
+
+    #[test]
+    fn test_flap_with_new_owner_reports_owner_change() {
+        let start = Instant::now();
+        let mut coalescer = ChangeCoalescer::new(&config());
+        coalescer.reset(&snapshot_map(vec![port(80, 1)]));
+
+        coalescer.push(vec![ChangeEntry::removed(port(80, 1))], start);
+        coalescer.push(vec![ChangeEntry::added(port(80, 2))], ms(start, 500));
+        assert_eq!(
+            flushed(coalescer.flush(ms(start, 500))),
+            vec![(
+                ChangeKind::OwnerChanged {
+                    old_pid: 1,
+                    new_pid: 2
+                },
+                80,
+                2
+            )]
+        );
+    }
+
+    #[test]
+    fn test_removed_after_flap_window() {
+        let start = Instant::now();
+        let mut coalescer = ChangeCoalescer::new(&config());
+        coalescer.reset(&snapshot_map(vec![port(80, 1)]));
+
+        coalescer.push(vec![ChangeEntry::removed(port(80, 1))], start);
+        assert!(coalescer.flush(ms(start, 2000)).is_none());
+        assert_eq!(
+            flushed(coalescer.flush(ms(start, 3000))),
+            vec![(ChangeKind::Removed, 80, 1)]
+        );
+    }
+
+    #[test]
+    fn test_changes_are_batched_and_sorted() {
+        let start = Instant::now();
+        let mut coalescer = ChangeCoalescer::new(&config());
+
+        coalescer.push(vec![ChangeEntry::added(port(9000, 1))], start);
+        coalescer.push(
+            vec![
+                ChangeEntry::added(port(443, 2)),
+                ChangeEntry::added(port(22, 3)),
+            ],
+            ms(start, 500),
+        );
+
+        // 第一个端口已满足最小存活时间，其余端口等到同一批次再发送
+        assert_eq!(
+            flushed(coalescer.flush(ms(start, 3000))),
+            vec![(ChangeKind::Added, 9000, 1)]
+        );
+        assert_eq!(
+            flushed(coalescer.flush(ms(start, 3500))),
+            vec![(ChangeKind::Added, 22, 3), (ChangeKind::Added, 443, 2)]
+        );
+    }
+
+    #[test]
+    fn test_reset_discards_pending_changes() {
+        let start = Instant::now();
+        let mut coalescer = ChangeCoalescer::new(&config());
+
+        coalescer.push(vec![ChangeEntry::added(port(8080, 1))], start);
+        coalescer.reset(&HashMap::new());
+        assert!(coalescer.flush(ms(start, 10_000)).is_none());
+    }
+
+    #[test]
+    fn test_sockets_sharing_a_port_are_tracked_separately() {
+        let start = Instant::now();
+        let mut coalescer = ChangeCoalescer::new(&config());
+        coalescer.reset(&snapshot_map(vec![port(80, 1)]));
+
+        let mut conn = port(80, 1);
+        conn.local_addr = "10.0.0.2".to_string();
+        conn.remote_addr = Some("10.0.0.9".to_string());
+        conn.remote_port = Some(51000);
+        conn.state = ConnectionState::Established;
+
+        // 监听端口上的连接不会覆盖监听 socket，连接消失也不会移除监听
+        coalescer.push(vec![ChangeEntry::added(conn.clone())], start);
+        let added = coalescer.flush(ms(start, 3000)).unwrap();
+        assert_eq!(added.len(), 1);
+        assert_eq!(added[0].info.remote_port, Some(51000));
+
+        coalescer.push(vec![ChangeEntry::removed(conn)], ms(start, 4000));
+        let removed = coalescer.flush(ms(start, 7000)).unwrap();
+        assert_eq!(removed.len(), 1);
+        assert_eq!(removed[0].kind, ChangeKind::Removed);
+        assert_eq!(removed[0].info.remote_port, Some(51000));
+        assert!(coalescer.flush(ms(start, 20_000)).is_none());
+    }
+}
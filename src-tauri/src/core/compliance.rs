@@ -0,0 +1,305 @@
+//! 监听策略合规检查
+//!
+//! 按策略逐个检查监听端口：未声明的端口、进程/程序/用户不符、可达范围过大，
+//! 以及策略要求但没有在监听的服务
+
+use crate::core::exposure::{exposure_level, host_interfaces};
+use crate::core::PortScanner;
+use crate::models::{
+    AppResult, ComplianceReport, ExposureLevel, ListenerPolicy, ListenerRule, NetworkInterface,
+    PolicyViolation, PortInfo, ViolationKind,
+};
+use crate::utils::time::now_millis;
+
+/// 扫描监听端口并按策略检查
+pub fn scan_compliance(
+    scanner: &PortScanner,
+    policy: &ListenerPolicy,
+) -> AppResult<ComplianceReport> {
+    let ports = scanner.scan_listening()?;
+    Ok(check_compliance(&ports, &host_interfaces(), policy))
+}
+
+/// 按策略检查端口列表中的监听端口
+pub fn check_compliance(
+    ports: &[PortInfo],
+    interfaces: &[NetworkInterface],
+    policy: &ListenerPolicy,
+) -> ComplianceReport {
+    let mut listeners: Vec<&PortInfo> = ports.iter().filter(|p| p.state.is_listening()).collect();
+    listeners.sort_by(|a, b| {
+        (a.port, a.protocol as u8, &a.local_addr).cmp(&(b.port, b.protocol as u8, &b.local_addr))
+    });
+
+    let mut present = vec![false; policy.listeners.len()];
+    let mut violations = Vec::new();
+    let mut checked = 0;
+
+    for port in listeners {
+        let candidates: Vec<(usize, &ListenerRule)> = policy
+            .listeners
+            .iter()
+            .enumerate()
+            .filter(|(_, rule)| rule.covers(port.port, port.protocol))
+            .collect();
+        for (i, _) in &candidates {
+            present[*i] = true;
+        }
+
+        let exposure = exposure_level(port, interfaces);
+        if policy.ignore_loopback && exposure == ExposureLevel::Loopback {
+            continue;
+        }
+        checked += 1;
+
+        let violation = |kind, rule: Option<&ListenerRule>, message: String| PolicyViolation {
+            kind,
+            rule: rule.map(ListenerRule::label),
+            port: port.port,
+            protocol: Some(port.protocol),
+            local_addr: Some(port.local_addr.clone()),
+            pid: Some(port.pid),
+            process_name: port.process.as_ref().map(|p| p.name.clone()),
+            message,
+        };
+
+        if candidates.is_empty() {
+            if !policy.allow_unlisted {
+                violations.push(violation(
+                    ViolationKind::Unexpected,
+                    None,
+                    "策略未声明该监听端口".to_string(),
+                ));
+            }
+            continue;
+        }
+
+        let mismatches: Vec<Vec<String>> = candidates
+            .iter()
+            .map(|(_, rule)| owner_mismatches(rule, port))
+            .collect();
+        let owned: Vec<&ListenerRule> = candidates
+            .iter()
+            .zip(&mismatches)
+            .filter(|(_, problems)| problems.is_empty())
+            .map(|((_, rule), _)| *rule)
+            .collect();
+
+        let Some(first) = owned.first() else {
+            violations.push(violation(
+                ViolationKind::WrongOwner,
+                Some(candidates[0].1),
+                mismatches[0].join("；"),
+            ));
+            continue;
+        };
+
+        let allowed = |rule: &ListenerRule| match rule.bind {
+            Some(bind) => exposure <= bind,
+            None => true,
+        };
+        if !owned.iter().any(|rule| allowed(rule)) {
+            let bind = first.bind.unwrap_or(ExposureLevel::Public);
+            violations.push(violation(
+                ViolationKind::TooBroad,
+                Some(first),
+                format!("可达范围为 {}，策略只允许 {}", exposure.name(), bind.name()),
+            ));
+        }
+    }
+
+    for (rule, present) in policy.listeners.iter().zip(present) {
+        if rule.required && !present {
+            violations.push(PolicyViolation {
+                kind: ViolationKind::Missing,
+                rule: Some(rule.label()),
+                port: rule.port.start,
+                protocol: rule.protocol,
+                local_addr: None,
+                pid: None,
+                process_name: None,
+                message: "策略要求的服务没有在监听".to_string(),
+            });
+        }
+    }
+
+    ComplianceReport {
+        checked_at: now_millis(),
+        checked,
+        violations,
+    }
+}
+
+/// 进程名、程序路径和用户与规则不符之处，无法获取的信息视为不符
+fn owner_mismatches(rule: &ListenerRule, port: &PortInfo) -> Vec<String> {
+    let mut problems = Vec::new();
+    let process = port.process.as_ref();
+
+    if let Some(expected) = &rule.process {
+        match process {
+            Some(p) if same_process_name(&p.name, expected) => {}
+            Some(p) => problems.push(format!("进程为 {}，策略要求 {}", p.name, expected)),
+            None => problems.push(format!("无法获取进程信息，策略要求 {}", expected)),
+        }
+    }
+
+    if let Some(expected) = &rule.exe {
+        match process.and_then(|p| p.exe_path.as_deref()) {
+            Some(path) if same_path(path, expected) => {}
+            Some(path) => problems.push(format!("程序为 {}，策略要求 {}", path, expected)),
+            None => problems.push(format!("无法获取程序路径，策略要求 {}", expected)),
+        }
+    }
+
+    if let Some(expected) = &rule.user {
+        match process_user(port.pid) {
+            Some(user) if user == *expected => {}
+            Some(user) => problems.push(format!("用户为 {}，策略要求 {}", user, expected)),
+            None => problems.push(format!("无法获取进程所属用户，策略要求 {}", expected)),
+        }
+    }
+
+    problems
+}
+
+fn same_process_name(actual: &str, expected: &str) -> bool {
+    let normalize = |name: &str| {
+        let name = name.to_lowercase();
+        name.strip_suffix(".exe")
+            .map(str::to_string)
+            .unwrap_or(name)
+    };
+    normalize(actual) == normalize(expected)
+}
+
+/// Windows 路径不区分大小写
+fn same_path(actual: &str, expected: &str) -> bool {
+    if cfg!(windows) {
+        actual.eq_ignore_ascii_case(expected)
+    } else {
+        actual == expected
+    }
+}
+
+/// 进程所属用户名（目前仅 Linux）
+fn process_user(pid: u32) -> Option<String> {
+    #[cfg(target_os = "linux")]
+    {
+        crate::platform::linux::process_uid(pid).and_then(crate::platform::linux::user_name)
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = pid;
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{ConnectionState, ProcessInfo, Protocol};
+
+    fn listener(port: u16, addr: &str, process: &str) -> PortInfo {
+        let mut info = PortInfo::new(port, Protocol::TCP, addr.to_string(), 100 + port as u32);
+        info.state = ConnectionState::Listen;
+        info.process = Some(ProcessInfo::new(info.pid, process.to_string()));
+        info
+    }
+
+    fn check(ports: &[PortInfo], policy: &str) -> Vec<(ViolationKind, u16)> {
+        let policy = ListenerPolicy::from_toml(policy).unwrap();
+        check_compliance(ports, &[], &policy)
+            .violations
+            .iter()
+            .map(|v| (v.kind, v.port))
+            .collect()
+    }
+
+    const SSH: &str = r#"
+        [[listener]]
+        port = 22
+        process = "sshd"
+        bind = "private_lan"
+    "#;
+
+    #[test]
+    fn test_compliant() {
+        let ports = [listener(22, "192.168.1.5", "sshd")];
+        assert!(check(&ports, SSH).is_empty());
+
+        // 非监听状态的端口不检查
+        let mut established = listener(40000, "192.168.1.5", "curl");
+        established.state = ConnectionState::Established;
+        assert!(check(&[established], SSH).is_empty());
+    }
+
+    #[test]
+    fn test_unexpected() {
+        let ports = [
+            listener(22, "192.168.1.5", "sshd"),
+            listener(8080, "127.0.0.1", "node"),
+        ];
+        assert_eq!(check(&ports, SSH), vec![(ViolationKind::Unexpected, 8080)]);
+
+        let ignore = format!("ignore_loopback = true\n{}", SSH);
+        assert!(check(&ports, &ignore).is_empty());
+        let allow = format!("allow_unlisted = true\n{}", SSH);
+        assert!(check(&ports, &allow).is_empty());
+    }
+
+    #[test]
+    fn test_missing() {
+        let policy = r#"
+            [[listener]]
+            name = "postgres"
+            port = 5432
+            required = true
+        "#;
+        let report = check_compliance(&[], &[], &ListenerPolicy::from_toml(policy).unwrap());
+        assert_eq!(report.violations.len(), 1);
+        let violation = &report.violations[0];
+        assert_eq!(violation.kind, ViolationKind::Missing);
+        assert_eq!(violation.rule.as_deref(), Some("postgres"));
+        assert_eq!(violation.pid, None);
+
+        let ports = [listener(5432, "127.0.0.1", "postgres")];
+        assert!(check(&ports, policy).is_empty());
+    }
+
+    #[test]
+    fn test_wrong_owner() {
+        let ports = [listener(22, "192.168.1.5", "dropbear")];
+        assert_eq!(check(&ports, SSH), vec![(ViolationKind::WrongOwner, 22)]);
+
+        // 无法获取进程信息视为不符
+        let mut unknown = listener(22, "192.168.1.5", "sshd");
+        unknown.process = None;
+        assert_eq!(
+            check(&[unknown], SSH),
+            vec![(ViolationKind::WrongOwner, 22)]
+        );
+
+        // 进程名不区分大小写，忽略 .exe 后缀
+        let ports = [listener(22, "192.168.1.5", "SSHD.exe")];
+        assert!(check(&ports, SSH).is_empty());
+    }
+
+    #[test]
+    fn test_too_broad() {
+        let ports = [listener(22, "0.0.0.0", "sshd")];
+        assert_eq!(check(&ports, SSH), vec![(ViolationKind::TooBroad, 22)]);
+    }
+
+    #[test]
+    fn test_rejects_unknown_fields() {
+        assert!(ListenerPolicy::from_toml("allow_unlisted = true").is_ok());
+        assert!(ListenerPolicy::from_toml("allow_unlisetd = true").is_err());
+        let typo = r#"
+            [[listener]]
+            port = 22
+            proces = "sshd"
+        "#;
+        assert!(ListenerPolicy::from_toml(typo).is_err());
+    }
+}
@@ -0,0 +1,330 @@
+//! 可疑行为检测
+//!
+//! 每个检测器根据当前扫描结果和近期的连接记录给出发现。连接记录由监控事件
+//! 和每次检测时的扫描结果累积，只保留最近一段时间
+
+mod rules;
+
+use std::collections::{HashMap, HashSet};
+use std::net::IpAddr;
+use std::sync::Mutex;
+
+use crate::core::monitor::{socket_key, SocketKey};
+use crate::core::PortScanner;
+use crate::models::{
+    AppResult, ChangeKind, ConnectionState, Finding, MonitorEvent, PortInfo, Protocol,
+};
+use crate::utils::net::parse_addr;
+use crate::utils::time::now_millis;
+
+pub use rules::{
+    FanOutDetector, ShellSocketDetector, TempDirListenerDetector, UserWildcardDetector,
+};
+
+/// 连接记录保留时长（毫秒）
+pub const HISTORY_RETENTION_MS: u64 = 10 * 60 * 1000;
+
+/// 检测器
+pub trait Detector: Send + Sync {
+    /// 检测器标识
+    fn id(&self) -> &'static str;
+
+    /// 检查当前端口和连接记录，返回发现
+    fn detect(&self, ctx: &DetectionContext<'_>) -> Vec<Finding>;
+}
+
+/// 检测输入
+pub struct DetectionContext<'a> {
+    pub ports: &'a [PortInfo],
+    pub history: &'a ConnectionHistory,
+    pub now: u64,
+}
+
+/// 与某个远程主机的连接时间
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HostSeen {
+    /// 首次看到的时间，开始记录前已存在的连接为 0
+    pub first: u64,
+    /// 最近一次看到的时间
+    pub last: u64,
+}
+
+/// 单个进程近期连接过的远程主机
+#[derive(Debug, Clone, Default)]
+pub struct ProcessActivity {
+    pub process: Option<String>,
+    pub hosts: HashMap<IpAddr, HostSeen>,
+}
+
+impl ProcessActivity {
+    /// `since` 之后首次连接的远程主机，持续存在的旧连接不计
+    pub fn new_hosts_since(&self, since: u64) -> Vec<IpAddr> {
+        let mut hosts: Vec<IpAddr> = self
+            .hosts
+            .iter()
+            .filter(|(_, seen)| seen.first >= since)
+            .map(|(ip, _)| *ip)
+            .collect();
+        hosts.sort();
+        hosts
+    }
+}
+
+/// 各进程的出站连接记录
+#[derive(Debug, Default)]
+pub struct ConnectionHistory {
+    processes: HashMap<u32, ProcessActivity>,
+    /// 按监控事件维护的监听 socket，用于排除入站连接
+    listening: HashSet<SocketKey>,
+    /// 已记录过完整扫描，此前的连接无法得知开始时间
+    primed: bool,
+}
+
+impl ConnectionHistory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 记录监控事件中的出站连接
+    pub fn observe(&mut self, event: &MonitorEvent) {
+        match event {
+            MonitorEvent::Snapshot(snapshot) => {
+                self.record_ports(&snapshot.ports, snapshot.timestamp);
+            }
+            MonitorEvent::Change(change) => {
+                for entry in &change.entries {
+                    let key = socket_key(&entry.info);
+                    match entry.kind {
+                        ChangeKind::Removed => {
+                            self.listening.remove(&key);
+                            continue;
+                        }
+                        _ if entry.info.state.is_listening() => {
+                            self.listening.insert(key);
+                        }
+                        _ => {
+                            self.listening.remove(&key);
+                        }
+                    }
+                    self.record(&entry.info, change.timestamp);
+                }
+                self.prune(change.timestamp);
+            }
+        }
+    }
+
+    /// 记录一次完整扫描中的出站连接
+    pub fn record_ports(&mut self, ports: &[PortInfo], now: u64) {
+        self.listening = ports
+            .iter()
+            .filter(|p| p.state.is_listening())
+            .map(socket_key)
+            .collect();
+        for port in ports {
+            self.record(port, now);
+        }
+        self.primed = true;
+        self.prune(now);
+    }
+
+    /// 各进程的连接记录
+    pub fn processes(&self) -> impl Iterator<Item = (u32, &ProcessActivity)> {
+        self.processes
+            .iter()
+            .map(|(pid, activity)| (*pid, activity))
+    }
+
+    /// 本地端口上是否有监听 socket（IPv4 与 IPv6 监听各自计入）
+    fn is_listening_port(&self, info: &PortInfo) -> bool {
+        self.listening
+            .iter()
+            .any(|(port, protocol, ..)| *port == info.port && *protocol == info.protocol)
+    }
+
+    fn record(&mut self, info: &PortInfo, now: u64) {
+        if info.pid == 0
+            || info.protocol != Protocol::TCP
+            || info.state != ConnectionState::Established
+            || self.is_listening_port(info)
+        {
+            return;
+        }
+        let Some(ip) = info.remote_addr.as_deref().and_then(parse_addr) else {
+            return;
+        };
+        let ip = ip.to_canonical();
+        if ip.is_loopback() || ip.is_unspecified() {
+            return;
+        }
+
+        let name = info.process.as_ref().map(|p| p.name.clone());
+        let activity = self.processes.entry(info.pid).or_default();
+        // PID 被其他程序复用时重新记录
+        if activity.process != name {
+            *activity = ProcessActivity {
+                process: name,
+                hosts: HashMap::new(),
+            };
+        }
+        let first = if self.primed { now } else { 0 };
+        let seen = activity
+            .hosts
+            .entry(ip)
+            .or_insert(HostSeen { first, last: now });
+        seen.last = seen.last.max(now);
+    }
+
+    fn prune(&mut self, now: u64) {
+        let cutoff = now.saturating_sub(HISTORY_RETENTION_MS);
+        for activity in self.processes.values_mut() {
+            activity.hosts.retain(|_, seen| seen.last >= cutoff);
+        }
+        self.processes
+            .retain(|_, activity| !activity.hosts.is_empty());
+    }
+}
+
+/// 一组检测器
+pub struct DetectorSet {
+    detectors: Vec<Box<dyn Detector>>,
+}
+
+impl Default for DetectorSet {
+    /// 内置检测器
+    fn default() -> Self {
+        Self::empty()
+            .with(TempDirListenerDetector)
+            .with(ShellSocketDetector)
+            .with(FanOutDetector::default())
+            .with(UserWildcardDetector)
+    }
+}
+
+impl DetectorSet {
+    /// 不含任何检测器
+    pub fn empty() -> Self {
+        Self {
+            detectors: Vec::new(),
+        }
+    }
+
+    /// 添加检测器
+    pub fn with(mut self, detector: impl Detector + 'static) -> Self {
+        self.detectors.push(Box::new(detector));
+        self
+    }
+
+    /// 检测器标识
+    pub fn ids(&self) -> Vec<&'static str> {
+        self.detectors.iter().map(|d| d.id()).collect()
+    }
+
+    /// 运行所有检测器，结果按严重程度从高到低排序
+    pub fn run(&self, ctx: &DetectionContext<'_>) -> Vec<Finding> {
+        let mut findings: Vec<Finding> =
+            self.detectors.iter().flat_map(|d| d.detect(ctx)).collect();
+        findings.sort_by(|a, b| b.severity.cmp(&a.severity).then(a.pid.cmp(&b.pid)));
+        findings
+    }
+}
+
+/// 扫描当前端口并运行内置检测器，扫描结果同时计入连接记录
+pub fn detect_suspicious(
+    scanner: &PortScanner,
+    history: &Mutex<ConnectionHistory>,
+) -> AppResult<Vec<Finding>> {
+    let ports = scanner.scan_all()?;
+    let now = now_millis();
+    let mut history = history.lock().unwrap_or_else(|e| e.into_inner());
+    history.record_ports(&ports, now);
+
+    Ok(DetectorSet::default().run(&DetectionContext {
+        ports: &ports,
+        history: &history,
+        now,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::ProcessInfo;
+
+    const T0: u64 = 1_000_000;
+
+    fn connection(pid: u32, local_port: u16, remote: String) -> PortInfo {
+        let mut info = PortInfo::new(local_port, Protocol::TCP, "10.0.0.2".to_string(), pid);
+        info.state = ConnectionState::Established;
+        info.remote_addr = Some(remote);
+        info.remote_port = Some(443);
+        info.process = Some(ProcessInfo::new(pid, "client".to_string()));
+        info
+    }
+
+    fn connections(pid: u32, count: u16, subnet: u8) -> Vec<PortInfo> {
+        (0..count)
+            .map(|i| connection(pid, 40000 + i, format!("8.{}.{}.1", subnet, i)))
+            .collect()
+    }
+
+    fn fan_out(history: &ConnectionHistory, now: u64) -> Vec<u32> {
+        DetectorSet::empty()
+            .with(FanOutDetector::default())
+            .run(&DetectionContext {
+                ports: &[],
+                history,
+                now,
+            })
+            .iter()
+            .map(|f| f.pid)
+            .collect()
+    }
+
+    #[test]
+    fn test_long_lived_connections_not_flagged() {
+        let ports = connections(7, 25, 1);
+        let mut history = ConnectionHistory::new();
+        history.record_ports(&ports, T0);
+        assert!(fan_out(&history, T0).is_empty());
+
+        // 每次扫描都会刷新最近看到的时间，但首次看到的时间不变
+        for i in 1..5 {
+            history.record_ports(&ports, T0 + i * 30_000);
+            assert!(fan_out(&history, T0 + i * 30_000).is_empty());
+        }
+    }
+
+    #[test]
+    fn test_new_hosts_in_window_flagged() {
+        let mut history = ConnectionHistory::new();
+        history.record_ports(&[], T0);
+
+        let ports = connections(7, 25, 1);
+        history.record_ports(&ports, T0 + 10_000);
+        assert_eq!(fan_out(&history, T0 + 20_000), vec![7]);
+
+        // 连接持续存在，窗口过后不再报告
+        history.record_ports(&ports, T0 + 80_000);
+        assert!(fan_out(&history, T0 + 80_000).is_empty());
+
+        // 少于阈值的新主机不报告
+        let mut more = ports.clone();
+        more.extend(connections(7, 10, 2));
+        history.record_ports(&more, T0 + 90_000);
+        assert!(fan_out(&history, T0 + 90_000).is_empty());
+    }
+
+    #[test]
+    fn test_inbound_and_loopback_ignored() {
+        let mut history = ConnectionHistory::new();
+        let mut listener = PortInfo::new(443, Protocol::TCP, "0.0.0.0".to_string(), 8);
+        listener.state = ConnectionState::Listen;
+        history.record_ports(&[listener.clone()], T0);
+
+        let mut ports = vec![listener];
+        ports.extend((0..25).map(|i| connection(8, 443, format!("9.9.{}.1", i))));
+        ports.extend((0..25).map(|i| connection(9, 40000 + i, "127.0.0.1".to_string())));
+        history.record_ports(&ports, T0 + 1000);
+        assert_eq!(history.processes().count(), 0);
+    }
+}
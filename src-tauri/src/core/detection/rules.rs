@@ -0,0 +1,497 @@
+//! 内置检测器
+
+use std::collections::BTreeMap;
+
+use super::{DetectionContext, Detector};
+use crate::models::{Finding, PortInfo, RiskLevel};
+use crate::utils::net::parse_addr;
+
+/// 临时目录和下载目录（小写，路径分隔符统一为 `/`）
+const TEMP_PREFIXES: &[&str] = &["/tmp/", "/var/tmp/", "/dev/shm/"];
+const TEMP_SEGMENTS: &[&str] = &["/downloads/", "/appdata/local/temp/", "/windows/temp/"];
+
+/// 交互式 shell 和脚本解释器外壳
+const SHELLS: &[&str] = &[
+    "sh",
+    "bash",
+    "dash",
+    "zsh",
+    "ksh",
+    "csh",
+    "tcsh",
+    "fish",
+    "ash",
+    "cmd",
+    "powershell",
+    "pwsh",
+];
+
+/// 普通登录用户的最小 UID，更小的是 root 和系统服务账号
+const MIN_LOGIN_UID: u32 = 1000;
+
+/// nobody
+const NOBODY_UID: u32 = 65534;
+
+/// 从临时目录或下载目录运行、且持有监听端口的进程
+#[derive(Debug, Default)]
+pub struct TempDirListenerDetector;
+
+impl Detector for TempDirListenerDetector {
+    fn id(&self) -> &'static str {
+        "temp_dir_listener"
+    }
+
+    fn detect(&self, ctx: &DetectionContext<'_>) -> Vec<Finding> {
+        by_pid(ctx.ports.iter().filter(|p| p.state.is_listening()))
+            .into_values()
+            .filter_map(|ports| {
+                let path = ports[0].process.as_ref()?.exe_path.as_deref()?;
+                if !in_temp_dir(path) {
+                    return None;
+                }
+                let mut evidence = vec![format!("程序路径: {}", path)];
+                evidence.extend(ports.iter().map(|p| describe(p)));
+                Some(finding(
+                    self.id(),
+                    RiskLevel::High,
+                    &ports,
+                    format!("从临时或下载目录运行的程序正在监听端口: {}", path),
+                    evidence,
+                    ctx.now,
+                ))
+            })
+            .collect()
+    }
+}
+
+/// 持有网络套接字的 shell
+#[derive(Debug, Default)]
+pub struct ShellSocketDetector;
+
+impl Detector for ShellSocketDetector {
+    fn id(&self) -> &'static str {
+        "shell_socket"
+    }
+
+    fn detect(&self, ctx: &DetectionContext<'_>) -> Vec<Finding> {
+        let shells = ctx
+            .ports
+            .iter()
+            .filter(|p| p.process.as_ref().is_some_and(|proc| is_shell(&proc.name)));
+        by_pid(shells)
+            .into_values()
+            .map(|ports| {
+                let name = ports[0]
+                    .process
+                    .as_ref()
+                    .map(|p| p.name.as_str())
+                    .unwrap_or("");
+                finding(
+                    self.id(),
+                    RiskLevel::High,
+                    &ports,
+                    format!("shell 进程 {} 持有网络套接字，可能是反弹或绑定 shell", name),
+                    ports.iter().map(|p| describe(p)).collect(),
+                    ctx.now,
+                )
+            })
+            .collect()
+    }
+}
+
+/// 短时间内新连接大量不同远程主机的进程
+#[derive(Debug)]
+pub struct FanOutDetector {
+    /// 统计窗口（毫秒）
+    pub window_ms: u64,
+    /// 窗口内首次连接的不同远程主机数达到该值时报告
+    pub min_hosts: usize,
+}
+
+impl Default for FanOutDetector {
+    fn default() -> Self {
+        Self {
+            window_ms: 60 * 1000,
+            min_hosts: 20,
+        }
+    }
+}
+
+impl Detector for FanOutDetector {
+    fn id(&self) -> &'static str {
+        "connection_fan_out"
+    }
+
+    fn detect(&self, ctx: &DetectionContext<'_>) -> Vec<Finding> {
+        let since = ctx.now.saturating_sub(self.window_ms);
+        ctx.history
+            .processes()
+            .filter_map(|(pid, activity)| {
+                let hosts = activity.new_hosts_since(since);
+                if hosts.len() < self.min_hosts {
+                    return None;
+                }
+                let label = activity.process.as_deref().unwrap_or("未知进程");
+                let mut evidence = vec![format!(
+                    "{} 秒内新连接 {} 个不同的远程主机",
+                    self.window_ms / 1000,
+                    hosts.len()
+                )];
+                evidence.extend(hosts.iter().take(5).map(|ip| format!("远程主机: {}", ip)));
+                Some(Finding {
+                    detector: self.id().to_string(),
+                    severity: RiskLevel::Medium,
+                    pid,
+                    process: activity.process.clone(),
+                    port: None,
+                    protocol: None,
+                    message: format!(
+                        "{} (PID {}) 短时间内连接了 {} 个远程主机，可能在扫描或外传数据",
+                        label,
+                        pid,
+                        hosts.len()
+                    ),
+                    evidence,
+                    detected_at: ctx.now,
+                })
+            })
+            .collect()
+    }
+}
+
+/// 普通用户进程在通配地址上监听高端口（目前仅 Linux）
+#[derive(Debug, Default)]
+pub struct UserWildcardDetector;
+
+impl Detector for UserWildcardDetector {
+    fn id(&self) -> &'static str {
+        "user_wildcard_listener"
+    }
+
+    fn detect(&self, ctx: &DetectionContext<'_>) -> Vec<Finding> {
+        self.detect_with(ctx, process_uid)
+    }
+}
+
+impl UserWildcardDetector {
+    /// `uid_of` 查询进程所属用户
+    fn detect_with(
+        &self,
+        ctx: &DetectionContext<'_>,
+        uid_of: impl Fn(u32) -> Option<u32>,
+    ) -> Vec<Finding> {
+        let candidates = ctx.ports.iter().filter(|p| {
+            p.state.is_listening()
+                && p.port >= 1024
+                && parse_addr(&p.local_addr).is_some_and(|ip| ip.is_unspecified())
+        });
+        by_pid(candidates)
+            .into_iter()
+            .filter_map(|(pid, ports)| {
+                let uid = uid_of(pid)?;
+                if !is_login_uid(uid) {
+                    return None;
+                }
+                let user = user_label(uid);
+                let mut evidence = vec![format!("用户: {}", user)];
+                evidence.extend(ports.iter().map(|p| describe(p)));
+                Some(finding(
+                    self.id(),
+                    RiskLevel::Low,
+                    &ports,
+                    format!("普通用户 {} 的进程在所有网络接口上监听高端口", user),
+                    evidence,
+                    ctx.now,
+                ))
+            })
+            .collect()
+    }
+}
+
+/// 按 PID 分组，跳过无法关联进程的端口
+fn by_pid<'a>(ports: impl Iterator<Item = &'a PortInfo>) -> BTreeMap<u32, Vec<&'a PortInfo>> {
+    let mut groups: BTreeMap<u32, Vec<&PortInfo>> = BTreeMap::new();
+    for port in ports.filter(|p| p.pid != 0) {
+        groups.entry(port.pid).or_default().push(port);
+    }
+    groups
+}
+
+/// 同一进程的一组端口生成一条发现，只涉及一个端口时填写端口号
+fn finding(
+    detector: &str,
+    severity: RiskLevel,
+    ports: &[&PortInfo],
+    message: String,
+    evidence: Vec<String>,
+    now: u64,
+) -> Finding {
+    let first = ports[0];
+    let single = ports
+        .iter()
+        .all(|p| p.port == first.port && p.protocol == first.protocol);
+    Finding {
+        detector: detector.to_string(),
+        severity,
+        pid: first.pid,
+        process: first.process.as_ref().map(|p| p.name.clone()),
+        port: single.then_some(first.port),
+        protocol: single.then_some(first.protocol),
+        message,
+        evidence,
+        detected_at: now,
+    }
+}
+
+/// 套接字描述，如 `TCP 0.0.0.0:4444 listen`
+fn describe(port: &PortInfo) -> String {
+    let mut text = format!("{:?} {}:{}", port.protocol, port.local_addr, port.port);
+    if let (Some(addr), Some(remote_port)) = (&port.remote_addr, port.remote_port) {
+        text.push_str(&format!(" -> {}:{}", addr, remote_port));
+    }
+    text.push(' ');
+    text.push_str(port.state.name());
+    text
+}
+
+fn in_temp_dir(path: &str) -> bool {
+    let path = path.replace('\\', "/").to_lowercase();
+    TEMP_PREFIXES.iter().any(|prefix| path.starts_with(prefix))
+        || TEMP_SEGMENTS.iter().any(|segment| path.contains(segment))
+}
+
+fn is_shell(name: &str) -> bool {
+    let name = name.to_lowercase();
+    let name = name.strip_suffix(".exe").unwrap_or(&name);
+    SHELLS.contains(&name)
+}
+
+/// 普通登录用户，排除 root、系统服务账号和 nobody
+fn is_login_uid(uid: u32) -> bool {
+    uid >= MIN_LOGIN_UID && uid != NOBODY_UID
+}
+
+fn process_uid(pid: u32) -> Option<u32> {
+    #[cfg(target_os = "linux")]
+    {
+        crate::platform::linux::process_uid(pid)
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = pid;
+        None
+    }
+}
+
+fn user_label(uid: u32) -> String {
+    #[cfg(target_os = "linux")]
+    if let Some(name) = crate::platform::linux::user_name(uid) {
+        return format!("{} ({})", name, uid);
+    }
+    uid.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::core::detection::ConnectionHistory;
+    use crate::models::{ConnectionState, ProcessInfo, Protocol};
+
+    const NOW: u64 = 1_000_000;
+
+    fn socket(port: u16, addr: &str, pid: u32, state: ConnectionState) -> PortInfo {
+        let mut info = PortInfo::new(port, Protocol::TCP, addr.to_string(), pid);
+        info.state = state;
+        info
+    }
+
+    fn listener(port: u16, addr: &str, pid: u32) -> PortInfo {
+        socket(port, addr, pid, ConnectionState::Listen)
+    }
+
+    fn with_process(mut port: PortInfo, name: &str, exe: Option<&str>) -> PortInfo {
+        let mut process = ProcessInfo::new(port.pid, name.to_string());
+        process.exe_path = exe.map(str::to_string);
+        port.process = Some(process);
+        port
+    }
+
+    fn run(detector: &dyn Detector, ports: &[PortInfo]) -> Vec<Finding> {
+        let history = ConnectionHistory::new();
+        detector.detect(&DetectionContext {
+            ports,
+            history: &history,
+            now: NOW,
+        })
+    }
+
+    fn pids(findings: &[Finding]) -> Vec<u32> {
+        findings.iter().map(|f| f.pid).collect()
+    }
+
+    #[test]
+    fn test_in_temp_dir() {
+        for path in [
+            "/tmp/x",
+            "/TMP/Payload",
+            "/var/tmp/.hidden/agent",
+            "/dev/shm/a",
+            "/home/alice/Downloads/tool",
+            r"C:\Users\Bob\AppData\Local\Temp\setup.exe",
+            r"c:\windows\temp\svc.exe",
+            r"D:\Users\bob\downloads\miner.exe",
+        ] {
+            assert!(in_temp_dir(path), "{}", path);
+        }
+        for path in [
+            "/usr/bin/python3",
+            "/home/alice/tmp/server",
+            "/opt/tmpfs/bin/x",
+            "/var/tmpx/a",
+            "/home/alice/Downloads",
+            r"C:\Program Files\App\app.exe",
+            r"C:\Users\Bob\AppData\Local\Programs\app.exe",
+        ] {
+            assert!(!in_temp_dir(path), "{}", path);
+        }
+    }
+
+    #[test]
+    fn test_temp_dir_listener_detector() {
+        let ports = vec![
+            with_process(listener(4444, "0.0.0.0", 10), "x", Some("/tmp/x")),
+            with_process(
+                listener(8080, "::", 11),
+                "setup.exe",
+                Some(r"C:\Users\Bob\AppData\Local\Temp\setup.exe"),
+            ),
+            with_process(listener(8081, "::", 11), "setup.exe", None),
+            // 临时目录中的程序只有出站连接时不报告
+            with_process(
+                socket(50000, "10.0.0.2", 12, ConnectionState::Established),
+                "y",
+                Some("/tmp/y"),
+            ),
+            with_process(
+                listener(3000, "127.0.0.1", 13),
+                "node",
+                Some("/usr/bin/node"),
+            ),
+            with_process(listener(5000, "0.0.0.0", 14), "z", None),
+            // 无法关联进程的端口
+            with_process(listener(6000, "0.0.0.0", 0), "?", Some("/tmp/unknown")),
+        ];
+
+        let findings = run(&TempDirListenerDetector, &ports);
+        assert_eq!(pids(&findings), vec![10, 11]);
+        assert!(findings.iter().all(|f| f.severity == RiskLevel::High));
+        assert_eq!(findings[0].port, Some(4444));
+        assert_eq!(findings[0].evidence[0], "程序路径: /tmp/x");
+        // 同一进程多个端口时不填端口号
+        assert_eq!(findings[1].port, None);
+        assert_eq!(findings[1].evidence.len(), 3);
+    }
+
+    #[test]
+    fn test_is_shell() {
+        for name in [
+            "sh",
+            "bash",
+            "BASH",
+            "zsh",
+            "cmd.exe",
+            "PowerShell.EXE",
+            "pwsh",
+        ] {
+            assert!(is_shell(name), "{}", name);
+        }
+        for name in [
+            "bash5",
+            "bashful",
+            "node",
+            "ssh",
+            "cmd.exe.bak",
+            ".exe",
+            "exe",
+            "",
+        ] {
+            assert!(!is_shell(name), "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_shell_socket_detector() {
+        let mut reverse = socket(50000, "10.0.0.2", 20, ConnectionState::Established);
+        reverse.remote_addr = Some("203.0.113.9".to_string());
+        reverse.remote_port = Some(4444);
+        let ports = vec![
+            with_process(reverse, "bash", None),
+            with_process(listener(4444, "0.0.0.0", 21), "CMD.EXE", None),
+            with_process(listener(3000, "0.0.0.0", 22), "node", None),
+            with_process(listener(3001, "0.0.0.0", 23), "bash-language-server", None),
+        ];
+
+        let findings = run(&ShellSocketDetector, &ports);
+        assert_eq!(pids(&findings), vec![20, 21]);
+        assert_eq!(findings[0].process.as_deref(), Some("bash"));
+        assert_eq!(
+            findings[0].evidence,
+            vec!["TCP 10.0.0.2:50000 -> 203.0.113.9:4444 established"]
+        );
+        assert!(findings[1].message.contains("CMD.EXE"));
+    }
+
+    #[test]
+    fn test_login_uid_thresholds() {
+        assert!(!is_login_uid(0));
+        assert!(!is_login_uid(MIN_LOGIN_UID - 1));
+        assert!(is_login_uid(MIN_LOGIN_UID));
+        assert!(is_login_uid(60000));
+        assert!(!is_login_uid(NOBODY_UID));
+        assert!(is_login_uid(NOBODY_UID + 1));
+    }
+
+    #[test]
+    fn test_user_wildcard_detector() {
+        let uids: HashMap<u32, u32> = [
+            (30, 1000),
+            (31, 999),
+            (32, NOBODY_UID),
+            (33, 1001),
+            (34, 1002),
+            (35, 1003),
+            (36, 1004),
+        ]
+        .into();
+        let ports = vec![
+            listener(8080, "0.0.0.0", 30),
+            listener(9000, "0.0.0.0", 31),
+            listener(9001, "0.0.0.0", 32),
+            // 特权端口
+            listener(1023, "0.0.0.0", 33),
+            // 只监听具体地址
+            listener(8000, "127.0.0.1", 34),
+            listener(8001, "192.168.1.5", 34),
+            listener(9229, "::", 35),
+            socket(50000, "0.0.0.0", 36, ConnectionState::Established),
+            // UID 未知
+            listener(7000, "0.0.0.0", 37),
+        ];
+
+        let history = ConnectionHistory::new();
+        let ctx = DetectionContext {
+            ports: &ports,
+            history: &history,
+            now: NOW,
+        };
+        let findings = UserWildcardDetector.detect_with(&ctx, |pid| uids.get(&pid).copied());
+        assert_eq!(pids(&findings), vec![30, 35]);
+        assert!(findings.iter().all(|f| f.severity == RiskLevel::Low));
+        assert_eq!(findings[0].port, Some(8080));
+        assert_eq!(findings[1].port, Some(9229));
+        assert!(findings[1].evidence[0].contains("1003"));
+    }
+}
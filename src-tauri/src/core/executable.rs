@@ -0,0 +1,227 @@
+//! 可执行文件检查：大小、修改时间、SHA-256 和所属软件包
+//!
+//! 结果按 (路径, 修改时间) 缓存，文件被替换后重新检查。Linux 上通过
+//! `/proc/<pid>/exe` 读取进程实际运行的文件，已删除或位于其它挂载命名空间
+//! （如 systemd 的 PrivateTmp）的文件也能检查
+
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::Read;
+use std::sync::{Mutex, MutexGuard, OnceLock};
+use std::time::UNIX_EPOCH;
+
+use ring::digest::{Context, SHA256};
+
+use crate::models::{AppError, AppResult, ExecutableInfo, PackageStatus, PortInfo, ProcessInfo};
+use crate::platform::traits::ProcessProvider;
+
+/// 缓存的最大条目数，超出时清空
+const MAX_CACHED: usize = 1024;
+
+/// 可执行文件检查结果缓存
+#[derive(Debug, Default)]
+pub struct ExecutableCache {
+    entries: Mutex<HashMap<(String, u64), ExecutableInfo>>,
+}
+
+/// 全局缓存
+pub fn executable_cache() -> &'static ExecutableCache {
+    static CACHE: OnceLock<ExecutableCache> = OnceLock::new();
+    CACHE.get_or_init(ExecutableCache::default)
+}
+
+impl ExecutableCache {
+    /// 检查进程 `pid` 的可执行文件 `path`，`hash` 为 true 时同时计算 SHA-256
+    ///
+    /// 无法读取时返回 `PackageStatus::Uninspectable`，不缓存
+    pub fn inspect(&self, pid: u32, path: &str, hash: bool) -> ExecutableInfo {
+        let source = source_path(pid, path);
+        let metadata = match fs::metadata(&source) {
+            Ok(metadata) => metadata,
+            Err(e) => return uninspectable(path, 0, 0, e.to_string()),
+        };
+        let modified = metadata
+            .modified()
+            .ok()
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0);
+        let key = (path.to_string(), modified);
+
+        let cached = self.lock().get(&key).cloned();
+        let mut info = match cached {
+            Some(info) if info.sha256.is_some() || !hash => return info,
+            Some(info) => info,
+            None => ExecutableInfo {
+                path: path.to_string(),
+                size: metadata.len(),
+                modified,
+                sha256: None,
+                package: if is_deleted(path) {
+                    PackageStatus::Deleted
+                } else {
+                    package_owner(path)
+                },
+            },
+        };
+        if hash {
+            match sha256_file(&source) {
+                Ok(sha256) => info.sha256 = Some(sha256),
+                Err(e) => return uninspectable(path, info.size, modified, e.to_string()),
+            }
+        }
+
+        let mut entries = self.lock();
+        if entries.len() >= MAX_CACHED {
+            entries.clear();
+        }
+        entries.insert(key, info.clone());
+        info
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashMap<(String, u64), ExecutableInfo>> {
+        self.entries.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// 获取进程信息并检查其可执行文件（含 SHA-256），进程不存在时返回 None
+pub fn inspect_process(
+    processes: &dyn ProcessProvider,
+    pid: u32,
+) -> AppResult<Option<ProcessInfo>> {
+    let Some(mut process) = processes.get_process_info(pid)? else {
+        return Ok(None);
+    };
+    // 读取其它用户进程的可执行文件路径需要 root 权限
+    let path = process.exe_path.clone().ok_or(AppError::AccessDenied)?;
+    process.executable = Some(executable_cache().inspect(pid, &path, true));
+    Ok(Some(process))
+}
+
+/// 为端口列表中的进程附加可执行文件信息（不计算哈希），没有可执行文件路径的进程保持为 None
+pub fn annotate_executables(ports: &mut [PortInfo]) {
+    let cache = executable_cache();
+    for process in ports.iter_mut().filter_map(|p| p.process.as_mut()) {
+        let Some(path) = &process.exe_path else {
+            continue;
+        };
+        let info = cache.inspect(process.pid, path, false);
+        if let PackageStatus::Uninspectable { reason } = &info.package {
+            tracing::debug!("Failed to inspect executable {}: {}", path, reason);
+        }
+        process.executable = Some(info);
+    }
+}
+
+/// 实际读取的路径：Linux 上为 `/proc/<pid>/exe`
+fn source_path(pid: u32, path: &str) -> String {
+    if cfg!(target_os = "linux") && pid != 0 {
+        format!("/proc/{}/exe", pid)
+    } else {
+        path.to_string()
+    }
+}
+
+/// 进程启动后可执行文件被删除或替换（Linux 的 readlink 结果带 ` (deleted)` 后缀）
+fn is_deleted(path: &str) -> bool {
+    cfg!(target_os = "linux") && path.ends_with(" (deleted)")
+}
+
+fn uninspectable(path: &str, size: u64, modified: u64, reason: String) -> ExecutableInfo {
+    ExecutableInfo {
+        path: path.to_string(),
+        size,
+        modified,
+        sha256: None,
+        package: PackageStatus::Uninspectable { reason },
+    }
+}
+
+fn package_owner(path: &str) -> PackageStatus {
+    #[cfg(target_os = "linux")]
+    {
+        crate::platform::linux::package_owner(path)
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = path;
+        PackageStatus::Unknown
+    }
+}
+
+fn sha256_file(path: &str) -> AppResult<String> {
+    let mut file = File::open(path).map_err(|e| AppError::Io(format!("{}: {}", path, e)))?;
+    let mut context = Context::new(&SHA256);
+    let mut buffer = vec![0u8; 64 * 1024];
+    loop {
+        let read = file
+            .read(&mut buffer)
+            .map_err(|e| AppError::Io(format!("{}: {}", path, e)))?;
+        if read == 0 {
+            break;
+        }
+        context.update(&buffer[..read]);
+    }
+
+    Ok(context
+        .finish()
+        .as_ref()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect())
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use std::process::Command;
+
+    use super::*;
+
+    fn exe_link(pid: u32) -> String {
+        fs::read_link(format!("/proc/{}/exe", pid))
+            .unwrap()
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    #[test]
+    fn test_inspect_self() {
+        let pid = std::process::id();
+        let info = ExecutableCache::default().inspect(pid, &exe_link(pid), true);
+        assert!(info.size > 0);
+        assert_eq!(info.sha256.as_ref().map(String::len), Some(64));
+        assert!(!matches!(
+            info.package,
+            PackageStatus::Deleted | PackageStatus::Uninspectable { .. }
+        ));
+    }
+
+    #[test]
+    fn test_inspect_deleted_binary() {
+        let path =
+            std::env::temp_dir().join(format!("port-detection-sleep-{}", std::process::id()));
+        fs::copy("/bin/sleep", &path).unwrap();
+        let mut child = Command::new(&path).arg("30").spawn().unwrap();
+        fs::remove_file(&path).unwrap();
+
+        let link = exe_link(child.id());
+        assert!(link.ends_with(" (deleted)"));
+        let info = ExecutableCache::default().inspect(child.id(), &link, true);
+        let expected = sha256_file("/bin/sleep").unwrap();
+        child.kill().unwrap();
+        let _ = child.wait();
+
+        assert_eq!(info.package, PackageStatus::Deleted);
+        assert_eq!(info.sha256, Some(expected));
+        assert!(info.package.is_unverified());
+    }
+
+    #[test]
+    fn test_uninspectable() {
+        let info = ExecutableCache::default().inspect(0, "/nonexistent/port-detection", true);
+        assert!(matches!(info.package, PackageStatus::Uninspectable { .. }));
+        assert!(info.package.is_unverified());
+        assert!(!info.package.is_unowned());
+    }
+}
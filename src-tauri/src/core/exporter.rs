@@ -0,0 +1,400 @@
+//! 扫描结果导出
+//!
+//! 支持 CSV、JSON、NDJSON 和 Markdown 表格，可选择导出列
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::models::{AppError, AppResult, PortInfo};
+
+/// 导出格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Csv,
+    Json,
+    Ndjson,
+    Markdown,
+}
+
+impl ExportFormat {
+    /// 从名称解析格式（不区分大小写）
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "csv" => Some(Self::Csv),
+            "json" => Some(Self::Json),
+            "ndjson" | "jsonl" => Some(Self::Ndjson),
+            "markdown" | "md" => Some(Self::Markdown),
+            _ => None,
+        }
+    }
+
+    /// 默认文件扩展名
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Csv => "csv",
+            Self::Json => "json",
+            Self::Ndjson => "ndjson",
+            Self::Markdown => "md",
+        }
+    }
+}
+
+/// 导出列
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportColumn {
+    Port,
+    Protocol,
+    ServiceName,
+    LocalAddr,
+    RemoteAddr,
+    RemotePort,
+    RemoteHostname,
+    State,
+    Pid,
+    ProcessName,
+    ExePath,
+    CmdLine,
+    CreateTime,
+    MemoryUsage,
+    // 以下为 HTTP 探测结果，需先探测，不在默认列中
+    HttpStatus,
+    HttpServer,
+    HttpTitle,
+    HttpLatency,
+    // TLS 检查结果，同样需先检查
+    TlsStatus,
+    TlsExpiresIn,
+}
+
+impl ExportColumn {
+    /// 未指定列时导出的全部列
+    pub const ALL: &'static [ExportColumn] = &[
+        Self::Port,
+        Self::Protocol,
+        Self::ServiceName,
+        Self::LocalAddr,
+        Self::RemoteAddr,
+        Self::RemotePort,
+        Self::RemoteHostname,
+        Self::State,
+        Self::Pid,
+        Self::ProcessName,
+        Self::ExePath,
+        Self::CmdLine,
+        Self::CreateTime,
+        Self::MemoryUsage,
+    ];
+
+    /// 列名
+    pub fn header(&self) -> &'static str {
+        match self {
+            Self::Port => "port",
+            Self::Protocol => "protocol",
+            Self::ServiceName => "service_name",
+            Self::LocalAddr => "local_addr",
+            Self::RemoteAddr => "remote_addr",
+            Self::RemotePort => "remote_port",
+            Self::RemoteHostname => "remote_hostname",
+            Self::State => "state",
+            Self::Pid => "pid",
+            Self::ProcessName => "process_name",
+            Self::ExePath => "exe_path",
+            Self::CmdLine => "cmd_line",
+            Self::CreateTime => "create_time",
+            Self::MemoryUsage => "memory_usage",
+            Self::HttpStatus => "http_status",
+            Self::HttpServer => "http_server",
+            Self::HttpTitle => "http_title",
+            Self::HttpLatency => "http_latency_ms",
+            Self::TlsStatus => "tls_status",
+            Self::TlsExpiresIn => "tls_expires_in_days",
+        }
+    }
+
+    /// 取值，缺失时为 Null
+    fn value(&self, port: &PortInfo) -> Value {
+        let process = port.process.as_ref();
+        let http = port.http.as_ref();
+        let tls = port.tls.as_ref();
+
+        match self {
+            Self::Port => port.port.into(),
+            Self::Protocol => format!("{:?}", port.protocol).into(),
+            Self::ServiceName => port.service_name.clone().into(),
+            Self::LocalAddr => port.local_addr.clone().into(),
+            Self::RemoteAddr => port.remote_addr.clone().into(),
+            Self::RemotePort => port.remote_port.into(),
+            Self::RemoteHostname => port.remote_hostname.clone().into(),
+            Self::State => format!("{:?}", port.state).into(),
+            Self::Pid => port.pid.into(),
+            Self::ProcessName => process.map(|p| p.name.clone()).into(),
+            Self::ExePath => process.and_then(|p| p.exe_path.clone()).into(),
+            Self::CmdLine => process.and_then(|p| p.cmd_line.clone()).into(),
+            Self::CreateTime => process.map(|p| p.create_time).into(),
+            Self::MemoryUsage => process.map(|p| p.memory_usage).into(),
+            Self::HttpStatus => http.map(|h| h.status).into(),
+            Self::HttpServer => http.and_then(|h| h.server.clone()).into(),
+            Self::HttpTitle => http.and_then(|h| h.title.clone()).into(),
+            Self::HttpLatency => http.map(|h| h.latency_ms).into(),
+            Self::TlsStatus => tls.map(|t| t.status.name()).into(),
+            Self::TlsExpiresIn => tls.and_then(|t| t.expires_in_days).into(),
+        }
+    }
+}
+
+/// 按指定格式导出端口列表，`columns` 为空时导出全部列
+pub fn export_ports(
+    ports: &[PortInfo],
+    format: ExportFormat,
+    columns: &[ExportColumn],
+) -> AppResult<String> {
+    let columns = if columns.is_empty() {
+        ExportColumn::ALL
+    } else {
+        columns
+    };
+
+    match format {
+        ExportFormat::Csv => Ok(to_csv(ports, columns)),
+        ExportFormat::Json => {
+            let rows: Vec<Value> = ports.iter().map(|p| to_object(p, columns)).collect();
+            serde_json::to_string_pretty(&rows).map_err(|e| AppError::Internal(e.to_string()))
+        }
+        ExportFormat::Ndjson => {
+            let mut out = String::new();
+            for port in ports {
+                let line = serde_json::to_string(&to_object(port, columns))
+                    .map_err(|e| AppError::Internal(e.to_string()))?;
+                out.push_str(&line);
+                out.push('\n');
+            }
+            Ok(out)
+        }
+        ExportFormat::Markdown => Ok(to_markdown(ports, columns)),
+    }
+}
+
+/// 转换为保持列顺序的 JSON 对象
+fn to_object(port: &PortInfo, columns: &[ExportColumn]) -> Value {
+    let mut obj = Map::new();
+    for column in columns {
+        obj.insert(column.header().to_string(), column.value(port));
+    }
+    Value::Object(obj)
+}
+
+/// 将取值转换为表格文本，Null 为空字符串
+fn cell_text(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+fn to_csv(ports: &[PortInfo], columns: &[ExportColumn]) -> String {
+    let mut out = String::new();
+
+    let header: Vec<&str> = columns.iter().map(|c| c.header()).collect();
+    out.push_str(&header.join(","));
+    out.push_str("\r\n");
+
+    for port in ports {
+        let row: Vec<String> = columns
+            .iter()
+            .map(|c| csv_escape(&cell_text(&c.value(port))))
+            .collect();
+        out.push_str(&row.join(","));
+        out.push_str("\r\n");
+    }
+
+    out
+}
+
+/// 按 RFC 4180 转义 CSV 字段
+///
+/// 以 `=`、`+`、`-`、`@`、制表符或回车开头的字段（如进程名、命令行）会被电子表格当作公式执行，
+/// 前面加 `'` 使其按文本显示
+fn csv_escape(field: &str) -> String {
+    let field = if field.starts_with(['=', '+', '-', '@', '\t', '\r']) {
+        format!("'{}", field)
+    } else {
+        field.to_string()
+    };
+
+    if field.contains([',', '"', '\r', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field
+    }
+}
+
+fn to_markdown(ports: &[PortInfo], columns: &[ExportColumn]) -> String {
+    let mut out = String::new();
+
+    let header: Vec<&str> = columns.iter().map(|c| c.header()).collect();
+    out.push_str(&format!("| {} |\n", header.join(" | ")));
+    out.push_str(&format!("|{}\n", " --- |".repeat(columns.len())));
+
+    for port in ports {
+        let row: Vec<String> = columns
+            .iter()
+            .map(|c| markdown_escape(&cell_text(&c.value(port))))
+            .collect();
+        out.push_str(&format!("| {} |\n", row.join(" | ")));
+    }
+
+    out
+}
+
+/// 转义 Markdown 表格单元格中的竖线和换行
+fn markdown_escape(cell: &str) -> String {
+    cell.replace('|', "\\|").replace(['\r', '\n'], " ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{ConnectionState, ProcessInfo, Protocol};
+
+    const COLUMNS: &[ExportColumn] = &[
+        ExportColumn::Port,
+        ExportColumn::Protocol,
+        ExportColumn::State,
+        ExportColumn::RemoteAddr,
+        ExportColumn::Pid,
+        ExportColumn::ProcessName,
+    ];
+
+    fn fixture() -> Vec<PortInfo> {
+        let mut ssh = PortInfo::new(22, Protocol::TCP, "0.0.0.0".to_string(), 1);
+        ssh.state = ConnectionState::Listen;
+        ssh.process = Some(ProcessInfo::new(1, "sshd".to_string()));
+
+        let mut db = PortInfo::new(5432, Protocol::TCP, "10.0.0.1".to_string(), 2);
+        db.state = ConnectionState::Established;
+        db.remote_addr = Some("10.0.0.5".to_string());
+        db.remote_port = Some(51234);
+        db.process = Some(ProcessInfo::new(2, r#"evil, "quoted" | piped"#.to_string()));
+
+        vec![ssh, db]
+    }
+
+    #[test]
+    fn test_export_csv() {
+        let out = export_ports(&fixture(), ExportFormat::Csv, COLUMNS).unwrap();
+        assert_eq!(
+            out,
+            "port,protocol,state,remote_addr,pid,process_name\r\n\
+             22,TCP,Listen,,1,sshd\r\n\
+             5432,TCP,Established,10.0.0.5,2,\"evil, \"\"quoted\"\" | piped\"\r\n"
+        );
+    }
+
+    #[test]
+    fn test_csv_neutralizes_formulas() {
+        let mut ports = fixture();
+        ports[0].process = Some(ProcessInfo::new(1, "=HYPERLINK(\"http://x\")".to_string()));
+        ports[1].process = Some(ProcessInfo::new(2, "\r@SUM(A1)".to_string()));
+        ports[1].remote_addr = Some("-2+3".to_string());
+
+        let out = export_ports(&ports, ExportFormat::Csv, COLUMNS).unwrap();
+        assert_eq!(
+            out,
+            "port,protocol,state,remote_addr,pid,process_name\r\n\
+             22,TCP,Listen,,1,\"'=HYPERLINK(\"\"http://x\"\")\"\r\n\
+             5432,TCP,Established,'-2+3,2,\"'\r@SUM(A1)\"\r\n"
+        );
+
+        for field in ["+1", "@cmd", "\tx"] {
+            assert_eq!(csv_escape(field), format!("'{}", field));
+        }
+        assert_eq!(csv_escape("a=b"), "a=b");
+    }
+
+    #[test]
+    fn test_export_json() {
+        let out = export_ports(&fixture(), ExportFormat::Json, COLUMNS).unwrap();
+        assert_eq!(
+            out,
+            r#"[
+  {
+    "port": 22,
+    "protocol": "TCP",
+    "state": "Listen",
+    "remote_addr": null,
+    "pid": 1,
+    "process_name": "sshd"
+  },
+  {
+    "port": 5432,
+    "protocol": "TCP",
+    "state": "Established",
+    "remote_addr": "10.0.0.5",
+    "pid": 2,
+    "process_name": "evil, \"quoted\" | piped"
+  }
+]"#
+        );
+    }
+
+    #[test]
+    fn test_export_ndjson() {
+        let out = export_ports(&fixture(), ExportFormat::Ndjson, COLUMNS).unwrap();
+        assert_eq!(
+            out,
+            r#"{"port":22,"protocol":"TCP","state":"Listen","remote_addr":null,"pid":1,"process_name":"sshd"}
+{"port":5432,"protocol":"TCP","state":"Established","remote_addr":"10.0.0.5","pid":2,"process_name":"evil, \"quoted\" | piped"}
+"#
+        );
+    }
+
+    #[test]
+    fn test_export_markdown() {
+        let out = export_ports(&fixture(), ExportFormat::Markdown, COLUMNS).unwrap();
+        assert_eq!(
+            out,
+            r#"| port | protocol | state | remote_addr | pid | process_name |
+| --- | --- | --- | --- | --- | --- |
+| 22 | TCP | Listen |  | 1 | sshd |
+| 5432 | TCP | Established | 10.0.0.5 | 2 | evil, "quoted" \| piped |
+"#
+        );
+    }
+
+    #[test]
+    fn test_markdown_escapes_newlines() {
+        let mut ports = fixture();
+        ports[0].process.as_mut().unwrap().cmd_line = Some("sshd -D\n-p 22".to_string());
+        let out = export_ports(
+            &ports[..1],
+            ExportFormat::Markdown,
+            &[ExportColumn::CmdLine],
+        )
+        .unwrap();
+        assert_eq!(out, "| cmd_line |\n| --- |\n| sshd -D -p 22 |\n");
+    }
+
+    #[test]
+    fn test_column_selection() {
+        let columns = [ExportColumn::ProcessName, ExportColumn::Port];
+        let out = export_ports(&fixture(), ExportFormat::Csv, &columns).unwrap();
+        assert_eq!(
+            out,
+            "process_name,port\r\nsshd,22\r\n\"evil, \"\"quoted\"\" | piped\",5432\r\n"
+        );
+
+        let out = export_ports(&fixture()[..1], ExportFormat::Ndjson, &columns).unwrap();
+        assert_eq!(out, "{\"process_name\":\"sshd\",\"port\":22}\n");
+    }
+
+    #[test]
+    fn test_default_columns() {
+        let out = export_ports(&[], ExportFormat::Csv, &[]).unwrap();
+        let headers: Vec<&str> = ExportColumn::ALL.iter().map(|c| c.header()).collect();
+        assert_eq!(out, format!("{}\r\n", headers.join(",")));
+        assert!(out.starts_with("port,protocol,service_name,local_addr,remote_addr,"));
+    }
+}
@@ -0,0 +1,510 @@
+//! 网络暴露面分析
+//!
+//! 根据监听地址和本机接口地址判断每个监听端口的可达范围，按进程汇总并标注风险
+
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use std::net::IpAddr;
+
+use crate::core::query::AddrClass;
+use crate::core::PortScanner;
+use crate::models::{
+    AppResult, ExposedService, ExposureLevel, ExposureReport, ExposureSummary, NetworkInterface,
+    PortInfo, ProcessExposure, RiskLevel, RiskNote,
+};
+use crate::utils::net::parse_addr;
+use crate::utils::time::now_millis;
+
+/// 敏感端口类别
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Sensitivity {
+    /// 数据库、缓存、消息队列
+    Datastore,
+    /// 远程管理
+    RemoteAdmin,
+    /// 容器与编排 API，访问即等同主机权限
+    ControlPlane,
+    /// 明文认证协议
+    Plaintext,
+}
+
+/// 敏感端口
+const SENSITIVE_PORTS: &[(u16, &str, Sensitivity)] = &[
+    (21, "FTP", Sensitivity::Plaintext),
+    (23, "Telnet", Sensitivity::Plaintext),
+    (135, "MS RPC", Sensitivity::RemoteAdmin),
+    (139, "NetBIOS", Sensitivity::RemoteAdmin),
+    (445, "SMB", Sensitivity::RemoteAdmin),
+    (1433, "SQL Server", Sensitivity::Datastore),
+    (1521, "Oracle", Sensitivity::Datastore),
+    (2181, "ZooKeeper", Sensitivity::Datastore),
+    (2375, "Docker API", Sensitivity::ControlPlane),
+    (2379, "etcd", Sensitivity::ControlPlane),
+    (3306, "MySQL", Sensitivity::Datastore),
+    (3389, "RDP", Sensitivity::RemoteAdmin),
+    (5432, "PostgreSQL", Sensitivity::Datastore),
+    (5672, "AMQP", Sensitivity::Datastore),
+    (5900, "VNC", Sensitivity::RemoteAdmin),
+    (5984, "CouchDB", Sensitivity::Datastore),
+    (6379, "Redis", Sensitivity::Datastore),
+    (7474, "Neo4j", Sensitivity::Datastore),
+    (8086, "InfluxDB", Sensitivity::Datastore),
+    (9042, "Cassandra", Sensitivity::Datastore),
+    (9092, "Kafka", Sensitivity::Datastore),
+    (9200, "Elasticsearch", Sensitivity::Datastore),
+    (10250, "kubelet", Sensitivity::ControlPlane),
+    (11211, "Memcached", Sensitivity::Datastore),
+    (15672, "RabbitMQ 管理界面", Sensitivity::RemoteAdmin),
+    (27017, "MongoDB", Sensitivity::Datastore),
+];
+
+/// 常见数据库进程名，用于识别非默认端口上的数据库
+const DATASTORE_PROCESSES: &[(&str, &str)] = &[
+    ("mysqld", "MySQL"),
+    ("mariadbd", "MariaDB"),
+    ("postgres", "PostgreSQL"),
+    ("redis-server", "Redis"),
+    ("mongod", "MongoDB"),
+    ("memcached", "Memcached"),
+    ("sqlservr", "SQL Server"),
+];
+
+/// 扫描监听端口并生成报告
+pub fn exposure_report(scanner: &PortScanner) -> AppResult<ExposureReport> {
+    let ports = scanner.scan_listening()?;
+    Ok(analyze_exposure(&ports, &host_interfaces()))
+}
+
+/// 本机接口地址，获取失败时返回空列表（通配地址将按公网处理）
+pub fn host_interfaces() -> Vec<NetworkInterface> {
+    #[cfg(target_os = "linux")]
+    let result = crate::platform::linux::list_interfaces();
+
+    #[cfg(windows)]
+    let result = crate::platform::windows::list_interfaces();
+
+    #[cfg(not(any(target_os = "linux", windows)))]
+    let result: AppResult<Vec<NetworkInterface>> = Ok(Vec::new());
+
+    result.unwrap_or_else(|e| {
+        tracing::warn!("Failed to list network interfaces: {}", e);
+        Vec::new()
+    })
+}
+
+/// 分析端口列表中的监听端口
+pub fn analyze_exposure(ports: &[PortInfo], interfaces: &[NetworkInterface]) -> ExposureReport {
+    let mut summary = ExposureSummary::default();
+    let mut by_pid: BTreeMap<u32, ProcessExposure> = BTreeMap::new();
+
+    for port in ports.iter().filter(|p| p.state.is_listening()) {
+        let service = analyze_port(port, interfaces);
+        match service.exposure {
+            ExposureLevel::Loopback => summary.loopback += 1,
+            ExposureLevel::LinkLocal => summary.link_local += 1,
+            ExposureLevel::PrivateLan => summary.private_lan += 1,
+            ExposureLevel::Public => summary.public += 1,
+        }
+
+        let process = port.process.as_ref();
+        let entry = by_pid.entry(port.pid).or_insert_with(|| ProcessExposure {
+            pid: port.pid,
+            process_name: process.map(|p| p.name.clone()),
+            exe_path: process.and_then(|p| p.exe_path.clone()),
+            exposure: ExposureLevel::Loopback,
+            risk: RiskLevel::Info,
+            services: Vec::new(),
+        });
+        entry.exposure = entry.exposure.max(service.exposure);
+        entry.risk = entry.risk.max(service.risk());
+        entry.services.push(service);
+    }
+
+    let mut processes: Vec<ProcessExposure> = by_pid.into_values().collect();
+    for process in &mut processes {
+        process
+            .services
+            .sort_by(|a, b| b.risk().cmp(&a.risk()).then(a.port.cmp(&b.port)));
+    }
+    processes.sort_by(|a, b| {
+        b.risk
+            .cmp(&a.risk)
+            .then(b.exposure.cmp(&a.exposure))
+            .then(a.pid.cmp(&b.pid))
+    });
+
+    ExposureReport {
+        generated_at: now_millis(),
+        interfaces: interfaces.to_vec(),
+        summary,
+        processes,
+    }
+}
+
+fn analyze_port(port: &PortInfo, interfaces: &[NetworkInterface]) -> ExposedService {
+    let wildcard = parse_addr(&port.local_addr).is_some_and(|ip| ip.is_unspecified());
+    let (exposure, reachable_via) = reachability(port, interfaces);
+
+    let mut risks = risks_for(port, exposure, wildcard);
+    risks.sort_by_key(|note| Reverse(note.level));
+
+    ExposedService {
+        port: port.port,
+        protocol: port.protocol,
+        local_addr: port.local_addr.clone(),
+        service_name: port.service_name.clone(),
+        exposure,
+        wildcard,
+        reachable_via,
+        risks,
+    }
+}
+
+/// 端口的可达范围
+pub fn exposure_level(port: &PortInfo, interfaces: &[NetworkInterface]) -> ExposureLevel {
+    reachability(port, interfaces).0
+}
+
+/// 可达范围及可通过的非回环接口
+fn reachability(port: &PortInfo, interfaces: &[NetworkInterface]) -> (ExposureLevel, Vec<String>) {
+    match parse_addr(&port.local_addr) {
+        Some(ip) if ip.is_unspecified() => {
+            let reachable: Vec<&NetworkInterface> = interfaces
+                .iter()
+                .filter(|iface| {
+                    parse_addr(&iface.addr)
+                        .is_some_and(|addr| !addr.is_loopback() && (ip.is_ipv6() || addr.is_ipv4()))
+                })
+                .collect();
+            let exposure = if interfaces.is_empty() {
+                ExposureLevel::Public
+            } else {
+                reachable
+                    .iter()
+                    .filter_map(|iface| parse_addr(&iface.addr))
+                    .map(level_of)
+                    .max()
+                    .unwrap_or(ExposureLevel::Loopback)
+            };
+            (exposure, reachable.into_iter().map(describe).collect())
+        }
+        Some(ip) => {
+            let reachable = interfaces
+                .iter()
+                .filter(|iface| !ip.is_loopback() && parse_addr(&iface.addr) == Some(ip))
+                .map(describe)
+                .collect();
+            (level_of(ip), reachable)
+        }
+        // 无法解析的地址按公网处理
+        None => (ExposureLevel::Public, Vec::new()),
+    }
+}
+
+fn level_of(ip: IpAddr) -> ExposureLevel {
+    match AddrClass::of(ip) {
+        AddrClass::Loopback => ExposureLevel::Loopback,
+        AddrClass::LinkLocal => ExposureLevel::LinkLocal,
+        AddrClass::Private => ExposureLevel::PrivateLan,
+        AddrClass::Any | AddrClass::Multicast | AddrClass::Public => ExposureLevel::Public,
+    }
+}
+
+fn describe(iface: &NetworkInterface) -> String {
+    format!("{} {}", iface.name, iface.addr)
+}
+
+fn risks_for(port: &PortInfo, exposure: ExposureLevel, wildcard: bool) -> Vec<RiskNote> {
+    let mut risks = Vec::new();
+    if exposure == ExposureLevel::Loopback {
+        return risks;
+    }
+
+    let scope = match exposure {
+        ExposureLevel::Public => "公网",
+        ExposureLevel::PrivateLan => "局域网",
+        _ => "本地链路",
+    };
+    let note = |level, message: String| RiskNote { level, message };
+
+    let sensitive = SENSITIVE_PORTS
+        .iter()
+        .find(|(p, _, _)| *p == port.port)
+        .map(|(_, name, kind)| (*name, *kind))
+        .or_else(|| {
+            let process = port.process.as_ref()?.name.to_lowercase();
+            let process = process.trim_end_matches(".exe");
+            DATASTORE_PROCESSES
+                .iter()
+                .find(|(name, _)| *name == process)
+                .map(|(_, label)| (*label, Sensitivity::Datastore))
+        });
+
+    match sensitive {
+        Some((name, Sensitivity::ControlPlane)) => risks.push(note(
+            RiskLevel::High,
+            format!("{} 可从{}访问，获得访问即可控制主机", name, scope),
+        )),
+        Some((name, Sensitivity::Plaintext)) => risks.push(note(
+            RiskLevel::High,
+            format!("{} 为明文协议，凭据可被{}窃听", name, scope),
+        )),
+        Some((name, kind)) => {
+            let level = match exposure {
+                ExposureLevel::Public => RiskLevel::High,
+                ExposureLevel::PrivateLan => RiskLevel::Medium,
+                _ => RiskLevel::Low,
+            };
+            let what = if kind == Sensitivity::Datastore {
+                "数据库/中间件"
+            } else {
+                "远程管理服务"
+            };
+            risks.push(note(
+                level,
+                format!("{}（{}）可从{}访问", what, name, scope),
+            ));
+        }
+        None if exposure == ExposureLevel::Public => {
+            risks.push(note(RiskLevel::Medium, "服务可从公网地址访问".to_string()))
+        }
+        None => {}
+    }
+
+    if wildcard {
+        risks.push(note(
+            RiskLevel::Low,
+            format!("监听在所有接口（{}），可改为绑定具体地址", port.local_addr),
+        ));
+    }
+
+    risks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{ConnectionState, ProcessInfo, Protocol};
+
+    fn iface(name: &str, addr: &str) -> NetworkInterface {
+        NetworkInterface {
+            name: name.to_string(),
+            addr: addr.to_string(),
+            prefix_len: 24,
+        }
+    }
+
+    fn listener(port: u16, addr: &str, pid: u32, process: &str) -> PortInfo {
+        let mut info = PortInfo::new(port, Protocol::TCP, addr.to_string(), pid);
+        info.state = ConnectionState::Listen;
+        info.process = Some(ProcessInfo::new(pid, process.to_string()));
+        info
+    }
+
+    fn lan_host() -> Vec<NetworkInterface> {
+        vec![
+            iface("lo", "127.0.0.1"),
+            iface("lo", "::1"),
+            iface("eth0", "192.168.1.5"),
+        ]
+    }
+
+    fn dual_stack_host() -> Vec<NetworkInterface> {
+        let mut interfaces = lan_host();
+        interfaces.push(iface("eth0", "2001:db8::5"));
+        interfaces.push(iface("eth0", "fe80::5"));
+        interfaces
+    }
+
+    #[test]
+    fn test_reachability_table() {
+        let loopback_only = vec![iface("lo", "127.0.0.1"), iface("lo", "::1")];
+        let cases: Vec<(&str, Vec<NetworkInterface>, ExposureLevel, Vec<&str>)> = vec![
+            // IPv4 通配地址不经过 IPv6 接口
+            (
+                "0.0.0.0",
+                dual_stack_host(),
+                ExposureLevel::PrivateLan,
+                vec!["eth0 192.168.1.5"],
+            ),
+            // IPv6 通配地址按双栈处理，同时经过 IPv4 接口
+            (
+                "::",
+                dual_stack_host(),
+                ExposureLevel::Public,
+                vec!["eth0 192.168.1.5", "eth0 2001:db8::5", "eth0 fe80::5"],
+            ),
+            (
+                "::",
+                lan_host(),
+                ExposureLevel::PrivateLan,
+                vec!["eth0 192.168.1.5"],
+            ),
+            (
+                "0.0.0.0",
+                loopback_only.clone(),
+                ExposureLevel::Loopback,
+                vec![],
+            ),
+            ("::", loopback_only, ExposureLevel::Loopback, vec![]),
+            // 接口未知时按最坏情况处理
+            ("0.0.0.0", vec![], ExposureLevel::Public, vec![]),
+            (
+                "192.168.1.5",
+                lan_host(),
+                ExposureLevel::PrivateLan,
+                vec!["eth0 192.168.1.5"],
+            ),
+            ("10.8.0.1", lan_host(), ExposureLevel::PrivateLan, vec![]),
+            (
+                "fe80::5%eth0",
+                dual_stack_host(),
+                ExposureLevel::LinkLocal,
+                vec!["eth0 fe80::5"],
+            ),
+            ("127.0.0.1", lan_host(), ExposureLevel::Loopback, vec![]),
+            (
+                "::ffff:127.0.0.1",
+                lan_host(),
+                ExposureLevel::Loopback,
+                vec![],
+            ),
+            ("[::1]", lan_host(), ExposureLevel::Loopback, vec![]),
+            ("203.0.113.9", lan_host(), ExposureLevel::Public, vec![]),
+            ("*", lan_host(), ExposureLevel::Public, vec![]),
+        ];
+
+        for (addr, interfaces, level, via) in cases {
+            let port = listener(8000, addr, 1, "app");
+            let (exposure, reachable) = reachability(&port, &interfaces);
+            assert_eq!(exposure, level, "{} on {:?}", addr, interfaces);
+            assert_eq!(reachable, via, "{} on {:?}", addr, interfaces);
+            assert_eq!(exposure_level(&port, &interfaces), level);
+        }
+    }
+
+    #[test]
+    fn test_risks_by_port_and_scope() {
+        let levels = |port: &PortInfo, interfaces: &[NetworkInterface]| {
+            let service = analyze_port(port, interfaces);
+            let levels: Vec<RiskLevel> = service.risks.iter().map(|r| r.level).collect();
+            (service.wildcard, levels)
+        };
+
+        // 仅回环可达时没有风险，即使监听在通配地址
+        let loopback = vec![iface("lo", "127.0.0.1")];
+        let port = listener(6379, "0.0.0.0", 1, "redis-server");
+        assert_eq!(levels(&port, &loopback), (true, vec![]));
+
+        // 无接口信息：公网 + 通配地址
+        let port = listener(8080, "0.0.0.0", 1, "java");
+        assert_eq!(
+            levels(&port, &[]),
+            (true, vec![RiskLevel::Medium, RiskLevel::Low])
+        );
+
+        // 数据库按可达范围分级
+        let port = listener(5432, "192.168.1.5", 1, "postgres");
+        assert_eq!(levels(&port, &lan_host()), (false, vec![RiskLevel::Medium]));
+        let port = listener(5432, "203.0.113.9", 1, "postgres");
+        assert_eq!(levels(&port, &lan_host()), (false, vec![RiskLevel::High]));
+        let port = listener(3389, "fe80::5", 1, "svchost.exe");
+        assert_eq!(
+            levels(&port, &dual_stack_host()),
+            (false, vec![RiskLevel::Low])
+        );
+
+        // 控制面和明文协议不论范围都是高风险
+        let port = listener(2375, "192.168.1.5", 1, "dockerd");
+        assert_eq!(levels(&port, &lan_host()), (false, vec![RiskLevel::High]));
+        let port = listener(23, "0.0.0.0", 1, "telnetd");
+        assert_eq!(
+            levels(&port, &lan_host()),
+            (true, vec![RiskLevel::High, RiskLevel::Low])
+        );
+
+        // 局域网上的普通服务没有风险说明
+        let port = listener(8080, "192.168.1.5", 1, "java");
+        assert_eq!(levels(&port, &lan_host()), (false, vec![]));
+    }
+
+    #[test]
+    fn test_datastore_on_non_default_port_by_process() {
+        let port = listener(15432, "192.168.1.5", 1, "postgres");
+        let service = analyze_port(&port, &lan_host());
+        assert_eq!(service.risk(), RiskLevel::Medium);
+        assert!(service.risks[0].message.contains("PostgreSQL"));
+
+        // Windows 进程名带 .exe 且大小写不一
+        let port = listener(16379, "203.0.113.9", 1, "Redis-Server.EXE");
+        let service = analyze_port(&port, &lan_host());
+        assert_eq!(service.risk(), RiskLevel::High);
+        assert!(service.risks[0].message.contains("Redis"));
+
+        let port = listener(15432, "203.0.113.9", 1, "postgres-exporter");
+        let service = analyze_port(&port, &lan_host());
+        assert!(!service.risks[0].message.contains("PostgreSQL"));
+        assert_eq!(service.risk(), RiskLevel::Medium);
+    }
+
+    #[test]
+    fn test_report_groups_and_orders_by_risk() {
+        let mut connection = listener(50000, "192.168.1.5", 5, "curl");
+        connection.state = ConnectionState::Established;
+        let ports = vec![
+            listener(3000, "127.0.0.1", 1, "node"),
+            listener(8080, "0.0.0.0", 2, "java"),
+            listener(8081, "192.168.1.5", 2, "java"),
+            listener(6379, "0.0.0.0", 3, "redis-server"),
+            listener(9000, "192.168.1.5", 4, "minio"),
+            listener(5432, "192.168.1.5", 4, "minio"),
+            connection,
+        ];
+
+        let report = analyze_exposure(&ports, &lan_host());
+        let summary = &report.summary;
+        assert_eq!(
+            (
+                summary.loopback,
+                summary.link_local,
+                summary.private_lan,
+                summary.public
+            ),
+            (1, 0, 5, 0)
+        );
+
+        let order: Vec<(u32, RiskLevel, ExposureLevel)> = report
+            .processes
+            .iter()
+            .map(|p| (p.pid, p.risk, p.exposure))
+            .collect();
+        assert_eq!(
+            order,
+            vec![
+                (3, RiskLevel::Medium, ExposureLevel::PrivateLan),
+                (4, RiskLevel::Medium, ExposureLevel::PrivateLan),
+                (2, RiskLevel::Low, ExposureLevel::PrivateLan),
+                (1, RiskLevel::Info, ExposureLevel::Loopback),
+            ]
+        );
+
+        // 进程内按风险降序，再按端口
+        let minio: Vec<u16> = report.processes[1]
+            .services
+            .iter()
+            .map(|s| s.port)
+            .collect();
+        assert_eq!(minio, vec![5432, 9000]);
+        let java: Vec<u16> = report.processes[2]
+            .services
+            .iter()
+            .map(|s| s.port)
+            .collect();
+        assert_eq!(java, vec![8080, 8081]);
+
+        // 单个端口的风险说明按等级降序
+        let redis = &report.processes[0].services[0];
+        let levels: Vec<RiskLevel> = redis.risks.iter().map(|r| r.level).collect();
+        assert_eq!(levels, vec![RiskLevel::Medium, RiskLevel::Low]);
+    }
+}
@@ -0,0 +1,20 @@
+//! 监听端口与主机防火墙规则的关联（目前仅支持 Linux）
+
+use crate::core::PortScanner;
+use crate::models::{AppResult, PortInfo};
+
+/// 扫描监听端口并填充防火墙状态
+pub fn scan_firewall(scanner: &PortScanner) -> AppResult<Vec<PortInfo>> {
+    let mut ports = scanner.scan_listening()?;
+    annotate_firewall(&mut ports);
+    Ok(ports)
+}
+
+/// 为非回环监听端口填充 `firewall`，其它平台保持为 None
+pub fn annotate_firewall(ports: &mut [PortInfo]) {
+    #[cfg(target_os = "linux")]
+    crate::platform::linux::annotate_firewall(ports);
+
+    #[cfg(not(target_os = "linux"))]
+    let _ = ports;
+}
@@ -0,0 +1,555 @@
+//! Prometheus 指标
+//!
+//! 计数器在进程内全局累计；仪表值在每次抓取时由扫描结果计算，
+//! 输出为 Prometheus 文本格式（0.0.4）
+
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::models::{ChangeKind, MonitorEvent, PortInfo};
+
+/// 超出标签数量限制时合并使用的标签值
+pub const OTHER_LABEL: &str = "other";
+
+/// 全局计数器
+pub struct Counters {
+    snapshot_events: AtomicU64,
+    change_events: AtomicU64,
+    added: AtomicU64,
+    removed: AtomicU64,
+    state_changed: AtomicU64,
+    owner_changed: AtomicU64,
+    kill_success: AtomicU64,
+    kill_failure: AtomicU64,
+}
+
+static COUNTERS: Counters = Counters::new();
+
+/// 获取全局计数器
+pub fn counters() -> &'static Counters {
+    &COUNTERS
+}
+
+impl Counters {
+    const fn new() -> Self {
+        Self {
+            snapshot_events: AtomicU64::new(0),
+            change_events: AtomicU64::new(0),
+            added: AtomicU64::new(0),
+            removed: AtomicU64::new(0),
+            state_changed: AtomicU64::new(0),
+            owner_changed: AtomicU64::new(0),
+            kill_success: AtomicU64::new(0),
+            kill_failure: AtomicU64::new(0),
+        }
+    }
+
+    /// 记录一个已发送的监控事件
+    pub fn record_event(&self, event: &MonitorEvent) {
+        match event {
+            MonitorEvent::Snapshot(_) => {
+                self.snapshot_events.fetch_add(1, Ordering::Relaxed);
+            }
+            MonitorEvent::Change(change) => {
+                self.change_events.fetch_add(1, Ordering::Relaxed);
+                for entry in &change.entries {
+                    let counter = match entry.kind {
+                        ChangeKind::Added => &self.added,
+                        ChangeKind::Removed => &self.removed,
+                        ChangeKind::StateChanged { .. } => &self.state_changed,
+                        ChangeKind::OwnerChanged { .. } => &self.owner_changed,
+                    };
+                    counter.fetch_add(1, Ordering::Relaxed);
+                }
+            }
+        }
+    }
+
+    /// 记录一次终止进程操作
+    pub fn record_kill(&self, success: bool) {
+        let counter = if success {
+            &self.kill_success
+        } else {
+            &self.kill_failure
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// 标签数量限制
+#[derive(Debug, Clone, Copy)]
+pub struct LabelLimits {
+    /// 按进程统计时最多输出的进程数
+    pub max_processes: usize,
+    /// 按本地端口统计时最多输出的端口数
+    pub max_ports: usize,
+}
+
+impl Default for LabelLimits {
+    fn default() -> Self {
+        Self {
+            max_processes: 50,
+            max_ports: 100,
+        }
+    }
+}
+
+/// 根据扫描结果和全局计数器生成指标文本
+pub fn render(ports: &[PortInfo], limits: &LabelLimits) -> String {
+    let mut out = String::new();
+
+    // 按协议和状态
+    let mut by_state: HashMap<(String, String), u64> = HashMap::new();
+    for port in ports {
+        let key = (format!("{:?}", port.protocol), format!("{:?}", port.state));
+        *by_state.entry(key).or_default() += 1;
+    }
+    write_header(
+        &mut out,
+        "port_detection_sockets",
+        "gauge",
+        "Number of sockets by protocol and state",
+    );
+    for ((protocol, state), count) in sorted(by_state) {
+        write_sample(
+            &mut out,
+            "port_detection_sockets",
+            &[("protocol", &protocol), ("state", &state)],
+            count,
+        );
+    }
+
+    // 按协议
+    let mut by_protocol: HashMap<String, u64> = HashMap::new();
+    for port in ports {
+        *by_protocol
+            .entry(format!("{:?}", port.protocol))
+            .or_default() += 1;
+    }
+    write_header(
+        &mut out,
+        "port_detection_protocol_sockets",
+        "gauge",
+        "Number of sockets by protocol",
+    );
+    for (protocol, count) in sorted(by_protocol) {
+        write_sample(
+            &mut out,
+            "port_detection_protocol_sockets",
+            &[("protocol", &protocol)],
+            count,
+        );
+    }
+
+    // 按进程
+    let mut by_process: HashMap<String, u64> = HashMap::new();
+    for port in ports {
+        let name = port
+            .process
+            .as_ref()
+            .map(|p| p.name.clone())
+            .unwrap_or_else(|| "unknown".to_string());
+        *by_process.entry(name).or_default() += 1;
+    }
+    write_header(
+        &mut out,
+        "port_detection_process_sockets",
+        "gauge",
+        "Number of sockets by owning process name",
+    );
+    for (process, count) in limit_labels(by_process, limits.max_processes) {
+        write_sample(
+            &mut out,
+            "port_detection_process_sockets",
+            &[("process", &process)],
+            count,
+        );
+    }
+
+    // 按本地监听端口
+    let mut by_port: HashMap<String, u64> = HashMap::new();
+    for port in ports.iter().filter(|p| p.state.is_listening()) {
+        let key = format!("{}/{:?}", port.port, port.protocol);
+        *by_port.entry(key).or_default() += 1;
+    }
+    write_header(
+        &mut out,
+        "port_detection_listeners",
+        "gauge",
+        "Number of listening sockets by local port",
+    );
+    for (label, count) in limit_labels(by_port, limits.max_ports) {
+        // 合并项没有协议，整体作为 port 标签输出
+        let (port, protocol) = label.split_once('/').unwrap_or((label.as_str(), ""));
+        write_sample(
+            &mut out,
+            "port_detection_listeners",
+            &[("port", port), ("protocol", protocol)],
+            count,
+        );
+    }
+
+    // 计数器
+    let c = counters();
+    write_header(
+        &mut out,
+        "port_detection_monitor_events_total",
+        "counter",
+        "Monitor events emitted by type",
+    );
+    for (kind, counter) in [
+        ("snapshot", &c.snapshot_events),
+        ("change", &c.change_events),
+    ] {
+        write_sample(
+            &mut out,
+            "port_detection_monitor_events_total",
+            &[("type", kind)],
+            counter.load(Ordering::Relaxed),
+        );
+    }
+
+    write_header(
+        &mut out,
+        "port_detection_monitor_changes_total",
+        "counter",
+        "Port change entries emitted by kind",
+    );
+    for (kind, counter) in [
+        ("added", &c.added),
+        ("removed", &c.removed),
+        ("state_changed", &c.state_changed),
+        ("owner_changed", &c.owner_changed),
+    ] {
+        write_sample(
+            &mut out,
+            "port_detection_monitor_changes_total",
+            &[("kind", kind)],
+            counter.load(Ordering::Relaxed),
+        );
+    }
+
+    write_header(
+        &mut out,
+        "port_detection_kill_actions_total",
+        "counter",
+        "Process kill actions by result",
+    );
+    for (result, counter) in [("success", &c.kill_success), ("failure", &c.kill_failure)] {
+        write_sample(
+            &mut out,
+            "port_detection_kill_actions_total",
+            &[("result", result)],
+            counter.load(Ordering::Relaxed),
+        );
+    }
+
+    out
+}
+
+fn write_header(out: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, kind);
+}
+
+fn write_sample(out: &mut String, name: &str, labels: &[(&str, &str)], value: u64) {
+    let labels: Vec<String> = labels
+        .iter()
+        .map(|(k, v)| format!("{}=\"{}\"", k, escape_label(v)))
+        .collect();
+    let _ = writeln!(out, "{}{{{}}} {}", name, labels.join(","), value);
+}
+
+/// 转义标签值中的反斜杠、双引号和换行
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// 按标签值排序，保证输出稳定
+fn sorted<K: Ord>(map: HashMap<K, u64>) -> Vec<(K, u64)> {
+    let mut items: Vec<(K, u64)> = map.into_iter().collect();
+    items.sort_by(|a, b| a.0.cmp(&b.0));
+    items
+}
+
+/// 保留数量最多的 `max` 个标签，其余合并为 `OTHER_LABEL`
+///
+/// 已有同名标签时合并到该项，避免输出重复的时间序列
+fn limit_labels(map: HashMap<String, u64>, max: usize) -> Vec<(String, u64)> {
+    let mut items: Vec<(String, u64)> = map.into_iter().collect();
+    items.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+    if items.len() <= max {
+        items.sort_by(|a, b| a.0.cmp(&b.0));
+        return items;
+    }
+
+    let other: u64 = items[max..].iter().map(|(_, count)| count).sum();
+    items.truncate(max);
+    match items.iter_mut().find(|(label, _)| label == OTHER_LABEL) {
+        Some((_, count)) => *count += other,
+        None => items.push((OTHER_LABEL.to_string(), other)),
+    }
+    items.sort_by(|a, b| a.0.cmp(&b.0));
+    items
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{BTreeMap, HashSet};
+
+    use super::*;
+    use crate::models::{ConnectionState, ProcessInfo, Protocol};
+
+    /// 解析后的样本
+    #[derive(Debug)]
+    struct Sample {
+        name: String,
+        labels: BTreeMap<String, String>,
+        value: f64,
+    }
+
+    fn is_metric_name(name: &str) -> bool {
+        let mut chars = name.chars();
+        chars
+            .next()
+            .is_some_and(|c| c.is_ascii_alphabetic() || c == '_' || c == ':')
+            && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == ':')
+    }
+
+    fn is_label_name(name: &str) -> bool {
+        let mut chars = name.chars();
+        chars
+            .next()
+            .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+            && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+    }
+
+    /// 解析 `{k="v",...}`，返回标签和剩余文本
+    fn parse_labels(text: &str) -> Result<(BTreeMap<String, String>, &str), String> {
+        let mut labels = BTreeMap::new();
+        let mut rest = text.strip_prefix('{').ok_or("missing {")?;
+        loop {
+            if let Some(after) = rest.strip_prefix('}') {
+                return Ok((labels, after));
+            }
+            let (name, after) = rest.split_once("=\"").ok_or("missing =\"")?;
+            if !is_label_name(name) {
+                return Err(format!("invalid label name {:?}", name));
+            }
+            let mut value = String::new();
+            let mut chars = after.char_indices();
+            let end = loop {
+                match chars.next().ok_or("unterminated label value")? {
+                    (_, '\\') => match chars.next().ok_or("dangling escape")?.1 {
+                        '\\' => value.push('\\'),
+                        '"' => value.push('"'),
+                        'n' => value.push('\n'),
+                        c => return Err(format!("invalid escape \\{}", c)),
+                    },
+                    (i, '"') => break i,
+                    (_, '\n') => return Err("raw newline in label value".to_string()),
+                    (_, c) => value.push(c),
+                }
+            };
+            if labels.insert(name.to_string(), value).is_some() {
+                return Err(format!("duplicate label {}", name));
+            }
+            rest = &after[end + 1..];
+            rest = rest.strip_prefix(',').unwrap_or(rest);
+        }
+    }
+
+    /// 按文本格式 0.0.4 解析并校验，返回所有样本
+    fn parse_exposition(text: &str) -> Result<Vec<Sample>, String> {
+        let mut types: HashMap<String, String> = HashMap::new();
+        let mut helps: HashSet<String> = HashSet::new();
+        let mut series: HashSet<(String, BTreeMap<String, String>)> = HashSet::new();
+        let mut samples = Vec::new();
+
+        for line in text.lines() {
+            if let Some(help) = line.strip_prefix("# HELP ") {
+                let (name, _) = help.split_once(' ').ok_or("HELP without text")?;
+                if !helps.insert(name.to_string()) {
+                    return Err(format!("duplicate HELP for {}", name));
+                }
+                continue;
+            }
+            if let Some(kind) = line.strip_prefix("# TYPE ") {
+                let (name, kind) = kind.split_once(' ').ok_or("TYPE without type")?;
+                if !matches!(
+                    kind,
+                    "counter" | "gauge" | "histogram" | "summary" | "untyped"
+                ) {
+                    return Err(format!("invalid type {}", kind));
+                }
+                if types.insert(name.to_string(), kind.to_string()).is_some() {
+                    return Err(format!("duplicate TYPE for {}", name));
+                }
+                continue;
+            }
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let name_end = line.find(['{', ' ']).ok_or("sample without value")?;
+            let name = &line[..name_end];
+            if !is_metric_name(name) {
+                return Err(format!("invalid metric name {:?}", name));
+            }
+            let kind = types
+                .get(name)
+                .ok_or(format!("sample {} before its TYPE", name))?;
+            if kind == "counter" && !name.ends_with("_total") {
+                return Err(format!("counter {} should end with _total", name));
+            }
+            let (labels, rest) = if line[name_end..].starts_with('{') {
+                parse_labels(&line[name_end..])?
+            } else {
+                (BTreeMap::new(), &line[name_end..])
+            };
+            let value: f64 = rest
+                .trim_start()
+                .parse()
+                .map_err(|_| format!("invalid value in {:?}", line))?;
+            if !series.insert((name.to_string(), labels.clone())) {
+                return Err(format!("duplicate series {} {:?}", name, labels));
+            }
+            samples.push(Sample {
+                name: name.to_string(),
+                labels,
+                value,
+            });
+        }
+
+        Ok(samples)
+    }
+
+    fn port(port: u16, protocol: Protocol, state: ConnectionState, process: &str) -> PortInfo {
+        let mut info = PortInfo::new(port, protocol, "0.0.0.0".to_string(), 1);
+        info.state = state;
+        info.process = Some(ProcessInfo::new(1, process.to_string()));
+        info
+    }
+
+    fn value(samples: &[Sample], name: &str, labels: &[(&str, &str)]) -> Option<f64> {
+        samples
+            .iter()
+            .find(|s| {
+                s.name == name
+                    && s.labels.len() == labels.len()
+                    && labels
+                        .iter()
+                        .all(|(k, v)| s.labels.get(*k).map(String::as_str) == Some(v))
+            })
+            .map(|s| s.value)
+    }
+
+    #[test]
+    fn test_render_is_valid_exposition() {
+        let ports = vec![
+            port(22, Protocol::TCP, ConnectionState::Listen, "sshd"),
+            port(22, Protocol::TCP, ConnectionState::Established, "sshd"),
+            port(53, Protocol::UDP, ConnectionState::Bound, "dnsmasq"),
+            port(
+                8080,
+                Protocol::TCP,
+                ConnectionState::Listen,
+                "we\"ird\\na\nme",
+            ),
+            port(9000, Protocol::TCP, ConnectionState::Listen, "other"),
+            port(9001, Protocol::TCP, ConnectionState::Listen, "other"),
+        ];
+        let text = render(&ports, &LabelLimits::default());
+        let samples = parse_exposition(&text).unwrap_or_else(|e| panic!("{}\n{}", e, text));
+
+        assert_eq!(
+            value(
+                &samples,
+                "port_detection_sockets",
+                &[("protocol", "TCP"), ("state", "Listen")]
+            ),
+            Some(4.0)
+        );
+        assert_eq!(
+            value(
+                &samples,
+                "port_detection_protocol_sockets",
+                &[("protocol", "UDP")]
+            ),
+            Some(1.0)
+        );
+        assert_eq!(
+            value(
+                &samples,
+                "port_detection_process_sockets",
+                &[("process", "we\"ird\\na\nme")]
+            ),
+            Some(1.0)
+        );
+        assert_eq!(
+            value(
+                &samples,
+                "port_detection_listeners",
+                &[("port", "53"), ("protocol", "UDP")]
+            ),
+            Some(1.0)
+        );
+        assert!(value(
+            &samples,
+            "port_detection_kill_actions_total",
+            &[("result", "success")]
+        )
+        .is_some());
+    }
+
+    #[test]
+    fn test_label_limits_merge_into_existing_other() {
+        let ports = vec![
+            port(1, Protocol::TCP, ConnectionState::Listen, "other"),
+            port(2, Protocol::TCP, ConnectionState::Listen, "other"),
+            port(3, Protocol::TCP, ConnectionState::Listen, "other"),
+            port(4, Protocol::TCP, ConnectionState::Listen, "nginx"),
+            port(5, Protocol::TCP, ConnectionState::Listen, "nginx"),
+            port(6, Protocol::TCP, ConnectionState::Listen, "redis"),
+            port(7, Protocol::TCP, ConnectionState::Listen, "sshd"),
+        ];
+        let limits = LabelLimits {
+            max_processes: 2,
+            max_ports: 3,
+        };
+        let text = render(&ports, &limits);
+        let samples = parse_exposition(&text).unwrap_or_else(|e| panic!("{}\n{}", e, text));
+
+        let processes: Vec<(&str, f64)> = samples
+            .iter()
+            .filter(|s| s.name == "port_detection_process_sockets")
+            .map(|s| (s.labels["process"].as_str(), s.value))
+            .collect();
+        assert_eq!(processes, vec![("nginx", 2.0), ("other", 5.0)]);
+
+        let listeners = samples
+            .iter()
+            .filter(|s| s.name == "port_detection_listeners")
+            .count();
+        assert_eq!(listeners, 4);
+        assert_eq!(
+            value(
+                &samples,
+                "port_detection_listeners",
+                &[("port", OTHER_LABEL), ("protocol", "")]
+            ),
+            Some(4.0)
+        );
+    }
+
+    #[test]
+    fn test_parser_rejects_duplicate_series() {
+        let text = "# HELP m help\n# TYPE m gauge\nm{a=\"x\"} 1\nm{a=\"x\"} 2\n";
+        assert!(parse_exposition(text).is_err());
+    }
+}
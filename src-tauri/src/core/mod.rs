@@ -1,7 +1,24 @@
 //! 核心业务逻辑
 
 mod port_scanner;
+pub mod analytics;
+pub mod baseline;
+pub mod coalescer;
+pub mod compliance;
+pub mod detection;
+pub mod executable;
+pub mod exporter;
+pub mod exposure;
+pub mod firewall;
+pub mod metrics;
 pub mod monitor;
+pub mod probe;
+pub mod query;
+pub mod resolver;
+pub mod sink;
+pub mod snapshot_store;
+pub mod trigger;
+pub mod watch;
 
-pub use port_scanner::PortScanner;
+pub use port_scanner::{PortFilter, PortScanner};
 pub use monitor::PortMonitor;
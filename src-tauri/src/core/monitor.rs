@@ -2,13 +2,20 @@
 
 use std::collections::HashMap;
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
-use tokio::sync::{mpsc, RwLock};
-use tokio::time::interval;
+use tokio::sync::{mpsc, Mutex};
+use tokio::time::{interval, interval_at, sleep_until, Instant as TokioInstant};
 
+use crate::core::coalescer::ChangeCoalescer;
+use crate::core::metrics;
+use crate::core::trigger::TriggerSource;
+use crate::core::watch::WatchRegistry;
 use crate::core::PortScanner;
-use crate::models::{AppResult, PortChange, PortInfo, Protocol};
+use crate::models::{
+    AppResult, ChangeEntry, MonitorEvent, PortChange, PortInfo, PortSnapshot, Protocol,
+};
+use crate::utils::time::now_millis;
 
 /// 监控配置
 #[derive(Debug, Clone)]
@@ -19,6 +26,16 @@ pub struct MonitorConfig {
     pub max_backoff: u32,
     /// 是否只监控监听端口
     pub listen_only: bool,
+    /// 新增端口的最小存活时间（毫秒），存活不足的短连接不上报
+    pub min_lifetime_ms: u64,
+    /// 抖动窗口（毫秒），移除后在窗口内重新出现的端口合并处理
+    pub flap_window_ms: u64,
+    /// 批量发送窗口（毫秒），0 表示每次采样后立即发送
+    pub batch_window_ms: u64,
+    /// 是否启用平台事件触发源（如 Linux netlink）
+    pub event_driven: bool,
+    /// 事件触发后的合并等待时间（毫秒）
+    pub trigger_debounce_ms: u64,
 }
 
 impl Default for MonitorConfig {
@@ -27,41 +44,74 @@ impl Default for MonitorConfig {
             interval_ms: 2000,
             max_backoff: 5,
             listen_only: false,
+            min_lifetime_ms: 3000,
+            flap_window_ms: 3000,
+            batch_window_ms: 1000,
+            event_driven: true,
+            trigger_debounce_ms: 200,
         }
     }
 }
 
+/// 端口的键，用于按端口跟踪（监听集合、端口时间线）
+pub type PortKey = (u16, Protocol);
+
+/// 快照中单个 socket 的键：(本地端口, 协议, 本地地址, 远程地址, 远程端口)
+///
+/// 同一端口上的监听 socket、各条连接以及 IPv4/IPv6 绑定各自独立，
+/// 快照和变化都以 socket 为单位，不会相互覆盖
+pub type SocketKey = (u16, Protocol, String, Option<String>, Option<u16>);
+
+/// 端口信息对应的 socket 键
+pub fn socket_key(info: &PortInfo) -> SocketKey {
+    (
+        info.port,
+        info.protocol,
+        info.local_addr.clone(),
+        info.remote_addr.clone(),
+        info.remote_port,
+    )
+}
+
 /// 端口监控器
 pub struct PortMonitor {
     config: MonitorConfig,
     scanner: PortScanner,
-    last_snapshot: HashMap<(u16, Protocol), PortInfo>,
+    last_snapshot: HashMap<SocketKey, PortInfo>,
     idle_count: u32,
+    /// 快照版本，只在发送基线或发送改变状态的变化时递增，
+    /// 被合并器抑制的原始采样不计入
+    version: u64,
+    /// 下一个事件序号
+    next_seq: u64,
 }
 
 impl PortMonitor {
     pub fn new(config: MonitorConfig) -> Self {
+        Self::with_scanner(config, PortScanner::new())
+    }
+
+    /// 使用指定扫描器创建监控器
+    pub fn with_scanner(config: MonitorConfig, scanner: PortScanner) -> Self {
         Self {
             config,
-            scanner: PortScanner::new(),
+            scanner,
             last_snapshot: HashMap::new(),
             idle_count: 0,
+            version: 0,
+            next_seq: 1,
         }
     }
 
     /// 执行一次扫描并返回变化
-    pub fn poll(&mut self) -> AppResult<Option<PortChange>> {
+    pub fn poll(&mut self) -> AppResult<Option<Vec<ChangeEntry>>> {
         let current = if self.config.listen_only {
             self.scanner.scan_listening()?
         } else {
             self.scanner.scan_all()?
         };
 
-        let current_map: HashMap<(u16, Protocol), PortInfo> = current
-            .into_iter()
-            .map(|p| ((p.port, p.protocol), p))
-            .collect();
-
+        let current_map = snapshot_map(current);
         let changes = self.diff(&current_map);
 
         if changes.is_empty() {
@@ -75,37 +125,62 @@ impl PortMonitor {
     }
 
     /// 计算差分
-    fn diff(&self, current: &HashMap<(u16, Protocol), PortInfo>) -> PortChange {
-        let mut added = Vec::new();
-        let mut removed = Vec::new();
-        let mut changed = Vec::new();
-
-        // 查找新增和变化的端口
-        for (key, port) in current {
-            match self.last_snapshot.get(key) {
-                None => added.push(port.clone()),
-                Some(old) => {
-                    if old.pid != port.pid || old.state != port.state {
-                        changed.push(port.clone());
-                    }
-                }
-            }
-        }
+    fn diff(&self, current: &HashMap<SocketKey, PortInfo>) -> Vec<ChangeEntry> {
+        diff_snapshots(&self.last_snapshot, current)
+    }
 
-        // 查找移除的端口
-        for (key, port) in &self.last_snapshot {
-            if !current.contains_key(key) {
-                removed.push(port.clone());
-            }
-        }
+    /// 扫描并建立基线，返回全量快照
+    ///
+    /// 之后的 `poll` 只返回相对基线的真实变化
+    pub fn baseline(&mut self) -> AppResult<PortSnapshot> {
+        let current = if self.config.listen_only {
+            self.scanner.scan_listening()?
+        } else {
+            self.scanner.scan_all()?
+        };
+
+        self.last_snapshot = snapshot_map(current.clone());
+        self.idle_count = 0;
+        self.version += 1;
+
+        let seq = self.next_seq;
+        self.next_seq += 1;
+
+        Ok(PortSnapshot {
+            seq,
+            timestamp: now_millis(),
+            snapshot_version: self.version,
+            ports: current,
+        })
+    }
+
+    /// 当前基线快照
+    pub fn current_snapshot(&self) -> &HashMap<SocketKey, PortInfo> {
+        &self.last_snapshot
+    }
+
+    /// 为待发送的变化分配序号、时间戳和快照版本
+    ///
+    /// 每个发送出去的变化都会改变订阅者的状态，因此版本随之递增；
+    /// 订阅者可据此确认自己按顺序应用了全部变化
+    pub fn stamp(&mut self, entries: Vec<ChangeEntry>) -> PortChange {
+        let seq = self.next_seq;
+        self.next_seq += 1;
+        self.version += 1;
 
         PortChange {
-            added,
-            removed,
-            changed,
+            seq,
+            timestamp: now_millis(),
+            snapshot_version: self.version,
+            entries,
         }
     }
 
+    /// 当前快照版本
+    pub fn snapshot_version(&self) -> u64 {
+        self.version
+    }
+
     /// 动态采样间隔
     pub fn current_interval(&self) -> Duration {
         Duration::from_millis(self.config.interval_ms * (1 + self.idle_count as u64))
@@ -118,56 +193,590 @@ impl PortMonitor {
     }
 }
 
+/// 将端口列表转换为以 socket 为键的快照
+pub fn snapshot_map(ports: Vec<PortInfo>) -> HashMap<SocketKey, PortInfo> {
+    ports.into_iter().map(|p| (socket_key(&p), p)).collect()
+}
+
+/// 计算两个快照之间的变化，结果按 socket 键排序
+pub fn diff_snapshots(
+    previous: &HashMap<SocketKey, PortInfo>,
+    current: &HashMap<SocketKey, PortInfo>,
+) -> Vec<ChangeEntry> {
+    let mut entries = Vec::new();
+
+    // 查找新增和变化的端口
+    for (key, port) in current {
+        match previous.get(key) {
+            None => entries.push(ChangeEntry::added(port.clone())),
+            Some(old) => entries.extend(ChangeEntry::between(old, port)),
+        }
+    }
+
+    // 查找移除的端口
+    for (key, port) in previous {
+        if !current.contains_key(key) {
+            entries.push(ChangeEntry::removed(port.clone()));
+        }
+    }
+
+    entries.sort_by_cached_key(|e| socket_key(&e.info));
+    entries
+}
+
+/// 监控服务控制指令
+#[derive(Debug, Clone, Copy)]
+enum MonitorCommand {
+    Stop,
+    Resync,
+}
+
 /// 监控服务句柄
+#[derive(Clone)]
 pub struct MonitorHandle {
-    stop_tx: mpsc::Sender<()>,
+    control_tx: mpsc::Sender<MonitorCommand>,
 }
 
 impl MonitorHandle {
     /// 停止监控
     pub async fn stop(&self) {
-        let _ = self.stop_tx.send(()).await;
+        let _ = self.control_tx.send(MonitorCommand::Stop).await;
+    }
+
+    /// 重新建立基线并发送全量快照
+    pub async fn resync(&self) {
+        let _ = self.control_tx.send(MonitorCommand::Resync).await;
+    }
+}
+
+/// 单次采样结果
+enum PollOutcome {
+    /// 有变化
+    Changed,
+    /// 无变化
+    Idle,
+    /// 事件接收端已关闭
+    Closed,
+}
+
+/// 监控服务的后台任务状态
+struct MonitorWorker {
+    config: MonitorConfig,
+    monitor: PortMonitor,
+    coalescer: ChangeCoalescer,
+    watches: Arc<Mutex<WatchRegistry>>,
+    event_tx: mpsc::Sender<MonitorEvent>,
+    /// 基线尚未建立（或建立失败）
+    needs_baseline: bool,
+}
+
+impl MonitorWorker {
+    /// 建立基线并发送快照，事件接收端已关闭时返回 false
+    async fn send_baseline(&mut self) -> bool {
+        let snapshot = match self.monitor.baseline() {
+            Ok(snapshot) => snapshot,
+            Err(e) => {
+                tracing::error!("Monitor baseline error: {}", e);
+                self.needs_baseline = true;
+                return true;
+            }
+        };
+
+        self.needs_baseline = false;
+        self.coalescer.reset(self.monitor.current_snapshot());
+        self.watches
+            .lock()
+            .await
+            .apply_snapshot(self.monitor.current_snapshot(), snapshot.timestamp);
+
+        let event = MonitorEvent::Snapshot(snapshot);
+        metrics::counters().record_event(&event);
+        self.event_tx.send(event).await.is_ok()
+    }
+
+    /// 执行一次采样，基线未建立时先建立基线
+    async fn poll(&mut self) -> PollOutcome {
+        if self.needs_baseline {
+            return if self.send_baseline().await {
+                PollOutcome::Changed
+            } else {
+                PollOutcome::Closed
+            };
+        }
+
+        match self.monitor.poll() {
+            Ok(Some(changes)) => {
+                self.watches
+                    .lock()
+                    .await
+                    .apply_snapshot(self.monitor.current_snapshot(), now_millis());
+                self.coalescer.push(changes, Instant::now());
+
+                if self.config.batch_window_ms == 0 && !self.flush().await {
+                    return PollOutcome::Closed;
+                }
+                PollOutcome::Changed
+            }
+            Ok(None) => PollOutcome::Idle,
+            Err(e) => {
+                tracing::error!("Monitor poll error: {}", e);
+                PollOutcome::Idle
+            }
+        }
+    }
+
+    /// 发送合并后的变化，事件接收端已关闭时返回 false
+    async fn flush(&mut self) -> bool {
+        match self.coalescer.flush(Instant::now()) {
+            Some(batch) => {
+                let event = MonitorEvent::Change(self.monitor.stamp(batch));
+                metrics::counters().record_event(&event);
+                self.event_tx.send(event).await.is_ok()
+            }
+            None => true,
+        }
     }
 }
 
 /// 启动监控服务
+///
+/// 启动后先发送一次 `MonitorEvent::Snapshot` 作为基线，之后只发送增量变化。
+/// `watches` 在合并前接收原始采样结果，保证时间线精度。
+/// `triggers` 中的事件源可触发立即扫描，定时采样始终保留作为兜底。
+/// 触发经 `trigger_debounce_ms` 合并，两次触发扫描之间至少间隔防抖时间与半个采样间隔中的较大者
 pub async fn start_monitor_service(
     config: MonitorConfig,
-    change_tx: mpsc::Sender<PortChange>,
+    event_tx: mpsc::Sender<MonitorEvent>,
+    watches: Arc<Mutex<WatchRegistry>>,
+    triggers: Vec<Box<dyn TriggerSource>>,
+) -> MonitorHandle {
+    start_monitor_service_with_scanner(config, PortScanner::new(), event_tx, watches, triggers)
+        .await
+}
+
+/// 使用指定扫描器启动监控服务
+pub async fn start_monitor_service_with_scanner(
+    config: MonitorConfig,
+    scanner: PortScanner,
+    event_tx: mpsc::Sender<MonitorEvent>,
+    watches: Arc<Mutex<WatchRegistry>>,
+    triggers: Vec<Box<dyn TriggerSource>>,
 ) -> MonitorHandle {
-    let (stop_tx, mut stop_rx) = mpsc::channel(1);
-    let monitor = Arc::new(RwLock::new(PortMonitor::new(config.clone())));
+    let (control_tx, mut control_rx) = mpsc::channel(8);
+    let (trigger_tx, mut trigger_rx) = mpsc::channel(16);
+
+    for source in triggers {
+        let name = source.name();
+        match source.start(trigger_tx.clone()) {
+            Ok(()) => tracing::info!("Monitor trigger source started: {}", name),
+            Err(e) => tracing::warn!(
+                "Monitor trigger source {} unavailable, falling back to polling: {}",
+                name,
+                e
+            ),
+        }
+    }
+
+    let mut worker = MonitorWorker {
+        monitor: PortMonitor::with_scanner(config.clone(), scanner),
+        coalescer: ChangeCoalescer::new(&config),
+        config,
+        watches,
+        event_tx,
+        needs_baseline: true,
+    };
 
     tokio::spawn(async move {
+        // 保留一个发送端，避免所有触发源退出后通道关闭
+        let _trigger_keepalive = trigger_tx;
+
+        let config = worker.config.clone();
+        let debounce = Duration::from_millis(config.trigger_debounce_ms);
+        // 两次触发扫描之间的最小间隔，避免持续的事件风暴导致连续全量扫描
+        let min_rescan = debounce.max(Duration::from_millis(config.interval_ms / 2));
         let mut ticker = interval(Duration::from_millis(config.interval_ms));
 
+        // 批量窗口为 0 时按采样间隔刷新，用于释放等待存活时间的端口
+        let flush_ms = if config.batch_window_ms > 0 {
+            config.batch_window_ms
+        } else {
+            config.interval_ms
+        };
+        let mut flush_ticker = interval(Duration::from_millis(flush_ms));
+
+        // 已收到触发、等待执行的扫描时刻；等待期间的触发合并到这一次
+        let mut pending_scan: Option<TokioInstant> = None;
+        let mut last_triggered_scan: Option<TokioInstant> = None;
+
         loop {
             tokio::select! {
-                _ = stop_rx.recv() => {
-                    tracing::info!("Monitor service stopped");
-                    break;
-                }
-                _ = ticker.tick() => {
-                    let mut mon = monitor.write().await;
-                    match mon.poll() {
-                        Ok(Some(changes)) => {
-                            if change_tx.send(changes).await.is_err() {
-                                tracing::warn!("Change receiver dropped, stopping monitor");
+                cmd = control_rx.recv() => {
+                    match cmd {
+                        Some(MonitorCommand::Resync) => {
+                            if !worker.send_baseline().await {
+                                tracing::warn!("Event receiver dropped, stopping monitor");
                                 break;
                             }
                         }
-                        Ok(None) => {
+                        Some(MonitorCommand::Stop) | None => {
+                            tracing::info!("Monitor service stopped");
+                            break;
+                        }
+                    }
+                }
+                _ = ticker.tick() => {
+                    match worker.poll().await {
+                        PollOutcome::Changed => {}
+                        PollOutcome::Idle => {
                             // 无变化，调整下次采样间隔
-                            ticker = interval(mon.current_interval());
+                            let next = worker.monitor.current_interval();
+                            ticker = interval_at(TokioInstant::now() + next, next);
+                        }
+                        PollOutcome::Closed => {
+                            tracing::warn!("Event receiver dropped, stopping monitor");
+                            break;
                         }
-                        Err(e) => {
-                            tracing::error!("Monitor poll error: {}", e);
+                    }
+                }
+                Some(reason) = trigger_rx.recv() => {
+                    tracing::trace!("Monitor triggered by {:?}", reason);
+                    if pending_scan.is_none() {
+                        let mut at = TokioInstant::now() + debounce;
+                        if let Some(last) = last_triggered_scan {
+                            at = at.max(last + min_rescan);
                         }
+                        pending_scan = Some(at);
+                    }
+                }
+                _ = sleep_until(pending_scan.unwrap_or_else(TokioInstant::now)), if pending_scan.is_some() => {
+                    pending_scan = None;
+                    last_triggered_scan = Some(TokioInstant::now());
+
+                    if let PollOutcome::Closed = worker.poll().await {
+                        tracing::warn!("Event receiver dropped, stopping monitor");
+                        break;
+                    }
+                    // 刚完成扫描，推迟下一次定时采样
+                    ticker.reset();
+                }
+                _ = flush_ticker.tick() => {
+                    if !worker.flush().await {
+                        tracing::warn!("Event receiver dropped, stopping monitor");
+                        break;
                     }
                 }
             }
         }
     });
 
-    MonitorHandle { stop_tx }
+    MonitorHandle { control_tx }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::trigger::{ManualTrigger, ManualTriggerHandle};
+    use crate::models::{ChangeKind, ConnectionState};
+    use crate::platform::fake::FakePlatform;
+    use tokio::time::timeout;
+
+    /// 定时采样间隔足够长，测试期间只有触发源会引起扫描
+    fn config() -> MonitorConfig {
+        MonitorConfig {
+            interval_ms: 60_000,
+            min_lifetime_ms: 0,
+            flap_window_ms: 0,
+            batch_window_ms: 0,
+            trigger_debounce_ms: 200,
+            ..MonitorConfig::default()
+        }
+    }
+
+    fn listener(port: u16) -> PortInfo {
+        let mut info = PortInfo::new(port, Protocol::TCP, "127.0.0.1".to_string(), 100);
+        info.state = ConnectionState::Listen;
+        info
+    }
+
+    async fn next_event(rx: &mut mpsc::Receiver<MonitorEvent>, limit: Duration) -> MonitorEvent {
+        timeout(limit, rx.recv())
+            .await
+            .expect("no monitor event")
+            .expect("monitor stopped")
+    }
+
+    async fn start(
+        platform: &Arc<FakePlatform>,
+    ) -> (
+        MonitorHandle,
+        mpsc::Receiver<MonitorEvent>,
+        ManualTriggerHandle,
+    ) {
+        let (trigger, fire) = ManualTrigger::new();
+        let (tx, rx) = mpsc::channel(16);
+        let scanner = PortScanner::with_providers(platform.clone(), platform.clone());
+        let handle = start_monitor_service_with_scanner(
+            config(),
+            scanner,
+            tx,
+            Arc::new(Mutex::new(WatchRegistry::new())),
+            vec![Box::new(trigger)],
+        )
+        .await;
+        (handle, rx, fire)
+    }
+
+    fn connection(port: u16, remote_port: u16) -> PortInfo {
+        let mut info = PortInfo::new(port, Protocol::TCP, "127.0.0.1".to_string(), 100);
+        info.state = ConnectionState::Established;
+        info.remote_addr = Some("10.0.0.1".to_string());
+        info.remote_port = Some(remote_port);
+        info
+    }
+
+    fn kinds(entries: &[ChangeEntry]) -> Vec<(u16, ChangeKind)> {
+        entries
+            .iter()
+            .map(|e| (e.info.port, e.kind.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn test_between_reports_state_and_owner_separately() {
+        let old = listener(80);
+        assert!(ChangeEntry::between(&old, &old.clone()).is_empty());
+
+        let mut closed = old.clone();
+        closed.state = ConnectionState::Closed;
+        assert_eq!(
+            kinds(&ChangeEntry::between(&old, &closed)),
+            vec![(
+                80,
+                ChangeKind::StateChanged {
+                    from: ConnectionState::Listen,
+                    to: ConnectionState::Closed,
+                }
+            )]
+        );
+
+        // 状态和进程同时变化时先报告状态，再报告进程，两条记录都携带新信息
+        let mut both = closed.clone();
+        both.pid = 200;
+        let entries = ChangeEntry::between(&old, &both);
+        assert_eq!(
+            kinds(&entries),
+            vec![
+                (
+                    80,
+                    ChangeKind::StateChanged {
+                        from: ConnectionState::Listen,
+                        to: ConnectionState::Closed,
+                    }
+                ),
+                (
+                    80,
+                    ChangeKind::OwnerChanged {
+                        old_pid: 100,
+                        new_pid: 200,
+                    }
+                ),
+            ]
+        );
+        assert!(entries.iter().all(|e| e.info.pid == 200));
+    }
+
+    #[test]
+    fn test_diff_snapshots_sorted_by_port() {
+        let mut moved = listener(443);
+        moved.pid = 300;
+        let mut udp = listener(53);
+        udp.protocol = Protocol::UDP;
+
+        let previous = snapshot_map(vec![listener(8080), listener(443), listener(22)]);
+        let current = snapshot_map(vec![udp, listener(22), moved, listener(53)]);
+
+        let entries = diff_snapshots(&previous, &current);
+        let summary: Vec<(u16, Protocol, ChangeKind)> = entries
+            .iter()
+            .map(|e| (e.info.port, e.info.protocol, e.kind.clone()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (53, Protocol::TCP, ChangeKind::Added),
+                (53, Protocol::UDP, ChangeKind::Added),
+                (
+                    443,
+                    Protocol::TCP,
+                    ChangeKind::OwnerChanged {
+                        old_pid: 100,
+                        new_pid: 300,
+                    }
+                ),
+                (8080, Protocol::TCP, ChangeKind::Removed),
+            ]
+        );
+        assert!(diff_snapshots(&current, &current).is_empty());
+    }
+
+    #[test]
+    fn test_sockets_sharing_a_port_are_kept_apart() {
+        let mut v6 = listener(22);
+        v6.local_addr = "::".to_string();
+        let ports = vec![
+            listener(22),
+            v6,
+            connection(22, 50000),
+            connection(22, 50001),
+        ];
+
+        let previous = snapshot_map(ports.clone());
+        assert_eq!(previous.len(), 4);
+
+        // 一条连接关闭只移除该连接，监听 socket 与其他连接不受影响
+        let current = snapshot_map(ports[..3].to_vec());
+        let entries = diff_snapshots(&previous, &current);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].kind, ChangeKind::Removed);
+        assert_eq!(entries[0].info.remote_port, Some(50001));
+    }
+
+    #[test]
+    fn test_snapshot_version_counts_emitted_changes_only() {
+        let platform = Arc::new(FakePlatform::new());
+        platform.set_ports(vec![listener(22)]);
+        let scanner = PortScanner::with_providers(platform.clone(), platform.clone());
+        let mut monitor = PortMonitor::with_scanner(config(), scanner);
+
+        let snapshot = monitor.baseline().unwrap();
+        assert_eq!(snapshot.snapshot_version, 1);
+
+        // 原始采样发现变化，但在合并器中被抵消，没有发送任何事件
+        platform.set_ports(vec![listener(22), connection(8080, 50000)]);
+        assert!(monitor.poll().unwrap().is_some());
+        platform.set_ports(vec![listener(22)]);
+        assert!(monitor.poll().unwrap().is_some());
+        assert!(monitor.poll().unwrap().is_none());
+        assert_eq!(monitor.snapshot_version(), 1);
+
+        // 发送出去的变化按顺序递增版本，与序号一样没有空洞
+        let first = monitor.stamp(vec![ChangeEntry::added(listener(80))]);
+        let second = monitor.stamp(vec![ChangeEntry::removed(listener(80))]);
+        assert_eq!(first.snapshot_version, 2);
+        assert_eq!(second.snapshot_version, 3);
+        assert_eq!(second.seq, first.seq + 1);
+    }
+
+    #[tokio::test]
+    async fn test_trigger_causes_immediate_rescan() {
+        let platform = Arc::new(FakePlatform::new());
+        platform.set_ports(vec![listener(22)]);
+        let (handle, mut rx, fire) = start(&platform).await;
+
+        let MonitorEvent::Snapshot(snapshot) = next_event(&mut rx, Duration::from_secs(1)).await
+        else {
+            panic!("expected snapshot");
+        };
+        assert_eq!(snapshot.ports.len(), 1);
+
+        platform.set_ports(vec![listener(22), listener(8080)]);
+        fire.fire();
+
+        // 远早于 60 秒的定时采样
+        let MonitorEvent::Change(change) = next_event(&mut rx, Duration::from_secs(2)).await else {
+            panic!("expected change");
+        };
+        assert_eq!(change.entries.len(), 1);
+        assert_eq!(change.entries[0].kind, ChangeKind::Added);
+        assert_eq!(change.entries[0].info.port, 8080);
+
+        handle.stop().await;
+    }
+
+    #[tokio::test]
+    async fn test_triggers_within_debounce_are_merged() {
+        let platform = Arc::new(FakePlatform::new());
+        let (handle, mut rx, fire) = start(&platform).await;
+        next_event(&mut rx, Duration::from_secs(1)).await;
+        let baseline_scans = platform.scans();
+
+        // 防抖等待期间的多次触发和端口变化合并为一次扫描
+        fire.fire();
+        platform.set_ports(vec![listener(8080)]);
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        fire.fire();
+        platform.set_ports(vec![listener(8080), listener(9090)]);
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        fire.fire();
+
+        let MonitorEvent::Change(change) = next_event(&mut rx, Duration::from_secs(2)).await else {
+            panic!("expected change");
+        };
+        let ports: Vec<u16> = change.entries.iter().map(|e| e.info.port).collect();
+        assert_eq!(ports, vec![8080, 9090]);
+
+        tokio::time::sleep(Duration::from_millis(400)).await;
+        assert_eq!(platform.scans(), baseline_scans + 1);
+        assert!(rx.try_recv().is_err());
+
+        handle.stop().await;
+    }
+
+    #[tokio::test]
+    async fn test_trigger_storm_is_rate_limited() {
+        let platform = Arc::new(FakePlatform::new());
+        let (handle, mut rx, fire) = start(&platform).await;
+        next_event(&mut rx, Duration::from_secs(1)).await;
+        let baseline_scans = platform.scans();
+
+        // 持续 1 秒的事件风暴：第一次触发按防抖时间扫描，
+        // 之后的触发受最小间隔（此处为半个采样间隔 30 秒）限制
+        for i in 0..50u16 {
+            platform.set_ports(vec![listener(10_000 + i)]);
+            fire.fire();
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+
+        let MonitorEvent::Change(_) = next_event(&mut rx, Duration::from_secs(1)).await else {
+            panic!("expected change");
+        };
+        assert_eq!(platform.scans(), baseline_scans + 1);
+
+        // 等待中的扫描不阻塞控制命令
+        handle.resync().await;
+        let MonitorEvent::Snapshot(snapshot) =
+            next_event(&mut rx, Duration::from_millis(500)).await
+        else {
+            panic!("expected snapshot");
+        };
+        assert_eq!(snapshot.ports[0].port, 10_049);
+        assert_eq!(platform.scans(), baseline_scans + 2);
+
+        handle.stop().await;
+    }
+
+    #[tokio::test]
+    async fn test_idle_backoff_waits_between_scans() {
+        let platform = Arc::new(FakePlatform::new());
+        let (tx, mut rx) = mpsc::channel(16);
+        let scanner = PortScanner::with_providers(platform.clone(), platform.clone());
+        let handle = start_monitor_service_with_scanner(
+            MonitorConfig {
+                interval_ms: 100,
+                ..config()
+            },
+            scanner,
+            tx,
+            Arc::new(Mutex::new(WatchRegistry::new())),
+            Vec::new(),
+        )
+        .await;
+        next_event(&mut rx, Duration::from_secs(1)).await;
+
+        // 无变化时间隔逐次拉长（100、200、300 毫秒……），不会连续扫描
+        tokio::time::sleep(Duration::from_millis(700)).await;
+        assert!(platform.scans() <= 5, "scanned {} times", platform.scans());
+
+        handle.stop().await;
+    }
 }
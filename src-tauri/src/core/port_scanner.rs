@@ -1,31 +1,111 @@
 //! 端口扫描器
 
-use crate::models::{AppResult, PortInfo, Protocol};
-use crate::platform::{WindowsPlatform, traits::PortProvider};
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use super::executable::annotate_executables;
+use super::query::PortQuery;
+use super::resolver::hostname_resolver;
+use crate::models::{annotate_services, AppResult, PortInfo, Protocol};
+use crate::platform::{NativePlatform, traits::{PortProvider, ProcessProvider}};
+
+/// 端口过滤条件，未设置的条件不参与过滤
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PortFilter {
+    /// 只保留监听端口
+    pub listen_only: Option<bool>,
+    /// 端口号
+    pub port: Option<u16>,
+    /// 协议
+    pub protocol: Option<Protocol>,
+    /// 进程 ID
+    pub pid: Option<u32>,
+    /// 进程名（不区分大小写的子串匹配）
+    pub process_name: Option<String>,
+    /// 只保留可执行文件不属于任何软件包、已删除或无法读取的端口（目前仅 Linux 的 dpkg / rpm）
+    pub unpackaged: Option<bool>,
+}
+
+impl PortFilter {
+    /// 判断端口是否满足条件
+    pub fn matches(&self, port: &PortInfo) -> bool {
+        if self.listen_only == Some(true) && !port.state.is_listening() {
+            return false;
+        }
+        if self.port.is_some_and(|p| p != port.port) {
+            return false;
+        }
+        if self.protocol.is_some_and(|p| p != port.protocol) {
+            return false;
+        }
+        if self.pid.is_some_and(|pid| pid != port.pid) {
+            return false;
+        }
+        if let Some(name) = &self.process_name {
+            let name_lower = name.to_lowercase();
+            let matched = port
+                .process
+                .as_ref()
+                .is_some_and(|proc| proc.name.to_lowercase().contains(&name_lower));
+            if !matched {
+                return false;
+            }
+        }
+        if self.unpackaged == Some(true) {
+            let unowned = port
+                .process
+                .as_ref()
+                .and_then(|proc| proc.executable.as_ref())
+                .is_some_and(|exe| exe.package.is_unverified());
+            if !unowned {
+                return false;
+            }
+        }
+        true
+    }
+}
 
 /// 端口扫描器
+#[derive(Clone)]
 pub struct PortScanner {
-    platform: WindowsPlatform,
+    ports: Arc<dyn PortProvider>,
+    processes: Arc<dyn ProcessProvider>,
 }
 
 impl PortScanner {
     pub fn new() -> Self {
-        Self {
-            platform: WindowsPlatform::new(),
-        }
+        let platform = Arc::new(NativePlatform::new());
+        Self::with_providers(platform.clone(), platform)
+    }
+
+    /// 使用指定的平台实现创建扫描器（用于测试或替换数据源）
+    pub fn with_providers(
+        ports: Arc<dyn PortProvider>,
+        processes: Arc<dyn ProcessProvider>,
+    ) -> Self {
+        Self { ports, processes }
+    }
+
+    /// 进程操作提供者
+    pub fn processes(&self) -> &Arc<dyn ProcessProvider> {
+        &self.processes
     }
 
     /// 扫描所有端口
     pub fn scan_all(&self) -> AppResult<Vec<PortInfo>> {
-        let mut ports = self.platform.get_all_ports()?;
+        let mut ports = self.ports.get_all_ports()?;
 
         // 为每个端口附加进程信息
         for port in &mut ports {
-            if let Ok(Some(process)) = crate::platform::windows::win_get_process_info(port.pid) {
+            if let Ok(Some(process)) = self.processes.get_process_info(port.pid) {
                 port.process = Some(process);
             }
         }
 
+        annotate_services(&mut ports);
+        hostname_resolver().annotate(&mut ports);
+
         // 按端口号排序
         ports.sort_by_key(|p| (p.port, matches!(p.protocol, Protocol::UDP)));
 
@@ -38,6 +118,21 @@ impl PortScanner {
         Ok(ports.into_iter().filter(|p| p.state.is_listening()).collect())
     }
 
+    /// 按过滤条件扫描，按软件包过滤时先检查进程的可执行文件
+    pub fn scan_filtered(&self, filter: &PortFilter) -> AppResult<Vec<PortInfo>> {
+        let mut ports = self.scan_all()?;
+        if filter.unpackaged == Some(true) {
+            annotate_executables(&mut ports);
+        }
+        Ok(ports.into_iter().filter(|p| filter.matches(p)).collect())
+    }
+
+    /// 按过滤表达式扫描
+    pub fn scan_query(&self, query: &PortQuery) -> AppResult<Vec<PortInfo>> {
+        let ports = self.scan_all()?;
+        Ok(ports.into_iter().filter(|p| query.matches(p)).collect())
+    }
+
     /// 查询指定端口
     pub fn query_port(&self, port: u16, protocol: Option<Protocol>) -> AppResult<Vec<PortInfo>> {
         let all_ports = self.scan_all()?;
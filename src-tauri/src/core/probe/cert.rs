@@ -0,0 +1,168 @@
+//! X.509 证书解析
+
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+use x509_parser::extensions::GeneralName;
+use x509_parser::oid_registry::{
+    OID_EC_P256, OID_KEY_TYPE_EC_PUBLIC_KEY, OID_NIST_EC_P384, OID_NIST_EC_P521,
+    OID_PKCS1_RSAENCRYPTION, OID_PKCS1_RSASSAPSS, OID_SIG_ED25519, OID_SIG_ED448,
+};
+use x509_parser::prelude::{FromDer, X509Certificate};
+use x509_parser::x509::SubjectPublicKeyInfo;
+
+use crate::models::{CertStatus, CertificateInfo};
+
+const DAY_SECS: i64 = 86_400;
+
+/// 解析 DER 编码的证书，无法解析时返回 None
+pub fn parse_certificate(der: &[u8]) -> Option<CertificateInfo> {
+    let (_, cert) = X509Certificate::from_der(der).ok()?;
+
+    let sans = match cert.subject_alternative_name() {
+        Ok(Some(ext)) => ext
+            .value
+            .general_names
+            .iter()
+            .filter_map(general_name)
+            .collect(),
+        _ => Vec::new(),
+    };
+    let subject = cert.subject().to_string();
+    let issuer = cert.issuer().to_string();
+
+    Some(CertificateInfo {
+        self_signed: subject == issuer,
+        subject,
+        issuer,
+        sans,
+        serial: cert.raw_serial_as_string(),
+        not_before: cert.validity().not_before.timestamp(),
+        not_after: cert.validity().not_after.timestamp(),
+        key_type: key_type(cert.public_key()),
+    })
+}
+
+/// 按证书链中最早过期（或尚未生效）的证书计算剩余天数和状态，证书链为空时状态未知
+pub fn chain_status(
+    chain: &[CertificateInfo],
+    now_secs: i64,
+    warn_days: u32,
+) -> (Option<i64>, CertStatus) {
+    let Some(not_after) = chain.iter().map(|c| c.not_after).min() else {
+        return (None, CertStatus::Unknown);
+    };
+    let expires_in_days = (not_after - now_secs).div_euclid(DAY_SECS);
+
+    let status = if not_after <= now_secs {
+        CertStatus::Expired
+    } else if chain.iter().any(|c| c.not_before > now_secs) {
+        CertStatus::NotYetValid
+    } else if expires_in_days < warn_days as i64 {
+        CertStatus::ExpiringSoon
+    } else {
+        CertStatus::Valid
+    };
+    (Some(expires_in_days), status)
+}
+
+fn general_name(name: &GeneralName<'_>) -> Option<String> {
+    Some(match name {
+        GeneralName::DNSName(dns) => format!("DNS:{}", dns),
+        GeneralName::RFC822Name(email) => format!("email:{}", email),
+        GeneralName::URI(uri) => format!("URI:{}", uri),
+        GeneralName::IPAddress(bytes) => {
+            let ip: IpAddr = match bytes.len() {
+                4 => Ipv4Addr::from(<[u8; 4]>::try_from(*bytes).ok()?).into(),
+                16 => Ipv6Addr::from(<[u8; 16]>::try_from(*bytes).ok()?).into(),
+                _ => return None,
+            };
+            format!("IP:{}", ip)
+        }
+        _ => return None,
+    })
+}
+
+fn key_type(spki: &SubjectPublicKeyInfo<'_>) -> String {
+    let algorithm = &spki.algorithm.algorithm;
+    let bits = spki.parsed().map(|key| key.key_size()).unwrap_or(0);
+
+    if *algorithm == OID_PKCS1_RSAENCRYPTION || *algorithm == OID_PKCS1_RSASSAPSS {
+        format!("RSA {}", bits)
+    } else if *algorithm == OID_KEY_TYPE_EC_PUBLIC_KEY {
+        let curve = spki
+            .algorithm
+            .parameters
+            .as_ref()
+            .and_then(|p| p.as_oid().ok());
+        match curve {
+            Some(oid) if oid == OID_EC_P256 => "EC P-256".to_string(),
+            Some(oid) if oid == OID_NIST_EC_P384 => "EC P-384".to_string(),
+            Some(oid) if oid == OID_NIST_EC_P521 => "EC P-521".to_string(),
+            Some(oid) => format!("EC {}", oid),
+            None => "EC".to_string(),
+        }
+    } else if *algorithm == OID_SIG_ED25519 {
+        "Ed25519".to_string()
+    } else if *algorithm == OID_SIG_ED448 {
+        "Ed448".to_string()
+    } else {
+        algorithm.to_id_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: i64 = 1_700_000_000;
+
+    fn cert(not_before: i64, not_after: i64) -> CertificateInfo {
+        CertificateInfo {
+            subject: "CN=test".to_string(),
+            issuer: "CN=test".to_string(),
+            sans: Vec::new(),
+            serial: "01".to_string(),
+            not_before,
+            not_after,
+            key_type: "EC P-256".to_string(),
+            self_signed: true,
+        }
+    }
+
+    #[test]
+    fn test_empty_chain_is_unknown() {
+        assert_eq!(chain_status(&[], NOW, 30), (None, CertStatus::Unknown));
+    }
+
+    #[test]
+    fn test_chain_status() {
+        let valid = cert(NOW - DAY_SECS, NOW + 90 * DAY_SECS);
+        assert_eq!(
+            chain_status(std::slice::from_ref(&valid), NOW, 30),
+            (Some(90), CertStatus::Valid)
+        );
+
+        let soon = cert(NOW - DAY_SECS, NOW + 10 * DAY_SECS);
+        assert_eq!(
+            chain_status(&[valid.clone(), soon], NOW, 30),
+            (Some(10), CertStatus::ExpiringSoon)
+        );
+
+        let expired = cert(NOW - 10 * DAY_SECS, NOW - DAY_SECS);
+        assert_eq!(
+            chain_status(&[expired], NOW, 30),
+            (Some(-1), CertStatus::Expired)
+        );
+
+        let future = cert(NOW + DAY_SECS, NOW + 90 * DAY_SECS);
+        assert_eq!(
+            chain_status(&[future], NOW, 30),
+            (Some(90), CertStatus::NotYetValid)
+        );
+    }
+
+    #[test]
+    fn test_unparseable_certificate() {
+        assert!(parse_certificate(b"not a certificate").is_none());
+    }
+}
@@ -0,0 +1,510 @@
+//! 协议识别
+//!
+//! 先等待服务端主动发送的横幅（SSH、SMTP、MySQL），没有横幅时依次发送探测报文，
+//! 每个探测使用新连接，任一响应可识别即停止
+
+use std::net::SocketAddr;
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::time::timeout;
+
+use super::ProbeConfig;
+use crate::models::{ProbedProtocol, ServiceFingerprint};
+use crate::utils::time::now_millis;
+
+/// 每次读取的最大字节数
+const READ_LIMIT: usize = 2048;
+/// 横幅最大长度（字符）
+const BANNER_LIMIT: usize = 200;
+
+/// 客户端探测报文
+#[derive(Debug, Clone, Copy)]
+enum Probe {
+    RedisPing,
+    HttpGet,
+    TlsHello,
+    PostgresSslRequest,
+    AmqpHeader,
+}
+
+/// 按顺序尝试：PING 与 GET 能同时识别 Redis、HTTP 以及 TLS 的告警响应，
+/// 需要特定报文的协议放在后面
+const PROBES: &[Probe] = &[
+    Probe::RedisPing,
+    Probe::HttpGet,
+    Probe::TlsHello,
+    Probe::PostgresSslRequest,
+    Probe::AmqpHeader,
+];
+
+/// 识别地址上的服务，无法连接或始终无响应时返回 None
+pub async fn fingerprint(addr: SocketAddr, config: &ProbeConfig) -> Option<ServiceFingerprint> {
+    let mut stream = connect(addr, config).await?;
+    let banner = read_some(&mut stream, config).await;
+    drop(stream);
+
+    if !banner.is_empty() {
+        return Some(classify_banner(&banner));
+    }
+
+    let mut unknown = None;
+    for probe in PROBES {
+        let Some(mut stream) = connect(addr, config).await else {
+            break;
+        };
+        if stream.write_all(&probe.payload(addr)).await.is_err() {
+            continue;
+        }
+        let response = read_some(&mut stream, config).await;
+        if response.is_empty() {
+            continue;
+        }
+
+        match classify_response(*probe, &response) {
+            Some(fingerprint) => return Some(fingerprint),
+            None => {
+                unknown = unknown.or_else(|| Some(found(ProbedProtocol::Unknown, None, &response)))
+            }
+        }
+    }
+
+    unknown
+}
+
+async fn connect(addr: SocketAddr, config: &ProbeConfig) -> Option<TcpStream> {
+    timeout(config.connect_timeout(), TcpStream::connect(addr))
+        .await
+        .ok()?
+        .ok()
+}
+
+/// 在读取超时内读取一段数据，超时、出错或连接关闭时返回已读到的内容
+async fn read_some(stream: &mut TcpStream, config: &ProbeConfig) -> Vec<u8> {
+    let mut buf = vec![0u8; READ_LIMIT];
+    match timeout(config.read_timeout(), stream.read(&mut buf)).await {
+        Ok(Ok(n)) => {
+            buf.truncate(n);
+            buf
+        }
+        _ => Vec::new(),
+    }
+}
+
+impl Probe {
+    fn payload(&self, addr: SocketAddr) -> Vec<u8> {
+        match self {
+            Self::RedisPing => b"PING\r\n".to_vec(),
+            Self::HttpGet => format!(
+                "GET / HTTP/1.0\r\nHost: {}\r\nUser-Agent: port-detection\r\nConnection: close\r\n\r\n",
+                addr
+            )
+            .into_bytes(),
+            Self::TlsHello => client_hello(),
+            // 长度 8，请求码 80877103
+            Self::PostgresSslRequest => vec![0, 0, 0, 8, 0x04, 0xd2, 0x16, 0x2f],
+            Self::AmqpHeader => b"AMQP\x00\x00\x09\x01".to_vec(),
+        }
+    }
+}
+
+/// 服务端主动发送的横幅
+fn classify_banner(data: &[u8]) -> ServiceFingerprint {
+    let line = first_line(data);
+
+    if data.starts_with(b"SSH-") {
+        let product = line
+            .split_whitespace()
+            .next()
+            .map(|v| v.splitn(3, '-').nth(2).unwrap_or(v).to_string());
+        return found(ProbedProtocol::Ssh, product, data);
+    }
+
+    if data.starts_with(b"220") && line.to_ascii_uppercase().contains("SMTP") {
+        let product = line.get(4..).map(|rest| rest.trim().to_string());
+        return found(ProbedProtocol::Smtp, product, data);
+    }
+
+    if let Some(fingerprint) = classify_mysql(data) {
+        return fingerprint;
+    }
+
+    found(ProbedProtocol::Unknown, None, data)
+}
+
+/// MySQL 握手包：3 字节长度、序号 0、协议版本 10 和以 0 结尾的服务端版本
+fn classify_mysql(data: &[u8]) -> Option<ServiceFingerprint> {
+    if data.len() < 5 || data[3] != 0 {
+        return None;
+    }
+    let len = u32::from_le_bytes([data[0], data[1], data[2], 0]) as usize;
+    let payload = data.get(4..)?;
+    if len == 0 || len > payload.len() + READ_LIMIT {
+        return None;
+    }
+
+    match payload[0] {
+        0x0a => {
+            let end = payload.iter().position(|b| *b == 0)?;
+            let version = String::from_utf8_lossy(&payload[1..end]).to_string();
+            Some(ServiceFingerprint {
+                protocol: ProbedProtocol::Mysql,
+                product: Some(version.clone()),
+                banner: Some(sanitize(&version)),
+                probed_at: now_millis(),
+            })
+        }
+        // 错误包（如主机不允许连接）：0xff、2 字节错误码、消息
+        0xff if payload.len() > 3 => {
+            let message = String::from_utf8_lossy(&payload[3..]).to_string();
+            Some(ServiceFingerprint {
+                protocol: ProbedProtocol::Mysql,
+                product: None,
+                banner: Some(sanitize(&message)),
+                probed_at: now_millis(),
+            })
+        }
+        _ => None,
+    }
+}
+
+/// 探测报文的响应
+fn classify_response(probe: Probe, data: &[u8]) -> Option<ServiceFingerprint> {
+    if data.starts_with(b"HTTP/") {
+        return Some(found(ProbedProtocol::Http, http_server(data), data));
+    }
+
+    if data.len() >= 3 && matches!(data[0], 0x15 | 0x16) && data[1] == 0x03 {
+        return Some(ServiceFingerprint {
+            protocol: ProbedProtocol::Tls,
+            product: Some(tls_version(data[2]).to_string()),
+            banner: None,
+            probed_at: now_millis(),
+        });
+    }
+
+    if data.starts_with(b"AMQP") || is_amqp_connection_start(data) {
+        let product = data
+            .get(4..8)
+            .filter(|_| data.starts_with(b"AMQP"))
+            .map(|v| format!("AMQP {}-{}-{}", v[1], v[2], v[3]));
+        return Some(ServiceFingerprint {
+            protocol: ProbedProtocol::Amqp,
+            product,
+            banner: None,
+            probed_at: now_millis(),
+        });
+    }
+
+    if matches!(probe, Probe::RedisPing)
+        && [
+            &b"+PONG"[..],
+            b"-NOAUTH",
+            b"-ERR",
+            b"-DENIED",
+            b"-WRONGPASS",
+        ]
+        .iter()
+        .any(|prefix| data.starts_with(prefix))
+    {
+        return Some(found(ProbedProtocol::Redis, None, data));
+    }
+
+    // SSLRequest 的响应为单字节 'S'（支持 SSL）或 'N'，旧版本可能直接返回错误消息 'E'
+    if matches!(probe, Probe::PostgresSslRequest)
+        && (data == b"S" || data == b"N" || (data[0] == b'E' && data.len() > 5))
+    {
+        let product = match data[0] {
+            b'S' => Some("ssl".to_string()),
+            b'N' => Some("no-ssl".to_string()),
+            _ => None,
+        };
+        return Some(ServiceFingerprint {
+            protocol: ProbedProtocol::Postgresql,
+            product,
+            banner: None,
+            probed_at: now_millis(),
+        });
+    }
+
+    None
+}
+
+/// AMQP 0-9-1 Connection.Start 帧：类型 1、通道 0、class 10、method 10
+fn is_amqp_connection_start(data: &[u8]) -> bool {
+    data.len() >= 11 && data[0] == 1 && data[1..3] == [0, 0] && data[7..11] == [0, 10, 0, 10]
+}
+
+fn http_server(data: &[u8]) -> Option<String> {
+    String::from_utf8_lossy(data)
+        .lines()
+        .take_while(|line| !line.is_empty())
+        .find_map(|line| {
+            let (name, value) = line.split_once(':')?;
+            name.trim()
+                .eq_ignore_ascii_case("server")
+                .then(|| sanitize(value.trim()))
+        })
+}
+
+fn tls_version(minor: u8) -> &'static str {
+    match minor {
+        0 => "SSL 3.0",
+        1 => "TLS 1.0",
+        2 => "TLS 1.1",
+        _ => "TLS 1.2+",
+    }
+}
+
+fn found(protocol: ProbedProtocol, product: Option<String>, data: &[u8]) -> ServiceFingerprint {
+    let banner = first_line(data);
+    ServiceFingerprint {
+        protocol,
+        product,
+        banner: (!banner.is_empty()).then_some(banner),
+        probed_at: now_millis(),
+    }
+}
+
+/// 响应首行，去除控制字符并截断
+fn first_line(data: &[u8]) -> String {
+    let text = String::from_utf8_lossy(data);
+    sanitize(text.lines().next().unwrap_or(""))
+}
+
+fn sanitize(text: &str) -> String {
+    text.chars()
+        .filter(|c| !c.is_control())
+        .take(BANNER_LIMIT)
+        .collect::<String>()
+        .trim()
+        .to_string()
+}
+
+/// 最小的 TLS 1.2 ClientHello，足以让 TLS 服务端返回 ServerHello 或告警
+fn client_hello() -> Vec<u8> {
+    const SUITES: &[u16] = &[
+        0x1301, 0x1302, 0x1303, 0xc02b, 0xc02f, 0xc02c, 0xc030, 0xcca9, 0xcca8, 0x009c, 0x002f,
+        0x0035,
+    ];
+
+    let mut extensions = Vec::new();
+    // supported_groups: x25519, secp256r1, secp384r1
+    push_extension(&mut extensions, 0x000a, &[0, 6, 0, 0x1d, 0, 0x17, 0, 0x18]);
+    // ec_point_formats: uncompressed
+    push_extension(&mut extensions, 0x000b, &[1, 0]);
+    // signature_algorithms
+    push_extension(
+        &mut extensions,
+        0x000d,
+        &[0, 8, 0x04, 0x03, 0x08, 0x04, 0x04, 0x01, 0x05, 0x01],
+    );
+
+    let mut hello = vec![0x03, 0x03];
+    hello.extend_from_slice(&[0x5a; 32]);
+    hello.push(0);
+    push_u16(&mut hello, (SUITES.len() * 2) as u16);
+    for suite in SUITES {
+        push_u16(&mut hello, *suite);
+    }
+    hello.extend_from_slice(&[1, 0]);
+    push_u16(&mut hello, extensions.len() as u16);
+    hello.extend_from_slice(&extensions);
+
+    let mut handshake = vec![0x01];
+    handshake.extend_from_slice(&(hello.len() as u32).to_be_bytes()[1..]);
+    handshake.extend_from_slice(&hello);
+
+    let mut record = vec![0x16, 0x03, 0x01];
+    push_u16(&mut record, handshake.len() as u16);
+    record.extend_from_slice(&handshake);
+    record
+}
+
+fn push_u16(buf: &mut Vec<u8>, value: u16) {
+    buf.extend_from_slice(&value.to_be_bytes());
+}
+
+fn push_extension(buf: &mut Vec<u8>, kind: u16, data: &[u8]) {
+    push_u16(buf, kind);
+    push_u16(buf, data.len() as u16);
+    buf.extend_from_slice(data);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use tokio::net::TcpListener;
+
+    use super::*;
+
+    fn config() -> ProbeConfig {
+        ProbeConfig {
+            connect_timeout_ms: 200,
+            read_timeout_ms: 200,
+            ..ProbeConfig::default()
+        }
+    }
+
+    /// 本地替身服务：连接后先发送 `banner`（为空时不发送），
+    /// 再按收到的请求用 `reply` 生成响应，返回 None 时直接关闭连接
+    async fn stand_in(banner: &'static [u8], reply: fn(&[u8]) -> Option<Vec<u8>>) -> SocketAddr {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                tokio::spawn(async move {
+                    if !banner.is_empty() {
+                        let _ = stream.write_all(banner).await;
+                        tokio::time::sleep(Duration::from_secs(5)).await;
+                        return;
+                    }
+                    let mut buf = vec![0u8; READ_LIMIT];
+                    let Ok(n) = stream.read(&mut buf).await else {
+                        return;
+                    };
+                    if let Some(response) = reply(&buf[..n]) {
+                        let _ = stream.write_all(&response).await;
+                        tokio::time::sleep(Duration::from_secs(5)).await;
+                    }
+                });
+            }
+        });
+        addr
+    }
+
+    fn no_reply(_: &[u8]) -> Option<Vec<u8>> {
+        None
+    }
+
+    async fn probe(addr: SocketAddr) -> ServiceFingerprint {
+        fingerprint(addr, &config()).await.expect("no fingerprint")
+    }
+
+    #[tokio::test]
+    async fn test_ssh_banner() {
+        let addr = stand_in(b"SSH-2.0-OpenSSH_9.6p1 Ubuntu-3\r\n", no_reply).await;
+        let fingerprint = probe(addr).await;
+        assert_eq!(fingerprint.protocol, ProbedProtocol::Ssh);
+        assert_eq!(fingerprint.product.as_deref(), Some("OpenSSH_9.6p1"));
+        assert_eq!(
+            fingerprint.banner.as_deref(),
+            Some("SSH-2.0-OpenSSH_9.6p1 Ubuntu-3")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_mysql_handshake() {
+        // 长度 0x1a、序号 0、协议版本 10、服务端版本 "8.0.36"、连接 ID 与部分盐值
+        const HANDSHAKE: &[u8] =
+            b"\x1a\x00\x00\x00\x0a8.0.36\x00\x08\x00\x00\x00abcdefgh\x00\xff\xf7\x21\x02\x00";
+        let addr = stand_in(HANDSHAKE, no_reply).await;
+        let fingerprint = probe(addr).await;
+        assert_eq!(fingerprint.protocol, ProbedProtocol::Mysql);
+        assert_eq!(fingerprint.product.as_deref(), Some("8.0.36"));
+    }
+
+    #[tokio::test]
+    async fn test_redis_pong() {
+        let addr = stand_in(b"", |req| {
+            req.starts_with(b"PING").then(|| b"+PONG\r\n".to_vec())
+        })
+        .await;
+        let fingerprint = probe(addr).await;
+        assert_eq!(fingerprint.protocol, ProbedProtocol::Redis);
+        assert_eq!(fingerprint.banner.as_deref(), Some("+PONG"));
+    }
+
+    #[tokio::test]
+    async fn test_http_response() {
+        let addr = stand_in(b"", |req| {
+            req.starts_with(b"GET / HTTP/1.0\r\n").then(|| {
+                b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\nServer: stand-in/1.0\r\n\r\n".to_vec()
+            })
+        })
+        .await;
+        let fingerprint = probe(addr).await;
+        assert_eq!(fingerprint.protocol, ProbedProtocol::Http);
+        assert_eq!(fingerprint.product.as_deref(), Some("stand-in/1.0"));
+        assert_eq!(fingerprint.banner.as_deref(), Some("HTTP/1.1 200 OK"));
+    }
+
+    #[tokio::test]
+    async fn test_postgres_ssl_reply() {
+        const SSL_REQUEST: &[u8] = &[0, 0, 0, 8, 0x04, 0xd2, 0x16, 0x2f];
+
+        let addr = stand_in(b"", |req| (req == SSL_REQUEST).then(|| b"S".to_vec())).await;
+        let fingerprint = probe(addr).await;
+        assert_eq!(fingerprint.protocol, ProbedProtocol::Postgresql);
+        assert_eq!(fingerprint.product.as_deref(), Some("ssl"));
+
+        let addr = stand_in(b"", |req| (req == SSL_REQUEST).then(|| b"N".to_vec())).await;
+        let fingerprint = probe(addr).await;
+        assert_eq!(fingerprint.protocol, ProbedProtocol::Postgresql);
+        assert_eq!(fingerprint.product.as_deref(), Some("no-ssl"));
+    }
+
+    #[tokio::test]
+    async fn test_amqp_header() {
+        // 不支持客户端版本时服务端回复自己支持的协议头
+        let addr = stand_in(b"", |req| {
+            req.starts_with(b"AMQP")
+                .then(|| b"AMQP\x00\x00\x09\x01".to_vec())
+        })
+        .await;
+        let fingerprint = probe(addr).await;
+        assert_eq!(fingerprint.protocol, ProbedProtocol::Amqp);
+        assert_eq!(fingerprint.product.as_deref(), Some("AMQP 0-9-1"));
+    }
+
+    #[tokio::test]
+    async fn test_tls_alert() {
+        // 对 ClientHello 回复 handshake_failure 告警
+        let addr = stand_in(b"", |req| {
+            req.starts_with(&[0x16, 0x03])
+                .then(|| vec![0x15, 0x03, 0x03, 0x00, 0x02, 0x02, 0x28])
+        })
+        .await;
+        let fingerprint = probe(addr).await;
+        assert_eq!(fingerprint.protocol, ProbedProtocol::Tls);
+        assert_eq!(fingerprint.product.as_deref(), Some("TLS 1.2+"));
+    }
+
+    #[tokio::test]
+    async fn test_unrecognized_response() {
+        let addr = stand_in(b"", |_| Some(b"hello?\n".to_vec())).await;
+        let fingerprint = probe(addr).await;
+        assert_eq!(fingerprint.protocol, ProbedProtocol::Unknown);
+        assert_eq!(fingerprint.banner.as_deref(), Some("hello?"));
+    }
+
+    #[tokio::test]
+    async fn test_silent_socket_times_out() {
+        // 接受连接后既不发送也不关闭
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let mut held = Vec::new();
+            while let Ok((stream, _)) = listener.accept().await {
+                held.push(stream);
+            }
+        });
+
+        let started = Instant::now();
+        assert!(fingerprint(addr, &config()).await.is_none());
+        // 横幅等待加每个探测各一次读取超时
+        let elapsed = started.elapsed();
+        let read_timeout = Duration::from_millis(config().read_timeout_ms);
+        assert!(elapsed >= read_timeout * (PROBES.len() as u32 + 1));
+        assert!(elapsed < read_timeout * (PROBES.len() as u32 + 1) + Duration::from_secs(2));
+    }
+
+    #[tokio::test]
+    async fn test_closed_port() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        drop(listener);
+        assert!(fingerprint(addr, &config()).await.is_none());
+    }
+}
@@ -0,0 +1,780 @@
+//! HTTP(S) 探测
+//!
+//! 对本机监听端口发起 `GET /`，记录状态码、`Server` 头、页面标题和响应耗时。
+//! 先尝试 HTTPS（不校验证书），握手失败再使用明文 HTTP；只跟随指向本机的重定向。
+//! 结果按 (PID, 端口) 缓存
+
+use std::net::{IpAddr, SocketAddr};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::time::timeout;
+
+use super::tls::connect_tls;
+use super::{tcp_listeners, ProbeCache};
+use crate::core::PortScanner;
+use crate::models::{AppResult, HttpProbe, PortInfo};
+use crate::utils::time::now_millis;
+
+/// 响应头最大长度
+const HEAD_LIMIT: usize = 16 * 1024;
+/// 标题最大长度（字符）
+const TITLE_LIMIT: usize = 200;
+
+/// HTTP 探测配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HttpProbeConfig {
+    /// 连接与 TLS 握手超时（毫秒）
+    pub connect_timeout_ms: u64,
+    /// 单个端口的总时长上限（毫秒），包含重定向
+    pub timeout_ms: u64,
+    /// 最多跟随的重定向次数
+    pub max_redirects: u8,
+    /// 读取的响应体上限（字节），用于提取标题
+    pub max_body_bytes: usize,
+    /// 结果缓存时长（毫秒）
+    pub cache_ttl_ms: u64,
+    /// 同时探测的端口数
+    pub concurrency: usize,
+}
+
+impl Default for HttpProbeConfig {
+    fn default() -> Self {
+        Self {
+            connect_timeout_ms: 1000,
+            timeout_ms: 3000,
+            max_redirects: 3,
+            max_body_bytes: 64 * 1024,
+            cache_ttl_ms: 30_000,
+            concurrency: 8,
+        }
+    }
+}
+
+/// 带缓存的 HTTP 探测器
+pub struct HttpProber {
+    config: HttpProbeConfig,
+    cache: ProbeCache<HttpProbe>,
+}
+
+impl Default for HttpProber {
+    fn default() -> Self {
+        Self::new(HttpProbeConfig::default())
+    }
+}
+
+impl HttpProber {
+    pub fn new(config: HttpProbeConfig) -> Self {
+        Self {
+            cache: ProbeCache::new(config.cache_ttl_ms),
+            config,
+        }
+    }
+
+    /// 探测单个端口，非 TCP 监听端口或不是 HTTP 服务时返回 None
+    pub async fn probe(&self, port: &PortInfo) -> Option<HttpProbe> {
+        let mut ports = [port.clone()];
+        self.enrich(&mut ports).await;
+        let [port] = ports;
+        port.http
+    }
+
+    /// 探测端口上的全部 TCP 监听，端口未被监听时返回 `PortNotFound`
+    pub async fn probe_port(&self, scanner: &PortScanner, port: u16) -> AppResult<Vec<PortInfo>> {
+        let mut ports = tcp_listeners(scanner, port)?;
+        self.enrich(&mut ports).await;
+        Ok(ports)
+    }
+
+    /// 为端口列表中的 TCP 监听端口填充 `http`，缓存有效的 (PID, 端口) 不再重复探测
+    pub async fn enrich(&self, ports: &mut [PortInfo]) {
+        let config = self.config.clone();
+        let results = self
+            .cache
+            .resolve(ports, self.config.concurrency, |addr| {
+                let config = config.clone();
+                async move { probe_http(addr, &config).await }
+            })
+            .await;
+        for (port, result) in ports.iter_mut().zip(results) {
+            port.http = result;
+        }
+    }
+
+    /// 清空缓存
+    pub fn clear(&self) {
+        self.cache.clear();
+    }
+}
+
+/// 请求目标
+#[derive(Debug, Clone)]
+struct Target {
+    addr: SocketAddr,
+    tls: bool,
+    /// `Host` 头
+    host: String,
+    path: String,
+}
+
+impl Target {
+    fn url(&self) -> String {
+        let scheme = if self.tls { "https" } else { "http" };
+        format!("{}://{}{}", scheme, self.host, self.path)
+    }
+}
+
+/// 解析后的响应
+struct Response {
+    status: u16,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+    /// 从建立连接到收到响应头的耗时
+    elapsed: Duration,
+}
+
+impl Response {
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// 探测地址上的 HTTP 服务，不是 HTTP 服务或超时时返回 None
+pub async fn probe_http(addr: SocketAddr, config: &HttpProbeConfig) -> Option<HttpProbe> {
+    let limit = Duration::from_millis(config.timeout_ms);
+    timeout(limit, probe_with_redirects(addr, config))
+        .await
+        .ok()
+        .flatten()
+}
+
+async fn probe_with_redirects(addr: SocketAddr, config: &HttpProbeConfig) -> Option<HttpProbe> {
+    let host = if addr.ip().is_loopback() {
+        format!("localhost:{}", addr.port())
+    } else {
+        authority(addr.ip(), addr.port())
+    };
+
+    let mut first = None;
+    for tls in [true, false] {
+        let target = Target {
+            addr,
+            tls,
+            host: host.clone(),
+            path: "/".to_string(),
+        };
+        if let Some(response) = fetch(&target, config).await {
+            first = Some((target, response));
+            break;
+        }
+    }
+    let (mut target, mut response) = first?;
+    let latency_ms = response.elapsed.as_millis() as u64;
+
+    let mut redirects = 0;
+    while (300..400).contains(&response.status) && redirects < config.max_redirects {
+        let Some(next) = response
+            .header("location")
+            .and_then(|location| resolve_location(location, &target))
+        else {
+            break;
+        };
+        let Some(next_response) = fetch(&next, config).await else {
+            break;
+        };
+        target = next;
+        response = next_response;
+        redirects += 1;
+    }
+
+    Some(HttpProbe {
+        url: target.url(),
+        tls: target.tls,
+        status: response.status,
+        server: response.header("server").map(sanitize),
+        title: extract_title(&response.body),
+        latency_ms,
+        redirects,
+        probed_at: now_millis(),
+    })
+}
+
+async fn fetch(target: &Target, config: &HttpProbeConfig) -> Option<Response> {
+    let limit = Duration::from_millis(config.connect_timeout_ms);
+    let start = Instant::now();
+
+    if target.tls {
+        let server_name = target.host.rsplit_once(':').map(|(h, _)| h)?;
+        let server_name = server_name.trim_start_matches('[').trim_end_matches(']');
+        let stream = connect_tls(target.addr, server_name, limit).await?;
+        exchange(stream, target, config, start).await
+    } else {
+        let stream = timeout(limit, TcpStream::connect(target.addr))
+            .await
+            .ok()?
+            .ok()?;
+        exchange(stream, target, config, start).await
+    }
+}
+
+/// 发送请求并读取响应头和部分响应体
+async fn exchange<S>(
+    mut stream: S,
+    target: &Target,
+    config: &HttpProbeConfig,
+    start: Instant,
+) -> Option<Response>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let request = format!(
+        "GET {} HTTP/1.1\r\nHost: {}\r\nUser-Agent: port-detection\r\nAccept: text/html,*/*\r\nConnection: close\r\n\r\n",
+        target.path, target.host
+    );
+    stream.write_all(request.as_bytes()).await.ok()?;
+
+    let mut data = Vec::new();
+    let mut buf = [0u8; 4096];
+    let head_end = loop {
+        let n = stream.read(&mut buf).await.ok()?;
+        if n == 0 {
+            return None;
+        }
+        data.extend_from_slice(&buf[..n]);
+        // 不是 HTTP 响应时不必等待到超时
+        if !data.starts_with(&b"HTTP/"[..data.len().min(5)]) {
+            return None;
+        }
+        if let Some(pos) = find(&data, b"\r\n\r\n") {
+            break pos;
+        }
+        if data.len() > HEAD_LIMIT {
+            return None;
+        }
+    };
+    let elapsed = start.elapsed();
+
+    let head = String::from_utf8_lossy(&data[..head_end]).to_string();
+    let mut lines = head.split("\r\n");
+    let mut status_line = lines.next()?.split_whitespace().skip(1);
+    let status = status_line.next()?.parse().ok()?;
+    let headers: Vec<(String, String)> = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(key, value)| (key.trim().to_string(), value.trim().to_string()))
+        .collect();
+
+    let mut response = Response {
+        status,
+        headers,
+        body: data.split_off(head_end + 4),
+        elapsed,
+    };
+
+    // 只读取到标题为止，重定向和非 HTML 响应不读取响应体
+    let wants_body = !(300..400).contains(&status)
+        && response
+            .header("content-type")
+            .map(|v| v.to_ascii_lowercase().contains("html"))
+            .unwrap_or(true);
+    let content_length = response
+        .header("content-length")
+        .and_then(|v| v.parse::<usize>().ok());
+    while wants_body
+        && response.body.len() < config.max_body_bytes
+        && content_length
+            .map(|len| response.body.len() < len)
+            .unwrap_or(true)
+        && find_ignore_case(&response.body, b"</title").is_none()
+    {
+        match stream.read(&mut buf).await {
+            Ok(0) | Err(_) => break,
+            Ok(n) => response.body.extend_from_slice(&buf[..n]),
+        }
+    }
+
+    let chunked = response
+        .header("transfer-encoding")
+        .map(|v| v.to_ascii_lowercase().contains("chunked"))
+        .unwrap_or(false);
+    if chunked {
+        response.body = decode_chunked(&response.body);
+    }
+    Some(response)
+}
+
+/// 解析重定向地址，只接受指向本机的地址
+fn resolve_location(location: &str, current: &Target) -> Option<Target> {
+    let location = location.split('#').next().unwrap_or("").trim();
+
+    let (tls, rest) = if let Some(rest) = location.strip_prefix("https://") {
+        (true, rest)
+    } else if let Some(rest) = location.strip_prefix("http://") {
+        (false, rest)
+    } else if let Some(rest) = location.strip_prefix("//") {
+        (current.tls, rest)
+    } else {
+        let path = if location.starts_with('/') {
+            location.to_string()
+        } else {
+            let base = current.path.split('?').next().unwrap_or("/");
+            let dir = &base[..base.rfind('/').map(|i| i + 1).unwrap_or(0)];
+            format!("{}{}", dir, location)
+        };
+        return Some(Target {
+            path,
+            ..current.clone()
+        });
+    };
+
+    let split = rest.find(['/', '?']).unwrap_or(rest.len());
+    let (authority_part, path) = rest.split_at(split);
+    let path = match path {
+        "" => "/".to_string(),
+        p if p.starts_with('?') => format!("/{}", p),
+        p => p.to_string(),
+    };
+
+    let default_port = if tls { 443 } else { 80 };
+    let (host, port) = split_authority(authority_part, default_port)?;
+    let is_local = host.eq_ignore_ascii_case("localhost")
+        || host
+            .parse::<IpAddr>()
+            .map(|ip| ip.is_loopback() || ip == current.addr.ip())
+            .unwrap_or(false);
+    if !is_local {
+        return None;
+    }
+
+    Some(Target {
+        addr: SocketAddr::new(current.addr.ip(), port),
+        tls,
+        host: format!("{}:{}", authority_host(host), port),
+        path,
+    })
+}
+
+/// 拆分 `host[:port]`，IPv6 地址带方括号
+fn split_authority(authority: &str, default_port: u16) -> Option<(&str, u16)> {
+    let authority = authority.rsplit('@').next()?;
+    if let Some(rest) = authority.strip_prefix('[') {
+        let (host, rest) = rest.split_once(']')?;
+        let port = match rest.strip_prefix(':') {
+            Some(port) => port.parse().ok()?,
+            None => default_port,
+        };
+        return Some((host, port));
+    }
+    match authority.split_once(':') {
+        Some((host, port)) => Some((host, port.parse().ok()?)),
+        None => Some((authority, default_port)),
+    }
+}
+
+fn authority_host(host: &str) -> String {
+    if host.contains(':') {
+        format!("[{}]", host)
+    } else {
+        host.to_string()
+    }
+}
+
+fn authority(ip: IpAddr, port: u16) -> String {
+    format!("{}:{}", authority_host(&ip.to_string()), port)
+}
+
+/// 提取 `<title>` 内容，解码常见实体并合并空白
+fn extract_title(body: &[u8]) -> Option<String> {
+    let start = find_ignore_case(body, b"<title")?;
+    let open_end = start + body[start..].iter().position(|b| *b == b'>')? + 1;
+    let close = find_ignore_case(&body[open_end..], b"</title")
+        .map(|i| open_end + i)
+        .unwrap_or(body.len());
+
+    let raw = String::from_utf8_lossy(&body[open_end..close]);
+    let text = raw
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&nbsp;", " ")
+        .replace("&amp;", "&");
+    let title = sanitize(&text.split_whitespace().collect::<Vec<_>>().join(" "));
+    (!title.is_empty()).then_some(title)
+}
+
+/// 解码分块传输的响应体，数据不完整时返回已解码的部分
+fn decode_chunked(data: &[u8]) -> Vec<u8> {
+    let mut out = Vec::new();
+    let mut rest = data;
+    while let Some(line_end) = find(rest, b"\r\n") {
+        let size_text = String::from_utf8_lossy(&rest[..line_end]);
+        let size_text = size_text.split(';').next().unwrap_or("").trim();
+        let Ok(size) = usize::from_str_radix(size_text, 16) else {
+            break;
+        };
+        if size == 0 {
+            break;
+        }
+        let chunk = &rest[line_end + 2..];
+        let take = size.min(chunk.len());
+        out.extend_from_slice(&chunk[..take]);
+        if take < size || chunk.len() < size + 2 {
+            break;
+        }
+        rest = &chunk[size + 2..];
+    }
+    out
+}
+
+fn sanitize(text: &str) -> String {
+    text.chars()
+        .filter(|c| !c.is_control())
+        .take(TITLE_LIMIT)
+        .collect::<String>()
+        .trim()
+        .to_string()
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+fn find_ignore_case(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window.eq_ignore_ascii_case(needle))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::Ipv4Addr;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    use tokio::net::TcpListener;
+
+    use super::*;
+    use crate::models::{ConnectionState, Protocol};
+
+    fn config() -> HttpProbeConfig {
+        HttpProbeConfig {
+            connect_timeout_ms: 300,
+            timeout_ms: 2000,
+            ..HttpProbeConfig::default()
+        }
+    }
+
+    /// 本地明文 HTTP 替身：按请求路径用 `reply` 生成完整响应，返回 None 时直接关闭连接。
+    /// 非 HTTP 请求（如 TLS ClientHello）立即关闭，探测随即回退到明文 HTTP。
+    /// 返回地址和收到的 HTTP 请求数
+    async fn stand_in<F>(reply: F) -> (SocketAddr, Arc<AtomicUsize>)
+    where
+        F: Fn(&str) -> Option<String> + Send + Sync + 'static,
+    {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let requests = Arc::new(AtomicUsize::new(0));
+        let reply = Arc::new(reply);
+        let counter = requests.clone();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let reply = reply.clone();
+                let counter = counter.clone();
+                tokio::spawn(async move {
+                    let mut buf = vec![0u8; 4096];
+                    let Ok(n) = stream.read(&mut buf).await else {
+                        return;
+                    };
+                    let request = String::from_utf8_lossy(&buf[..n]).to_string();
+                    let Some(path) = request
+                        .strip_prefix("GET ")
+                        .and_then(|rest| rest.split_whitespace().next())
+                    else {
+                        return;
+                    };
+                    counter.fetch_add(1, Ordering::SeqCst);
+                    if let Some(response) = reply(path) {
+                        let _ = stream.write_all(response.as_bytes()).await;
+                    }
+                });
+            }
+        });
+        (addr, requests)
+    }
+
+    fn html(title: &str) -> String {
+        let body = format!("<html><head><title>{}</title></head></html>", title);
+        format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: text/html\r\nContent-Length: {}\r\n\r\n{}",
+            body.len(),
+            body
+        )
+    }
+
+    fn redirect(location: &str) -> String {
+        format!(
+            "HTTP/1.1 302 Found\r\nLocation: {}\r\nContent-Length: 0\r\n\r\n",
+            location
+        )
+    }
+
+    fn listener_on(addr: SocketAddr, pid: u32) -> PortInfo {
+        let mut port = PortInfo::new(addr.port(), Protocol::TCP, addr.ip().to_string(), pid);
+        port.state = ConnectionState::Listen;
+        port
+    }
+
+    #[tokio::test]
+    async fn test_plain_http_response() {
+        let (addr, _) = stand_in(|path| {
+            (path == "/").then(|| {
+                "HTTP/1.1 404 Not Found\r\nsErVeR:   stand-in/1.0  \r\nX-Empty:\r\n\
+                 Content-Type: text/html; charset=utf-8\r\n\r\n\
+                 <TITLE lang=\"en\">\n  Not   Found &amp; gone </TITLE>"
+                    .to_string()
+            })
+        })
+        .await;
+
+        let probe = probe_http(addr, &config()).await.unwrap();
+        assert!(!probe.tls);
+        assert_eq!(probe.url, format!("http://localhost:{}/", addr.port()));
+        assert_eq!(probe.status, 404);
+        assert_eq!(probe.server.as_deref(), Some("stand-in/1.0"));
+        assert_eq!(probe.title.as_deref(), Some("Not Found & gone"));
+        assert_eq!(probe.redirects, 0);
+    }
+
+    #[tokio::test]
+    async fn test_chunked_body_title() {
+        let (addr, _) = stand_in(|_| {
+            Some(
+                "HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n\
+                 7\r\n<title>\r\n5;ext=1\r\nDash \r\n6\r\nboard<\r\n8\r\n/title>\n\r\n0\r\n\r\n"
+                    .to_string(),
+            )
+        })
+        .await;
+
+        let probe = probe_http(addr, &config()).await.unwrap();
+        assert_eq!(probe.status, 200);
+        assert_eq!(probe.server, None);
+        assert_eq!(probe.title.as_deref(), Some("Dash board"));
+    }
+
+    #[tokio::test]
+    async fn test_follows_local_redirects_up_to_limit() {
+        let (addr, requests) = stand_in(|path| {
+            let n: u32 = path.trim_start_matches("/r").parse().unwrap_or(0);
+            Some(redirect(&format!("/r{}", n + 1)))
+        })
+        .await;
+
+        let probe = probe_http(addr, &config()).await.unwrap();
+        assert_eq!(probe.status, 302);
+        assert_eq!(probe.redirects, 3);
+        assert_eq!(probe.url, format!("http://localhost:{}/r3", addr.port()));
+        assert_eq!(requests.load(Ordering::SeqCst), 4);
+
+        let config = HttpProbeConfig {
+            max_redirects: 0,
+            ..config()
+        };
+        let probe = probe_http(addr, &config).await.unwrap();
+        assert_eq!((probe.status, probe.redirects), (302, 0));
+    }
+
+    #[tokio::test]
+    async fn test_redirect_chain_ends_at_page() {
+        let (addr, _) = stand_in(|path| match path {
+            "/" => Some(redirect("/app/")),
+            "/app/" => Some(redirect("login?next=%2F#top")),
+            "/app/login?next=%2F" => Some(html("Sign in")),
+            _ => None,
+        })
+        .await;
+
+        let probe = probe_http(addr, &config()).await.unwrap();
+        assert_eq!(probe.status, 200);
+        assert_eq!(probe.redirects, 2);
+        assert_eq!(probe.title.as_deref(), Some("Sign in"));
+        assert!(probe.url.ends_with("/app/login?next=%2F"));
+    }
+
+    #[tokio::test]
+    async fn test_does_not_follow_external_redirect() {
+        let (addr, requests) = stand_in(|_| Some(redirect("https://example.com/"))).await;
+
+        let probe = probe_http(addr, &config()).await.unwrap();
+        assert_eq!((probe.status, probe.redirects), (302, 0));
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_resolve_location() {
+        let current = Target {
+            addr: SocketAddr::new(Ipv4Addr::LOCALHOST.into(), 8080),
+            tls: false,
+            host: "localhost:8080".to_string(),
+            path: "/a/b?x=1".to_string(),
+        };
+        let resolve = |location: &str| {
+            resolve_location(location, &current).map(|t| (t.addr.port(), t.tls, t.url()))
+        };
+
+        assert_eq!(
+            resolve("c"),
+            Some((8080, false, "http://localhost:8080/a/c".to_string()))
+        );
+        assert_eq!(
+            resolve("/root#frag"),
+            Some((8080, false, "http://localhost:8080/root".to_string()))
+        );
+        assert_eq!(
+            resolve("https://localhost:8443"),
+            Some((8443, true, "https://localhost:8443/".to_string()))
+        );
+        assert_eq!(
+            resolve("http://127.0.0.1?q"),
+            Some((80, false, "http://127.0.0.1:80/?q".to_string()))
+        );
+        assert_eq!(
+            resolve("//user@[::1]:9000/x"),
+            Some((9000, false, "http://[::1]:9000/x".to_string()))
+        );
+        assert_eq!(resolve("http://example.com/"), None);
+        assert_eq!(resolve("http://10.0.0.5:8080/"), None);
+        assert_eq!(resolve("http://localhost:notaport/"), None);
+    }
+
+    #[test]
+    fn test_extract_title() {
+        assert_eq!(
+            extract_title(
+                b"<html><Title id=t>A &lt;b&gt; &quot;c&quot; &#39;d&#39;&nbsp;e</title>"
+            ),
+            Some("A <b> \"c\" 'd' e".to_string())
+        );
+        // 缺少结束标签时取到结尾，`&amp;` 最后解码
+        assert_eq!(
+            extract_title(b"<title>\r\n\tfoo &amp;lt; bar"),
+            Some("foo &lt; bar".to_string())
+        );
+        assert_eq!(extract_title(b"<title>  </title>"), None);
+        assert_eq!(extract_title(b"<html>no title</html>"), None);
+        assert_eq!(extract_title(b"<title"), None);
+
+        let long = format!("<title>{}</title>", "x".repeat(500));
+        assert_eq!(
+            extract_title(long.as_bytes()).map(|t| t.chars().count()),
+            Some(TITLE_LIMIT)
+        );
+    }
+
+    #[test]
+    fn test_decode_chunked() {
+        assert_eq!(
+            decode_chunked(b"3\r\nabc\r\n2\r\nde\r\n0\r\n\r\n"),
+            b"abcde"
+        );
+        // 数据不完整或长度无效时返回已解码的部分
+        assert_eq!(decode_chunked(b"3\r\nabc\r\n5\r\nde"), b"abcde");
+        assert_eq!(decode_chunked(b"3\r\nabc\r\nzz\r\nde\r\n"), b"abc");
+    }
+
+    #[tokio::test]
+    async fn test_non_http_service() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                tokio::spawn(async move {
+                    let _ = stream.write_all(b"SSH-2.0-OpenSSH_9.6\r\n").await;
+                    tokio::time::sleep(Duration::from_secs(5)).await;
+                });
+            }
+        });
+
+        // 不是 HTTP 响应时立即放弃，不等待超时
+        let started = Instant::now();
+        assert!(probe_http(addr, &config()).await.is_none());
+        assert!(started.elapsed() < Duration::from_millis(1500));
+    }
+
+    #[tokio::test]
+    async fn test_silent_service_times_out() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let mut held = Vec::new();
+            while let Ok((stream, _)) = listener.accept().await {
+                held.push(stream);
+            }
+        });
+
+        let config = HttpProbeConfig {
+            connect_timeout_ms: 100,
+            timeout_ms: 400,
+            ..config()
+        };
+        let started = Instant::now();
+        assert!(probe_http(addr, &config).await.is_none());
+        let elapsed = started.elapsed();
+        assert!(elapsed >= Duration::from_millis(400));
+        assert!(elapsed < Duration::from_millis(2000));
+    }
+
+    #[tokio::test]
+    async fn test_results_cached_per_pid_and_port() {
+        let (addr, requests) = stand_in(|_| Some(html("cached"))).await;
+        let prober = HttpProber::new(config());
+
+        let port = listener_on(addr, 100);
+        let probe = prober.probe(&port).await.unwrap();
+        assert_eq!(probe.title.as_deref(), Some("cached"));
+        assert_eq!(prober.probe(&port).await, Some(probe.clone()));
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+
+        // 同一端口换了进程时重新探测
+        assert!(prober.probe(&listener_on(addr, 101)).await.is_some());
+        assert_eq!(requests.load(Ordering::SeqCst), 2);
+
+        // 非监听端口不探测
+        let mut connection = listener_on(addr, 100);
+        connection.state = ConnectionState::Established;
+        assert_eq!(prober.probe(&connection).await, None);
+
+        // 同一批中的重复端口只探测一次
+        let mut ports = vec![listener_on(addr, 102), listener_on(addr, 102)];
+        prober.enrich(&mut ports).await;
+        assert!(ports.iter().all(|p| p.http.is_some()));
+        assert_eq!(requests.load(Ordering::SeqCst), 3);
+
+        prober.clear();
+        assert!(prober.probe(&port).await.is_some());
+        assert_eq!(requests.load(Ordering::SeqCst), 4);
+    }
+
+    #[tokio::test]
+    async fn test_cache_expires() {
+        let (addr, requests) = stand_in(|_| Some(html("fresh"))).await;
+        let prober = HttpProber::new(HttpProbeConfig {
+            cache_ttl_ms: 50,
+            ..config()
+        });
+
+        let port = listener_on(addr, 100);
+        assert!(prober.probe(&port).await.is_some());
+        tokio::time::sleep(Duration::from_millis(80)).await;
+        assert!(prober.probe(&port).await.is_some());
+        assert_eq!(requests.load(Ordering::SeqCst), 2);
+    }
+}
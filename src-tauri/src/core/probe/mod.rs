@@ -0,0 +1,285 @@
+//! 本地监听端口的主动探测（需显式调用）
+//!
+//! 只连接本机地址；每个端口有连接、读取和总时长限制，并发数受限
+
+mod cert;
+mod fingerprint;
+mod http;
+mod tls;
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+
+use crate::core::query::{Field, PageQuery, PortPage, PortQuery, ScanCache};
+use crate::core::PortScanner;
+use crate::models::{AppError, AppResult, ConnectionState, PortInfo, Protocol, ServiceFingerprint};
+use crate::utils::net::parse_addr;
+use crate::utils::time::now_millis;
+
+pub use fingerprint::fingerprint;
+pub use http::{probe_http, HttpProbeConfig, HttpProber};
+pub use tls::{inspect_tls, TlsInspectConfig, TlsInspector};
+
+/// 探测配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProbeConfig {
+    /// 连接超时（毫秒）
+    pub connect_timeout_ms: u64,
+    /// 单次读取超时（毫秒）
+    pub read_timeout_ms: u64,
+    /// 单个端口的总时长上限（毫秒）
+    pub port_timeout_ms: u64,
+    /// 同时探测的端口数
+    pub concurrency: usize,
+}
+
+impl Default for ProbeConfig {
+    fn default() -> Self {
+        Self {
+            connect_timeout_ms: 300,
+            read_timeout_ms: 800,
+            port_timeout_ms: 5000,
+            concurrency: 16,
+        }
+    }
+}
+
+impl ProbeConfig {
+    /// 将各项限制在默认值以内（用于非特权的守护进程客户端）
+    pub fn limited(&self) -> Self {
+        let default = Self::default();
+        Self {
+            connect_timeout_ms: self.connect_timeout_ms.min(default.connect_timeout_ms),
+            read_timeout_ms: self.read_timeout_ms.min(default.read_timeout_ms),
+            port_timeout_ms: self.port_timeout_ms.min(default.port_timeout_ms),
+            concurrency: self.concurrency.clamp(1, default.concurrency),
+        }
+    }
+
+    fn connect_timeout(&self) -> Duration {
+        Duration::from_millis(self.connect_timeout_ms)
+    }
+
+    fn read_timeout(&self) -> Duration {
+        Duration::from_millis(self.read_timeout_ms)
+    }
+}
+
+/// 扫描并探测 TCP 监听端口，`only` 为 None 时探测全部，返回的列表只包含 TCP 监听端口
+pub async fn probe_listening(
+    scanner: &PortScanner,
+    only: Option<&[u16]>,
+    config: &ProbeConfig,
+) -> AppResult<Vec<PortInfo>> {
+    let mut ports = scanner.scan_listening()?;
+    ports.retain(|p| {
+        p.protocol == Protocol::TCP
+            && match only {
+                Some(only) => only.contains(&p.port),
+                None => true,
+            }
+    });
+
+    fingerprint_ports(&mut ports, config).await;
+    Ok(ports)
+}
+
+/// 按过滤表达式扫描端口，表达式用到 `tls_expires_in` 时先检查 TCP 监听端口的证书
+pub async fn scan_query(
+    scanner: &PortScanner,
+    inspector: &TlsInspector,
+    query: &PortQuery,
+) -> AppResult<Vec<PortInfo>> {
+    if !query.references(Field::TlsExpiresIn) {
+        return scanner.scan_query(query);
+    }
+
+    let mut ports = scanner.scan_all()?;
+    inspector.enrich(&mut ports).await;
+    Ok(ports.into_iter().filter(|p| query.matches(p)).collect())
+}
+
+/// 分页查询，首次查询的表达式用到 `tls_expires_in` 时先检查证书再缓存扫描结果；
+/// 翻页沿用缓存中的检查结果
+pub async fn scan_page(
+    cache: &ScanCache,
+    scanner: &PortScanner,
+    inspector: &TlsInspector,
+    query: &PageQuery,
+) -> AppResult<PortPage> {
+    let filter = PortQuery::parse(query.filter.as_deref().unwrap_or(""))?;
+    if query.scan_id.is_some() || !filter.references(Field::TlsExpiresIn) {
+        return cache.page(scanner, query);
+    }
+
+    let mut ports = scanner.scan_all()?;
+    inspector.enrich(&mut ports).await;
+    cache.page_from(ports, query)
+}
+
+/// 端口上的全部 TCP 监听，端口未被监听时返回 `PortNotFound`
+fn tcp_listeners(scanner: &PortScanner, port: u16) -> AppResult<Vec<PortInfo>> {
+    let mut ports = scanner.query_port(port, Some(Protocol::TCP))?;
+    ports.retain(|p| p.state == ConnectionState::Listen);
+    if ports.is_empty() {
+        return Err(AppError::PortNotFound(port));
+    }
+    Ok(ports)
+}
+
+/// 探测端口列表中的 TCP 监听端口，结果写入 `fingerprint`
+///
+/// 同一地址只探测一次；超时或无响应的端口保持为 None
+pub async fn fingerprint_ports(ports: &mut [PortInfo], config: &ProbeConfig) {
+    let mut targets: HashMap<SocketAddr, Option<ServiceFingerprint>> = HashMap::new();
+    for port in ports.iter() {
+        if let Some(addr) = probe_target(port) {
+            targets.insert(addr, None);
+        }
+    }
+
+    let semaphore = Arc::new(Semaphore::new(config.concurrency.max(1)));
+    let mut tasks = JoinSet::new();
+    for addr in targets.keys().copied() {
+        let semaphore = semaphore.clone();
+        let config = config.clone();
+        tasks.spawn(async move {
+            let _permit = semaphore.acquire_owned().await.ok()?;
+            let limit = Duration::from_millis(config.port_timeout_ms);
+            let result = tokio::time::timeout(limit, fingerprint(addr, &config))
+                .await
+                .ok()
+                .flatten();
+            Some((addr, result))
+        });
+    }
+
+    while let Some(joined) = tasks.join_next().await {
+        if let Ok(Some((addr, result))) = joined {
+            targets.insert(addr, result);
+        }
+    }
+
+    for port in ports.iter_mut() {
+        if let Some(addr) = probe_target(port) {
+            port.fingerprint = targets.get(&addr).cloned().flatten();
+        }
+    }
+}
+
+/// TCP 监听端口的连接地址，通配地址替换为同协议族的回环地址
+///
+/// 监听地址总是本机地址，因此探测不会连接到外部主机
+fn probe_target(port: &PortInfo) -> Option<SocketAddr> {
+    if port.protocol != Protocol::TCP || port.state != ConnectionState::Listen {
+        return None;
+    }
+
+    let ip: IpAddr = match parse_addr(&port.local_addr)? {
+        IpAddr::V4(v4) if v4.is_unspecified() => Ipv4Addr::LOCALHOST.into(),
+        IpAddr::V6(v6) if v6.is_unspecified() => Ipv6Addr::LOCALHOST.into(),
+        ip => ip,
+    };
+
+    Some(SocketAddr::new(ip, port.port))
+}
+
+struct CachedResult<T> {
+    at: u64,
+    result: Option<T>,
+}
+
+/// 按 (PID, 端口) 缓存的探测结果，无结果也会缓存
+struct ProbeCache<T> {
+    ttl_ms: u64,
+    entries: Mutex<HashMap<(u32, u16), CachedResult<T>>>,
+}
+
+impl<T: Clone + Send + 'static> ProbeCache<T> {
+    fn new(ttl_ms: u64) -> Self {
+        Self {
+            ttl_ms,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// 返回与 `ports` 一一对应的结果，缓存中没有的 TCP 监听端口以 `concurrency` 的并发调用 `probe`
+    async fn resolve<F, Fut>(
+        &self,
+        ports: &[PortInfo],
+        concurrency: usize,
+        probe: F,
+    ) -> Vec<Option<T>>
+    where
+        F: Fn(SocketAddr) -> Fut,
+        Fut: Future<Output = Option<T>> + Send + 'static,
+    {
+        let now = now_millis();
+        let mut pending: HashMap<(u32, u16), SocketAddr> = HashMap::new();
+        {
+            let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+            entries.retain(|_, entry| now.saturating_sub(entry.at) < self.ttl_ms);
+            for port in ports {
+                let key = (port.pid, port.port);
+                if let Some(addr) = probe_target(port) {
+                    if !entries.contains_key(&key) {
+                        pending.entry(key).or_insert(addr);
+                    }
+                }
+            }
+        }
+
+        let semaphore = Arc::new(Semaphore::new(concurrency.max(1)));
+        let mut tasks = JoinSet::new();
+        for (key, addr) in pending {
+            let semaphore = semaphore.clone();
+            let probe = probe(addr);
+            tasks.spawn(async move {
+                let _permit = semaphore.acquire_owned().await.ok()?;
+                Some((key, probe.await))
+            });
+        }
+
+        let mut results = Vec::new();
+        while let Some(joined) = tasks.join_next().await {
+            if let Ok(Some(result)) = joined {
+                results.push(result);
+            }
+        }
+
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        for (key, result) in results {
+            entries.insert(
+                key,
+                CachedResult {
+                    at: now_millis(),
+                    result,
+                },
+            );
+        }
+        ports
+            .iter()
+            .map(|port| match probe_target(port) {
+                Some(_) => entries
+                    .get(&(port.pid, port.port))
+                    .and_then(|entry| entry.result.clone()),
+                None => None,
+            })
+            .collect()
+    }
+
+    fn clear(&self) {
+        self.entries
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clear();
+    }
+}
@@ -0,0 +1,227 @@
+//! 本机 TLS 连接与证书检查
+//!
+//! 本地服务大多使用自签名证书，探测时不校验证书链和主机名，只用于连接回环或本机地址
+
+use std::net::SocketAddr;
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tokio::net::TcpStream;
+use tokio::time::timeout;
+use tokio_rustls::client::TlsStream;
+use tokio_rustls::rustls::client::danger::{
+    HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier,
+};
+use tokio_rustls::rustls::crypto::{ring, CryptoProvider};
+use tokio_rustls::rustls::pki_types::{CertificateDer, ServerName, UnixTime};
+use tokio_rustls::rustls::{
+    ClientConfig, DigitallySignedStruct, Error, ProtocolVersion, SignatureScheme,
+};
+use tokio_rustls::TlsConnector;
+
+use super::cert::{chain_status, parse_certificate};
+use super::{tcp_listeners, ProbeCache};
+use crate::core::PortScanner;
+use crate::models::{AppResult, PortInfo, TlsInfo};
+use crate::utils::time::now_millis;
+
+/// 接受任何证书的校验器
+#[derive(Debug)]
+struct AcceptAnyCert(Arc<CryptoProvider>);
+
+impl ServerCertVerifier for AcceptAnyCert {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> Result<ServerCertVerified, Error> {
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        _message: &[u8],
+        _cert: &CertificateDer<'_>,
+        _dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, Error> {
+        Ok(HandshakeSignatureValid::assertion())
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        _message: &[u8],
+        _cert: &CertificateDer<'_>,
+        _dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, Error> {
+        Ok(HandshakeSignatureValid::assertion())
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.0.signature_verification_algorithms.supported_schemes()
+    }
+}
+
+/// 不校验证书的连接器，`alpn` 为协商的应用层协议
+pub(crate) fn insecure_connector(alpn: &[&[u8]]) -> TlsConnector {
+    let provider = Arc::new(ring::default_provider());
+    let mut config = ClientConfig::builder_with_provider(provider.clone())
+        .with_safe_default_protocol_versions()
+        .expect("ring 提供程序支持默认协议版本")
+        .dangerous()
+        .with_custom_certificate_verifier(Arc::new(AcceptAnyCert(provider)))
+        .with_no_client_auth();
+    config.alpn_protocols = alpn.iter().map(|p| p.to_vec()).collect();
+    TlsConnector::from(Arc::new(config))
+}
+
+/// 建立 TLS 连接，SNI 使用 `server_name`（为 IP 地址时不发送 SNI）
+pub(crate) async fn connect_tls(
+    addr: SocketAddr,
+    server_name: &str,
+    limit: Duration,
+) -> Option<TlsStream<TcpStream>> {
+    static CONNECTOR: OnceLock<TlsConnector> = OnceLock::new();
+    let connector = CONNECTOR.get_or_init(|| insecure_connector(&[b"http/1.1"]));
+    connect_with(connector, addr, server_name, limit).await
+}
+
+async fn connect_with(
+    connector: &TlsConnector,
+    addr: SocketAddr,
+    server_name: &str,
+    limit: Duration,
+) -> Option<TlsStream<TcpStream>> {
+    let name = ServerName::try_from(server_name.to_string()).ok()?;
+    let tcp = timeout(limit, TcpStream::connect(addr)).await.ok()?.ok()?;
+    timeout(limit, connector.connect(name, tcp))
+        .await
+        .ok()?
+        .ok()
+}
+
+/// TLS 检查配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TlsInspectConfig {
+    /// 连接与握手的超时（毫秒）
+    pub timeout_ms: u64,
+    /// 剩余天数少于该值时标记为即将过期
+    pub warn_days: u32,
+    /// 结果缓存时长（毫秒）
+    pub cache_ttl_ms: u64,
+    /// 同时检查的端口数
+    pub concurrency: usize,
+}
+
+impl Default for TlsInspectConfig {
+    fn default() -> Self {
+        Self {
+            timeout_ms: 3000,
+            warn_days: 30,
+            cache_ttl_ms: 300_000,
+            concurrency: 8,
+        }
+    }
+}
+
+/// 带缓存的 TLS 证书检查器
+pub struct TlsInspector {
+    config: TlsInspectConfig,
+    cache: ProbeCache<TlsInfo>,
+}
+
+impl Default for TlsInspector {
+    fn default() -> Self {
+        Self::new(TlsInspectConfig::default())
+    }
+}
+
+impl TlsInspector {
+    pub fn new(config: TlsInspectConfig) -> Self {
+        Self {
+            cache: ProbeCache::new(config.cache_ttl_ms),
+            config,
+        }
+    }
+
+    /// 检查端口上的全部 TCP 监听，端口未被监听时返回 `PortNotFound`
+    pub async fn inspect_port(&self, scanner: &PortScanner, port: u16) -> AppResult<Vec<PortInfo>> {
+        let mut ports = tcp_listeners(scanner, port)?;
+        self.enrich(&mut ports).await;
+        Ok(ports)
+    }
+
+    /// 为端口列表中的 TCP 监听端口填充 `tls`，非 TLS 端口保持为 None
+    pub async fn enrich(&self, ports: &mut [PortInfo]) {
+        let config = self.config.clone();
+        let results = self
+            .cache
+            .resolve(ports, self.config.concurrency, |addr| {
+                let config = config.clone();
+                async move { inspect_tls(addr, &config).await }
+            })
+            .await;
+        for (port, result) in ports.iter_mut().zip(results) {
+            port.tls = result;
+        }
+    }
+
+    /// 清空缓存
+    pub fn clear(&self) {
+        self.cache.clear();
+    }
+}
+
+/// 与地址握手并读取证书链，握手失败（不是 TLS 服务）时返回 None
+pub async fn inspect_tls(addr: SocketAddr, config: &TlsInspectConfig) -> Option<TlsInfo> {
+    static CONNECTOR: OnceLock<TlsConnector> = OnceLock::new();
+    let connector = CONNECTOR.get_or_init(|| insecure_connector(&[b"h2", b"http/1.1"]));
+
+    let server_name = if addr.ip().is_loopback() {
+        "localhost".to_string()
+    } else {
+        addr.ip().to_string()
+    };
+    let limit = Duration::from_millis(config.timeout_ms);
+    let stream = connect_with(connector, addr, &server_name, limit).await?;
+    let (_, conn) = stream.get_ref();
+
+    let chain: Vec<_> = conn
+        .peer_certificates()
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|der| parse_certificate(der))
+        .collect();
+    let now = now_millis();
+    let (expires_in_days, status) = chain_status(&chain, (now / 1000) as i64, config.warn_days);
+
+    Some(TlsInfo {
+        protocol_version: conn
+            .protocol_version()
+            .map(protocol_name)
+            .unwrap_or_default(),
+        cipher_suite: conn
+            .negotiated_cipher_suite()
+            .map(|suite| format!("{:?}", suite.suite()))
+            .unwrap_or_default(),
+        alpn: conn
+            .alpn_protocol()
+            .map(|p| String::from_utf8_lossy(p).to_string()),
+        chain,
+        expires_in_days,
+        status,
+        inspected_at: now,
+    })
+}
+
+fn protocol_name(version: ProtocolVersion) -> String {
+    match version {
+        ProtocolVersion::TLSv1_2 => "TLSv1.2".to_string(),
+        ProtocolVersion::TLSv1_3 => "TLSv1.3".to_string(),
+        other => format!("{:?}", other),
+    }
+}
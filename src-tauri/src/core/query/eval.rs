@@ -0,0 +1,264 @@
+//! 过滤表达式求值
+
+use std::net::IpAddr;
+
+use super::{AddrClass, CompareOp, Expr, Field, Predicate, Value};
+use crate::models::{PortInfo, Protocol};
+use crate::utils::net::parse_addr;
+
+/// 字段的实际值
+enum Actual<'a> {
+    Number(u64),
+    Text(&'a str),
+    Missing,
+}
+
+impl Expr {
+    /// 判断端口是否满足表达式
+    pub fn matches(&self, port: &PortInfo) -> bool {
+        match self {
+            Self::And(a, b) => a.matches(port) && b.matches(port),
+            Self::Or(a, b) => a.matches(port) || b.matches(port),
+            Self::Not(inner) => !inner.matches(port),
+            Self::Pred(pred) => pred.matches(port),
+        }
+    }
+}
+
+impl Predicate {
+    fn matches(&self, port: &PortInfo) -> bool {
+        match self {
+            Self::Compare { field, op, value } => compare(&actual(*field, port), *op, value),
+            Self::Range { field, start, end } => match actual(*field, port) {
+                Actual::Number(n) => (*start..=*end).contains(&n),
+                _ => false,
+            },
+            Self::List { field, values } => {
+                let actual = actual(*field, port);
+                values
+                    .iter()
+                    .any(|value| compare(&actual, CompareOp::Eq, value))
+            }
+            Self::Class { field, class } => match actual(*field, port) {
+                Actual::Text(addr) => parse_addr(addr).is_some_and(|ip| AddrClass::of(ip) == *class),
+                _ => false,
+            },
+        }
+    }
+}
+
+fn actual(field: Field, port: &PortInfo) -> Actual<'_> {
+    fn text(value: Option<&str>) -> Actual<'_> {
+        value.map(Actual::Text).unwrap_or(Actual::Missing)
+    }
+
+    let process = port.process.as_ref();
+
+    match field {
+        Field::Port => Actual::Number(port.port as u64),
+        Field::RemotePort => port
+            .remote_port
+            .map(|p| Actual::Number(p as u64))
+            .unwrap_or(Actual::Missing),
+        Field::Pid => Actual::Number(port.pid as u64),
+        Field::State => Actual::Text(port.state.name()),
+        Field::Protocol => Actual::Text(match port.protocol {
+            Protocol::TCP => "tcp",
+            Protocol::UDP => "udp",
+        }),
+        Field::Process => text(process.map(|p| p.name.as_str())),
+        Field::Service => text(port.service_name.as_deref()),
+        Field::Exe => text(process.and_then(|p| p.exe_path.as_deref())),
+        Field::Cmd => text(process.and_then(|p| p.cmd_line.as_deref())),
+        Field::Local => Actual::Text(&port.local_addr),
+        Field::Remote => text(port.remote_addr.as_deref()),
+        Field::TlsExpiresIn => port
+            .tls
+            .as_ref()
+            .and_then(|tls| tls.expires_in_days)
+            .map(|days| Actual::Number(days.max(0) as u64))
+            .unwrap_or(Actual::Missing),
+    }
+}
+
+fn compare(actual: &Actual<'_>, op: CompareOp, value: &Value) -> bool {
+    match (actual, value) {
+        (Actual::Number(a), Value::Number(b)) => match op {
+            CompareOp::Eq => a == b,
+            CompareOp::Ne => a != b,
+            CompareOp::Lt => a < b,
+            CompareOp::Le => a <= b,
+            CompareOp::Gt => a > b,
+            CompareOp::Ge => a >= b,
+            CompareOp::Contains | CompareOp::NotContains => false,
+        },
+        (Actual::Text(a), Value::Text(b)) => match op {
+            CompareOp::Eq => text_eq(a, b),
+            CompareOp::Ne => !text_eq(a, b),
+            CompareOp::Contains => contains(a, b),
+            CompareOp::NotContains => !contains(a, b),
+            _ => false,
+        },
+        (Actual::Missing, _) => matches!(op, CompareOp::Ne | CompareOp::NotContains),
+        _ => false,
+    }
+}
+
+/// 不区分大小写比较，两边都是 IP 地址时按地址比较（`::1` 与 `0:0::1` 相等）
+fn text_eq(a: &str, b: &str) -> bool {
+    match (parse_addr(a), parse_addr(b)) {
+        (Some(a), Some(b)) => a == b,
+        _ => a.to_lowercase() == b.to_lowercase(),
+    }
+}
+
+fn contains(haystack: &str, needle: &str) -> bool {
+    haystack.to_lowercase().contains(&needle.to_lowercase())
+}
+
+impl AddrClass {
+    /// 地址分类
+    pub fn of(ip: IpAddr) -> Self {
+        // IPv4 映射地址按 IPv4 处理
+        let ip = match ip {
+            IpAddr::V6(v6) => v6.to_ipv4_mapped().map(IpAddr::V4).unwrap_or(ip),
+            v4 => v4,
+        };
+
+        if ip.is_loopback() {
+            return Self::Loopback;
+        }
+        if ip.is_unspecified() {
+            return Self::Any;
+        }
+        if ip.is_multicast() {
+            return Self::Multicast;
+        }
+
+        match ip {
+            IpAddr::V4(v4) if v4.is_private() => Self::Private,
+            IpAddr::V4(v4) if v4.is_link_local() => Self::LinkLocal,
+            IpAddr::V6(v6) if (v6.segments()[0] & 0xfe00) == 0xfc00 => Self::Private,
+            IpAddr::V6(v6) if (v6.segments()[0] & 0xffc0) == 0xfe80 => Self::LinkLocal,
+            _ => Self::Public,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::query::parse;
+    use crate::models::{ConnectionState, ProcessInfo};
+
+    fn listener() -> PortInfo {
+        let mut port = PortInfo::new(3000, Protocol::TCP, "::1".to_string(), 42);
+        port.state = ConnectionState::Listen;
+        port.process = Some(ProcessInfo::new(42, "Node".to_string()));
+        port
+    }
+
+    fn connection() -> PortInfo {
+        let mut port = PortInfo::new(50000, Protocol::TCP, "192.168.1.10".to_string(), 7);
+        port.state = ConnectionState::Established;
+        port.remote_addr = Some("8.8.8.8".to_string());
+        port.remote_port = Some(443);
+        port
+    }
+
+    fn matches(source: &str, port: &PortInfo) -> bool {
+        parse(source).unwrap().matches(port)
+    }
+
+    #[test]
+    fn test_compare_and_ranges() {
+        let port = listener();
+        assert!(matches(
+            "port = 3000 and state = listen and proto = tcp",
+            &port
+        ));
+        assert!(matches("port in 3000..3000", &port));
+        assert!(!matches("port in 3001..4000", &port));
+        assert!(matches("port >= 3000 and port < 3001", &port));
+        assert!(matches("state in (established, listen)", &port));
+        assert!(matches("port not in (80, 443)", &port));
+        assert!(matches("process = NODE and process ~ od", &port));
+        assert!(!matches("process !~ OD", &port));
+    }
+
+    #[test]
+    fn test_missing_fields() {
+        // 无进程信息、无远程地址、无证书
+        let port = connection();
+        for source in [
+            "process != node",
+            "process !~ node",
+            "service != http",
+            "exe !~ bin",
+            "tls_expires_in != 0",
+        ] {
+            assert!(matches(source, &port), "{}", source);
+        }
+        for source in [
+            "process = node",
+            "process ~ node",
+            "cmd ~ \"\"",
+            "tls_expires_in < 30",
+            "tls_expires_in in 0..30",
+            "process in (node, java)",
+        ] {
+            assert!(!matches(source, &port), "{}", source);
+        }
+
+        let port = listener();
+        assert!(matches("remote_port != 443", &port));
+        assert!(!matches("remote_port <= 65535", &port));
+        assert!(!matches("remote public", &port));
+        assert!(matches("not remote public", &port));
+    }
+
+    #[test]
+    fn test_text_eq_compares_addresses() {
+        assert!(text_eq("::1", "0:0::1"));
+        assert!(text_eq("[::1]", "::1"));
+        assert!(text_eq("fe80::1%eth0", "FE80::1"));
+        assert!(!text_eq("::1", "127.0.0.1"));
+        assert!(text_eq("Node", "node"));
+        assert!(!text_eq("node", "node "));
+
+        assert!(matches("local = \"0::1\"", &listener()));
+        assert!(matches("remote != \"8.8.4.4\"", &connection()));
+    }
+
+    #[test]
+    fn test_address_classes() {
+        let cases = [
+            ("127.0.0.1", AddrClass::Loopback),
+            ("::1", AddrClass::Loopback),
+            ("0.0.0.0", AddrClass::Any),
+            ("::", AddrClass::Any),
+            ("10.0.0.1", AddrClass::Private),
+            ("172.16.5.4", AddrClass::Private),
+            ("192.168.1.1", AddrClass::Private),
+            ("fd12::1", AddrClass::Private),
+            ("169.254.1.1", AddrClass::LinkLocal),
+            ("fe80::1", AddrClass::LinkLocal),
+            ("224.0.0.251", AddrClass::Multicast),
+            ("ff02::fb", AddrClass::Multicast),
+            ("8.8.8.8", AddrClass::Public),
+            ("2001:db8::1", AddrClass::Public),
+            // IPv4 映射地址按 IPv4 分类
+            ("::ffff:127.0.0.1", AddrClass::Loopback),
+            ("::ffff:0.0.0.0", AddrClass::Any),
+            ("::ffff:192.168.0.1", AddrClass::Private),
+            ("::ffff:169.254.0.1", AddrClass::LinkLocal),
+            ("::ffff:8.8.8.8", AddrClass::Public),
+        ];
+        for (addr, class) in cases {
+            assert_eq!(AddrClass::of(addr.parse().unwrap()), class, "{}", addr);
+        }
+
+        assert!(matches("local loopback", &listener()));
+        assert!(matches("local private and remote public", &connection()));
+    }
+}
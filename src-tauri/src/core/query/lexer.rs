@@ -0,0 +1,277 @@
+//! 过滤表达式词法分析
+
+use super::ParseError;
+
+/// 词法单元
+#[derive(Debug, Clone, PartialEq)]
+pub enum TokenKind {
+    /// 标识符或关键字（已转为小写）
+    Ident(String),
+    Number(u64),
+    /// 双引号字符串
+    Str(String),
+    /// 比较运算符：= == != < <= > >= ~ !~
+    Op(&'static str),
+    LParen,
+    RParen,
+    Comma,
+    /// 范围 `..`
+    DotDot,
+    Eof,
+}
+
+/// 带位置的词法单元
+#[derive(Debug, Clone, PartialEq)]
+pub struct Token {
+    pub kind: TokenKind,
+    /// 起始字节偏移
+    pub offset: usize,
+    /// 字节长度
+    pub len: usize,
+}
+
+impl TokenKind {
+    /// 用于错误信息的描述
+    pub fn describe(&self) -> String {
+        match self {
+            Self::Ident(name) => format!("'{}'", name),
+            Self::Number(n) => format!("数字 {}", n),
+            Self::Str(s) => format!("字符串 \"{}\"", s),
+            Self::Op(op) => format!("'{}'", op),
+            Self::LParen => "'('".to_string(),
+            Self::RParen => "')'".to_string(),
+            Self::Comma => "','".to_string(),
+            Self::DotDot => "'..'".to_string(),
+            Self::Eof => "表达式结尾".to_string(),
+        }
+    }
+}
+
+/// 将表达式切分为词法单元，末尾总是 `Eof`
+pub fn tokenize(source: &str) -> Result<Vec<Token>, ParseError> {
+    let bytes = source.as_bytes();
+    let mut tokens = Vec::new();
+    let mut pos = 0;
+
+    while pos < bytes.len() {
+        let c = bytes[pos];
+
+        if c.is_ascii_whitespace() {
+            pos += 1;
+            continue;
+        }
+
+        let start = pos;
+        let kind = match c {
+            b'(' => {
+                pos += 1;
+                TokenKind::LParen
+            }
+            b')' => {
+                pos += 1;
+                TokenKind::RParen
+            }
+            b',' => {
+                pos += 1;
+                TokenKind::Comma
+            }
+            b'.' if bytes.get(pos + 1) == Some(&b'.') => {
+                pos += 2;
+                TokenKind::DotDot
+            }
+            b'=' | b'!' | b'<' | b'>' | b'~' => {
+                let (op, len) = match (c, bytes.get(pos + 1)) {
+                    (b'=', Some(b'=')) => ("=", 2),
+                    (b'=', _) => ("=", 1),
+                    (b'!', Some(b'=')) => ("!=", 2),
+                    (b'!', Some(b'~')) => ("!~", 2),
+                    (b'<', Some(b'=')) => ("<=", 2),
+                    (b'<', _) => ("<", 1),
+                    (b'>', Some(b'=')) => (">=", 2),
+                    (b'>', _) => (">", 1),
+                    (b'~', _) => ("~", 1),
+                    _ => {
+                        return Err(ParseError::new(
+                            "无效的运算符 '!'，可用 '!=' 或 '!~'，取反请使用 not",
+                            start,
+                            1,
+                        ))
+                    }
+                };
+                pos += len;
+                TokenKind::Op(op)
+            }
+            b'"' => {
+                let (value, end) = read_string(source, pos)?;
+                pos = end;
+                TokenKind::Str(value)
+            }
+            b'0'..=b'9' => {
+                while pos < bytes.len() && bytes[pos].is_ascii_digit() {
+                    pos += 1;
+                }
+                // 地址需要加引号，否则 `127.0.0.1` 会被拆成数字
+                if bytes.get(pos) == Some(&b'.') && bytes.get(pos + 1) != Some(&b'.') {
+                    return Err(ParseError::new(
+                        "数字后不能直接跟 '.'，地址请使用双引号，如 \"127.0.0.1\"",
+                        start,
+                        pos + 1 - start,
+                    ));
+                }
+                let number = source[start..pos]
+                    .parse()
+                    .map_err(|_| ParseError::new("数字过大", start, pos - start))?;
+                TokenKind::Number(number)
+            }
+            c if c.is_ascii_alphabetic() || c == b'_' => {
+                while pos < bytes.len()
+                    && (bytes[pos].is_ascii_alphanumeric() || bytes[pos] == b'_')
+                {
+                    pos += 1;
+                }
+                TokenKind::Ident(source[start..pos].to_ascii_lowercase())
+            }
+            _ => {
+                let len = source[start..]
+                    .chars()
+                    .next()
+                    .map(char::len_utf8)
+                    .unwrap_or(1);
+                return Err(ParseError::new(
+                    format!("无法识别的字符 '{}'", &source[start..start + len]),
+                    start,
+                    len,
+                ));
+            }
+        };
+
+        tokens.push(Token {
+            kind,
+            offset: start,
+            len: pos - start,
+        });
+    }
+
+    tokens.push(Token {
+        kind: TokenKind::Eof,
+        offset: source.len(),
+        len: 0,
+    });
+    Ok(tokens)
+}
+
+/// 读取双引号字符串，支持 `\"` 和 `\\` 转义，返回内容和结束位置
+fn read_string(source: &str, start: usize) -> Result<(String, usize), ParseError> {
+    let mut value = String::new();
+    let mut chars = source[start + 1..].char_indices();
+
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' => return Ok((value, start + 1 + i + 1)),
+            '\\' => match chars.next() {
+                Some((_, escaped @ ('"' | '\\'))) => value.push(escaped),
+                Some((j, other)) => {
+                    return Err(ParseError::new(
+                        format!("不支持的转义 '\\{}'", other),
+                        start + 1 + i,
+                        j - i + other.len_utf8(),
+                    ))
+                }
+                None => break,
+            },
+            c => value.push(c),
+        }
+    }
+
+    Err(ParseError::new(
+        "字符串缺少结束的双引号",
+        start,
+        source.len() - start,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(source: &str) -> Vec<TokenKind> {
+        tokenize(source)
+            .unwrap()
+            .into_iter()
+            .map(|token| token.kind)
+            .collect()
+    }
+
+    #[test]
+    fn test_tokenize_operators_and_punctuation() {
+        assert_eq!(
+            kinds("= == != < <= > >= ~ !~ ( ) , .."),
+            vec![
+                TokenKind::Op("="),
+                TokenKind::Op("="),
+                TokenKind::Op("!="),
+                TokenKind::Op("<"),
+                TokenKind::Op("<="),
+                TokenKind::Op(">"),
+                TokenKind::Op(">="),
+                TokenKind::Op("~"),
+                TokenKind::Op("!~"),
+                TokenKind::LParen,
+                TokenKind::RParen,
+                TokenKind::Comma,
+                TokenKind::DotDot,
+                TokenKind::Eof,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tokenize_positions_and_case() {
+        let tokens = tokenize("Port in 80..443").unwrap();
+        let spans: Vec<(usize, usize)> = tokens.iter().map(|t| (t.offset, t.len)).collect();
+        assert_eq!(
+            spans,
+            vec![(0, 4), (5, 2), (8, 2), (10, 2), (12, 3), (15, 0)]
+        );
+        assert_eq!(tokens[0].kind, TokenKind::Ident("port".to_string()));
+        assert_eq!(tokens[2].kind, TokenKind::Number(80));
+    }
+
+    #[test]
+    fn test_tokenize_string_escapes() {
+        assert_eq!(
+            kinds(r#""a \"b\" \\ 端口""#),
+            vec![
+                TokenKind::Str(r#"a "b" \ 端口"#.to_string()),
+                TokenKind::Eof
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tokenize_errors() {
+        let err = tokenize("port ! 80").unwrap_err();
+        assert_eq!((err.offset, err.len), (5, 1));
+        assert!(err.message.contains("not"));
+
+        let err = tokenize("local = 127.0.0.1").unwrap_err();
+        assert_eq!((err.offset, err.len), (8, 4));
+        assert!(err.message.contains("双引号"));
+
+        let err = tokenize("process = \"node").unwrap_err();
+        assert_eq!((err.offset, err.len), (10, 5));
+        assert_eq!(err.message, "字符串缺少结束的双引号");
+
+        let err = tokenize(r#"cmd ~ "a\n""#).unwrap_err();
+        assert_eq!((err.offset, err.len), (8, 2));
+        assert_eq!(err.message, "不支持的转义 '\\n'");
+
+        let err = tokenize("port = 99999999999999999999").unwrap_err();
+        assert_eq!((err.offset, err.len), (7, 20));
+        assert_eq!(err.message, "数字过大");
+
+        let err = tokenize("process = 节点").unwrap_err();
+        assert_eq!((err.offset, err.len), (10, 3));
+        assert_eq!(err.message, "无法识别的字符 '节'");
+    }
+}
@@ -0,0 +1,526 @@
+//! 端口过滤表达式
+//!
+//! 示例：`port in 3000..4000 and state = listen and process ~ "node" and not local loopback`
+//!
+//! - 逻辑运算：`and`、`or`、`not` 和括号，优先级 not > and > or
+//! - 比较运算：`=`、`!=`、`<`、`<=`、`>`、`>=`、`~`（包含）、`!~`（不包含），文本比较不区分大小写
+//! - 集合：`port in 3000..4000`、`state in (listen, established)`，可写作 `not in`
+//! - 地址分类：`local loopback`、`remote public` 等
+//!
+//! 字段缺失（如无远程地址、无进程信息）时只有 `!=` 和 `!~` 成立。
+//! `tls_expires_in`（证书剩余天数，已过期为 0）需要连接端口检查证书，只有用到该字段时才会检查
+
+mod eval;
+mod lexer;
+mod page;
+mod parser;
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::models::{AppError, AppResult, PortInfo};
+
+pub use page::{
+    paginate, GroupBy, PageQuery, PortGroup, PortPage, ScanCache, SortField, SortKey,
+    DEFAULT_PAGE_LIMIT, MAX_PAGE_LIMIT,
+};
+
+/// 表达式语法树
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Expr {
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    Pred(Predicate),
+}
+
+/// 单个条件
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Predicate {
+    /// `field op value`
+    Compare {
+        field: Field,
+        op: CompareOp,
+        value: Value,
+    },
+    /// `field in start..end`（闭区间）
+    Range { field: Field, start: u64, end: u64 },
+    /// `field in (a, b, ...)`
+    List { field: Field, values: Vec<Value> },
+    /// `local loopback` 等地址分类
+    Class { field: Field, class: AddrClass },
+}
+
+/// 可过滤的字段
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+    Port,
+    RemotePort,
+    Pid,
+    State,
+    Protocol,
+    Process,
+    Service,
+    Exe,
+    Cmd,
+    Local,
+    Remote,
+    TlsExpiresIn,
+}
+
+/// 字段的值类型，决定可用的运算符
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldKind {
+    Number,
+    State,
+    Protocol,
+    Text,
+    Address,
+}
+
+/// 比较运算符
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompareOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Contains,
+    NotContains,
+}
+
+/// 字面量
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Value {
+    Number(u64),
+    Text(String),
+}
+
+/// 地址分类
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AddrClass {
+    /// 回环地址
+    Loopback,
+    /// 通配地址（0.0.0.0 / ::）
+    Any,
+    /// 私有地址（RFC 1918 / ULA）
+    Private,
+    /// 链路本地地址
+    LinkLocal,
+    /// 组播地址
+    Multicast,
+    /// 以上都不是的地址
+    Public,
+}
+
+/// 解析错误，位置为字节偏移
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ParseError {
+    pub message: String,
+    pub offset: usize,
+    pub len: usize,
+}
+
+const FIELDS: &[(&str, Field)] = &[
+    ("port", Field::Port),
+    ("remote_port", Field::RemotePort),
+    ("pid", Field::Pid),
+    ("state", Field::State),
+    ("protocol", Field::Protocol),
+    ("proto", Field::Protocol),
+    ("process", Field::Process),
+    ("service", Field::Service),
+    ("exe", Field::Exe),
+    ("cmd", Field::Cmd),
+    ("local", Field::Local),
+    ("local_addr", Field::Local),
+    ("remote", Field::Remote),
+    ("remote_addr", Field::Remote),
+    ("tls_expires_in", Field::TlsExpiresIn),
+];
+
+const CLASSES: &[(&str, AddrClass)] = &[
+    ("loopback", AddrClass::Loopback),
+    ("any", AddrClass::Any),
+    ("private", AddrClass::Private),
+    ("link_local", AddrClass::LinkLocal),
+    ("multicast", AddrClass::Multicast),
+    ("public", AddrClass::Public),
+];
+
+impl Field {
+    pub fn from_name(name: &str) -> Option<Self> {
+        FIELDS.iter().find(|(n, _)| *n == name).map(|(_, f)| *f)
+    }
+
+    /// 规范名称
+    pub fn name(&self) -> &'static str {
+        FIELDS
+            .iter()
+            .find(|(_, f)| f == self)
+            .map(|(n, _)| *n)
+            .unwrap_or("")
+    }
+
+    pub fn kind(&self) -> FieldKind {
+        match self {
+            Self::Port | Self::RemotePort | Self::Pid | Self::TlsExpiresIn => FieldKind::Number,
+            Self::State => FieldKind::State,
+            Self::Protocol => FieldKind::Protocol,
+            Self::Process | Self::Service | Self::Exe | Self::Cmd => FieldKind::Text,
+            Self::Local | Self::Remote => FieldKind::Address,
+        }
+    }
+
+    /// 数值字段的上限
+    fn max_value(&self) -> u64 {
+        match self {
+            Self::Pid | Self::TlsExpiresIn => u32::MAX as u64,
+            _ => u16::MAX as u64,
+        }
+    }
+}
+
+impl FieldKind {
+    /// 该类型支持的运算符
+    fn operators(&self) -> &'static [CompareOp] {
+        use CompareOp::*;
+        match self {
+            Self::Number => &[Eq, Ne, Lt, Le, Gt, Ge],
+            Self::State | Self::Protocol => &[Eq, Ne],
+            Self::Text | Self::Address => &[Eq, Ne, Contains, NotContains],
+        }
+    }
+}
+
+impl CompareOp {
+    fn from_symbol(symbol: &str) -> Option<Self> {
+        Some(match symbol {
+            "=" => Self::Eq,
+            "!=" => Self::Ne,
+            "<" => Self::Lt,
+            "<=" => Self::Le,
+            ">" => Self::Gt,
+            ">=" => Self::Ge,
+            "~" => Self::Contains,
+            "!~" => Self::NotContains,
+            _ => return None,
+        })
+    }
+
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Self::Eq => "=",
+            Self::Ne => "!=",
+            Self::Lt => "<",
+            Self::Le => "<=",
+            Self::Gt => ">",
+            Self::Ge => ">=",
+            Self::Contains => "~",
+            Self::NotContains => "!~",
+        }
+    }
+}
+
+impl AddrClass {
+    pub fn from_name(name: &str) -> Option<Self> {
+        CLASSES.iter().find(|(n, _)| *n == name).map(|(_, c)| *c)
+    }
+
+    pub fn name(&self) -> &'static str {
+        CLASSES
+            .iter()
+            .find(|(_, c)| c == self)
+            .map(|(n, _)| *n)
+            .unwrap_or("")
+    }
+}
+
+impl ParseError {
+    pub fn new(message: impl Into<String>, offset: usize, len: usize) -> Self {
+        Self {
+            message: message.into(),
+            offset,
+            len,
+        }
+    }
+
+    /// 附带原表达式和位置标记的多行说明
+    pub fn render(&self, source: &str) -> String {
+        let offset = floor_char_boundary(source, self.offset);
+        let end = floor_char_boundary(source, offset + self.len);
+        let column = source[..offset].chars().count();
+        let width = source[offset..end].chars().count().max(1);
+
+        format!(
+            "第 {} 列: {}\n  {}\n  {}{}",
+            column + 1,
+            self.message,
+            source,
+            " ".repeat(column),
+            "^".repeat(width)
+        )
+    }
+}
+
+fn floor_char_boundary(source: &str, mut index: usize) -> usize {
+    index = index.min(source.len());
+    while !source.is_char_boundary(index) {
+        index -= 1;
+    }
+    index
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}（位置 {}）", self.message, self.offset)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// 解析表达式
+pub fn parse(source: &str) -> Result<Expr, ParseError> {
+    parser::Parser::new(lexer::tokenize(source)?).parse()
+}
+
+/// 已解析的过滤表达式，空表达式匹配全部端口
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PortQuery {
+    expr: Option<Expr>,
+}
+
+impl PortQuery {
+    /// 解析表达式，错误信息中标出出错位置
+    pub fn parse(source: &str) -> AppResult<Self> {
+        if source.trim().is_empty() {
+            return Ok(Self { expr: None });
+        }
+
+        parse(source)
+            .map(|expr| Self { expr: Some(expr) })
+            .map_err(|e| AppError::InvalidArgument(format!("过滤表达式错误，{}", e.render(source))))
+    }
+
+    pub fn expr(&self) -> Option<&Expr> {
+        self.expr.as_ref()
+    }
+
+    /// 表达式是否用到字段
+    pub fn references(&self, field: Field) -> bool {
+        self.expr.as_ref().is_some_and(|expr| expr.references(field))
+    }
+
+    pub fn matches(&self, port: &PortInfo) -> bool {
+        match &self.expr {
+            Some(expr) => expr.matches(port),
+            None => true,
+        }
+    }
+}
+
+impl Expr {
+    /// 表达式是否用到字段
+    pub fn references(&self, field: Field) -> bool {
+        match self {
+            Self::And(a, b) | Self::Or(a, b) => a.references(field) || b.references(field),
+            Self::Not(inner) => inner.references(field),
+            Self::Pred(pred) => pred.field() == field,
+        }
+    }
+
+    /// 按优先级输出，只在必要时加括号：0 = or，1 = and，2 = 一元
+    fn write_prec(&self, f: &mut fmt::Formatter<'_>, prec: u8) -> fmt::Result {
+        let (own, lhs, rhs, op) = match self {
+            Self::Or(a, b) => (0, a, b, "or"),
+            Self::And(a, b) => (1, a, b, "and"),
+            Self::Not(inner) => {
+                write!(f, "not ")?;
+                return inner.write_prec(f, 2);
+            }
+            Self::Pred(pred) => return write!(f, "{}", pred),
+        };
+
+        if own < prec {
+            write!(f, "(")?;
+        }
+        lhs.write_prec(f, own)?;
+        write!(f, " {} ", op)?;
+        rhs.write_prec(f, own + 1)?;
+        if own < prec {
+            write!(f, ")")?;
+        }
+        Ok(())
+    }
+}
+
+/// 输出的表达式重新解析后得到相同的语法树
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write_prec(f, 0)
+    }
+}
+
+impl Predicate {
+    pub fn field(&self) -> Field {
+        match self {
+            Self::Compare { field, .. }
+            | Self::Range { field, .. }
+            | Self::List { field, .. }
+            | Self::Class { field, .. } => *field,
+        }
+    }
+}
+
+impl fmt::Display for Predicate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Compare { field, op, value } => {
+                write!(f, "{} {} ", field.name(), op.symbol())?;
+                write_value(f, *field, value)
+            }
+            Self::Range { field, start, end } => {
+                write!(f, "{} in {}..{}", field.name(), start, end)
+            }
+            Self::List { field, values } => {
+                write!(f, "{} in (", field.name())?;
+                for (i, value) in values.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write_value(f, *field, value)?;
+                }
+                write!(f, ")")
+            }
+            Self::Class { field, class } => write!(f, "{} {}", field.name(), class.name()),
+        }
+    }
+}
+
+/// 状态和协议以标识符输出，其余文本加引号
+fn write_value(f: &mut fmt::Formatter<'_>, field: Field, value: &Value) -> fmt::Result {
+    match value {
+        Value::Number(n) => write!(f, "{}", n),
+        Value::Text(s) if matches!(field.kind(), FieldKind::State | FieldKind::Protocol) => {
+            write!(f, "{}", s)
+        }
+        Value::Text(s) => write!(f, "\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\"")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+    use proptest::sample::select;
+
+    use super::*;
+    use crate::models::CONNECTION_STATE_NAMES;
+
+    fn field() -> impl Strategy<Value = Field> {
+        select(FIELDS.iter().map(|(_, field)| *field).collect::<Vec<_>>())
+    }
+
+    fn value(field: Field) -> BoxedStrategy<Value> {
+        match field.kind() {
+            FieldKind::Number => (0..=field.max_value()).prop_map(Value::Number).boxed(),
+            FieldKind::State => select(CONNECTION_STATE_NAMES.to_vec())
+                .prop_map(|name| Value::Text(name.to_string()))
+                .boxed(),
+            FieldKind::Protocol => select(vec!["tcp", "udp"])
+                .prop_map(|name| Value::Text(name.to_string()))
+                .boxed(),
+            FieldKind::Text | FieldKind::Address => any::<String>().prop_map(Value::Text).boxed(),
+        }
+    }
+
+    fn predicate() -> impl Strategy<Value = Predicate> {
+        field().prop_flat_map(|field| {
+            let ops = field.kind().operators().to_vec();
+            let mut options = vec![
+                (select(ops), value(field))
+                    .prop_map(move |(op, value)| Predicate::Compare { field, op, value })
+                    .boxed(),
+                prop::collection::vec(value(field), 1..4)
+                    .prop_map(move |values| Predicate::List { field, values })
+                    .boxed(),
+            ];
+            if field.kind() == FieldKind::Number {
+                let max = field.max_value();
+                options.push(
+                    (0..=max, 0..=max)
+                        .prop_map(move |(a, b)| Predicate::Range {
+                            field,
+                            start: a.min(b),
+                            end: a.max(b),
+                        })
+                        .boxed(),
+                );
+            }
+            if field.kind() == FieldKind::Address {
+                let classes = CLASSES.iter().map(|(_, class)| *class).collect::<Vec<_>>();
+                options.push(
+                    select(classes)
+                        .prop_map(move |class| Predicate::Class { field, class })
+                        .boxed(),
+                );
+            }
+            prop::strategy::Union::new(options)
+        })
+    }
+
+    fn expr() -> impl Strategy<Value = Expr> {
+        predicate()
+            .prop_map(Expr::Pred)
+            .prop_recursive(6, 32, 2, |inner| {
+                prop_oneof![
+                    (inner.clone(), inner.clone())
+                        .prop_map(|(a, b)| Expr::And(Box::new(a), Box::new(b))),
+                    (inner.clone(), inner.clone())
+                        .prop_map(|(a, b)| Expr::Or(Box::new(a), Box::new(b))),
+                    inner.prop_map(|e| Expr::Not(Box::new(e))),
+                ]
+            })
+    }
+
+    proptest! {
+        #[test]
+        fn test_display_round_trips(expr in expr()) {
+            let text = expr.to_string();
+            prop_assert_eq!(parse(&text), Ok(expr), "{}", text);
+        }
+    }
+
+    #[test]
+    fn test_empty_query_matches_everything() {
+        let query = PortQuery::parse("  ").unwrap();
+        assert!(query.expr().is_none());
+        assert!(query.matches(&PortInfo::new(
+            80,
+            crate::models::Protocol::TCP,
+            "0.0.0.0".to_string(),
+            1
+        )));
+        assert!(!query.references(Field::Port));
+        assert!(PortQuery::parse("tls_expires_in < 7 or port = 1")
+            .unwrap()
+            .references(Field::TlsExpiresIn));
+    }
+
+    #[test]
+    fn test_error_render_marks_position() {
+        let err = PortQuery::parse("进程 = 1").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "参数无效: 过滤表达式错误，第 1 列: 无法识别的字符 '进'\n  进程 = 1\n  ^"
+        );
+
+        let err = parse("port = 1 and 端口 = 2").unwrap_err();
+        assert_eq!(
+            err.render("port = 1 and 端口 = 2"),
+            "第 14 列: 无法识别的字符 '端'\n  port = 1 and 端口 = 2\n               ^"
+        );
+    }
+}
@@ -0,0 +1,545 @@
+//! 分页、排序与分组
+//!
+//! 一次扫描的结果缓存在 [`ScanCache`] 中，翻页时携带 `scan_id` 复用同一份数据，不再重新扫描
+
+use std::cmp::Ordering;
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering as AtomicOrdering};
+use std::sync::{Arc, Mutex};
+
+use serde::{Deserialize, Serialize};
+
+use super::PortQuery;
+use crate::core::PortScanner;
+use crate::models::{AppError, AppResult, PortInfo, Protocol};
+use crate::utils::time::now_millis;
+
+/// 默认每页条数
+pub const DEFAULT_PAGE_LIMIT: usize = 200;
+/// 每页条数上限
+pub const MAX_PAGE_LIMIT: usize = 5000;
+
+/// 排序字段
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SortField {
+    Port,
+    Protocol,
+    LocalAddr,
+    RemoteAddr,
+    RemotePort,
+    State,
+    Pid,
+    Process,
+    Service,
+}
+
+/// 排序键
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct SortKey {
+    pub field: SortField,
+    /// 降序
+    #[serde(default)]
+    pub desc: bool,
+}
+
+/// 分组字段
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GroupBy {
+    Process,
+    Service,
+    Port,
+    RemoteHost,
+    State,
+}
+
+/// 分页查询
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PageQuery {
+    /// 上一页返回的扫描 ID，为空时重新扫描
+    pub scan_id: Option<String>,
+    /// 过滤表达式
+    pub filter: Option<String>,
+    /// 排序键，按顺序比较，默认按端口升序
+    pub sort: Vec<SortKey>,
+    pub offset: usize,
+    pub limit: usize,
+    /// 分组统计
+    pub group_by: Option<GroupBy>,
+    /// 只返回指定分组中的端口（需同时设置 `group_by`）
+    pub group: Option<String>,
+    /// 为本页的 TCP 监听端口附加 HTTP 探测结果
+    pub http: bool,
+}
+
+impl Default for PageQuery {
+    fn default() -> Self {
+        Self {
+            scan_id: None,
+            filter: None,
+            sort: Vec::new(),
+            offset: 0,
+            limit: DEFAULT_PAGE_LIMIT,
+            group_by: None,
+            group: None,
+            http: false,
+        }
+    }
+}
+
+/// 分组统计
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PortGroup {
+    pub key: String,
+    pub count: usize,
+}
+
+/// 一页结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PortPage {
+    /// 本次使用的扫描 ID，翻页时传回
+    pub scan_id: String,
+    /// 扫描时间（Unix 时间戳，毫秒）
+    pub scanned_at: u64,
+    /// 过滤（及分组筛选）后的总数
+    pub total: usize,
+    pub offset: usize,
+    pub items: Vec<PortInfo>,
+    /// 各分组的数量，按数量降序
+    pub groups: Option<Vec<PortGroup>>,
+}
+
+struct CachedScan {
+    id: String,
+    scanned_at: u64,
+    last_used: u64,
+    ports: Arc<Vec<PortInfo>>,
+}
+
+/// 最近扫描结果的缓存
+pub struct ScanCache {
+    entries: Mutex<VecDeque<CachedScan>>,
+    next_seq: AtomicU64,
+    capacity: usize,
+    ttl_ms: u64,
+}
+
+impl Default for ScanCache {
+    fn default() -> Self {
+        Self::new(4, 60_000)
+    }
+}
+
+impl ScanCache {
+    /// `capacity` 为保留的扫描数，`ttl_ms` 为最后一次访问后的有效期
+    pub fn new(capacity: usize, ttl_ms: u64) -> Self {
+        Self {
+            entries: Mutex::new(VecDeque::new()),
+            next_seq: AtomicU64::new(1),
+            capacity: capacity.max(1),
+            ttl_ms,
+        }
+    }
+
+    /// 按 `query` 返回一页，`scan_id` 已过期时返回 `ScanExpired`
+    pub fn page(&self, scanner: &PortScanner, query: &PageQuery) -> AppResult<PortPage> {
+        // 先解析表达式，避免无效请求触发扫描
+        let filter = PortQuery::parse(query.filter.as_deref().unwrap_or(""))?;
+        let (id, scanned_at, ports) = match &query.scan_id {
+            Some(id) => self.get(id)?,
+            None => self.insert(scanner.scan_all()?),
+        };
+        paginate(&ports, &filter, query, id, scanned_at)
+    }
+
+    /// 缓存已扫描的端口并返回第一页，`query.scan_id` 被忽略
+    pub fn page_from(&self, ports: Vec<PortInfo>, query: &PageQuery) -> AppResult<PortPage> {
+        let filter = PortQuery::parse(query.filter.as_deref().unwrap_or(""))?;
+        let (id, scanned_at, ports) = self.insert(ports);
+        paginate(&ports, &filter, query, id, scanned_at)
+    }
+
+    fn get(&self, id: &str) -> AppResult<(String, u64, Arc<Vec<PortInfo>>)> {
+        let now = now_millis();
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries.retain(|e| now.saturating_sub(e.last_used) < self.ttl_ms);
+
+        let entry = entries
+            .iter_mut()
+            .find(|e| e.id == id)
+            .ok_or_else(|| AppError::ScanExpired(id.to_string()))?;
+        entry.last_used = now;
+        Ok((entry.id.clone(), entry.scanned_at, entry.ports.clone()))
+    }
+
+    fn insert(&self, ports: Vec<PortInfo>) -> (String, u64, Arc<Vec<PortInfo>>) {
+        let now = now_millis();
+        let ports = Arc::new(ports);
+        // ID 中带上时间，重启后旧 ID 不会误命中
+        let seq = self.next_seq.fetch_add(1, AtomicOrdering::Relaxed);
+        let id = format!("{:x}-{}", now, seq);
+
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries.push_back(CachedScan {
+            id: id.clone(),
+            scanned_at: now,
+            last_used: now,
+            ports: ports.clone(),
+        });
+        while entries.len() > self.capacity {
+            entries.pop_front();
+        }
+        (id, now, ports)
+    }
+}
+
+/// 过滤、分组、排序并截取一页
+pub fn paginate(
+    ports: &[PortInfo],
+    filter: &PortQuery,
+    query: &PageQuery,
+    scan_id: String,
+    scanned_at: u64,
+) -> AppResult<PortPage> {
+    if query.group.is_some() && query.group_by.is_none() {
+        return Err(AppError::InvalidArgument(
+            "指定 group 时必须同时指定 group_by".to_string(),
+        ));
+    }
+
+    let mut matched: Vec<&PortInfo> = ports.iter().filter(|p| filter.matches(p)).collect();
+
+    let groups = query.group_by.map(|group_by| {
+        let mut counts: HashMap<String, usize> = HashMap::new();
+        for port in &matched {
+            *counts.entry(group_key(port, group_by)).or_default() += 1;
+        }
+        let mut groups: Vec<PortGroup> = counts
+            .into_iter()
+            .map(|(key, count)| PortGroup { key, count })
+            .collect();
+        groups.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.key.cmp(&b.key)));
+        groups
+    });
+
+    if let (Some(group_by), Some(group)) = (query.group_by, &query.group) {
+        matched.retain(|p| group_key(p, group_by) == *group);
+    }
+
+    let sort: &[SortKey] = if query.sort.is_empty() {
+        &[SortKey {
+            field: SortField::Port,
+            desc: false,
+        }]
+    } else {
+        &query.sort
+    };
+    matched.sort_by(|a, b| {
+        sort.iter()
+            .map(|key| {
+                let ord = compare(a, b, key.field);
+                if key.desc {
+                    ord.reverse()
+                } else {
+                    ord
+                }
+            })
+            .find(|ord| *ord != Ordering::Equal)
+            .unwrap_or(Ordering::Equal)
+    });
+
+    let limit = query.limit.clamp(1, MAX_PAGE_LIMIT);
+    let items = matched
+        .iter()
+        .skip(query.offset)
+        .take(limit)
+        .map(|p| (*p).clone())
+        .collect();
+
+    Ok(PortPage {
+        scan_id,
+        scanned_at,
+        total: matched.len(),
+        offset: query.offset,
+        items,
+        groups,
+    })
+}
+
+/// 分组键，缺失的值记为 "-"
+fn group_key(port: &PortInfo, group_by: GroupBy) -> String {
+    match group_by {
+        GroupBy::Process => port
+            .process
+            .as_ref()
+            .map(|p| p.name.clone())
+            .unwrap_or_else(|| "-".to_string()),
+        GroupBy::Service => port.service_name.clone().unwrap_or_else(|| "-".to_string()),
+        GroupBy::Port => format!("{}/{:?}", port.port, port.protocol),
+        GroupBy::RemoteHost => port.remote_addr.clone().unwrap_or_else(|| "-".to_string()),
+        GroupBy::State => port.state.name().to_string(),
+    }
+}
+
+fn compare(a: &PortInfo, b: &PortInfo, field: SortField) -> Ordering {
+    let protocol = |p: &PortInfo| p.protocol == Protocol::UDP;
+    match field {
+        SortField::Port => a.port.cmp(&b.port),
+        SortField::Protocol => protocol(a).cmp(&protocol(b)),
+        SortField::LocalAddr => a.local_addr.cmp(&b.local_addr),
+        SortField::RemoteAddr => a.remote_addr.cmp(&b.remote_addr),
+        SortField::RemotePort => a.remote_port.cmp(&b.remote_port),
+        SortField::State => a.state.name().cmp(b.state.name()),
+        SortField::Pid => a.pid.cmp(&b.pid),
+        SortField::Service => a.service_name.cmp(&b.service_name),
+        SortField::Process => match (&a.process, &b.process) {
+            // 不区分大小写，避免为每次比较分配字符串
+            (Some(a), Some(b)) => a
+                .name
+                .chars()
+                .flat_map(char::to_lowercase)
+                .cmp(b.name.chars().flat_map(char::to_lowercase)),
+            (a, b) => a.is_some().cmp(&b.is_some()),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{ConnectionState, ProcessInfo};
+    use crate::platform::fake::FakePlatform;
+
+    fn port(port: u16, process: &str, state: ConnectionState) -> PortInfo {
+        let mut info = PortInfo::new(port, Protocol::TCP, "127.0.0.1".to_string(), 1);
+        info.state = state;
+        info.process = Some(ProcessInfo::new(1, process.to_string()));
+        info
+    }
+
+    fn sample() -> Vec<PortInfo> {
+        vec![
+            port(443, "nginx", ConnectionState::Listen),
+            port(80, "nginx", ConnectionState::Listen),
+            port(5432, "Postgres", ConnectionState::Listen),
+            port(8080, "java", ConnectionState::Listen),
+            port(50000, "java", ConnectionState::Established),
+            port(50001, "java", ConnectionState::Established),
+        ]
+    }
+
+    fn paged(ports: &[PortInfo], query: &PageQuery) -> AppResult<PortPage> {
+        let filter = PortQuery::parse(query.filter.as_deref().unwrap_or("")).unwrap();
+        paginate(ports, &filter, query, "scan".to_string(), 0)
+    }
+
+    fn ports_of(page: &PortPage) -> Vec<u16> {
+        page.items.iter().map(|p| p.port).collect()
+    }
+
+    #[test]
+    fn test_default_sort_is_port_ascending() {
+        let page = paged(&sample(), &PageQuery::default()).unwrap();
+        assert_eq!(ports_of(&page), vec![80, 443, 5432, 8080, 50000, 50001]);
+        assert_eq!(page.total, 6);
+        assert!(page.groups.is_none());
+    }
+
+    #[test]
+    fn test_multi_key_sort_with_desc() {
+        let query = PageQuery {
+            sort: vec![
+                SortKey {
+                    field: SortField::State,
+                    desc: true,
+                },
+                SortKey {
+                    field: SortField::Process,
+                    desc: false,
+                },
+                SortKey {
+                    field: SortField::Port,
+                    desc: true,
+                },
+            ],
+            ..Default::default()
+        };
+        let page = paged(&sample(), &query).unwrap();
+        // listen 在 established 之后（降序），进程名不区分大小写
+        assert_eq!(ports_of(&page), vec![8080, 443, 80, 5432, 50001, 50000]);
+    }
+
+    #[test]
+    fn test_groups_sorted_by_count_then_key() {
+        let query = PageQuery {
+            group_by: Some(GroupBy::Process),
+            ..Default::default()
+        };
+        let page = paged(&sample(), &query).unwrap();
+        let groups: Vec<(String, usize)> = page
+            .groups
+            .unwrap()
+            .into_iter()
+            .map(|g| (g.key, g.count))
+            .collect();
+        assert_eq!(
+            groups,
+            vec![
+                ("java".to_string(), 3),
+                ("nginx".to_string(), 2),
+                ("Postgres".to_string(), 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_group_filter() {
+        let query = PageQuery {
+            filter: Some("state = listen".to_string()),
+            group_by: Some(GroupBy::Process),
+            group: Some("java".to_string()),
+            ..Default::default()
+        };
+        let page = paged(&sample(), &query).unwrap();
+        assert_eq!(ports_of(&page), vec![8080]);
+        assert_eq!(page.total, 1);
+        // 分组统计不受 group 筛选影响
+        assert_eq!(page.groups.unwrap().len(), 3);
+
+        let query = PageQuery {
+            group: Some("java".to_string()),
+            ..Default::default()
+        };
+        assert!(matches!(
+            paged(&sample(), &query).unwrap_err(),
+            AppError::InvalidArgument(message) if message.contains("group_by")
+        ));
+    }
+
+    #[test]
+    fn test_offset_and_limit() {
+        let query = PageQuery {
+            offset: 4,
+            limit: 10,
+            ..Default::default()
+        };
+        let page_two = paged(&sample(), &query).unwrap();
+        assert_eq!(ports_of(&page_two), vec![50000, 50001]);
+
+        let query = PageQuery {
+            offset: 100,
+            ..Default::default()
+        };
+        let past_end = paged(&sample(), &query).unwrap();
+        assert!(past_end.items.is_empty());
+        assert_eq!((past_end.total, past_end.offset), (6, 100));
+
+        let query = PageQuery {
+            limit: 0,
+            ..Default::default()
+        };
+        assert_eq!(ports_of(&paged(&sample(), &query).unwrap()), vec![80]);
+
+        let many: Vec<PortInfo> = (0..=MAX_PAGE_LIMIT as u16)
+            .map(|p| port(p, "x", ConnectionState::Listen))
+            .collect();
+        let query = PageQuery {
+            limit: usize::MAX,
+            ..Default::default()
+        };
+        let clamped = paged(&many, &query).unwrap();
+        assert_eq!(clamped.items.len(), MAX_PAGE_LIMIT);
+        assert_eq!(clamped.total, MAX_PAGE_LIMIT + 1);
+    }
+
+    fn scanner() -> PortScanner {
+        let platform = Arc::new(FakePlatform::new());
+        platform.set_ports(sample());
+        PortScanner::with_providers(platform.clone(), platform)
+    }
+
+    #[test]
+    fn test_scan_id_reuses_cached_scan() {
+        let platform = Arc::new(FakePlatform::new());
+        platform.set_ports(sample());
+        let scanner = PortScanner::with_providers(platform.clone(), platform.clone());
+        let cache = ScanCache::new(4, 60_000);
+
+        let first = cache.page(&scanner, &PageQuery::default()).unwrap();
+        platform.set_ports(Vec::new());
+
+        let query = PageQuery {
+            scan_id: Some(first.scan_id.clone()),
+            offset: 3,
+            ..Default::default()
+        };
+        let next = cache.page(&scanner, &query).unwrap();
+        assert_eq!(next.scan_id, first.scan_id);
+        assert_eq!(next.scanned_at, first.scanned_at);
+        assert_eq!(ports_of(&next), vec![8080, 50000, 50001]);
+
+        // 无 scan_id 时重新扫描
+        let fresh = cache.page(&scanner, &PageQuery::default()).unwrap();
+        assert_ne!(fresh.scan_id, first.scan_id);
+        assert_eq!(fresh.total, 0);
+    }
+
+    #[test]
+    fn test_scan_expires_after_ttl() {
+        let cache = ScanCache::new(4, 50);
+        let first = cache.page_from(sample(), &PageQuery::default()).unwrap();
+        let query = PageQuery {
+            scan_id: Some(first.scan_id.clone()),
+            ..Default::default()
+        };
+        assert!(cache.page(&scanner(), &query).is_ok());
+
+        std::thread::sleep(std::time::Duration::from_millis(80));
+        assert!(matches!(
+            cache.page(&scanner(), &query),
+            Err(AppError::ScanExpired(id)) if id == first.scan_id
+        ));
+    }
+
+    #[test]
+    fn test_capacity_evicts_oldest_scan() {
+        let cache = ScanCache::new(2, 60_000);
+        let ids: Vec<String> = (0..3)
+            .map(|_| {
+                cache
+                    .page_from(sample(), &PageQuery::default())
+                    .unwrap()
+                    .scan_id
+            })
+            .collect();
+
+        let lookup = |id: &str| {
+            let query = PageQuery {
+                scan_id: Some(id.to_string()),
+                ..Default::default()
+            };
+            cache.page(&scanner(), &query)
+        };
+        assert!(matches!(lookup(&ids[0]), Err(AppError::ScanExpired(_))));
+        assert!(lookup(&ids[1]).is_ok());
+        assert!(lookup(&ids[2]).is_ok());
+        assert!(matches!(lookup("unknown"), Err(AppError::ScanExpired(_))));
+    }
+
+    #[test]
+    fn test_invalid_filter_does_not_scan() {
+        let platform = Arc::new(FakePlatform::new());
+        let scanner = PortScanner::with_providers(platform.clone(), platform.clone());
+        let query = PageQuery {
+            filter: Some("port =".to_string()),
+            ..Default::default()
+        };
+        assert!(matches!(
+            ScanCache::default().page(&scanner, &query),
+            Err(AppError::InvalidArgument(_))
+        ));
+        assert_eq!(platform.scans(), 0);
+    }
+}
@@ -0,0 +1,513 @@
+//! 过滤表达式语法分析（递归下降）
+//!
+//! ```text
+//! expr      := and ("or" and)*
+//! and       := unary ("and" unary)*
+//! unary     := "not" unary | "(" expr ")" | predicate
+//! predicate := field op value
+//!            | field ["not"] "in" (number ".." number | "(" value ("," value)* ")")
+//!            | ("local" | "remote") class
+//! ```
+
+use super::lexer::{Token, TokenKind};
+use super::{
+    AddrClass, CompareOp, Expr, Field, FieldKind, ParseError, Predicate, Value, CLASSES, FIELDS,
+};
+use crate::models::CONNECTION_STATE_NAMES;
+
+/// 嵌套深度上限，防止恶意输入导致栈溢出
+const MAX_DEPTH: usize = 64;
+
+/// 词法单元数量上限，同样用于限制语法树深度
+const MAX_TOKENS: usize = 4096;
+
+const KEYWORDS: &[&str] = &["and", "or", "not", "in"];
+
+pub struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    depth: usize,
+}
+
+impl Parser {
+    pub fn new(tokens: Vec<Token>) -> Self {
+        Self {
+            tokens,
+            pos: 0,
+            depth: 0,
+        }
+    }
+
+    pub fn parse(mut self) -> Result<Expr, ParseError> {
+        if self.tokens.len() > MAX_TOKENS {
+            let token = &self.tokens[MAX_TOKENS];
+            return Err(self.error_at(token, format!("表达式过长，最多 {} 个词法单元", MAX_TOKENS)));
+        }
+
+        let expr = self.parse_or()?;
+        let token = self.peek();
+        if token.kind != TokenKind::Eof {
+            let hint = if token.kind == TokenKind::RParen {
+                "多余的 ')'".to_string()
+            } else {
+                format!("意外的 {}，条件之间需要 and 或 or", token.kind.describe())
+            };
+            return Err(self.error_at(token, hint));
+        }
+        Ok(expr)
+    }
+
+    fn peek(&self) -> &Token {
+        &self.tokens[self.pos]
+    }
+
+    fn next(&mut self) -> Token {
+        let token = self.tokens[self.pos].clone();
+        if token.kind != TokenKind::Eof {
+            self.pos += 1;
+        }
+        token
+    }
+
+    fn is_keyword(&self, keyword: &str) -> bool {
+        matches!(&self.peek().kind, TokenKind::Ident(name) if name == keyword)
+    }
+
+    fn error_at(&self, token: &Token, message: impl Into<String>) -> ParseError {
+        ParseError::new(message, token.offset, token.len)
+    }
+
+    fn expect(&mut self, kind: TokenKind, what: &str) -> Result<Token, ParseError> {
+        let token = self.next();
+        if token.kind == kind {
+            Ok(token)
+        } else {
+            Err(self.error_at(
+                &token,
+                format!("期望 {}，实际为 {}", what, token.kind.describe()),
+            ))
+        }
+    }
+
+    fn parse_or(&mut self) -> Result<Expr, ParseError> {
+        let mut expr = self.parse_and()?;
+        while self.is_keyword("or") {
+            self.next();
+            let rhs = self.parse_and()?;
+            expr = Expr::Or(Box::new(expr), Box::new(rhs));
+        }
+        Ok(expr)
+    }
+
+    fn parse_and(&mut self) -> Result<Expr, ParseError> {
+        let mut expr = self.parse_unary()?;
+        while self.is_keyword("and") {
+            self.next();
+            let rhs = self.parse_unary()?;
+            expr = Expr::And(Box::new(expr), Box::new(rhs));
+        }
+        Ok(expr)
+    }
+
+    fn parse_unary(&mut self) -> Result<Expr, ParseError> {
+        if self.depth >= MAX_DEPTH {
+            return Err(self.error_at(self.peek(), format!("嵌套层数超过 {}", MAX_DEPTH)));
+        }
+        self.depth += 1;
+        let result = self.parse_unary_inner();
+        self.depth -= 1;
+        result
+    }
+
+    fn parse_unary_inner(&mut self) -> Result<Expr, ParseError> {
+        if self.is_keyword("not") {
+            self.next();
+            return Ok(Expr::Not(Box::new(self.parse_unary()?)));
+        }
+
+        if self.peek().kind == TokenKind::LParen {
+            let open = self.next();
+            let expr = self.parse_or()?;
+            if self.peek().kind != TokenKind::RParen {
+                let message = format!("括号未闭合，在 {} 处期望 ')'", self.peek().kind.describe());
+                return Err(self.error_at(&open, message));
+            }
+            self.next();
+            return Ok(expr);
+        }
+
+        self.parse_predicate()
+    }
+
+    fn parse_predicate(&mut self) -> Result<Expr, ParseError> {
+        let token = self.next();
+        let field = match &token.kind {
+            TokenKind::Ident(name) => match Field::from_name(name) {
+                Some(field) => field,
+                None if KEYWORDS.contains(&name.as_str()) => {
+                    return Err(self.error_at(&token, format!("'{}' 前缺少条件", name)))
+                }
+                None => {
+                    return Err(self.error_at(
+                        &token,
+                        format!("未知字段 '{}'，可用字段: {}", name, field_names()),
+                    ))
+                }
+            },
+            TokenKind::Eof => {
+                return Err(self.error_at(
+                    &token,
+                    format!("表达式不完整，期望字段名（{}）", field_names()),
+                ))
+            }
+            other => {
+                return Err(
+                    self.error_at(&token, format!("期望字段名，实际为 {}", other.describe()))
+                )
+            }
+        };
+
+        let token = self.next();
+        match &token.kind {
+            TokenKind::Op(symbol) => {
+                let op = CompareOp::from_symbol(symbol).unwrap_or(CompareOp::Eq);
+                let allowed = field.kind().operators();
+                if !allowed.contains(&op) {
+                    let list: Vec<&str> = allowed.iter().map(CompareOp::symbol).collect();
+                    return Err(self.error_at(
+                        &token,
+                        format!(
+                            "字段 {} 不支持运算符 '{}'，可用: {}",
+                            field.name(),
+                            symbol,
+                            list.join(" ")
+                        ),
+                    ));
+                }
+                let value = self.parse_value(field)?;
+                Ok(Expr::Pred(Predicate::Compare { field, op, value }))
+            }
+            TokenKind::Ident(name) if name == "in" => self.parse_in(field).map(Expr::Pred),
+            TokenKind::Ident(name) if name == "not" && self.is_keyword("in") => {
+                self.next();
+                let pred = self.parse_in(field)?;
+                Ok(Expr::Not(Box::new(Expr::Pred(pred))))
+            }
+            TokenKind::Ident(name) if field.kind() == FieldKind::Address => {
+                match AddrClass::from_name(name) {
+                    Some(class) => Ok(Expr::Pred(Predicate::Class { field, class })),
+                    None => Err(self.error_at(
+                        &token,
+                        format!("未知地址分类 '{}'，可用: {}", name, class_names()),
+                    )),
+                }
+            }
+            TokenKind::Eof => {
+                Err(self.error_at(&token, format!("字段 {} 后缺少运算符", field.name())))
+            }
+            other => Err(self.error_at(
+                &token,
+                format!(
+                    "字段 {} 后期望运算符或 in，实际为 {}",
+                    field.name(),
+                    other.describe()
+                ),
+            )),
+        }
+    }
+
+    fn parse_in(&mut self, field: Field) -> Result<Predicate, ParseError> {
+        if self.peek().kind == TokenKind::LParen {
+            self.next();
+            let mut values = vec![self.parse_value(field)?];
+            while self.peek().kind == TokenKind::Comma {
+                self.next();
+                values.push(self.parse_value(field)?);
+            }
+            self.expect(TokenKind::RParen, "',' 或 ')'")?;
+            return Ok(Predicate::List { field, values });
+        }
+
+        if field.kind() != FieldKind::Number {
+            return Err(self.error_at(
+                self.peek(),
+                format!(
+                    "字段 {} 只支持列表形式，如 {} in (a, b)",
+                    field.name(),
+                    field.name()
+                ),
+            ));
+        }
+
+        let start_token = self.peek().clone();
+        let start = self.parse_number(field)?;
+        self.expect(TokenKind::DotDot, "'..'")?;
+        let end = self.parse_number(field)?;
+        if start > end {
+            return Err(ParseError::new(
+                format!("范围起点 {} 大于终点 {}", start, end),
+                start_token.offset,
+                self.peek().offset.saturating_sub(start_token.offset),
+            ));
+        }
+        Ok(Predicate::Range { field, start, end })
+    }
+
+    fn parse_number(&mut self, field: Field) -> Result<u64, ParseError> {
+        let token = self.next();
+        match &token.kind {
+            TokenKind::Number(n) if *n <= field.max_value() => Ok(*n),
+            TokenKind::Number(n) => Err(self.error_at(
+                &token,
+                format!(
+                    "{} 超出字段 {} 的范围（最大 {}）",
+                    n,
+                    field.name(),
+                    field.max_value()
+                ),
+            )),
+            other => Err(self.error_at(&token, format!("期望数字，实际为 {}", other.describe()))),
+        }
+    }
+
+    fn parse_value(&mut self, field: Field) -> Result<Value, ParseError> {
+        if field.kind() == FieldKind::Number {
+            return self.parse_number(field).map(Value::Number);
+        }
+
+        let token = self.next();
+        let text = match &token.kind {
+            TokenKind::Str(s) => s.clone(),
+            TokenKind::Ident(s) if !KEYWORDS.contains(&s.as_str()) => s.clone(),
+            TokenKind::Number(n) if field.kind() == FieldKind::Text => n.to_string(),
+            other => {
+                return Err(self.error_at(
+                    &token,
+                    format!(
+                        "字段 {} 期望文本值，实际为 {}",
+                        field.name(),
+                        other.describe()
+                    ),
+                ))
+            }
+        };
+
+        match field.kind() {
+            FieldKind::State => {
+                let normalized = text.to_ascii_lowercase().replace(['-', ' '], "_");
+                match CONNECTION_STATE_NAMES
+                    .iter()
+                    .find(|name| name.replace('_', "") == normalized.replace('_', ""))
+                {
+                    Some(name) => Ok(Value::Text(name.to_string())),
+                    None => Err(self.error_at(
+                        &token,
+                        format!(
+                            "未知状态 '{}'，可用: {}",
+                            text,
+                            CONNECTION_STATE_NAMES.join(", ")
+                        ),
+                    )),
+                }
+            }
+            FieldKind::Protocol => match text.to_ascii_lowercase().as_str() {
+                name @ ("tcp" | "udp") => Ok(Value::Text(name.to_string())),
+                _ => Err(self.error_at(&token, format!("未知协议 '{}'，可用: tcp, udp", text))),
+            },
+            _ => Ok(Value::Text(text)),
+        }
+    }
+}
+
+fn field_names() -> String {
+    let mut names: Vec<&str> = Vec::new();
+    for (name, field) in FIELDS {
+        if field.name() == *name {
+            names.push(name);
+        }
+    }
+    names.join(", ")
+}
+
+fn class_names() -> String {
+    CLASSES
+        .iter()
+        .map(|(name, _)| *name)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::query::parse;
+
+    fn compare(field: Field, op: CompareOp, value: Value) -> Expr {
+        Expr::Pred(Predicate::Compare { field, op, value })
+    }
+
+    fn port_eq(port: u64) -> Expr {
+        compare(Field::Port, CompareOp::Eq, Value::Number(port))
+    }
+
+    fn error(source: &str) -> ParseError {
+        parse(source).unwrap_err()
+    }
+
+    #[test]
+    fn test_precedence() {
+        let not = |e: Expr| Expr::Not(Box::new(e));
+        let and = |a: Expr, b: Expr| Expr::And(Box::new(a), Box::new(b));
+        let or = |a: Expr, b: Expr| Expr::Or(Box::new(a), Box::new(b));
+
+        assert_eq!(
+            parse("port = 1 or port = 2 and not port = 3").unwrap(),
+            or(port_eq(1), and(port_eq(2), not(port_eq(3))))
+        );
+        assert_eq!(
+            parse("(port = 1 or port = 2) and port = 3").unwrap(),
+            and(or(port_eq(1), port_eq(2)), port_eq(3))
+        );
+        assert_eq!(
+            parse("not not port = 1 and port = 2").unwrap(),
+            and(not(not(port_eq(1))), port_eq(2))
+        );
+        // 同级运算左结合
+        assert_eq!(
+            parse("port = 1 or port = 2 or port = 3").unwrap(),
+            or(or(port_eq(1), port_eq(2)), port_eq(3))
+        );
+    }
+
+    #[test]
+    fn test_ranges_and_lists() {
+        assert_eq!(
+            parse("port in 3000..4000").unwrap(),
+            Expr::Pred(Predicate::Range {
+                field: Field::Port,
+                start: 3000,
+                end: 4000
+            })
+        );
+        assert_eq!(
+            parse("port not in 1..1").unwrap(),
+            Expr::Not(Box::new(Expr::Pred(Predicate::Range {
+                field: Field::Port,
+                start: 1,
+                end: 1
+            })))
+        );
+        assert_eq!(
+            parse("state in (LISTEN, \"Time-Wait\") and proto in (udp)").unwrap(),
+            Expr::And(
+                Box::new(Expr::Pred(Predicate::List {
+                    field: Field::State,
+                    values: vec![
+                        Value::Text("listen".to_string()),
+                        Value::Text("time_wait".to_string())
+                    ]
+                })),
+                Box::new(Expr::Pred(Predicate::List {
+                    field: Field::Protocol,
+                    values: vec![Value::Text("udp".to_string())]
+                }))
+            )
+        );
+        assert_eq!(
+            parse("process = 8080").unwrap(),
+            compare(
+                Field::Process,
+                CompareOp::Eq,
+                Value::Text("8080".to_string())
+            )
+        );
+        assert_eq!(
+            parse("remote_addr private").unwrap(),
+            Expr::Pred(Predicate::Class {
+                field: Field::Remote,
+                class: AddrClass::Private
+            })
+        );
+    }
+
+    #[test]
+    fn test_error_messages_and_positions() {
+        let err = error("port in 10..5");
+        assert_eq!(err.message, "范围起点 10 大于终点 5");
+        assert_eq!((err.offset, err.len), (8, 5));
+
+        let err = error("state < listen");
+        assert_eq!(err.message, "字段 state 不支持运算符 '<'，可用: = !=");
+        assert_eq!((err.offset, err.len), (6, 1));
+
+        let err = error("prot = 1");
+        assert!(err
+            .message
+            .starts_with("未知字段 'prot'，可用字段: port, remote_port"));
+        assert_eq!((err.offset, err.len), (0, 4));
+
+        let err = error("(port = 1 or port = 2");
+        assert_eq!(err.message, "括号未闭合，在 表达式结尾 处期望 ')'");
+        assert_eq!((err.offset, err.len), (0, 1));
+
+        let err = error("port = 1)");
+        assert_eq!(err.message, "多余的 ')'");
+        assert_eq!(err.offset, 8);
+
+        let err = error("port = 1 pid = 2");
+        assert_eq!(err.message, "意外的 'pid'，条件之间需要 and 或 or");
+        assert_eq!(err.offset, 9);
+
+        let err = error("port = 70000");
+        assert_eq!(err.message, "70000 超出字段 port 的范围（最大 65535）");
+        assert_eq!(err.offset, 7);
+
+        let err = error("port = 1 and");
+        assert!(err.message.starts_with("表达式不完整"));
+        assert_eq!(err.offset, 12);
+
+        let err = error("and port = 1");
+        assert_eq!(err.message, "'and' 前缺少条件");
+
+        let err = error("state = open");
+        assert!(err.message.starts_with("未知状态 'open'"));
+        assert_eq!((err.offset, err.len), (8, 4));
+
+        let err = error("local lan");
+        assert!(err.message.starts_with("未知地址分类 'lan'"));
+
+        let err = error("process in 1..2");
+        assert!(err.message.starts_with("字段 process 只支持列表形式"));
+
+        let err = error("port");
+        assert_eq!(err.message, "字段 port 后缺少运算符");
+    }
+
+    #[test]
+    fn test_depth_limit() {
+        let nested = |depth: usize| format!("{}port = 1", "not ".repeat(depth));
+        assert!(parse(&nested(MAX_DEPTH - 1)).is_ok());
+        let err = error(&nested(MAX_DEPTH));
+        assert_eq!(err.message, format!("嵌套层数超过 {}", MAX_DEPTH));
+
+        let parens = format!("{}port = 1{}", "(".repeat(MAX_DEPTH), ")".repeat(MAX_DEPTH));
+        assert_eq!(
+            error(&parens).message,
+            format!("嵌套层数超过 {}", MAX_DEPTH)
+        );
+    }
+
+    #[test]
+    fn test_token_limit() {
+        // 每个 `or port = 1` 4 个词法单元，加上开头的条件和 Eof 超过上限
+        let long = format!("port = 1{}", " or port = 1".repeat(MAX_TOKENS / 4));
+        let err = error(&long);
+        assert_eq!(
+            err.message,
+            format!("表达式过长，最多 {} 个词法单元", MAX_TOKENS)
+        );
+
+        let fits = format!("port = 1{}", " or port = 1".repeat(MAX_TOKENS / 4 - 1));
+        assert!(parse(&fits).is_ok());
+    }
+}
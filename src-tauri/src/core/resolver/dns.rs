@@ -0,0 +1,268 @@
+//! 最小化的 DNS 查询（UDP，单个问题，递归请求）：PTR，以及用于正向确认的 A / AAAA
+
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::time::Duration;
+
+use ring::rand::{SecureRandom, SystemRandom};
+use tokio::net::UdpSocket;
+use tokio::time::timeout;
+
+const TYPE_A: u16 = 1;
+const TYPE_PTR: u16 = 12;
+const TYPE_AAAA: u16 = 28;
+const CLASS_IN: u16 = 1;
+const RCODE_NXDOMAIN: u8 = 3;
+
+/// 压缩指针最多跳转次数，防止恶意报文造成死循环
+const MAX_POINTER_JUMPS: usize = 16;
+
+/// 主机名的最大长度（不含末尾的点）
+const MAX_NAME_LEN: usize = 253;
+
+/// PTR 查询结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PtrAnswer {
+    Found {
+        name: String,
+        ttl: u32,
+    },
+    /// 记录不存在（NXDOMAIN 或没有 PTR 记录）
+    NotFound,
+}
+
+/// 反向解析域名，如 `4.3.2.1.in-addr.arpa`
+pub fn reverse_name(ip: IpAddr) -> String {
+    match ip {
+        IpAddr::V4(v4) => {
+            let [a, b, c, d] = v4.octets();
+            format!("{}.{}.{}.{}.in-addr.arpa", d, c, b, a)
+        }
+        IpAddr::V6(v6) => {
+            let mut name = String::with_capacity(72);
+            for byte in v6.octets().iter().rev() {
+                name.push_str(&format!("{:x}.{:x}.", byte & 0x0f, byte >> 4));
+            }
+            name.push_str("ip6.arpa");
+            name
+        }
+    }
+}
+
+/// 向 `server` 查询 `ip` 的 PTR 记录，超时或服务器出错时返回错误
+pub async fn query_ptr(server: SocketAddr, ip: IpAddr, limit: Duration) -> io::Result<PtrAnswer> {
+    exchange(server, &reverse_name(ip), TYPE_PTR, limit, parse_response).await
+}
+
+/// 向 `server` 查询 `name` 的 A（`ipv6` 为 true 时为 AAAA）记录，不存在时返回空列表
+pub async fn query_addresses(
+    server: SocketAddr,
+    name: &str,
+    ipv6: bool,
+    limit: Duration,
+) -> io::Result<Vec<IpAddr>> {
+    let qtype = if ipv6 { TYPE_AAAA } else { TYPE_A };
+    exchange(server, name, qtype, limit, |id, packet| {
+        parse_addresses(id, qtype, packet)
+    })
+    .await
+}
+
+/// 发送查询并等待 ID 相符的响应
+async fn exchange<T>(
+    server: SocketAddr,
+    name: &str,
+    qtype: u16,
+    limit: Duration,
+    parse: impl Fn(u16, &[u8]) -> io::Result<Option<T>>,
+) -> io::Result<T> {
+    let id = query_id()?;
+    let query = build_query(id, name, qtype);
+
+    let bind: SocketAddr = match server {
+        SocketAddr::V4(_) => (Ipv4Addr::UNSPECIFIED, 0).into(),
+        SocketAddr::V6(_) => (Ipv6Addr::UNSPECIFIED, 0).into(),
+    };
+    let socket = UdpSocket::bind(bind).await?;
+    // 已连接的 UDP 套接字只接收来自该服务器的报文
+    socket.connect(server).await?;
+    socket.send(&query).await?;
+
+    let exchange = async {
+        let mut buf = [0u8; 1500];
+        loop {
+            let len = socket.recv(&mut buf).await?;
+            // ID 不符的报文可能是迟到的旧响应或伪造报文，继续等待
+            match parse(id, &buf[..len]) {
+                Ok(Some(answer)) => return Ok(answer),
+                Ok(None) => continue,
+                Err(e) => return Err(e),
+            }
+        }
+    };
+    timeout(limit, exchange)
+        .await
+        .map_err(|_| io::Error::new(io::ErrorKind::TimedOut, "DNS 查询超时"))?
+}
+
+fn query_id() -> io::Result<u16> {
+    let mut id = [0u8; 2];
+    SystemRandom::new()
+        .fill(&mut id)
+        .map_err(|_| io::Error::other("无法生成随机数"))?;
+    Ok(u16::from_be_bytes(id))
+}
+
+/// 构造查询报文
+pub fn build_query(id: u16, name: &str, qtype: u16) -> Vec<u8> {
+    let mut packet = Vec::with_capacity(12 + name.len() + 6);
+    packet.extend_from_slice(&id.to_be_bytes());
+    // RD = 1
+    packet.extend_from_slice(&0x0100u16.to_be_bytes());
+    // QDCOUNT = 1，其余为 0
+    packet.extend_from_slice(&[0, 1, 0, 0, 0, 0, 0, 0]);
+    for label in name.split('.').filter(|l| !l.is_empty()) {
+        packet.push(label.len() as u8);
+        packet.extend_from_slice(label.as_bytes());
+    }
+    packet.push(0);
+    packet.extend_from_slice(&qtype.to_be_bytes());
+    packet.extend_from_slice(&CLASS_IN.to_be_bytes());
+    packet
+}
+
+/// 解析 PTR 响应；ID 不符或不是响应时返回 None，格式错误或服务器出错时返回错误
+///
+/// 不是合法主机名的 PTR 记录被忽略
+pub fn parse_response(id: u16, packet: &[u8]) -> io::Result<Option<PtrAnswer>> {
+    let records = parse_records(id, TYPE_PTR, packet, |rdata, _| {
+        let (name, _) = read_name(packet, rdata)?;
+        Some(is_hostname(&name).then_some(name))
+    })?;
+    Ok(records.map(|records| match records.into_iter().next() {
+        Some((name, ttl)) => PtrAnswer::Found { name, ttl },
+        None => PtrAnswer::NotFound,
+    }))
+}
+
+/// 解析 A / AAAA 响应，返回其中的地址
+fn parse_addresses(id: u16, qtype: u16, packet: &[u8]) -> io::Result<Option<Vec<IpAddr>>> {
+    let records = parse_records(id, qtype, packet, |rdata, rdlen| {
+        let bytes = &packet[rdata..rdata + rdlen];
+        let ip: IpAddr = match bytes.len() {
+            4 => Ipv4Addr::from(<[u8; 4]>::try_from(bytes).ok()?).into(),
+            16 => Ipv6Addr::from(<[u8; 16]>::try_from(bytes).ok()?).into(),
+            _ => return None,
+        };
+        Some(Some(ip))
+    })?;
+    Ok(records.map(|records| records.into_iter().map(|(ip, _)| ip).collect()))
+}
+
+/// 读取响应中类型为 `rtype` 的记录及其 TTL；ID 不符或不是响应时返回 None，
+/// NXDOMAIN 时返回空列表
+///
+/// `read` 接收记录数据的起始位置和长度，格式错误时返回 None，需要忽略该记录时返回 `Some(None)`
+fn parse_records<T>(
+    id: u16,
+    rtype: u16,
+    packet: &[u8],
+    read: impl Fn(usize, usize) -> Option<Option<T>>,
+) -> io::Result<Option<Vec<(T, u32)>>> {
+    let invalid = || io::Error::new(io::ErrorKind::InvalidData, "无效的 DNS 响应");
+
+    if packet.len() < 12 {
+        return Err(invalid());
+    }
+    let is_response = packet[2] & 0x80 != 0;
+    if u16::from_be_bytes([packet[0], packet[1]]) != id || !is_response {
+        return Ok(None);
+    }
+    match packet[3] & 0x0f {
+        0 => {}
+        RCODE_NXDOMAIN => return Ok(Some(Vec::new())),
+        rcode => {
+            return Err(io::Error::other(format!("DNS 服务器返回错误码 {}", rcode)));
+        }
+    }
+
+    let questions = u16::from_be_bytes([packet[4], packet[5]]);
+    let answers = u16::from_be_bytes([packet[6], packet[7]]);
+
+    let mut pos = 12;
+    for _ in 0..questions {
+        let (_, next) = read_name(packet, pos).ok_or_else(invalid)?;
+        pos = next + 4;
+    }
+
+    let mut records = Vec::new();
+    for _ in 0..answers {
+        let (_, next) = read_name(packet, pos).ok_or_else(invalid)?;
+        let header = packet.get(next..next + 10).ok_or_else(invalid)?;
+        let ttl = u32::from_be_bytes([header[4], header[5], header[6], header[7]]);
+        let rdlen = u16::from_be_bytes([header[8], header[9]]) as usize;
+        let rdata = next + 10;
+        if packet.len() < rdata + rdlen {
+            return Err(invalid());
+        }
+        // 跳过 CNAME 等其他记录（RFC 2317 无类别委派会返回 CNAME）
+        if u16::from_be_bytes([header[0], header[1]]) == rtype {
+            if let Some(value) = read(rdata, rdlen).ok_or_else(invalid)? {
+                records.push((value, ttl));
+            }
+        }
+        pos = rdata + rdlen;
+    }
+
+    Ok(Some(records))
+}
+
+/// 是否为合法主机名：标签只含字母、数字和连字符（LDH），连字符不在首尾
+pub fn is_hostname(name: &str) -> bool {
+    !name.is_empty()
+        && name.len() <= MAX_NAME_LEN
+        && name.split('.').all(|label| {
+            !label.is_empty()
+                && label.len() <= 63
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label
+                    .bytes()
+                    .all(|b| b.is_ascii_alphanumeric() || b == b'-')
+        })
+}
+
+/// 读取域名（支持压缩指针），返回域名和名字之后的位置
+fn read_name(packet: &[u8], start: usize) -> Option<(String, usize)> {
+    let mut labels: Vec<String> = Vec::new();
+    let mut pos = start;
+    let mut end = None;
+    let mut jumps = 0;
+
+    loop {
+        let len = *packet.get(pos)? as usize;
+        match len {
+            0 => {
+                end.get_or_insert(pos + 1);
+                break;
+            }
+            l if l & 0xc0 == 0xc0 => {
+                let offset = ((l & 0x3f) << 8) | *packet.get(pos + 1)? as usize;
+                end.get_or_insert(pos + 2);
+                jumps += 1;
+                if jumps > MAX_POINTER_JUMPS {
+                    return None;
+                }
+                pos = offset;
+            }
+            l if l & 0xc0 == 0 => {
+                let label = packet.get(pos + 1..pos + 1 + l)?;
+                labels.push(String::from_utf8_lossy(label).into_owned());
+                pos += 1 + l;
+            }
+            _ => return None,
+        }
+    }
+
+    Some((labels.join("."), end?))
+}
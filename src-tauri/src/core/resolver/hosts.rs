@@ -0,0 +1,81 @@
+//! hosts 文件
+
+use std::collections::HashMap;
+use std::fs;
+use std::net::IpAddr;
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+use crate::utils::net::parse_addr;
+
+/// hosts 文件内容，文件修改后重新读取
+#[derive(Debug, Default)]
+pub struct HostsFile {
+    path: Option<PathBuf>,
+    modified: Option<SystemTime>,
+    entries: HashMap<IpAddr, String>,
+}
+
+impl HostsFile {
+    /// `path` 为 None 时使用系统 hosts 文件
+    pub fn new(path: Option<PathBuf>) -> Self {
+        Self {
+            path: path.or_else(system_hosts_path),
+            modified: None,
+            entries: HashMap::new(),
+        }
+    }
+
+    /// 文件修改时间变化时重新读取，文件不存在时清空
+    pub fn refresh(&mut self) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        let modified = fs::metadata(path).and_then(|m| m.modified()).ok();
+        if modified.is_some() && modified == self.modified {
+            return;
+        }
+        self.modified = modified;
+        self.entries = fs::read_to_string(path)
+            .map(|content| parse_hosts(&content))
+            .unwrap_or_default();
+    }
+
+    /// 地址对应的第一个主机名
+    pub fn lookup(&self, ip: IpAddr) -> Option<&str> {
+        self.entries.get(&ip.to_canonical()).map(String::as_str)
+    }
+}
+
+/// 解析 hosts 文件，同一地址出现多次时取第一个主机名
+pub fn parse_hosts(content: &str) -> HashMap<IpAddr, String> {
+    let mut entries = HashMap::new();
+    for line in content.lines() {
+        let line = line.split('#').next().unwrap_or("");
+        let mut fields = line.split_whitespace();
+        let (Some(addr), Some(name)) = (fields.next(), fields.next()) else {
+            continue;
+        };
+        if let Some(ip) = parse_addr(addr) {
+            entries
+                .entry(ip.to_canonical())
+                .or_insert_with(|| name.to_string());
+        }
+    }
+    entries
+}
+
+/// 系统 hosts 文件路径
+pub fn system_hosts_path() -> Option<PathBuf> {
+    #[cfg(windows)]
+    {
+        std::env::var_os("SystemRoot")
+            .map(PathBuf::from)
+            .map(|root| root.join(r"System32\drivers\etc\hosts"))
+    }
+
+    #[cfg(not(windows))]
+    {
+        Some(PathBuf::from("/etc/hosts"))
+    }
+}
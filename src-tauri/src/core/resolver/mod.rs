@@ -0,0 +1,631 @@
+//! 远程地址的主机名解析
+//!
+//! 扫描时只从 hosts 文件和缓存填充 `remote_hostname`，缓存未命中的地址放入有界
+//! 队列，由后台按配置的并发数做反向 DNS（PTR）查询，结果在之后的扫描中出现。
+//! 队列已满时丢弃请求，下次扫描再排队。关闭反向 DNS 后只使用 hosts 文件
+//!
+//! PTR 记录由地址的所有者控制，可以随意声称任何名字。只接受合法主机名，并且
+//! 该名字的 A / AAAA 记录必须包含原地址（正向确认），否则视为没有主机名
+
+mod dns;
+mod hosts;
+
+use std::collections::{HashMap, HashSet};
+use std::net::{IpAddr, SocketAddr};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, MutexGuard, OnceLock, RwLock, Weak};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::{mpsc, Semaphore};
+
+use crate::models::PortInfo;
+use crate::utils::net::parse_addr;
+use crate::utils::time::now_millis;
+
+pub use dns::{query_addresses, query_ptr, reverse_name, PtrAnswer};
+pub use hosts::{parse_hosts, HostsFile};
+
+/// 缓存的最大条目数，超出时先清理过期条目，仍超出则清空
+const MAX_CACHED: usize = 4096;
+
+/// 解析成功的最短缓存时长（毫秒），避免 TTL 很短的记录反复查询
+const MIN_TTL_MS: u64 = 60 * 1000;
+
+/// 主机名解析配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ResolverConfig {
+    /// 是否做反向 DNS 查询，隔离网络中可关闭
+    pub reverse_dns: bool,
+    /// DNS 服务器，为空时使用系统配置
+    pub nameservers: Vec<SocketAddr>,
+    /// hosts 文件，为 None 时使用系统 hosts 文件
+    pub hosts_file: Option<PathBuf>,
+    /// 同时进行的查询数
+    pub workers: usize,
+    /// 等待查询的地址数上限
+    pub queue_capacity: usize,
+    /// 单个 DNS 服务器的查询超时（毫秒）
+    pub timeout_ms: u64,
+    /// 解析成功的缓存时长上限（毫秒），记录 TTL 更短时按 TTL
+    pub ttl_ms: u64,
+    /// 解析失败或没有记录时的缓存时长（毫秒）
+    pub negative_ttl_ms: u64,
+}
+
+impl Default for ResolverConfig {
+    fn default() -> Self {
+        Self {
+            reverse_dns: true,
+            nameservers: Vec::new(),
+            hosts_file: None,
+            workers: 4,
+            queue_capacity: 256,
+            timeout_ms: 2000,
+            ttl_ms: 60 * 60 * 1000,
+            negative_ttl_ms: 5 * 60 * 1000,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct CachedName {
+    name: Option<String>,
+    expires_at: u64,
+}
+
+struct Inner {
+    config: RwLock<ResolverConfig>,
+    hosts: Mutex<HostsFile>,
+    cache: Mutex<HashMap<IpAddr, CachedName>>,
+    /// 已排队或正在查询的地址
+    pending: Mutex<HashSet<IpAddr>>,
+    /// 查询队列，首次排队时在当前 tokio 运行时上启动
+    queue: Mutex<Option<mpsc::Sender<IpAddr>>>,
+}
+
+/// 主机名解析器，克隆后共享缓存和查询队列
+#[derive(Clone)]
+pub struct HostnameResolver {
+    inner: Arc<Inner>,
+}
+
+/// 全局解析器，扫描结果由它填充主机名
+pub fn hostname_resolver() -> &'static HostnameResolver {
+    static RESOLVER: OnceLock<HostnameResolver> = OnceLock::new();
+    RESOLVER.get_or_init(|| HostnameResolver::new(ResolverConfig::default()))
+}
+
+impl HostnameResolver {
+    pub fn new(config: ResolverConfig) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                hosts: Mutex::new(HostsFile::new(config.hosts_file.clone())),
+                config: RwLock::new(config),
+                cache: Mutex::new(HashMap::new()),
+                pending: Mutex::new(HashSet::new()),
+                queue: Mutex::new(None),
+            }),
+        }
+    }
+
+    /// 当前配置
+    pub fn config(&self) -> ResolverConfig {
+        self.inner
+            .config
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// 替换配置并清空缓存，查询队列在下次排队时按新配置重建
+    pub fn configure(&self, config: ResolverConfig) {
+        *lock(&self.inner.hosts) = HostsFile::new(config.hosts_file.clone());
+        *self.inner.config.write().unwrap_or_else(|e| e.into_inner()) = config;
+        lock(&self.inner.cache).clear();
+        *lock(&self.inner.queue) = None;
+    }
+
+    /// 开启或关闭反向 DNS 查询
+    pub fn set_reverse_dns(&self, enabled: bool) {
+        self.configure(ResolverConfig {
+            reverse_dns: enabled,
+            ..self.config()
+        });
+    }
+
+    /// 从 hosts 文件和缓存填充 `remote_hostname`，未命中的地址排队查询，不等待结果
+    pub fn annotate(&self, ports: &mut [PortInfo]) {
+        let reverse_dns = self.config().reverse_dns;
+        let now = now_millis();
+        let mut hosts = lock(&self.inner.hosts);
+        hosts.refresh();
+
+        for port in ports.iter_mut() {
+            let Some(ip) = remote_ip(port) else {
+                continue;
+            };
+            if let Some(name) = hosts.lookup(ip) {
+                port.remote_hostname = Some(name.to_string());
+                continue;
+            }
+            if !reverse_dns || ip.is_loopback() {
+                continue;
+            }
+            match self.cached(ip, now) {
+                Some(name) => port.remote_hostname = name,
+                None => self.enqueue(ip),
+            }
+        }
+    }
+
+    /// 解析单个地址并等待结果，查询结果写入缓存
+    pub async fn resolve(&self, ip: IpAddr) -> Option<String> {
+        let ip = ip.to_canonical();
+        let config = self.config();
+        {
+            let mut hosts = lock(&self.inner.hosts);
+            hosts.refresh();
+            if let Some(name) = hosts.lookup(ip) {
+                return Some(name.to_string());
+            }
+        }
+        if !config.reverse_dns || ip.is_loopback() || ip.is_unspecified() {
+            return None;
+        }
+        if let Some(name) = self.cached(ip, now_millis()) {
+            return name;
+        }
+
+        let servers = if config.nameservers.is_empty() {
+            system_nameservers()
+        } else {
+            config.nameservers.clone()
+        };
+        let limit = Duration::from_millis(config.timeout_ms);
+        let mut answer = None;
+        for server in &servers {
+            match query_ptr(*server, ip, limit).await {
+                Ok(result) => {
+                    answer = Some(result);
+                    break;
+                }
+                Err(e) => tracing::debug!("PTR lookup for {} via {} failed: {}", ip, server, e),
+            }
+        }
+
+        // 所有服务器都失败或正向确认失败时同样按否定结果缓存，避免反复查询
+        let (name, ttl_ms) = match answer {
+            Some(PtrAnswer::Found { name, ttl }) if confirms(&servers, &name, ip, limit).await => {
+                let ttl_ms = (ttl as u64 * 1000).clamp(MIN_TTL_MS, config.ttl_ms.max(MIN_TTL_MS));
+                (Some(name), ttl_ms)
+            }
+            _ => (None, config.negative_ttl_ms),
+        };
+        self.store(ip, name.clone(), now_millis() + ttl_ms);
+        name
+    }
+
+    /// 缓存中未过期的结果，外层 None 表示未命中
+    fn cached(&self, ip: IpAddr, now: u64) -> Option<Option<String>> {
+        lock(&self.inner.cache)
+            .get(&ip)
+            .filter(|c| c.expires_at > now)
+            .map(|c| c.name.clone())
+    }
+
+    fn store(&self, ip: IpAddr, name: Option<String>, expires_at: u64) {
+        let mut cache = lock(&self.inner.cache);
+        if cache.len() >= MAX_CACHED {
+            let now = now_millis();
+            cache.retain(|_, c| c.expires_at > now);
+            if cache.len() >= MAX_CACHED {
+                cache.clear();
+            }
+        }
+        cache.insert(ip, CachedName { name, expires_at });
+    }
+
+    fn enqueue(&self, ip: IpAddr) {
+        if !lock(&self.inner.pending).insert(ip) {
+            return;
+        }
+        let queued = self
+            .sender()
+            .is_some_and(|sender| sender.try_send(ip).is_ok());
+        if !queued {
+            lock(&self.inner.pending).remove(&ip);
+        }
+    }
+
+    /// 查询队列，首次调用时启动分发任务；不在 tokio 运行时中时返回 None
+    fn sender(&self) -> Option<mpsc::Sender<IpAddr>> {
+        let mut queue = lock(&self.inner.queue);
+        if let Some(sender) = queue.as_ref() {
+            return Some(sender.clone());
+        }
+
+        let runtime = tokio::runtime::Handle::try_current().ok()?;
+        let config = self.config();
+        let (tx, rx) = mpsc::channel(config.queue_capacity.max(1));
+        let workers = Arc::new(Semaphore::new(config.workers.max(1)));
+        runtime.spawn(dispatch(Arc::downgrade(&self.inner), rx, workers));
+        *queue = Some(tx.clone());
+        Some(tx)
+    }
+}
+
+/// 从队列取出地址，在并发上限内逐个查询；解析器释放或队列被替换后退出
+async fn dispatch(inner: Weak<Inner>, mut rx: mpsc::Receiver<IpAddr>, workers: Arc<Semaphore>) {
+    while let Some(ip) = rx.recv().await {
+        let Ok(permit) = workers.clone().acquire_owned().await else {
+            break;
+        };
+        let Some(inner) = inner.upgrade() else {
+            break;
+        };
+        tokio::spawn(async move {
+            let resolver = HostnameResolver { inner };
+            resolver.resolve(ip).await;
+            lock(&resolver.inner.pending).remove(&ip);
+            drop(permit);
+        });
+    }
+}
+
+/// 正向确认：`name` 的 A / AAAA 记录包含 `ip`
+async fn confirms(servers: &[SocketAddr], name: &str, ip: IpAddr, limit: Duration) -> bool {
+    for server in servers {
+        match query_addresses(*server, name, ip.is_ipv6(), limit).await {
+            Ok(addresses) => {
+                let confirmed = addresses.iter().any(|addr| addr.to_canonical() == ip);
+                if !confirmed {
+                    tracing::debug!("PTR name {} for {} is not forward-confirmed", name, ip);
+                }
+                return confirmed;
+            }
+            Err(e) => tracing::debug!("Address lookup for {} via {} failed: {}", name, server, e),
+        }
+    }
+    false
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn remote_ip(port: &PortInfo) -> Option<IpAddr> {
+    let ip = parse_addr(port.remote_addr.as_deref()?)?.to_canonical();
+    (!ip.is_unspecified()).then_some(ip)
+}
+
+/// 系统配置的 DNS 服务器
+fn system_nameservers() -> Vec<SocketAddr> {
+    #[cfg(windows)]
+    {
+        match crate::platform::windows::dns_servers() {
+            Ok(servers) => servers.into_iter().map(|ip| (ip, 53).into()).collect(),
+            Err(e) => {
+                tracing::warn!("Failed to read DNS servers: {}", e);
+                Vec::new()
+            }
+        }
+    }
+
+    #[cfg(not(windows))]
+    {
+        std::fs::read_to_string("/etc/resolv.conf")
+            .map(|content| parse_resolv_conf(&content))
+            .unwrap_or_default()
+    }
+}
+
+/// 解析 resolv.conf 中的 `nameserver` 行
+pub fn parse_resolv_conf(content: &str) -> Vec<SocketAddr> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            match (fields.next(), fields.next()) {
+                (Some("nameserver"), Some(addr)) => parse_addr(addr),
+                _ => None,
+            }
+        })
+        .map(|ip| (ip, 53).into())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use tokio::net::UdpSocket;
+
+    /// 本地 DNS 桩服务器：`records` 中的名字按查询类型返回 PTR 或 A / AAAA，其余返回 NXDOMAIN
+    async fn stub_server(records: &[(&str, &str)]) -> (SocketAddr, Arc<AtomicUsize>) {
+        let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let addr = socket.local_addr().unwrap();
+        let records: HashMap<String, String> = records
+            .iter()
+            .map(|(q, a)| (q.to_string(), a.to_string()))
+            .collect();
+        let queries = Arc::new(AtomicUsize::new(0));
+        let counter = queries.clone();
+
+        tokio::spawn(async move {
+            let mut buf = [0u8; 512];
+            while let Ok((len, peer)) = socket.recv_from(&mut buf).await {
+                counter.fetch_add(1, Ordering::SeqCst);
+                let query = &buf[..len];
+
+                let mut labels = Vec::new();
+                let mut pos = 12;
+                while query[pos] != 0 {
+                    let l = query[pos] as usize;
+                    labels.push(String::from_utf8_lossy(&query[pos + 1..pos + 1 + l]).into_owned());
+                    pos += 1 + l;
+                }
+                let qtype = u16::from_be_bytes([query[pos + 1], query[pos + 2]]);
+                let question_end = pos + 5;
+                let answer = records.get(&labels.join(".")).and_then(|value| {
+                    match (qtype, value.parse::<IpAddr>()) {
+                        (1, Ok(IpAddr::V4(v4))) => Some(v4.octets().to_vec()),
+                        (28, Ok(IpAddr::V6(v6))) => Some(v6.octets().to_vec()),
+                        (12, Err(_)) => {
+                            let mut rdata = Vec::new();
+                            for label in value.split('.') {
+                                rdata.push(label.len() as u8);
+                                rdata.extend_from_slice(label.as_bytes());
+                            }
+                            rdata.push(0);
+                            Some(rdata)
+                        }
+                        _ => None,
+                    }
+                });
+
+                let mut reply = query[..2].to_vec();
+                reply.extend_from_slice(if answer.is_some() {
+                    &[0x81, 0x80]
+                } else {
+                    &[0x81, 0x83]
+                });
+                reply.extend_from_slice(&[0, 1, 0, answer.is_some() as u8, 0, 0, 0, 0]);
+                reply.extend_from_slice(&query[12..question_end]);
+                if let Some(rdata) = answer {
+                    // 名字用压缩指针指向问题部分
+                    reply.extend_from_slice(&[0xc0, 0x0c]);
+                    reply.extend_from_slice(&qtype.to_be_bytes());
+                    reply.extend_from_slice(&[0, 1, 0, 0, 0x01, 0x2c]);
+                    reply.extend_from_slice(&(rdata.len() as u16).to_be_bytes());
+                    reply.extend_from_slice(&rdata);
+                }
+                let _ = socket.send_to(&reply, peer).await;
+            }
+        });
+
+        (addr, queries)
+    }
+
+    fn test_config(server: SocketAddr, hosts_file: PathBuf) -> ResolverConfig {
+        ResolverConfig {
+            nameservers: vec![server],
+            hosts_file: Some(hosts_file),
+            timeout_ms: 500,
+            ..ResolverConfig::default()
+        }
+    }
+
+    fn remote(addr: &str) -> PortInfo {
+        let mut port = PortInfo::new(
+            50000,
+            crate::models::Protocol::TCP,
+            "10.0.0.2".to_string(),
+            1,
+        );
+        port.remote_addr = Some(addr.to_string());
+        port.remote_port = Some(443);
+        port
+    }
+
+    fn hosts_file(name: &str, content: &str) -> PathBuf {
+        let path =
+            std::env::temp_dir().join(format!("port-detection-{}-{}", name, std::process::id()));
+        std::fs::write(&path, content).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_reverse_name() {
+        assert_eq!(
+            reverse_name("192.0.2.10".parse().unwrap()),
+            "10.2.0.192.in-addr.arpa"
+        );
+        assert!(reverse_name("2001:db8::1".parse().unwrap()).starts_with(
+            "1.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.8.b.d.0.1.0.0.2.ip6.arpa"
+        ));
+    }
+
+    #[test]
+    fn test_parse_resolv_conf() {
+        let servers =
+            parse_resolv_conf("# comment\nnameserver 10.0.0.1\nsearch lan\nnameserver ::1\n");
+        assert_eq!(
+            servers,
+            vec![
+                "10.0.0.1:53".parse::<SocketAddr>().unwrap(),
+                "[::1]:53".parse().unwrap()
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_resolve_with_stub_server() {
+        let (server, queries) = stub_server(&[
+            ("7.100.51.198.in-addr.arpa", "web.example.test"),
+            ("web.example.test", "198.51.100.7"),
+            (
+                "1.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.8.b.d.0.1.0.0.2.ip6.arpa",
+                "v6.example.test",
+            ),
+            ("v6.example.test", "2001:db8::1"),
+        ])
+        .await;
+        let resolver = HostnameResolver::new(test_config(server, hosts_file("resolve", "")));
+
+        let ip: IpAddr = "198.51.100.7".parse().unwrap();
+        assert_eq!(
+            resolver.resolve(ip).await.as_deref(),
+            Some("web.example.test")
+        );
+        assert_eq!(
+            resolver
+                .resolve("2001:db8::1".parse().unwrap())
+                .await
+                .as_deref(),
+            Some("v6.example.test")
+        );
+        assert_eq!(
+            resolver.resolve("198.51.100.8".parse().unwrap()).await,
+            None
+        );
+        assert_eq!(queries.load(Ordering::SeqCst), 5);
+
+        // 成功和否定结果都走缓存
+        assert_eq!(
+            resolver.resolve(ip).await.as_deref(),
+            Some("web.example.test")
+        );
+        assert_eq!(
+            resolver.resolve("198.51.100.8".parse().unwrap()).await,
+            None
+        );
+        assert_eq!(queries.load(Ordering::SeqCst), 5);
+    }
+
+    #[tokio::test]
+    async fn test_annotate_does_not_wait() {
+        let (server, _) = stub_server(&[
+            ("7.100.51.198.in-addr.arpa", "web.example.test"),
+            ("web.example.test", "198.51.100.7"),
+        ])
+        .await;
+        let resolver = HostnameResolver::new(test_config(server, hosts_file("annotate", "")));
+
+        let mut ports = vec![remote("198.51.100.7")];
+        resolver.annotate(&mut ports);
+        assert_eq!(ports[0].remote_hostname, None);
+
+        for _ in 0..50 {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            resolver.annotate(&mut ports);
+            if ports[0].remote_hostname.is_some() {
+                break;
+            }
+        }
+        assert_eq!(
+            ports[0].remote_hostname.as_deref(),
+            Some("web.example.test")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_hosts_file_without_reverse_dns() {
+        let (server, queries) =
+            stub_server(&[("7.100.51.198.in-addr.arpa", "web.example.test")]).await;
+        let hosts = hosts_file(
+            "hosts",
+            "# local\n198.51.100.9  db.internal db\n::ffff:198.51.100.10 cache\n",
+        );
+        let resolver = HostnameResolver::new(ResolverConfig {
+            reverse_dns: false,
+            ..test_config(server, hosts)
+        });
+
+        let mut ports = vec![
+            remote("198.51.100.9"),
+            remote("198.51.100.10"),
+            remote("198.51.100.7"),
+        ];
+        resolver.annotate(&mut ports);
+        assert_eq!(ports[0].remote_hostname.as_deref(), Some("db.internal"));
+        assert_eq!(ports[1].remote_hostname.as_deref(), Some("cache"));
+        assert_eq!(
+            resolver.resolve("198.51.100.7".parse().unwrap()).await,
+            None
+        );
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        resolver.annotate(&mut ports);
+        assert_eq!(ports[2].remote_hostname, None);
+        assert_eq!(queries.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn test_unconfirmed_ptr_ignored() {
+        let (server, queries) = stub_server(&[
+            // 声称是别人的名字，正向记录指向其它地址
+            ("9.100.51.198.in-addr.arpa", "bank.example.test"),
+            ("bank.example.test", "203.0.113.5"),
+            // 没有正向记录
+            ("10.100.51.198.in-addr.arpa", "orphan.example.test"),
+        ])
+        .await;
+        let resolver = HostnameResolver::new(test_config(server, hosts_file("confirm", "")));
+
+        assert_eq!(
+            resolver.resolve("198.51.100.9".parse().unwrap()).await,
+            None
+        );
+        assert_eq!(
+            resolver.resolve("198.51.100.10".parse().unwrap()).await,
+            None
+        );
+        assert_eq!(queries.load(Ordering::SeqCst), 4);
+
+        // 确认失败同样缓存
+        assert_eq!(
+            resolver.resolve("198.51.100.9".parse().unwrap()).await,
+            None
+        );
+        assert_eq!(queries.load(Ordering::SeqCst), 4);
+    }
+
+    #[tokio::test]
+    async fn test_invalid_ptr_names_rejected() {
+        let (server, queries) = stub_server(&[
+            ("11.100.51.198.in-addr.arpa", "evil\x1b[2J.example.test"),
+            ("12.100.51.198.in-addr.arpa", "<script>.example.test"),
+        ])
+        .await;
+        let resolver = HostnameResolver::new(test_config(server, hosts_file("invalid", "")));
+
+        assert_eq!(
+            resolver.resolve("198.51.100.11".parse().unwrap()).await,
+            None
+        );
+        assert_eq!(
+            resolver.resolve("198.51.100.12".parse().unwrap()).await,
+            None
+        );
+        // 名字不合法时不做正向查询
+        assert_eq!(queries.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_is_hostname() {
+        assert!(dns::is_hostname("web-1.example.test"));
+        assert!(dns::is_hostname("1e100.net"));
+        assert!(!dns::is_hostname(""));
+        assert!(!dns::is_hostname("a..b"));
+        assert!(!dns::is_hostname("-web.example.test"));
+        assert!(!dns::is_hostname("web-.example.test"));
+        assert!(!dns::is_hostname("under_score.example.test"));
+        assert!(!dns::is_hostname("bell\x07.test"));
+        assert!(!dns::is_hostname("ex\u{0430}mple.test"));
+        assert!(!dns::is_hostname(&"a".repeat(64)));
+        assert!(!dns::is_hostname(&["a"; 128].join(".")));
+    }
+}
@@ -0,0 +1,263 @@
+//! 本地套接字输出
+//!
+//! Unix 上监听 Unix 域套接字，Windows 上监听命名管道。
+//! 客户端连接后先收到当前快照，之后按行接收 NDJSON 格式的事件
+
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+use tokio::sync::mpsc;
+
+use super::{ClientFanout, ClientSubscription, EventSink};
+use crate::models::{AppError, AppResult, MonitorEvent};
+
+/// 本地套接字输出端
+pub struct LocalSocketSink {
+    path: String,
+    #[cfg(unix)]
+    listener: std::os::unix::net::UnixListener,
+}
+
+impl LocalSocketSink {
+    /// 默认监听路径
+    pub fn default_path() -> String {
+        #[cfg(unix)]
+        {
+            std::env::temp_dir()
+                .join("port-detection-events.sock")
+                .display()
+                .to_string()
+        }
+
+        #[cfg(windows)]
+        {
+            r"\\.\pipe\port-detection-events".to_string()
+        }
+    }
+
+    /// 绑定监听地址，残留的套接字文件会被替换
+    ///
+    /// 套接字先在新建的 0700 私有目录中绑定并设为 0600，再移动到目标路径，
+    /// 避免在共享目录中出现短暂可被其他用户连接的窗口
+    #[cfg(unix)]
+    pub fn bind(path: &str) -> AppResult<Self> {
+        use std::os::unix::fs::{DirBuilderExt, FileTypeExt, PermissionsExt};
+        use std::path::Path;
+        use std::sync::atomic::{AtomicU64, Ordering};
+
+        static NEXT_STAGING: AtomicU64 = AtomicU64::new(0);
+
+        if let Ok(meta) = std::fs::symlink_metadata(path) {
+            if !meta.file_type().is_socket() {
+                return Err(AppError::InvalidArgument(format!("{} 已存在且不是套接字", path)));
+            }
+        }
+
+        let parent = Path::new(path)
+            .parent()
+            .filter(|p| !p.as_os_str().is_empty())
+            .unwrap_or(Path::new("."));
+        let staging = parent.join(format!(
+            ".port-detection-bind-{}-{}",
+            std::process::id(),
+            NEXT_STAGING.fetch_add(1, Ordering::Relaxed)
+        ));
+        // 目录已存在时创建失败，不会使用他人预先创建的目录
+        std::fs::DirBuilder::new()
+            .mode(0o700)
+            .create(&staging)
+            .map_err(|e| AppError::Io(format!("{}: {}", staging.display(), e)))?;
+
+        let staged = staging.join("events.sock");
+        let bound = std::os::unix::net::UnixListener::bind(&staged)
+            .and_then(|listener| {
+                // 事件中包含进程信息，仅允许当前用户连接
+                std::fs::set_permissions(&staged, std::fs::Permissions::from_mode(0o600))?;
+                std::fs::rename(&staged, path)?;
+                Ok(listener)
+            })
+            .map_err(|e| AppError::Io(format!("{}: {}", path, e)));
+        let _ = std::fs::remove_file(&staged);
+        let _ = std::fs::remove_dir(&staging);
+
+        let listener = bound?;
+        listener
+            .set_nonblocking(true)
+            .map_err(|e| AppError::Io(e.to_string()))?;
+
+        Ok(Self {
+            path: path.to_string(),
+            listener,
+        })
+    }
+
+    /// 校验管道名称，管道实例在启动时创建
+    #[cfg(windows)]
+    pub fn bind(path: &str) -> AppResult<Self> {
+        if !path.starts_with(r"\\.\pipe\") {
+            return Err(AppError::InvalidArgument(format!(
+                "命名管道须以 \\\\.\\pipe\\ 开头: {}",
+                path
+            )));
+        }
+        Ok(Self {
+            path: path.to_string(),
+        })
+    }
+}
+
+impl EventSink for LocalSocketSink {
+    fn kind(&self) -> &'static str {
+        "local_socket"
+    }
+
+    fn describe(&self) -> String {
+        self.path.clone()
+    }
+
+    #[cfg(unix)]
+    fn start(self: Box<Self>, mut rx: mpsc::Receiver<MonitorEvent>) -> AppResult<()> {
+        let listener = tokio::net::UnixListener::from_std(self.listener)
+            .map_err(|e| AppError::Io(e.to_string()))?;
+        let path = self.path;
+        let fanout = ClientFanout::new();
+
+        tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    event = rx.recv() => match event {
+                        Some(event) => fanout.publish(event),
+                        None => break,
+                    },
+                    accepted = listener.accept() => match accepted {
+                        Ok((stream, _)) => {
+                            tokio::spawn(serve_client(stream, fanout.subscribe()));
+                        }
+                        Err(e) => tracing::warn!("Local socket accept failed: {}", e),
+                    },
+                }
+            }
+
+            fanout.close();
+            let _ = std::fs::remove_file(&path);
+        });
+
+        Ok(())
+    }
+
+    #[cfg(windows)]
+    fn start(self: Box<Self>, mut rx: mpsc::Receiver<MonitorEvent>) -> AppResult<()> {
+        use crate::platform::windows::{create_pipe, current_user_sid};
+
+        // 事件中包含进程信息，与 Unix 上的 0600 一致，仅允许 SYSTEM 和当前用户连接
+        let sid = current_user_sid().map_err(|e| AppError::Io(e.to_string()))?;
+        let sddl = format!("D:P(A;;GA;;;SY)(A;;GA;;;{})", sid);
+
+        let path = self.path;
+        let mut server = create_pipe(&path, true, &sddl)
+            .map_err(|e| AppError::Io(format!("{}: {}", path, e)))?;
+        let fanout = ClientFanout::new();
+
+        tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    event = rx.recv() => match event {
+                        Some(event) => fanout.publish(event),
+                        None => break,
+                    },
+                    connected = server.connect() => {
+                        if let Err(e) = connected {
+                            tracing::warn!("Named pipe connect failed: {}", e);
+                            continue;
+                        }
+                        // 已连接的实例交给客户端，再创建新实例等待下一个连接
+                        let next = match create_pipe(&path, false, &sddl) {
+                            Ok(next) => next,
+                            Err(e) => {
+                                tracing::error!("Failed to create named pipe {}: {}", path, e);
+                                break;
+                            }
+                        };
+                        let client = std::mem::replace(&mut server, next);
+                        tokio::spawn(serve_client(client, fanout.subscribe()));
+                    },
+                }
+            }
+
+            fanout.close();
+        });
+
+        Ok(())
+    }
+}
+
+/// 向客户端逐行写入事件，写入失败（客户端断开）时退出
+async fn serve_client<W>(mut writer: W, mut subscription: ClientSubscription)
+where
+    W: AsyncWrite + Unpin,
+{
+    while let Some(event) = subscription.next().await {
+        let mut line = match serde_json::to_string(&event) {
+            Ok(line) => line,
+            Err(e) => {
+                tracing::error!("Failed to serialize monitor event: {}", e);
+                continue;
+            }
+        };
+        line.push('\n');
+
+        if writer.write_all(line.as_bytes()).await.is_err() {
+            break;
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use std::os::unix::fs::PermissionsExt;
+
+    use tokio::io::{AsyncBufReadExt, BufReader};
+
+    use super::*;
+    use crate::models::PortSnapshot;
+
+    #[tokio::test]
+    async fn test_socket_is_private_and_serves_snapshot() {
+        let dir = std::env::temp_dir().join(format!("port-detection-sink-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("events.sock").display().to_string();
+
+        // 残留的套接字被替换，其他类型的文件拒绝覆盖
+        drop(std::os::unix::net::UnixListener::bind(&path).unwrap());
+        let sink = LocalSocketSink::bind(&path).unwrap();
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        // 绑定用的临时目录已清理
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+
+        let (tx, rx) = mpsc::channel(8);
+        Box::new(sink).start(rx).unwrap();
+        tx.send(MonitorEvent::Snapshot(PortSnapshot {
+            seq: 1,
+            timestamp: 1,
+            snapshot_version: 1,
+            ports: Vec::new(),
+        }))
+        .await
+        .unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+
+        let stream = tokio::net::UnixStream::connect(&path).await.unwrap();
+        let mut line = String::new();
+        BufReader::new(stream).read_line(&mut line).await.unwrap();
+        assert!(line.starts_with(r#"{"type":"snapshot""#), "{}", line);
+
+        drop(tx);
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        assert!(!std::path::Path::new(&path).exists());
+
+        let file = dir.join("plain");
+        std::fs::write(&file, "").unwrap();
+        assert!(LocalSocketSink::bind(&file.display().to_string()).is_err());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,536 @@
+//! 监控事件输出
+//!
+//! `SinkHub` 将监控事件分发给多个输出端。每个输出端有独立的有界队列，
+//! 队列满时暂停向该输出端发送，待其恢复后补发一份当前全量快照，
+//! 慢速输出端不会阻塞监控服务或其他输出端
+
+pub mod local_socket;
+pub mod ndjson;
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::{broadcast, mpsc};
+
+use crate::core::monitor::{socket_key, SocketKey};
+use crate::models::{AppResult, ChangeKind, MonitorEvent, PortInfo, PortSnapshot};
+
+pub use local_socket::LocalSocketSink;
+pub use ndjson::NdjsonFileSink;
+
+/// 每个输出端的事件队列长度
+pub const SINK_QUEUE_CAPACITY: usize = 256;
+
+/// 多客户端输出端中每个客户端的缓冲长度
+pub const CLIENT_BUFFER_CAPACITY: usize = 256;
+
+/// 监控事件输出端
+pub trait EventSink: Send + 'static {
+    /// 输出端类型
+    fn kind(&self) -> &'static str;
+
+    /// 输出目标描述（文件路径、监听地址等）
+    fn describe(&self) -> String;
+
+    /// 启动输出端，之后依次从 `rx` 读取事件，`rx` 关闭时应退出并释放资源
+    fn start(self: Box<Self>, rx: mpsc::Receiver<MonitorEvent>) -> AppResult<()>;
+}
+
+/// 输出端信息
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SinkInfo {
+    pub id: u64,
+    pub kind: String,
+    pub description: String,
+    /// 已送入队列的事件数
+    pub delivered: u64,
+    /// 因队列已满而丢弃的事件数
+    pub dropped: u64,
+    /// 是否处于落后状态（等待补发快照）
+    pub lagging: bool,
+}
+
+/// 根据事件流维护的当前端口状态，用于给落后或新接入的订阅者补发快照
+#[derive(Debug, Default)]
+pub struct EventState {
+    seq: u64,
+    timestamp: u64,
+    snapshot_version: u64,
+    ports: HashMap<SocketKey, PortInfo>,
+    /// 是否已收到过基线快照
+    ready: bool,
+}
+
+impl EventState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 应用一个监控事件
+    pub fn apply(&mut self, event: &MonitorEvent) {
+        match event {
+            MonitorEvent::Snapshot(snapshot) => {
+                self.ports = snapshot
+                    .ports
+                    .iter()
+                    .map(|p| (socket_key(p), p.clone()))
+                    .collect();
+                self.seq = snapshot.seq;
+                self.timestamp = snapshot.timestamp;
+                self.snapshot_version = snapshot.snapshot_version;
+                self.ready = true;
+            }
+            MonitorEvent::Change(change) => {
+                for entry in &change.entries {
+                    let key = socket_key(&entry.info);
+                    if entry.kind == ChangeKind::Removed {
+                        self.ports.remove(&key);
+                    } else {
+                        self.ports.insert(key, entry.info.clone());
+                    }
+                }
+                self.seq = change.seq;
+                self.timestamp = change.timestamp;
+                self.snapshot_version = change.snapshot_version;
+            }
+        }
+    }
+
+    /// 以当前状态生成快照事件，序号为最近一个事件的序号；尚无基线时返回 None
+    pub fn snapshot(&self) -> Option<MonitorEvent> {
+        if !self.ready {
+            return None;
+        }
+
+        let mut ports: Vec<PortInfo> = self.ports.values().cloned().collect();
+        ports.sort_by_cached_key(socket_key);
+
+        Some(MonitorEvent::Snapshot(PortSnapshot {
+            seq: self.seq,
+            timestamp: self.timestamp,
+            snapshot_version: self.snapshot_version,
+            ports,
+        }))
+    }
+}
+
+/// 已接入的输出端
+struct AttachedSink {
+    id: u64,
+    kind: &'static str,
+    description: String,
+    tx: mpsc::Sender<MonitorEvent>,
+    delivered: u64,
+    dropped: u64,
+    lagging: bool,
+}
+
+impl AttachedSink {
+    fn info(&self) -> SinkInfo {
+        SinkInfo {
+            id: self.id,
+            kind: self.kind.to_string(),
+            description: self.description.clone(),
+            delivered: self.delivered,
+            dropped: self.dropped,
+            lagging: self.lagging,
+        }
+    }
+}
+
+/// 输出端集合
+#[derive(Default)]
+pub struct SinkHub {
+    sinks: Vec<AttachedSink>,
+    state: EventState,
+    next_id: u64,
+}
+
+impl SinkHub {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 接入输出端，监控已在运行时先发送当前快照
+    pub fn attach(&mut self, sink: Box<dyn EventSink>) -> AppResult<SinkInfo> {
+        let kind = sink.kind();
+        let description = sink.describe();
+        let (tx, rx) = mpsc::channel(SINK_QUEUE_CAPACITY);
+
+        if let Some(snapshot) = self.state.snapshot() {
+            let _ = tx.try_send(snapshot);
+        }
+        sink.start(rx)?;
+
+        self.next_id += 1;
+        let attached = AttachedSink {
+            id: self.next_id,
+            kind,
+            description,
+            tx,
+            delivered: 0,
+            dropped: 0,
+            lagging: false,
+        };
+        let info = attached.info();
+        self.sinks.push(attached);
+
+        tracing::info!("Event sink {} attached ({})", info.id, kind);
+        Ok(info)
+    }
+
+    /// 移除输出端，返回是否存在
+    pub fn detach(&mut self, id: u64) -> bool {
+        let before = self.sinks.len();
+        // 丢弃发送端后输出端读到队列关闭并自行退出
+        self.sinks.retain(|s| s.id != id);
+        before != self.sinks.len()
+    }
+
+    /// 已接入的输出端
+    pub fn list(&self) -> Vec<SinkInfo> {
+        self.sinks.iter().map(|s| s.info()).collect()
+    }
+
+    /// 分发事件，不会阻塞
+    pub fn dispatch(&mut self, event: MonitorEvent) {
+        self.state.apply(&event);
+
+        let mut closed = Vec::new();
+
+        for sink in &mut self.sinks {
+            // 落后的输出端改为发送包含本次事件的全量快照
+            let outgoing = if sink.lagging {
+                match self.state.snapshot() {
+                    Some(snapshot) => snapshot,
+                    None => continue,
+                }
+            } else {
+                event.clone()
+            };
+
+            match sink.tx.try_send(outgoing) {
+                Ok(()) => {
+                    sink.delivered += 1;
+                    if sink.lagging {
+                        tracing::info!("Event sink {} caught up, resent snapshot", sink.id);
+                        sink.lagging = false;
+                    }
+                }
+                Err(mpsc::error::TrySendError::Full(_)) => {
+                    if !sink.lagging {
+                        tracing::warn!("Event sink {} is falling behind, dropping events", sink.id);
+                        sink.lagging = true;
+                    }
+                    sink.dropped += 1;
+                }
+                Err(mpsc::error::TrySendError::Closed(_)) => closed.push(sink.id),
+            }
+        }
+
+        // 输出端自行退出（如写入失败）时移除
+        for id in closed {
+            tracing::warn!("Event sink {} closed, detaching", id);
+            self.detach(id);
+        }
+    }
+}
+
+/// 多客户端输出端（本地套接字、WebSocket）的事件分发
+///
+/// 客户端接入时先收到当前快照，之后接收增量事件；客户端落后时跳过积压事件并补发快照
+#[derive(Clone)]
+pub struct ClientFanout {
+    inner: Arc<Mutex<FanoutInner>>,
+}
+
+struct FanoutInner {
+    state: EventState,
+    /// 关闭后为 None，所有客户端随之断开
+    events: Option<broadcast::Sender<MonitorEvent>>,
+}
+
+impl ClientFanout {
+    pub fn new() -> Self {
+        let (events, _) = broadcast::channel(CLIENT_BUFFER_CAPACITY);
+        Self {
+            inner: Arc::new(Mutex::new(FanoutInner {
+                state: EventState::new(),
+                events: Some(events),
+            })),
+        }
+    }
+
+    /// 向所有客户端发布事件
+    pub fn publish(&self, event: MonitorEvent) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.state.apply(&event);
+        if let Some(events) = &inner.events {
+            // 没有客户端时发送失败，忽略即可
+            let _ = events.send(event);
+        }
+    }
+
+    /// 新客户端订阅
+    pub fn subscribe(&self) -> ClientSubscription {
+        // 持锁订阅并生成快照，保证快照与后续事件之间没有遗漏
+        let inner = self.inner.lock().unwrap();
+        let rx = match &inner.events {
+            Some(events) => events.subscribe(),
+            None => broadcast::channel(1).1,
+        };
+
+        ClientSubscription {
+            rx,
+            fanout: self.clone(),
+            pending: inner.state.snapshot(),
+            last_seq: 0,
+        }
+    }
+
+    /// 关闭分发，断开所有客户端
+    pub fn close(&self) {
+        self.inner.lock().unwrap().events = None;
+    }
+
+    fn snapshot(&self) -> Option<MonitorEvent> {
+        self.inner.lock().unwrap().state.snapshot()
+    }
+}
+
+impl Default for ClientFanout {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 单个客户端的事件订阅
+pub struct ClientSubscription {
+    rx: broadcast::Receiver<MonitorEvent>,
+    fanout: ClientFanout,
+    /// 待发送的快照
+    pending: Option<MonitorEvent>,
+    /// 已发送的最大序号，补发快照后用于跳过已包含在快照中的事件
+    last_seq: u64,
+}
+
+impl ClientSubscription {
+    /// 下一个要发送给客户端的事件，分发关闭时返回 None
+    pub async fn next(&mut self) -> Option<MonitorEvent> {
+        loop {
+            if let Some(event) = self.pending.take() {
+                self.last_seq = event.seq();
+                return Some(event);
+            }
+
+            match self.rx.recv().await {
+                // 快照总是发送：监控重启后序号从头开始
+                Ok(event @ MonitorEvent::Snapshot(_)) => self.pending = Some(event),
+                Ok(event) if event.seq() > self.last_seq => self.pending = Some(event),
+                Ok(_) => {}
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    tracing::debug!("Event client lagged by {} events, resending snapshot", skipped);
+                    self.pending = self.fanout.snapshot();
+                }
+                Err(broadcast::error::RecvError::Closed) => return None,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{ChangeEntry, PortChange, Protocol};
+
+    /// 不读取事件的输出端，测试通过共享的接收端手动读取
+    struct HeldSink(Arc<Mutex<Option<mpsc::Receiver<MonitorEvent>>>>);
+
+    impl EventSink for HeldSink {
+        fn kind(&self) -> &'static str {
+            "held"
+        }
+
+        fn describe(&self) -> String {
+            String::new()
+        }
+
+        fn start(self: Box<Self>, rx: mpsc::Receiver<MonitorEvent>) -> AppResult<()> {
+            *self.0.lock().unwrap() = Some(rx);
+            Ok(())
+        }
+    }
+
+    /// 启动后立即退出的输出端
+    struct ClosedSink;
+
+    impl EventSink for ClosedSink {
+        fn kind(&self) -> &'static str {
+            "closed"
+        }
+
+        fn describe(&self) -> String {
+            String::new()
+        }
+
+        fn start(self: Box<Self>, _rx: mpsc::Receiver<MonitorEvent>) -> AppResult<()> {
+            Ok(())
+        }
+    }
+
+    fn port(port: u16) -> PortInfo {
+        PortInfo::new(port, Protocol::TCP, "0.0.0.0".to_string(), 1)
+    }
+
+    fn snapshot(seq: u64, ports: Vec<PortInfo>) -> MonitorEvent {
+        MonitorEvent::Snapshot(PortSnapshot {
+            seq,
+            timestamp: seq,
+            snapshot_version: seq,
+            ports,
+        })
+    }
+
+    fn added(seq: u64, port_number: u16) -> MonitorEvent {
+        MonitorEvent::Change(PortChange {
+            seq,
+            timestamp: seq,
+            snapshot_version: seq,
+            entries: vec![ChangeEntry::added(port(port_number))],
+        })
+    }
+
+    fn ports_of(event: &MonitorEvent) -> Vec<u16> {
+        match event {
+            MonitorEvent::Snapshot(s) => s.ports.iter().map(|p| p.port).collect(),
+            MonitorEvent::Change(c) => c.entries.iter().map(|e| e.info.port).collect(),
+        }
+    }
+
+    #[test]
+    fn test_slow_sink_lags_and_recovers_with_snapshot() {
+        let mut hub = SinkHub::new();
+        let held = Arc::new(Mutex::new(None));
+        hub.attach(Box::new(HeldSink(held.clone()))).unwrap();
+        let mut rx = held.lock().unwrap().take().unwrap();
+
+        hub.dispatch(snapshot(1, vec![port(22)]));
+        let total = SINK_QUEUE_CAPACITY as u64 + 5;
+        for seq in 2..=total {
+            hub.dispatch(added(seq, 1000 + seq as u16));
+        }
+
+        let info = &hub.list()[0];
+        assert!(info.lagging);
+        assert_eq!(info.delivered, SINK_QUEUE_CAPACITY as u64);
+        assert_eq!(info.dropped, 5);
+
+        // 输出端读完积压事件后，下一个事件以包含全部状态的快照补发
+        while rx.try_recv().is_ok() {}
+        hub.dispatch(added(total + 1, 9999));
+
+        let info = &hub.list()[0];
+        assert!(!info.lagging);
+        assert_eq!(info.dropped, 5);
+        let MonitorEvent::Snapshot(resent) = rx.try_recv().unwrap() else {
+            panic!("expected snapshot");
+        };
+        assert_eq!(resent.seq, total + 1);
+        assert_eq!(resent.ports.len(), total as usize + 1);
+        assert_eq!(resent.ports.last().unwrap().port, 9999);
+
+        // 恢复后继续发送增量事件
+        hub.dispatch(added(total + 2, 10_000));
+        assert_eq!(ports_of(&rx.try_recv().unwrap()), vec![10_000]);
+    }
+
+    #[test]
+    fn test_closed_sink_is_detached() {
+        let mut hub = SinkHub::new();
+        hub.attach(Box::new(ClosedSink)).unwrap();
+        let held = Arc::new(Mutex::new(None));
+        let kept = hub.attach(Box::new(HeldSink(held.clone()))).unwrap();
+        assert_eq!(hub.list().len(), 2);
+
+        hub.dispatch(snapshot(1, vec![port(22)]));
+        let remaining: Vec<u64> = hub.list().iter().map(|s| s.id).collect();
+        assert_eq!(remaining, vec![kept.id]);
+        assert!(held.lock().unwrap().as_mut().unwrap().try_recv().is_ok());
+
+        assert!(hub.detach(kept.id));
+        assert!(!hub.detach(kept.id));
+    }
+
+    #[test]
+    fn test_late_sink_receives_snapshot_first() {
+        let mut hub = SinkHub::new();
+        // 尚无基线时接入不发送任何内容
+        let early = Arc::new(Mutex::new(None));
+        hub.attach(Box::new(HeldSink(early.clone()))).unwrap();
+        assert!(early.lock().unwrap().as_mut().unwrap().try_recv().is_err());
+
+        hub.dispatch(snapshot(1, vec![port(22)]));
+        hub.dispatch(added(2, 80));
+
+        let late = Arc::new(Mutex::new(None));
+        hub.attach(Box::new(HeldSink(late.clone()))).unwrap();
+        let mut rx = late.lock().unwrap().take().unwrap();
+        let first = rx.try_recv().unwrap();
+        assert!(matches!(first, MonitorEvent::Snapshot(_)));
+        assert_eq!(first.seq(), 2);
+        assert_eq!(ports_of(&first), vec![22, 80]);
+
+        hub.dispatch(added(3, 443));
+        assert_eq!(ports_of(&rx.try_recv().unwrap()), vec![443]);
+    }
+
+    #[tokio::test]
+    async fn test_client_subscription_snapshot_then_deltas() {
+        let fanout = ClientFanout::new();
+        fanout.publish(snapshot(1, vec![port(22)]));
+        fanout.publish(added(2, 80));
+
+        let mut client = fanout.subscribe();
+        let first = client.next().await.unwrap();
+        assert!(matches!(first, MonitorEvent::Snapshot(_)));
+        assert_eq!(ports_of(&first), vec![22, 80]);
+
+        fanout.publish(added(3, 443));
+        assert_eq!(ports_of(&client.next().await.unwrap()), vec![443]);
+
+        fanout.close();
+        assert!(client.next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_lagging_client_gets_snapshot_and_skips_included_events() {
+        let fanout = ClientFanout::new();
+        fanout.publish(snapshot(1, Vec::new()));
+        let mut client = fanout.subscribe();
+        assert!(matches!(
+            client.next().await,
+            Some(MonitorEvent::Snapshot(_))
+        ));
+
+        let total = CLIENT_BUFFER_CAPACITY as u64 + 10;
+        for seq in 2..=total {
+            fanout.publish(added(seq, seq as u16));
+        }
+
+        let MonitorEvent::Snapshot(resent) = client.next().await.unwrap() else {
+            panic!("expected snapshot");
+        };
+        assert_eq!(resent.seq, total);
+        assert_eq!(resent.ports.len(), total as usize - 1);
+
+        // 仍在缓冲中的旧事件已包含在快照里，直接跳过
+        let skipped = tokio::time::timeout(std::time::Duration::from_millis(50), client.next());
+        assert!(skipped.await.is_err());
+
+        fanout.publish(added(total + 1, 60_000));
+        let next = client.next().await.unwrap();
+        assert_eq!(next.seq(), total + 1);
+        assert_eq!(ports_of(&next), vec![60_000]);
+    }
+}
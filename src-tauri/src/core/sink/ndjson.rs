@@ -0,0 +1,71 @@
+//! NDJSON 文件输出
+//!
+//! 每个事件追加一行 JSON，便于日志采集程序 tail
+
+use std::fs::OpenOptions;
+use std::path::PathBuf;
+
+use tokio::io::AsyncWriteExt;
+use tokio::sync::mpsc;
+
+use super::EventSink;
+use crate::models::{AppError, AppResult, MonitorEvent};
+
+/// NDJSON 文件输出端
+pub struct NdjsonFileSink {
+    path: PathBuf,
+    file: std::fs::File,
+}
+
+impl NdjsonFileSink {
+    /// 以追加方式打开文件，不存在时创建
+    pub fn open(path: impl Into<PathBuf>) -> AppResult<Self> {
+        let path = path.into();
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .map_err(|e| AppError::Io(format!("{}: {}", path.display(), e)))?;
+        Ok(Self { path, file })
+    }
+}
+
+impl EventSink for NdjsonFileSink {
+    fn kind(&self) -> &'static str {
+        "ndjson_file"
+    }
+
+    fn describe(&self) -> String {
+        self.path.display().to_string()
+    }
+
+    fn start(self: Box<Self>, mut rx: mpsc::Receiver<MonitorEvent>) -> AppResult<()> {
+        let path = self.path;
+        let mut file = tokio::fs::File::from_std(self.file);
+
+        tokio::spawn(async move {
+            while let Some(event) = rx.recv().await {
+                let mut line = match serde_json::to_string(&event) {
+                    Ok(line) => line,
+                    Err(e) => {
+                        tracing::error!("Failed to serialize monitor event: {}", e);
+                        continue;
+                    }
+                };
+                line.push('\n');
+
+                let result = async {
+                    file.write_all(line.as_bytes()).await?;
+                    file.flush().await
+                }
+                .await;
+                if let Err(e) = result {
+                    tracing::error!("Failed to write {}: {}", path.display(), e);
+                    break;
+                }
+            }
+        });
+
+        Ok(())
+    }
+}
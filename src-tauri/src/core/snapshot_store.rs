@@ -0,0 +1,390 @@
+//! 命名快照的保存、加载与比较
+//!
+//! 快照以带版本号的 JSON 文件保存在指定目录下，文件名即快照名
+
+use std::fs;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::core::monitor::{diff_snapshots, snapshot_map};
+use crate::models::{AppError, AppResult, ChangeEntry, PortInfo};
+use crate::utils::time::now_millis;
+
+/// 当前快照文件格式版本
+///
+/// 版本 2 增加了 `listen_only`
+pub const SNAPSHOT_FORMAT_VERSION: u32 = 2;
+
+/// 与实时状态比较时使用的名称
+pub const LIVE_SNAPSHOT_NAME: &str = "<live>";
+
+/// 主机信息
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HostMetadata {
+    /// 主机名
+    pub hostname: String,
+    /// 操作系统
+    pub os: String,
+    /// CPU 架构
+    pub arch: String,
+}
+
+impl HostMetadata {
+    /// 获取当前主机信息
+    pub fn current() -> Self {
+        Self {
+            hostname: current_hostname(),
+            os: std::env::consts::OS.to_string(),
+            arch: std::env::consts::ARCH.to_string(),
+        }
+    }
+}
+
+/// 保存的快照
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedSnapshot {
+    /// 文件格式版本
+    pub format_version: u32,
+    /// 快照名
+    pub name: String,
+    /// 创建时间（Unix 时间戳，毫秒）
+    pub created_at: u64,
+    /// 主机信息
+    pub host: HostMetadata,
+    /// 是否只包含监听端口，与实时状态比较时按相同范围扫描
+    #[serde(default)]
+    pub listen_only: bool,
+    /// 端口列表
+    pub ports: Vec<PortInfo>,
+}
+
+/// 快照摘要
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotSummary {
+    pub name: String,
+    pub created_at: u64,
+    pub host: HostMetadata,
+    pub listen_only: bool,
+    pub port_count: usize,
+}
+
+impl From<&SavedSnapshot> for SnapshotSummary {
+    fn from(snapshot: &SavedSnapshot) -> Self {
+        Self {
+            name: snapshot.name.clone(),
+            created_at: snapshot.created_at,
+            host: snapshot.host.clone(),
+            listen_only: snapshot.listen_only,
+            port_count: snapshot.ports.len(),
+        }
+    }
+}
+
+/// 两个快照之间的差异
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotDiff {
+    /// 基准快照名
+    pub from: String,
+    /// 目标快照名，实时状态为 `LIVE_SNAPSHOT_NAME`
+    pub to: String,
+    /// 从基准到目标的变化
+    pub entries: Vec<ChangeEntry>,
+}
+
+impl SnapshotDiff {
+    /// 比较两组端口
+    pub fn between(
+        from: String,
+        from_ports: &[PortInfo],
+        to: String,
+        to_ports: &[PortInfo],
+    ) -> Self {
+        let previous = snapshot_map(from_ports.to_vec());
+        let current = snapshot_map(to_ports.to_vec());
+
+        Self {
+            from,
+            to,
+            entries: diff_snapshots(&previous, &current),
+        }
+    }
+}
+
+/// 快照存储
+pub struct SnapshotStore {
+    dir: PathBuf,
+}
+
+impl SnapshotStore {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// 保存快照，同名快照会被覆盖；`listen_only` 表示 `ports` 是否只来自监听端口扫描
+    pub fn save(
+        &self,
+        name: &str,
+        ports: Vec<PortInfo>,
+        listen_only: bool,
+    ) -> AppResult<SnapshotSummary> {
+        let path = self.path_for(name)?;
+        fs::create_dir_all(&self.dir).map_err(|e| AppError::Io(e.to_string()))?;
+
+        let snapshot = SavedSnapshot {
+            format_version: SNAPSHOT_FORMAT_VERSION,
+            name: name.to_string(),
+            created_at: now_millis(),
+            host: HostMetadata::current(),
+            listen_only,
+            ports,
+        };
+
+        let json = serde_json::to_string_pretty(&snapshot)
+            .map_err(|e| AppError::Internal(e.to_string()))?;
+        fs::write(&path, json).map_err(|e| AppError::Io(e.to_string()))?;
+
+        Ok(SnapshotSummary::from(&snapshot))
+    }
+
+    /// 加载快照
+    pub fn load(&self, name: &str) -> AppResult<SavedSnapshot> {
+        let path = self.path_for(name)?;
+        if !path.exists() {
+            return Err(AppError::SnapshotNotFound(name.to_string()));
+        }
+
+        let content = fs::read_to_string(&path).map_err(|e| AppError::Io(e.to_string()))?;
+        let mut snapshot: SavedSnapshot = serde_json::from_str(&content)
+            .map_err(|e| AppError::Internal(format!("快照格式错误 {}: {}", name, e)))?;
+
+        if snapshot.format_version > SNAPSHOT_FORMAT_VERSION {
+            return Err(AppError::Internal(format!(
+                "快照 {} 的格式版本 {} 高于当前支持的版本 {}",
+                name, snapshot.format_version, SNAPSHOT_FORMAT_VERSION
+            )));
+        }
+
+        // 版本 1 没有记录扫描范围，只能按端口状态推断
+        if snapshot.format_version < 2 {
+            snapshot.listen_only =
+                !snapshot.ports.is_empty() && snapshot.ports.iter().all(|p| p.state.is_listening());
+        }
+
+        Ok(snapshot)
+    }
+
+    /// 列出所有快照，按创建时间排序，无法解析的文件会被跳过
+    pub fn list(&self) -> AppResult<Vec<SnapshotSummary>> {
+        let entries = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(AppError::Io(e.to_string())),
+        };
+
+        let mut summaries = Vec::new();
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("json") {
+                continue;
+            }
+            let Some(name) = path.file_stem().and_then(|s| s.to_str()) else {
+                continue;
+            };
+
+            match self.load(name) {
+                Ok(snapshot) => summaries.push(SnapshotSummary::from(&snapshot)),
+                Err(e) => tracing::warn!("Skipping unreadable snapshot {}: {}", name, e),
+            }
+        }
+
+        summaries.sort_by_key(|s| s.created_at);
+        Ok(summaries)
+    }
+
+    /// 删除快照
+    pub fn delete(&self, name: &str) -> AppResult<()> {
+        let path = self.path_for(name)?;
+        if !path.exists() {
+            return Err(AppError::SnapshotNotFound(name.to_string()));
+        }
+        fs::remove_file(path).map_err(|e| AppError::Io(e.to_string()))
+    }
+
+    /// 快照文件路径，名称只允许字母、数字、`-`、`_` 和 `.`
+    fn path_for(&self, name: &str) -> AppResult<PathBuf> {
+        let valid = !name.is_empty()
+            && !name.starts_with('.')
+            && name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+
+        if !valid {
+            return Err(AppError::InvalidArgument(format!("快照名称: {}", name)));
+        }
+
+        Ok(self.dir.join(format!("{}.json", name)))
+    }
+}
+
+/// 当前主机名
+fn current_hostname() -> String {
+    #[cfg(windows)]
+    let hostname = std::env::var("COMPUTERNAME").ok();
+
+    #[cfg(not(windows))]
+    let hostname = fs::read_to_string("/proc/sys/kernel/hostname")
+        .ok()
+        .map(|s| s.trim().to_string())
+        .or_else(|| std::env::var("HOSTNAME").ok());
+
+    hostname
+        .filter(|h| !h.is_empty())
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{ConnectionState, Protocol};
+
+    /// 每个测试使用独立的临时目录
+    fn store(name: &str) -> SnapshotStore {
+        let dir = std::env::temp_dir().join(format!(
+            "port-detection-snapshots-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        SnapshotStore::new(dir)
+    }
+
+    fn listener(port: u16) -> PortInfo {
+        let mut info = PortInfo::new(port, Protocol::TCP, "0.0.0.0".to_string(), 1);
+        info.state = ConnectionState::Listen;
+        info
+    }
+
+    #[test]
+    fn test_name_validation() {
+        let store = store("names");
+        for name in [
+            "",
+            ".hidden",
+            "..",
+            "../escape",
+            "a/b",
+            "a\\b",
+            "with space",
+            "名",
+        ] {
+            assert!(
+                matches!(
+                    store.save(name, Vec::new(), false),
+                    Err(AppError::InvalidArgument(_))
+                ),
+                "accepted {:?}",
+                name
+            );
+            assert!(matches!(
+                store.load(name),
+                Err(AppError::InvalidArgument(_))
+            ));
+        }
+
+        store.save("release-1.2_rc", Vec::new(), false).unwrap();
+        assert!(store.dir.join("release-1.2_rc.json").exists());
+        let _ = fs::remove_dir_all(&store.dir);
+    }
+
+    #[test]
+    fn test_save_overwrites_and_keeps_listen_only() {
+        let store = store("overwrite");
+        store.save("s", vec![listener(22)], false).unwrap();
+        let summary = store
+            .save("s", vec![listener(80), listener(443)], true)
+            .unwrap();
+        assert_eq!(summary.port_count, 2);
+        assert!(summary.listen_only);
+
+        let loaded = store.load("s").unwrap();
+        assert_eq!(loaded.format_version, SNAPSHOT_FORMAT_VERSION);
+        assert!(loaded.listen_only);
+        let ports: Vec<u16> = loaded.ports.iter().map(|p| p.port).collect();
+        assert_eq!(ports, vec![80, 443]);
+        assert_eq!(store.list().unwrap().len(), 1);
+
+        // 全量扫描的快照即使恰好只有监听端口也保持 listen_only = false
+        store.save("full", vec![listener(22)], false).unwrap();
+        assert!(!store.load("full").unwrap().listen_only);
+        let _ = fs::remove_dir_all(&store.dir);
+    }
+
+    #[test]
+    fn test_list_sorted_by_creation_and_skips_corrupt_files() {
+        let store = store("list");
+        assert!(store.list().unwrap().is_empty());
+
+        store.save("zeta", Vec::new(), false).unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        store.save("alpha", Vec::new(), false).unwrap();
+        fs::write(store.dir.join("broken.json"), "{ not json").unwrap();
+        fs::write(store.dir.join("notes.txt"), "ignored").unwrap();
+
+        let names: Vec<String> = store.list().unwrap().into_iter().map(|s| s.name).collect();
+        assert_eq!(names, vec!["zeta", "alpha"]);
+        assert!(matches!(store.load("broken"), Err(AppError::Internal(_))));
+        let _ = fs::remove_dir_all(&store.dir);
+    }
+
+    #[test]
+    fn test_load_rejects_newer_format_and_upgrades_v1() {
+        let store = store("versions");
+        fs::create_dir_all(&store.dir).unwrap();
+
+        let mut snapshot = SavedSnapshot {
+            format_version: SNAPSHOT_FORMAT_VERSION + 1,
+            name: "future".to_string(),
+            created_at: 1,
+            host: HostMetadata::current(),
+            listen_only: false,
+            ports: Vec::new(),
+        };
+        let write = |snapshot: &SavedSnapshot| {
+            let json = serde_json::to_string(snapshot).unwrap();
+            fs::write(store.dir.join(format!("{}.json", snapshot.name)), json).unwrap();
+        };
+        write(&snapshot);
+        assert!(matches!(store.load("future"), Err(AppError::Internal(_))));
+
+        // 版本 1 的文件没有 listen_only，按端口状态推断
+        snapshot.format_version = 1;
+        snapshot.name = "legacy".to_string();
+        snapshot.ports = vec![listener(22)];
+        write(&snapshot);
+        assert!(store.load("legacy").unwrap().listen_only);
+        let _ = fs::remove_dir_all(&store.dir);
+    }
+
+    #[test]
+    fn test_delete() {
+        let store = store("delete");
+        store.save("old", Vec::new(), false).unwrap();
+        store.delete("old").unwrap();
+
+        assert!(matches!(
+            store.load("old"),
+            Err(AppError::SnapshotNotFound(_))
+        ));
+        assert!(matches!(
+            store.delete("old"),
+            Err(AppError::SnapshotNotFound(_))
+        ));
+        assert!(matches!(
+            store.delete("../old"),
+            Err(AppError::InvalidArgument(_))
+        ));
+        assert!(store.list().unwrap().is_empty());
+        let _ = fs::remove_dir_all(&store.dir);
+    }
+}
@@ -0,0 +1,90 @@
+//! 扫描触发源
+//!
+//! 监控服务除了定时采样外，还可以由外部事件（如内核套接字销毁、进程退出）触发立即扫描。
+//! 定时采样始终保留，作为事件丢失时的兜底
+
+use tokio::sync::mpsc;
+
+use crate::models::AppResult;
+
+/// 触发原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TriggerReason {
+    /// 套接字事件
+    SocketEvent,
+    /// 进程启动或退出
+    ProcessEvent,
+    /// 手动触发
+    Manual,
+}
+
+/// 扫描触发源
+pub trait TriggerSource: Send + 'static {
+    /// 触发源名称（用于日志）
+    fn name(&self) -> &'static str;
+
+    /// 启动触发源，之后通过 `tx` 发送触发事件
+    ///
+    /// 返回错误时监控服务忽略该触发源，仅依赖定时采样
+    fn start(self: Box<Self>, tx: mpsc::Sender<TriggerReason>) -> AppResult<()>;
+}
+
+/// 手动触发源，主要用于测试和按需刷新
+pub struct ManualTrigger {
+    rx: mpsc::Receiver<()>,
+}
+
+/// 手动触发句柄
+#[derive(Clone)]
+pub struct ManualTriggerHandle {
+    tx: mpsc::Sender<()>,
+}
+
+impl ManualTrigger {
+    /// 创建手动触发源及其句柄
+    pub fn new() -> (Self, ManualTriggerHandle) {
+        let (tx, rx) = mpsc::channel(1);
+        (Self { rx }, ManualTriggerHandle { tx })
+    }
+}
+
+impl ManualTriggerHandle {
+    /// 请求立即扫描，已有未处理的请求时合并
+    pub fn fire(&self) {
+        let _ = self.tx.try_send(());
+    }
+}
+
+impl TriggerSource for ManualTrigger {
+    fn name(&self) -> &'static str {
+        "manual"
+    }
+
+    fn start(self: Box<Self>, tx: mpsc::Sender<TriggerReason>) -> AppResult<()> {
+        let mut rx = self.rx;
+        tokio::spawn(async move {
+            while rx.recv().await.is_some() {
+                if tx.send(TriggerReason::Manual).await.is_err() {
+                    break;
+                }
+            }
+        });
+        Ok(())
+    }
+}
+
+/// 当前平台可用的事件触发源
+pub fn platform_trigger_sources() -> Vec<Box<dyn TriggerSource>> {
+    #[cfg(target_os = "linux")]
+    {
+        vec![
+            Box::new(crate::platform::linux::SockDiagTrigger::new()),
+            Box::new(crate::platform::linux::ProcConnectorTrigger::new()),
+        ]
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        Vec::new()
+    }
+}
@@ -0,0 +1,290 @@
+//! 单端口生命周期跟踪
+//!
+//! 基于 `PortMonitor` 的采样结果记录指定端口的占用者变化和空闲区间
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::core::monitor::{socket_key, PortKey, SocketKey};
+use crate::models::{ConnectionState, PortInfo, Protocol};
+
+/// 端口占用者
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PortOwner {
+    /// 进程 ID
+    pub pid: u32,
+    /// 进程名称
+    pub name: Option<String>,
+}
+
+/// 时间线中的一段，端口空闲时 `owner` 与 `state` 均为 None
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimelineSegment {
+    /// 占用进程
+    pub owner: Option<PortOwner>,
+    /// 连接状态
+    pub state: Option<ConnectionState>,
+    /// 开始时间（Unix 时间戳，毫秒）
+    pub start: u64,
+    /// 结束时间，仍在持续时为 None
+    pub end: Option<u64>,
+    /// 持续时长（毫秒），仍在持续的段计算到查询时刻
+    pub duration_ms: u64,
+}
+
+impl TimelineSegment {
+    /// 是否为空闲段
+    pub fn is_down(&self) -> bool {
+        self.owner.is_none()
+    }
+}
+
+/// 端口时间线
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PortTimeline {
+    pub port: u16,
+    pub protocol: Protocol,
+    /// 开始跟踪的时间
+    pub watching_since: u64,
+    /// 按时间排列的各段
+    pub segments: Vec<TimelineSegment>,
+    /// 占用进程变化次数
+    pub owner_transitions: u32,
+    /// 空闲总时长（毫秒）
+    pub total_downtime_ms: u64,
+}
+
+/// 单个端口的跟踪状态
+#[derive(Debug, Clone)]
+struct PortWatch {
+    watching_since: u64,
+    segments: Vec<TimelineSegment>,
+}
+
+impl PortWatch {
+    fn new(initial: Option<&PortInfo>, now: u64) -> Self {
+        let mut watch = Self {
+            watching_since: now,
+            segments: Vec::new(),
+        };
+        watch.observe(initial, now);
+        watch
+    }
+
+    /// 记录端口的最新状态，与当前段相同时忽略
+    fn observe(&mut self, info: Option<&PortInfo>, at: u64) {
+        let owner = info.map(|p| PortOwner {
+            pid: p.pid,
+            name: p.process.as_ref().map(|proc| proc.name.clone()),
+        });
+        let state = info.map(|p| p.state);
+
+        if let Some(last) = self.segments.last_mut() {
+            let same_owner = last.owner.as_ref().map(|o| o.pid) == owner.as_ref().map(|o| o.pid);
+            if same_owner && last.state == state {
+                return;
+            }
+            last.end = Some(at);
+            last.duration_ms = at.saturating_sub(last.start);
+        }
+
+        self.segments.push(TimelineSegment {
+            owner,
+            state,
+            start: at,
+            end: None,
+            duration_ms: 0,
+        });
+    }
+
+    fn timeline(&self, key: PortKey, now: u64) -> PortTimeline {
+        let mut segments = self.segments.clone();
+        if let Some(last) = segments.last_mut() {
+            if last.end.is_none() {
+                last.duration_ms = now.saturating_sub(last.start);
+            }
+        }
+
+        let owner_transitions = segments
+            .windows(2)
+            .filter(|w| {
+                let before = w[0].owner.as_ref().map(|o| o.pid);
+                let after = w[1].owner.as_ref().map(|o| o.pid);
+                before != after && after.is_some()
+            })
+            .count() as u32;
+
+        let total_downtime_ms = segments
+            .iter()
+            .filter(|s| s.is_down())
+            .map(|s| s.duration_ms)
+            .sum();
+
+        PortTimeline {
+            port: key.0,
+            protocol: key.1,
+            watching_since: self.watching_since,
+            segments,
+            owner_transitions,
+            total_downtime_ms,
+        }
+    }
+}
+
+/// 端口跟踪注册表
+#[derive(Debug, Default)]
+pub struct WatchRegistry {
+    watches: HashMap<PortKey, PortWatch>,
+}
+
+impl WatchRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 开始跟踪端口，已在跟踪时保留原有时间线
+    pub fn watch(&mut self, key: PortKey, initial: Option<&PortInfo>, now: u64) {
+        self.watches
+            .entry(key)
+            .or_insert_with(|| PortWatch::new(initial, now));
+    }
+
+    /// 停止跟踪端口，返回是否存在
+    pub fn unwatch(&mut self, key: &PortKey) -> bool {
+        self.watches.remove(key).is_some()
+    }
+
+    /// 正在跟踪的端口
+    pub fn watched(&self) -> Vec<PortKey> {
+        let mut keys: Vec<PortKey> = self.watches.keys().copied().collect();
+        keys.sort_by_key(|(port, protocol)| (*port, matches!(protocol, Protocol::UDP)));
+        keys
+    }
+
+    /// 获取端口时间线
+    pub fn timeline(&self, key: &PortKey, now: u64) -> Option<PortTimeline> {
+        self.watches.get(key).map(|w| w.timeline(*key, now))
+    }
+
+    /// 应用最新的 socket 快照，每次采样或重新建立基线后调用
+    pub fn apply_snapshot(&mut self, snapshot: &HashMap<SocketKey, PortInfo>, at: u64) {
+        for (key, watch) in self.watches.iter_mut() {
+            watch.observe(port_occupant(snapshot.values(), *key), at);
+        }
+    }
+}
+
+/// 端口的占用者：优先取监听 socket，没有监听时才取该端口上的连接
+///
+/// 同一优先级有多个 socket（如 IPv4 与 IPv6 同时监听）时取 socket 键最小的一个，
+/// 保证多次采样的结果稳定
+pub fn port_occupant<'a>(
+    ports: impl IntoIterator<Item = &'a PortInfo>,
+    key: PortKey,
+) -> Option<&'a PortInfo> {
+    ports
+        .into_iter()
+        .filter(|p| p.port == key.0 && p.protocol == key.1)
+        .min_by_key(|p| (!p.state.is_listening(), socket_key(p)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::monitor::snapshot_map;
+
+    const HTTP: PortKey = (80, Protocol::TCP);
+
+    fn listener(pid: u32) -> PortInfo {
+        let mut info = PortInfo::new(80, Protocol::TCP, "0.0.0.0".to_string(), pid);
+        info.state = ConnectionState::Listen;
+        info
+    }
+
+    fn connection(pid: u32, remote_port: u16) -> PortInfo {
+        let mut info = PortInfo::new(80, Protocol::TCP, "10.0.0.2".to_string(), pid);
+        info.state = ConnectionState::Established;
+        info.remote_addr = Some("10.0.0.9".to_string());
+        info.remote_port = Some(remote_port);
+        info
+    }
+
+    fn owners(timeline: &PortTimeline) -> Vec<Option<u32>> {
+        timeline
+            .segments
+            .iter()
+            .map(|s| s.owner.as_ref().map(|o| o.pid))
+            .collect()
+    }
+
+    #[test]
+    fn test_occupant_prefers_listener() {
+        // 连接的 socket 键更小，但端口仍归监听进程所有
+        let sockets = vec![connection(9, 1000), listener(1), connection(7, 2000)];
+        assert_eq!(port_occupant(&sockets, HTTP).map(|p| p.pid), Some(1));
+
+        // 没有监听时才以连接为占用者，并且结果与顺序无关
+        let sockets = vec![connection(7, 2000), connection(9, 1000)];
+        assert_eq!(port_occupant(&sockets, HTTP).map(|p| p.pid), Some(9));
+
+        assert!(port_occupant(&sockets, (80, Protocol::UDP)).is_none());
+    }
+
+    #[test]
+    fn test_owner_change_starts_new_segment() {
+        let mut registry = WatchRegistry::new();
+        registry.watch(HTTP, Some(&listener(1)), 1000);
+
+        // 同一进程的重复采样和新增连接不产生新段
+        registry.apply_snapshot(&snapshot_map(vec![listener(1)]), 2000);
+        registry.apply_snapshot(&snapshot_map(vec![listener(1), connection(5, 3000)]), 2500);
+        registry.apply_snapshot(&snapshot_map(vec![listener(2)]), 3000);
+
+        let timeline = registry.timeline(&HTTP, 4000).unwrap();
+        assert_eq!(owners(&timeline), vec![Some(1), Some(2)]);
+        assert_eq!(timeline.owner_transitions, 1);
+        assert_eq!(timeline.segments[0].end, Some(3000));
+        assert_eq!(timeline.segments[0].duration_ms, 2000);
+        assert_eq!(timeline.segments[1].duration_ms, 1000);
+        assert_eq!(timeline.total_downtime_ms, 0);
+    }
+
+    #[test]
+    fn test_down_and_up_accumulate_downtime() {
+        let mut registry = WatchRegistry::new();
+        registry.watch(HTTP, Some(&listener(1)), 1000);
+
+        registry.apply_snapshot(&HashMap::new(), 2000);
+        registry.apply_snapshot(&HashMap::new(), 3000);
+        registry.apply_snapshot(&snapshot_map(vec![listener(1)]), 5000);
+        registry.apply_snapshot(&HashMap::new(), 6000);
+
+        let timeline = registry.timeline(&HTTP, 6500).unwrap();
+        assert_eq!(owners(&timeline), vec![Some(1), None, Some(1), None]);
+        assert!(timeline.segments[1].is_down());
+        // 空闲后重新占用计为一次占用者变化
+        assert_eq!(timeline.owner_transitions, 1);
+        assert_eq!(timeline.total_downtime_ms, 3000 + 500);
+    }
+
+    #[test]
+    fn test_watch_idle_port_and_unwatch() {
+        let mut registry = WatchRegistry::new();
+        registry.watch(HTTP, None, 1000);
+        // 已在跟踪时保留原有时间线
+        registry.watch(HTTP, Some(&listener(1)), 1500);
+        registry.apply_snapshot(&snapshot_map(vec![listener(1)]), 2000);
+
+        let timeline = registry.timeline(&HTTP, 2000).unwrap();
+        assert_eq!(timeline.watching_since, 1000);
+        assert_eq!(owners(&timeline), vec![None, Some(1)]);
+        assert_eq!(registry.watched(), vec![HTTP]);
+
+        assert!(registry.unwatch(&HTTP));
+        assert!(!registry.unwatch(&HTTP));
+        registry.apply_snapshot(&HashMap::new(), 3000);
+        assert!(registry.timeline(&HTTP, 3000).is_none());
+        assert!(registry.watched().is_empty());
+    }
+}
@@ -0,0 +1,398 @@
+//! 守护进程客户端（GUI 与 CLI 共用）
+
+use tokio::io::BufReader;
+
+use super::protocol::{
+    DaemonInfo, Request, RequestBody, Response, ResponseBody, PROTOCOL_VERSION,
+};
+use super::transport::{self, read_message, write_message, BoxReader, BoxWriter};
+use crate::core::probe::ProbeConfig;
+use crate::core::query::{PageQuery, PortPage};
+use crate::core::snapshot_store::{SavedSnapshot, SnapshotSummary};
+use crate::core::watch::PortTimeline;
+use crate::core::PortFilter;
+use crate::models::{
+    AppError, AppResult, Baseline, BaselineStatus, ComplianceReport, ConnectionSummary, DriftEvent, ExposureReport,
+    Finding, ListenerPolicy, MonitorEvent, PortInfo, ProcessInfo, Protocol, ServiceEntry,
+};
+
+/// 守护进程客户端
+pub struct DaemonClient {
+    reader: BufReader<BoxReader>,
+    writer: BoxWriter,
+    next_id: u64,
+    info: DaemonInfo,
+}
+
+impl DaemonClient {
+    /// 连接默认地址
+    pub async fn connect_default() -> AppResult<Self> {
+        Self::connect(&transport::default_endpoint()).await
+    }
+
+    /// 连接并握手，协议版本不一致时返回错误
+    pub async fn connect(endpoint: &str) -> AppResult<Self> {
+        let conn = transport::connect(endpoint).await?;
+        let mut client = Self {
+            reader: conn.reader,
+            writer: conn.writer,
+            next_id: 0,
+            info: DaemonInfo::default(),
+        };
+
+        match client.call(RequestBody::Hello).await? {
+            ResponseBody::Hello(info) if info.protocol_version == PROTOCOL_VERSION => {
+                client.info = info;
+                Ok(client)
+            }
+            ResponseBody::Hello(info) => Err(AppError::Ipc(format!(
+                "协议版本不一致：守护进程 {}，客户端 {}",
+                info.protocol_version, PROTOCOL_VERSION
+            ))),
+            other => Err(unexpected(other)),
+        }
+    }
+
+    /// 握手时获取的守护进程信息
+    pub fn info(&self) -> &DaemonInfo {
+        &self.info
+    }
+
+    /// 发送请求并等待对应的响应
+    pub async fn call(&mut self, body: RequestBody) -> AppResult<ResponseBody> {
+        self.next_id += 1;
+        let id = self.next_id;
+
+        let request = Request {
+            version: PROTOCOL_VERSION,
+            id,
+            body,
+        };
+        write_message(&mut self.writer, &request).await?;
+
+        loop {
+            let response: Response = read_message(&mut self.reader)
+                .await?
+                .ok_or_else(|| AppError::Ipc("守护进程已断开连接".to_string()))?;
+
+            // 跳过之前订阅残留的事件
+            if response.id != id {
+                continue;
+            }
+
+            return match response.body {
+                ResponseBody::Error(e) => Err(e.into()),
+                body => Ok(body),
+            };
+        }
+    }
+
+    pub async fn scan_ports(&mut self, filter: PortFilter) -> AppResult<Vec<PortInfo>> {
+        match self.call(RequestBody::ScanPorts { filter }).await? {
+            ResponseBody::Ports(ports) => Ok(ports),
+            other => Err(unexpected(other)),
+        }
+    }
+
+    pub async fn query_ports(&mut self, filter: &str) -> AppResult<Vec<PortInfo>> {
+        let body = RequestBody::QueryPorts {
+            filter: filter.to_string(),
+        };
+        match self.call(body).await? {
+            ResponseBody::Ports(ports) => Ok(ports),
+            other => Err(unexpected(other)),
+        }
+    }
+
+    pub async fn scan_ports_page(&mut self, query: PageQuery) -> AppResult<PortPage> {
+        match self.call(RequestBody::ScanPortsPage { query }).await? {
+            ResponseBody::Page(page) => Ok(page),
+            other => Err(unexpected(other)),
+        }
+    }
+
+    pub async fn query_port(
+        &mut self,
+        port: u16,
+        protocol: Option<Protocol>,
+    ) -> AppResult<Vec<PortInfo>> {
+        match self.call(RequestBody::QueryPort { port, protocol }).await? {
+            ResponseBody::Ports(ports) => Ok(ports),
+            other => Err(unexpected(other)),
+        }
+    }
+
+    pub async fn get_process_info(&mut self, pid: u32) -> AppResult<Option<ProcessInfo>> {
+        match self.call(RequestBody::GetProcessInfo { pid }).await? {
+            ResponseBody::Process(info) => Ok(info),
+            other => Err(unexpected(other)),
+        }
+    }
+
+    pub async fn get_process_executable(&mut self, pid: u32) -> AppResult<Option<ProcessInfo>> {
+        match self.call(RequestBody::GetProcessExecutable { pid }).await? {
+            ResponseBody::Process(info) => Ok(info),
+            other => Err(unexpected(other)),
+        }
+    }
+
+    pub async fn kill_process(&mut self, pid: u32, create_time: Option<u64>) -> AppResult<()> {
+        self.expect_done(RequestBody::KillProcess { pid, create_time })
+            .await
+    }
+
+    pub async fn resync(&mut self) -> AppResult<()> {
+        self.expect_done(RequestBody::Resync).await
+    }
+
+    pub async fn watch_port(&mut self, port: u16, protocol: Protocol) -> AppResult<PortTimeline> {
+        match self.call(RequestBody::WatchPort { port, protocol }).await? {
+            ResponseBody::Timeline(Some(timeline)) => Ok(timeline),
+            ResponseBody::Timeline(None) => Err(AppError::PortNotFound(port)),
+            other => Err(unexpected(other)),
+        }
+    }
+
+    pub async fn unwatch_port(&mut self, port: u16, protocol: Protocol) -> AppResult<bool> {
+        match self.call(RequestBody::UnwatchPort { port, protocol }).await? {
+            ResponseBody::Bool(existed) => Ok(existed),
+            other => Err(unexpected(other)),
+        }
+    }
+
+    pub async fn list_watches(&mut self) -> AppResult<Vec<PortTimeline>> {
+        match self.call(RequestBody::ListWatches).await? {
+            ResponseBody::Timelines(timelines) => Ok(timelines),
+            other => Err(unexpected(other)),
+        }
+    }
+
+    pub async fn get_port_timeline(
+        &mut self,
+        port: u16,
+        protocol: Protocol,
+    ) -> AppResult<Option<PortTimeline>> {
+        match self.call(RequestBody::GetPortTimeline { port, protocol }).await? {
+            ResponseBody::Timeline(timeline) => Ok(timeline),
+            other => Err(unexpected(other)),
+        }
+    }
+
+    pub async fn save_snapshot(
+        &mut self,
+        name: &str,
+        listen_only: bool,
+    ) -> AppResult<SnapshotSummary> {
+        let body = RequestBody::SaveSnapshot {
+            name: name.to_string(),
+            listen_only,
+        };
+        match self.call(body).await? {
+            ResponseBody::SnapshotSummary(summary) => Ok(summary),
+            other => Err(unexpected(other)),
+        }
+    }
+
+    pub async fn list_snapshots(&mut self) -> AppResult<Vec<SnapshotSummary>> {
+        match self.call(RequestBody::ListSnapshots).await? {
+            ResponseBody::Snapshots(list) => Ok(list),
+            other => Err(unexpected(other)),
+        }
+    }
+
+    pub async fn load_snapshot(&mut self, name: &str) -> AppResult<SavedSnapshot> {
+        let body = RequestBody::LoadSnapshot {
+            name: name.to_string(),
+        };
+        match self.call(body).await? {
+            ResponseBody::Snapshot(snapshot) => Ok(snapshot),
+            other => Err(unexpected(other)),
+        }
+    }
+
+    pub async fn delete_snapshot(&mut self, name: &str) -> AppResult<()> {
+        self.expect_done(RequestBody::DeleteSnapshot {
+            name: name.to_string(),
+        })
+        .await
+    }
+
+    pub async fn probe_services(
+        &mut self,
+        ports: Option<Vec<u16>>,
+        config: ProbeConfig,
+    ) -> AppResult<Vec<PortInfo>> {
+        match self.call(RequestBody::ProbeServices { ports, config }).await? {
+            ResponseBody::Ports(ports) => Ok(ports),
+            other => Err(unexpected(other)),
+        }
+    }
+
+    pub async fn probe_http(&mut self, port: u16) -> AppResult<Vec<PortInfo>> {
+        match self.call(RequestBody::ProbeHttp { port }).await? {
+            ResponseBody::Ports(ports) => Ok(ports),
+            other => Err(unexpected(other)),
+        }
+    }
+
+    pub async fn inspect_tls(&mut self, port: u16) -> AppResult<Vec<PortInfo>> {
+        match self.call(RequestBody::InspectTls { port }).await? {
+            ResponseBody::Ports(ports) => Ok(ports),
+            other => Err(unexpected(other)),
+        }
+    }
+
+    pub async fn get_exposure_report(&mut self) -> AppResult<ExposureReport> {
+        match self.call(RequestBody::GetExposureReport).await? {
+            ResponseBody::Exposure(report) => Ok(report),
+            other => Err(unexpected(other)),
+        }
+    }
+
+    pub async fn get_firewall_status(&mut self) -> AppResult<Vec<PortInfo>> {
+        match self.call(RequestBody::GetFirewallStatus).await? {
+            ResponseBody::Ports(ports) => Ok(ports),
+            other => Err(unexpected(other)),
+        }
+    }
+
+    pub async fn check_compliance(&mut self, policy: ListenerPolicy) -> AppResult<ComplianceReport> {
+        match self.call(RequestBody::CheckCompliance { policy }).await? {
+            ResponseBody::Compliance(report) => Ok(report),
+            other => Err(unexpected(other)),
+        }
+    }
+
+    pub async fn list_service_overrides(&mut self) -> AppResult<Vec<ServiceEntry>> {
+        match self.call(RequestBody::ListServiceOverrides).await? {
+            ResponseBody::Services(entries) => Ok(entries),
+            other => Err(unexpected(other)),
+        }
+    }
+
+    pub async fn set_service_overrides(&mut self, entries: Vec<ServiceEntry>) -> AppResult<()> {
+        self.expect_done(RequestBody::SetServiceOverrides { entries })
+            .await
+    }
+
+    pub async fn start_baseline_training(&mut self, duration_ms: u64) -> AppResult<BaselineStatus> {
+        match self.call(RequestBody::StartBaselineTraining { duration_ms }).await? {
+            ResponseBody::BaselineStatus(status) => Ok(status),
+            other => Err(unexpected(other)),
+        }
+    }
+
+    pub async fn get_baseline_status(&mut self) -> AppResult<BaselineStatus> {
+        match self.call(RequestBody::GetBaselineStatus).await? {
+            ResponseBody::BaselineStatus(status) => Ok(status),
+            other => Err(unexpected(other)),
+        }
+    }
+
+    pub async fn get_baseline(&mut self) -> AppResult<Option<Baseline>> {
+        match self.call(RequestBody::GetBaseline).await? {
+            ResponseBody::Baseline(baseline) => Ok(baseline),
+            other => Err(unexpected(other)),
+        }
+    }
+
+    pub async fn list_drifts(&mut self) -> AppResult<Vec<DriftEvent>> {
+        match self.call(RequestBody::ListDrifts).await? {
+            ResponseBody::Drifts(drifts) => Ok(drifts),
+            other => Err(unexpected(other)),
+        }
+    }
+
+    pub async fn approve_drifts(&mut self, ids: Vec<u64>) -> AppResult<usize> {
+        match self.call(RequestBody::ApproveDrifts { ids }).await? {
+            ResponseBody::Count(count) => Ok(count),
+            other => Err(unexpected(other)),
+        }
+    }
+
+    pub async fn dismiss_drifts(&mut self, ids: Vec<u64>) -> AppResult<usize> {
+        match self.call(RequestBody::DismissDrifts { ids }).await? {
+            ResponseBody::Count(count) => Ok(count),
+            other => Err(unexpected(other)),
+        }
+    }
+
+    pub async fn detect_suspicious(&mut self) -> AppResult<Vec<Finding>> {
+        match self.call(RequestBody::DetectSuspicious).await? {
+            ResponseBody::Findings(findings) => Ok(findings),
+            other => Err(unexpected(other)),
+        }
+    }
+
+    pub async fn get_connection_summary(
+        &mut self,
+        limit: Option<usize>,
+    ) -> AppResult<ConnectionSummary> {
+        match self.call(RequestBody::GetConnectionSummary { limit }).await? {
+            ResponseBody::ConnectionSummary(summary) => Ok(summary),
+            other => Err(unexpected(other)),
+        }
+    }
+
+    pub async fn set_reverse_dns(&mut self, enabled: bool) -> AppResult<()> {
+        self.expect_done(RequestBody::SetReverseDns { enabled }).await
+    }
+
+    pub async fn resolve_hostname(&mut self, addr: String) -> AppResult<Option<String>> {
+        match self.call(RequestBody::ResolveHostname { addr }).await? {
+            ResponseBody::Hostname(name) => Ok(name),
+            other => Err(unexpected(other)),
+        }
+    }
+
+    /// 订阅监控事件，连接随后专用于接收事件
+    pub async fn subscribe(mut self) -> AppResult<DaemonEvents> {
+        self.expect_done(RequestBody::Subscribe).await?;
+        Ok(DaemonEvents {
+            reader: self.reader,
+            _writer: self.writer,
+            id: self.next_id,
+        })
+    }
+
+    async fn expect_done(&mut self, body: RequestBody) -> AppResult<()> {
+        match self.call(body).await? {
+            ResponseBody::Done => Ok(()),
+            other => Err(unexpected(other)),
+        }
+    }
+}
+
+/// 守护进程推送的监控事件流
+pub struct DaemonEvents {
+    reader: BufReader<BoxReader>,
+    /// 保持写端打开，避免守护进程认为连接已关闭
+    _writer: BoxWriter,
+    id: u64,
+}
+
+impl DaemonEvents {
+    /// 下一个事件，连接断开时返回 None
+    pub async fn next(&mut self) -> AppResult<Option<MonitorEvent>> {
+        loop {
+            let response: Option<Response> = read_message(&mut self.reader).await?;
+            match response {
+                Some(Response {
+                    id,
+                    body: ResponseBody::Event(event),
+                    ..
+                }) if id == self.id => return Ok(Some(event)),
+                Some(_) => continue,
+                None => return Ok(None),
+            }
+        }
+    }
+}
+
+fn unexpected(body: ResponseBody) -> AppError {
+    let kind = serde_json::to_value(&body)
+        .ok()
+        .and_then(|v| v.get("type").and_then(|t| t.as_str()).map(str::to_string))
+        .unwrap_or_default();
+    AppError::Ipc(format!("意外的响应类型: {}", kind))
+}
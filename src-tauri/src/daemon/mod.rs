@@ -0,0 +1,19 @@
+//! 后台守护进程与 IPC
+//!
+//! 守护进程以特权身份常驻运行，负责扫描、监控和历史记录；
+//! GUI 与 CLI 作为客户端通过本地 IPC 访问，无需提升权限
+
+pub mod client;
+pub mod protocol;
+pub mod server;
+pub mod transport;
+
+pub use client::{DaemonClient, DaemonEvents};
+pub use protocol::{DaemonInfo, PROTOCOL_VERSION};
+pub use server::{run, DaemonConfig};
+pub use transport::{default_endpoint, ListenOptions};
+
+/// 守护进程是否在默认地址上运行
+pub fn is_running() -> bool {
+    transport::is_listening(&default_endpoint())
+}
@@ -0,0 +1,322 @@
+//! IPC 协议
+//!
+//! 每条消息为一行 JSON。请求和响应都携带协议版本 `version` 与请求 ID `id`，
+//! 守护进程拒绝版本不一致的请求。订阅事件后，事件以 `event` 响应推送，`id` 为订阅请求的 ID
+
+use serde::{Deserialize, Serialize};
+
+use crate::core::probe::ProbeConfig;
+use crate::core::query::{PageQuery, PortPage};
+use crate::core::snapshot_store::{SavedSnapshot, SnapshotSummary};
+use crate::core::watch::PortTimeline;
+use crate::core::PortFilter;
+use crate::models::{
+    AppError, Baseline, BaselineStatus, ComplianceReport, ConnectionSummary, DriftEvent, ExposureReport, Finding,
+    ListenerPolicy, MonitorEvent, PortInfo, ProcessInfo, Protocol, ServiceEntry,
+};
+
+/// 协议版本，请求或响应结构发生不兼容变化时递增
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// 请求
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Request {
+    pub version: u32,
+    pub id: u64,
+    #[serde(flatten)]
+    pub body: RequestBody,
+}
+
+/// 请求内容
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "method", content = "params", rename_all = "snake_case")]
+pub enum RequestBody {
+    /// 握手，返回守护进程信息
+    Hello,
+    /// 按条件扫描端口
+    ScanPorts { filter: PortFilter },
+    /// 按过滤表达式扫描端口，表达式在守护进程中解析
+    QueryPorts { filter: String },
+    /// 分页查询，携带 scan_id 时复用守护进程缓存的扫描结果
+    ScanPortsPage { query: PageQuery },
+    /// 查询指定端口
+    QueryPort {
+        port: u16,
+        protocol: Option<Protocol>,
+    },
+    /// 获取进程信息
+    GetProcessInfo { pid: u32 },
+    /// 获取进程信息并检查其可执行文件
+    GetProcessExecutable { pid: u32 },
+    /// 终止进程
+    KillProcess {
+        pid: u32,
+        create_time: Option<u64>,
+    },
+    /// 订阅监控事件，先收到当前快照
+    Subscribe,
+    /// 取消订阅
+    Unsubscribe,
+    /// 重新发送基线快照
+    Resync,
+    /// 开始跟踪端口
+    WatchPort { port: u16, protocol: Protocol },
+    /// 停止跟踪端口
+    UnwatchPort { port: u16, protocol: Protocol },
+    /// 所有正在跟踪的端口时间线
+    ListWatches,
+    /// 获取端口时间线
+    GetPortTimeline { port: u16, protocol: Protocol },
+    /// 扫描当前端口并保存为命名快照
+    SaveSnapshot { name: String, listen_only: bool },
+    /// 列出已保存的快照
+    ListSnapshots,
+    /// 加载快照
+    LoadSnapshot { name: String },
+    /// 删除快照
+    DeleteSnapshot { name: String },
+    /// 探测 TCP 监听端口上的服务
+    ProbeServices {
+        ports: Option<Vec<u16>>,
+        config: ProbeConfig,
+    },
+    /// 探测端口上的 HTTP 服务
+    ProbeHttp { port: u16 },
+    /// 检查端口上的 TLS 证书
+    InspectTls { port: u16 },
+    /// 生成网络暴露面报告
+    GetExposureReport,
+    /// 扫描监听端口并判断防火墙是否放行
+    GetFirewallStatus,
+    /// 按监听策略检查当前监听端口
+    CheckCompliance { policy: ListenerPolicy },
+    /// 获取用户自定义的服务名
+    ListServiceOverrides,
+    /// 替换用户自定义的服务名
+    SetServiceOverrides { entries: Vec<ServiceEntry> },
+    /// 开始学习行为基线
+    StartBaselineTraining { duration_ms: u64 },
+    /// 获取基线状态
+    GetBaselineStatus,
+    /// 获取当前基线
+    GetBaseline,
+    /// 列出待处理的偏离事件
+    ListDrifts,
+    /// 将偏离并入基线
+    ApproveDrifts { ids: Vec<u64> },
+    /// 忽略偏离
+    DismissDrifts { ids: Vec<u64> },
+    /// 运行可疑行为检测
+    DetectSuspicious,
+    /// 汇总已建立的连接，每个维度最多返回 `limit` 个分组
+    GetConnectionSummary { limit: Option<usize> },
+    /// 开启或关闭远程地址的反向 DNS 查询
+    SetReverseDns { enabled: bool },
+    /// 解析单个地址的主机名（等待查询完成）
+    ResolveHostname { addr: String },
+}
+
+impl RequestBody {
+    /// 方法名（用于日志）
+    pub fn method(&self) -> &'static str {
+        match self {
+            Self::Hello => "hello",
+            Self::ScanPorts { .. } => "scan_ports",
+            Self::QueryPorts { .. } => "query_ports",
+            Self::ScanPortsPage { .. } => "scan_ports_page",
+            Self::QueryPort { .. } => "query_port",
+            Self::GetProcessInfo { .. } => "get_process_info",
+            Self::GetProcessExecutable { .. } => "get_process_executable",
+            Self::KillProcess { .. } => "kill_process",
+            Self::Subscribe => "subscribe",
+            Self::Unsubscribe => "unsubscribe",
+            Self::Resync => "resync",
+            Self::WatchPort { .. } => "watch_port",
+            Self::UnwatchPort { .. } => "unwatch_port",
+            Self::ListWatches => "list_watches",
+            Self::GetPortTimeline { .. } => "get_port_timeline",
+            Self::SaveSnapshot { .. } => "save_snapshot",
+            Self::ListSnapshots => "list_snapshots",
+            Self::LoadSnapshot { .. } => "load_snapshot",
+            Self::DeleteSnapshot { .. } => "delete_snapshot",
+            Self::ProbeServices { .. } => "probe_services",
+            Self::ProbeHttp { .. } => "probe_http",
+            Self::InspectTls { .. } => "inspect_tls",
+            Self::GetExposureReport => "get_exposure_report",
+            Self::GetFirewallStatus => "get_firewall_status",
+            Self::CheckCompliance { .. } => "check_compliance",
+            Self::ListServiceOverrides => "list_service_overrides",
+            Self::SetServiceOverrides { .. } => "set_service_overrides",
+            Self::StartBaselineTraining { .. } => "start_baseline_training",
+            Self::GetBaselineStatus => "get_baseline_status",
+            Self::GetBaseline => "get_baseline",
+            Self::ListDrifts => "list_drifts",
+            Self::ApproveDrifts { .. } => "approve_drifts",
+            Self::DismissDrifts { .. } => "dismiss_drifts",
+            Self::DetectSuspicious => "detect_suspicious",
+            Self::GetConnectionSummary { .. } => "get_connection_summary",
+            Self::SetReverseDns { .. } => "set_reverse_dns",
+            Self::ResolveHostname { .. } => "resolve_hostname",
+        }
+    }
+
+    /// 是否修改守护进程的共享状态或持久化数据
+    ///
+    /// 这类请求只允许管理组成员调用，未配置管理组时所有能连接套接字 / 管道的用户都可以调用；
+    /// 终止进程另有检查，普通用户只能终止自己的进程
+    pub fn requires_admin(&self) -> bool {
+        match self {
+            Self::WatchPort { .. }
+            | Self::UnwatchPort { .. }
+            | Self::SaveSnapshot { .. }
+            | Self::DeleteSnapshot { .. }
+            | Self::SetServiceOverrides { .. }
+            | Self::StartBaselineTraining { .. }
+            | Self::ApproveDrifts { .. }
+            | Self::DismissDrifts { .. }
+            | Self::SetReverseDns { .. } => true,
+            Self::Hello
+            | Self::ScanPorts { .. }
+            | Self::QueryPorts { .. }
+            | Self::ScanPortsPage { .. }
+            | Self::QueryPort { .. }
+            | Self::GetProcessInfo { .. }
+            | Self::GetProcessExecutable { .. }
+            | Self::KillProcess { .. }
+            | Self::Subscribe
+            | Self::Unsubscribe
+            | Self::Resync
+            | Self::ListWatches
+            | Self::GetPortTimeline { .. }
+            | Self::ListSnapshots
+            | Self::LoadSnapshot { .. }
+            | Self::ProbeServices { .. }
+            | Self::ProbeHttp { .. }
+            | Self::InspectTls { .. }
+            | Self::GetExposureReport
+            | Self::GetFirewallStatus
+            | Self::CheckCompliance { .. }
+            | Self::ListServiceOverrides
+            | Self::GetBaselineStatus
+            | Self::GetBaseline
+            | Self::ListDrifts
+            | Self::DetectSuspicious
+            | Self::GetConnectionSummary { .. }
+            | Self::ResolveHostname { .. } => false,
+        }
+    }
+}
+
+/// 响应
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Response {
+    pub version: u32,
+    pub id: u64,
+    #[serde(flatten)]
+    pub body: ResponseBody,
+}
+
+/// 响应内容
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", content = "data", rename_all = "snake_case")]
+pub enum ResponseBody {
+    Hello(DaemonInfo),
+    Ports(Vec<PortInfo>),
+    Page(PortPage),
+    Process(Option<ProcessInfo>),
+    /// 操作完成，无返回值
+    Done,
+    /// 操作完成，返回是否生效（如取消跟踪时端口是否在跟踪中）
+    Bool(bool),
+    /// 操作完成，返回处理的数量
+    Count(usize),
+    Event(MonitorEvent),
+    Timeline(Option<PortTimeline>),
+    Timelines(Vec<PortTimeline>),
+    Snapshot(SavedSnapshot),
+    SnapshotSummary(SnapshotSummary),
+    Snapshots(Vec<SnapshotSummary>),
+    Services(Vec<ServiceEntry>),
+    Exposure(ExposureReport),
+    Compliance(ComplianceReport),
+    BaselineStatus(BaselineStatus),
+    Baseline(Option<Baseline>),
+    Drifts(Vec<DriftEvent>),
+    Findings(Vec<Finding>),
+    ConnectionSummary(ConnectionSummary),
+    Hostname(Option<String>),
+    Error(IpcError),
+}
+
+/// 守护进程信息
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DaemonInfo {
+    /// 协议版本
+    pub protocol_version: u32,
+    /// 守护进程程序版本
+    pub daemon_version: String,
+    /// 守护进程 PID
+    pub pid: u32,
+    /// 启动时间（Unix 时间戳，毫秒）
+    pub started_at: u64,
+    /// 监控采样间隔（毫秒）
+    pub interval_ms: u64,
+    /// 是否只监控监听端口
+    pub listen_only: bool,
+}
+
+/// 错误类别
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    /// 协议版本不一致
+    UnsupportedVersion,
+    /// 请求格式或参数错误
+    BadRequest,
+    NotFound,
+    AccessDenied,
+    /// 受保护进程或 PID 已复用
+    Conflict,
+    Internal,
+}
+
+/// 错误响应
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IpcError {
+    pub code: ErrorCode,
+    pub message: String,
+}
+
+impl IpcError {
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+impl From<AppError> for IpcError {
+    fn from(e: AppError) -> Self {
+        let code = match &e {
+            AppError::ProcessNotFound(_)
+            | AppError::PortNotFound(_)
+            | AppError::SnapshotNotFound(_)
+            | AppError::ScanExpired(_) => ErrorCode::NotFound,
+            AppError::AccessDenied => ErrorCode::AccessDenied,
+            AppError::ProtectedProcess(_) | AppError::PidReused => ErrorCode::Conflict,
+            AppError::InvalidArgument(_) => ErrorCode::BadRequest,
+            _ => ErrorCode::Internal,
+        };
+        Self::new(code, e.to_string())
+    }
+}
+
+impl From<IpcError> for AppError {
+    fn from(e: IpcError) -> Self {
+        match e.code {
+            ErrorCode::AccessDenied => AppError::AccessDenied,
+            _ => AppError::Daemon(e.message),
+        }
+    }
+}
@@ -0,0 +1,493 @@
+//! 守护进程服务端
+
+use std::future::Future;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use tokio::sync::{mpsc, Mutex};
+
+use super::protocol::{
+    DaemonInfo, ErrorCode, IpcError, Request, RequestBody, Response, ResponseBody,
+    PROTOCOL_VERSION,
+};
+use super::transport::{
+    read_message_limited, write_message, Connection, IpcListener, ListenOptions, PeerInfo,
+    MAX_MESSAGE_BYTES,
+};
+use crate::core::analytics::{connection_summary, ConnectionTracker, DEFAULT_SUMMARY_LIMIT};
+use crate::core::baseline::BaselineRecorder;
+use crate::core::detection::{detect_suspicious, ConnectionHistory};
+use crate::core::compliance::scan_compliance;
+use crate::core::executable::inspect_process;
+use crate::core::exposure::exposure_report;
+use crate::core::firewall::scan_firewall;
+use crate::core::monitor::{start_monitor_service, MonitorConfig, MonitorHandle};
+use crate::core::probe::{probe_listening, scan_page, scan_query, HttpProber, TlsInspector};
+use crate::core::query::{PortQuery, ScanCache};
+use crate::core::resolver::{hostname_resolver, ResolverConfig};
+use crate::core::sink::{ClientFanout, ClientSubscription};
+use crate::core::snapshot_store::SnapshotStore;
+use crate::core::trigger::platform_trigger_sources;
+use crate::core::watch::{port_occupant, WatchRegistry};
+use crate::core::{metrics, PortFilter, PortScanner};
+use crate::models::{service_registry, AppError, AppResult, MonitorEvent};
+use crate::utils::net::parse_addr;
+use crate::utils::time::now_millis;
+
+/// 守护进程配置
+#[derive(Debug, Clone)]
+pub struct DaemonConfig {
+    /// IPC 地址
+    pub endpoint: String,
+    /// 监听选项
+    pub listen: ListenOptions,
+    /// 监控配置
+    pub monitor: MonitorConfig,
+    /// 数据目录（快照等）
+    pub data_dir: PathBuf,
+    /// 远程地址主机名解析
+    pub resolver: ResolverConfig,
+}
+
+/// 非特权客户端单条请求的最大长度
+const MAX_UNPRIVILEGED_REQUEST_BYTES: u64 = 1024 * 1024;
+
+/// 请求处理共享状态
+struct DaemonContext {
+    info: DaemonInfo,
+    scanner: PortScanner,
+    monitor: MonitorHandle,
+    watches: Arc<Mutex<WatchRegistry>>,
+    baseline: Arc<std::sync::Mutex<BaselineRecorder>>,
+    /// 可疑行为检测使用的连接记录
+    history: Arc<std::sync::Mutex<ConnectionHistory>>,
+    /// 已建立连接的汇总
+    connections: Arc<std::sync::Mutex<ConnectionTracker>>,
+    fanout: ClientFanout,
+    snapshots: SnapshotStore,
+    scans: ScanCache,
+    http: HttpProber,
+    tls: TlsInspector,
+    /// 服务名覆盖文件
+    service_overrides: PathBuf,
+}
+
+/// 运行守护进程，直到 `shutdown` 完成
+pub async fn run(config: DaemonConfig, shutdown: impl Future<Output = ()>) -> AppResult<()> {
+    let mut listener = IpcListener::bind(&config.endpoint, &config.listen)?;
+    tracing::info!("Daemon listening on {}", listener.endpoint());
+
+    let service_overrides = config.data_dir.join("service_overrides.json");
+    if let Err(e) = service_registry()
+        .write()
+        .unwrap_or_else(|e| e.into_inner())
+        .load_overrides(&service_overrides)
+    {
+        tracing::warn!("Failed to load service overrides: {}", e);
+    }
+
+    hostname_resolver().configure(config.resolver.clone());
+
+    let mut recorder = BaselineRecorder::new();
+    if let Err(e) = recorder.load(&config.data_dir.join("baseline.json")) {
+        tracing::warn!("Failed to load baseline: {}", e);
+    }
+    let baseline = Arc::new(std::sync::Mutex::new(recorder));
+    let history = Arc::new(std::sync::Mutex::new(ConnectionHistory::new()));
+    let connections = Arc::new(std::sync::Mutex::new(ConnectionTracker::new()));
+
+    let watches = Arc::new(Mutex::new(WatchRegistry::new()));
+    let (event_tx, mut event_rx) = mpsc::channel(32);
+    let triggers = if config.monitor.event_driven {
+        platform_trigger_sources()
+    } else {
+        Vec::new()
+    };
+    let monitor =
+        start_monitor_service(config.monitor.clone(), event_tx, watches.clone(), triggers).await;
+
+    let fanout = ClientFanout::new();
+    let publisher = fanout.clone();
+    let observer = baseline.clone();
+    let recent = history.clone();
+    let tracker = connections.clone();
+    tokio::spawn(async move {
+        while let Some(event) = event_rx.recv().await {
+            observer
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .observe(&event);
+            recent
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .observe(&event);
+            tracker
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .observe(&event);
+            publisher.publish(event);
+        }
+        publisher.close();
+    });
+
+    let ctx = Arc::new(DaemonContext {
+        info: DaemonInfo {
+            protocol_version: PROTOCOL_VERSION,
+            daemon_version: env!("CARGO_PKG_VERSION").to_string(),
+            pid: std::process::id(),
+            started_at: now_millis(),
+            interval_ms: config.monitor.interval_ms,
+            listen_only: config.monitor.listen_only,
+        },
+        scanner: PortScanner::new(),
+        monitor: monitor.clone(),
+        watches,
+        baseline,
+        history,
+        connections,
+        fanout,
+        snapshots: SnapshotStore::new(config.data_dir.join("snapshots")),
+        scans: ScanCache::default(),
+        http: HttpProber::default(),
+        tls: TlsInspector::default(),
+        service_overrides,
+    });
+
+    tokio::pin!(shutdown);
+    loop {
+        tokio::select! {
+            _ = &mut shutdown => break,
+            accepted = listener.accept() => match accepted {
+                Ok(conn) => {
+                    tokio::spawn(handle_connection(ctx.clone(), conn));
+                }
+                Err(e) => tracing::warn!("IPC accept failed: {}", e),
+            },
+        }
+    }
+
+    monitor.stop().await;
+    tracing::info!("Daemon stopped");
+    Ok(())
+}
+
+/// 单条请求的最大长度
+fn request_limit(peer: &PeerInfo) -> u64 {
+    if peer.privileged {
+        MAX_MESSAGE_BYTES
+    } else {
+        MAX_UNPRIVILEGED_REQUEST_BYTES
+    }
+}
+
+/// 处理单个连接，请求按顺序处理，订阅的事件与响应交错发送
+async fn handle_connection(ctx: Arc<DaemonContext>, mut conn: Connection) {
+    // Windows 上读到第一条消息后才能确定对端身份，第一条消息按当前已知身份限制长度
+    let first =
+        read_message_limited::<serde_json::Value, _>(&mut conn.reader, request_limit(&conn.peer))
+            .await;
+    conn.resolve_peer();
+
+    let Connection {
+        mut reader,
+        mut writer,
+        peer,
+        ..
+    } = conn;
+    let limit = request_limit(&peer);
+
+    // 读取放在独立任务中，避免与事件推送竞争时丢失半行数据
+    let (request_tx, mut request_rx) = mpsc::channel::<Result<Request, (u64, IpcError)>>(8);
+    tokio::spawn(async move {
+        let mut read = first;
+        loop {
+            let parsed = match read {
+                Ok(Some(value)) => parse_request(value),
+                Ok(None) => break,
+                Err(AppError::InvalidArgument(msg)) => Err((0, IpcError::new(ErrorCode::BadRequest, msg))),
+                Err(e) => {
+                    tracing::debug!("IPC read failed: {}", e);
+                    break;
+                }
+            };
+            if request_tx.send(parsed).await.is_err() {
+                break;
+            }
+            read = read_message_limited::<serde_json::Value, _>(&mut reader, limit).await;
+        }
+    });
+
+    let mut subscription: Option<(u64, ClientSubscription)> = None;
+
+    loop {
+        let response = tokio::select! {
+            request = request_rx.recv() => match request {
+                Some(Ok(request)) => {
+                    let id = request.id;
+                    let body = match request.body {
+                        RequestBody::Subscribe => {
+                            subscription = Some((id, ctx.fanout.subscribe()));
+                            ResponseBody::Done
+                        }
+                        RequestBody::Unsubscribe => {
+                            subscription = None;
+                            ResponseBody::Done
+                        }
+                        body => {
+                            let method = body.method();
+                            match ctx.handle(body, &peer).await {
+                                Ok(body) => body,
+                                Err(e) => {
+                                    tracing::debug!("IPC {} failed: {}", method, e);
+                                    ResponseBody::Error(e.into())
+                                }
+                            }
+                        }
+                    };
+                    Response { version: PROTOCOL_VERSION, id, body }
+                }
+                Some(Err((id, error))) => Response {
+                    version: PROTOCOL_VERSION,
+                    id,
+                    body: ResponseBody::Error(error),
+                },
+                None => break,
+            },
+            event = next_event(&mut subscription) => match event {
+                Some((id, event)) => Response {
+                    version: PROTOCOL_VERSION,
+                    id,
+                    body: ResponseBody::Event(event),
+                },
+                None => {
+                    subscription = None;
+                    continue;
+                }
+            },
+        };
+
+        if write_message(&mut writer, &response).await.is_err() {
+            break;
+        }
+    }
+}
+
+/// 校验协议版本后解析请求
+fn parse_request(value: serde_json::Value) -> Result<Request, (u64, IpcError)> {
+    let id = value.get("id").and_then(|v| v.as_u64()).unwrap_or(0);
+    let version = value.get("version").and_then(|v| v.as_u64());
+
+    if version != Some(PROTOCOL_VERSION as u64) {
+        return Err((
+            id,
+            IpcError::new(
+                ErrorCode::UnsupportedVersion,
+                format!("不支持的协议版本 {:?}，守护进程版本为 {}", version, PROTOCOL_VERSION),
+            ),
+        ));
+    }
+
+    serde_json::from_value(value)
+        .map_err(|e| (id, IpcError::new(ErrorCode::BadRequest, e.to_string())))
+}
+
+/// 订阅中的下一个事件，未订阅时永不返回
+async fn next_event(
+    subscription: &mut Option<(u64, ClientSubscription)>,
+) -> Option<(u64, MonitorEvent)> {
+    match subscription {
+        Some((id, sub)) => sub.next().await.map(|event| (*id, event)),
+        None => std::future::pending().await,
+    }
+}
+
+impl DaemonContext {
+    fn baseline(&self) -> std::sync::MutexGuard<'_, BaselineRecorder> {
+        self.baseline.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    async fn handle(&self, body: RequestBody, peer: &PeerInfo) -> AppResult<ResponseBody> {
+        if body.requires_admin() && !peer.admin {
+            return Err(AppError::AccessDenied);
+        }
+
+        let response = match body {
+            RequestBody::Hello => ResponseBody::Hello(self.info.clone()),
+            RequestBody::ScanPorts { filter } => {
+                ResponseBody::Ports(self.scanner.scan_filtered(&filter)?)
+            }
+            RequestBody::QueryPorts { filter } => {
+                let query = PortQuery::parse(&filter)?;
+                ResponseBody::Ports(scan_query(&self.scanner, &self.tls, &query).await?)
+            }
+            RequestBody::ScanPortsPage { query } => {
+                let mut page = scan_page(&self.scans, &self.scanner, &self.tls, &query).await?;
+                if query.http {
+                    self.http.enrich(&mut page.items).await;
+                }
+                ResponseBody::Page(page)
+            }
+            RequestBody::QueryPort { port, protocol } => {
+                ResponseBody::Ports(self.scanner.query_port(port, protocol)?)
+            }
+            RequestBody::GetProcessInfo { pid } => {
+                ResponseBody::Process(self.scanner.processes().get_process_info(pid)?)
+            }
+            RequestBody::GetProcessExecutable { pid } => {
+                ResponseBody::Process(inspect_process(self.scanner.processes().as_ref(), pid)?)
+            }
+            RequestBody::KillProcess { pid, create_time } => {
+                authorize_kill(peer, pid)?;
+                let result = self.scanner.processes().kill_process(pid, create_time);
+                metrics::counters().record_kill(result.is_ok());
+                result?;
+                tracing::info!("Process {} killed on behalf of {:?}", pid, peer);
+                ResponseBody::Done
+            }
+            RequestBody::Resync => {
+                self.monitor.resync().await;
+                ResponseBody::Done
+            }
+            RequestBody::WatchPort { port, protocol } => {
+                let key = (port, protocol);
+                let sockets = self.scanner.query_port(port, Some(protocol))?;
+                let now = now_millis();
+                let mut watches = self.watches.lock().await;
+                watches.watch(key, port_occupant(&sockets, key), now);
+                ResponseBody::Timeline(watches.timeline(&key, now))
+            }
+            RequestBody::UnwatchPort { port, protocol } => {
+                ResponseBody::Bool(self.watches.lock().await.unwatch(&(port, protocol)))
+            }
+            RequestBody::ListWatches => {
+                let now = now_millis();
+                let watches = self.watches.lock().await;
+                ResponseBody::Timelines(
+                    watches
+                        .watched()
+                        .iter()
+                        .filter_map(|key| watches.timeline(key, now))
+                        .collect(),
+                )
+            }
+            RequestBody::GetPortTimeline { port, protocol } => ResponseBody::Timeline(
+                self.watches
+                    .lock()
+                    .await
+                    .timeline(&(port, protocol), now_millis()),
+            ),
+            RequestBody::SaveSnapshot { name, listen_only } => {
+                let filter = PortFilter {
+                    listen_only: Some(listen_only),
+                    ..PortFilter::default()
+                };
+                let ports = self.scanner.scan_filtered(&filter)?;
+                ResponseBody::SnapshotSummary(self.snapshots.save(&name, ports, listen_only)?)
+            }
+            RequestBody::ListSnapshots => ResponseBody::Snapshots(self.snapshots.list()?),
+            RequestBody::LoadSnapshot { name } => ResponseBody::Snapshot(self.snapshots.load(&name)?),
+            RequestBody::DeleteSnapshot { name } => {
+                self.snapshots.delete(&name)?;
+                ResponseBody::Done
+            }
+            RequestBody::ProbeServices { ports, config } => {
+                let config = if peer.privileged {
+                    config
+                } else {
+                    config.limited()
+                };
+                ResponseBody::Ports(probe_listening(&self.scanner, ports.as_deref(), &config).await?)
+            }
+            RequestBody::ProbeHttp { port } => {
+                ResponseBody::Ports(self.http.probe_port(&self.scanner, port).await?)
+            }
+            RequestBody::InspectTls { port } => {
+                ResponseBody::Ports(self.tls.inspect_port(&self.scanner, port).await?)
+            }
+            RequestBody::GetExposureReport => {
+                ResponseBody::Exposure(exposure_report(&self.scanner)?)
+            }
+            RequestBody::GetFirewallStatus => ResponseBody::Ports(scan_firewall(&self.scanner)?),
+            RequestBody::CheckCompliance { policy } => {
+                ResponseBody::Compliance(scan_compliance(&self.scanner, &policy)?)
+            }
+            RequestBody::ListServiceOverrides => {
+                let registry = service_registry().read().unwrap_or_else(|e| e.into_inner());
+                ResponseBody::Services(registry.overrides())
+            }
+            RequestBody::SetServiceOverrides { entries } => {
+                let mut registry = service_registry().write().unwrap_or_else(|e| e.into_inner());
+                registry.set_overrides(entries);
+                registry.save_overrides(&self.service_overrides)?;
+                ResponseBody::Done
+            }
+            RequestBody::StartBaselineTraining { duration_ms } => ResponseBody::BaselineStatus(
+                self.baseline().start_training(duration_ms, now_millis())?,
+            ),
+            RequestBody::GetBaselineStatus => {
+                ResponseBody::BaselineStatus(self.baseline().status(now_millis()))
+            }
+            RequestBody::GetBaseline => {
+                ResponseBody::Baseline(self.baseline().baseline().cloned())
+            }
+            RequestBody::ListDrifts => {
+                ResponseBody::Drifts(self.baseline().drifts().to_vec())
+            }
+            RequestBody::ApproveDrifts { ids } => {
+                ResponseBody::Count(self.baseline().approve(&ids)?)
+            }
+            RequestBody::DismissDrifts { ids } => {
+                ResponseBody::Count(self.baseline().dismiss(&ids)?)
+            }
+            RequestBody::DetectSuspicious => {
+                ResponseBody::Findings(detect_suspicious(&self.scanner, &self.history)?)
+            }
+            RequestBody::SetReverseDns { enabled } => {
+                hostname_resolver().set_reverse_dns(enabled);
+                ResponseBody::Done
+            }
+            RequestBody::ResolveHostname { addr } => {
+                let ip = parse_addr(&addr)
+                    .ok_or_else(|| AppError::InvalidArgument(format!("无效的地址: {}", addr)))?;
+                ResponseBody::Hostname(hostname_resolver().resolve(ip).await)
+            }
+            RequestBody::GetConnectionSummary { limit } => {
+                ResponseBody::ConnectionSummary(connection_summary(
+                    &self.scanner,
+                    &self.connections,
+                    limit.unwrap_or(DEFAULT_SUMMARY_LIMIT),
+                )?)
+            }
+            // 订阅请求在连接层处理
+            RequestBody::Subscribe | RequestBody::Unsubscribe => ResponseBody::Done,
+        };
+        Ok(response)
+    }
+}
+
+/// 终止进程的权限检查：root / 管理员可终止任意进程，普通用户只能终止自己的进程
+fn authorize_kill(peer: &PeerInfo, pid: u32) -> AppResult<()> {
+    if peer.privileged {
+        return Ok(());
+    }
+
+    #[cfg(target_os = "linux")]
+    if let (Some(uid), Some(owner)) = (peer.uid, crate::platform::linux::process_uid(pid)) {
+        if uid == owner {
+            return Ok(());
+        }
+    }
+
+    // 比较客户端模拟令牌与目标进程令牌的用户 SID
+    #[cfg(windows)]
+    if let (Some(sid), Ok(owner)) = (&peer.sid, crate::platform::windows::process_user_sid(pid)) {
+        if *sid == owner {
+            return Ok(());
+        }
+    }
+
+    #[cfg(not(any(target_os = "linux", windows)))]
+    let _ = pid;
+
+    Err(AppError::AccessDenied)
+}
@@ -0,0 +1,444 @@
+//! IPC 传输
+//!
+//! Unix 上使用 Unix 域套接字，Windows 上使用命名管道，消息按行分帧
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader};
+
+use crate::models::{AppError, AppResult};
+
+/// 覆盖默认 IPC 地址的环境变量
+pub const ENDPOINT_ENV: &str = "PORT_DETECTION_SOCKET";
+
+/// 单条消息的最大长度
+pub const MAX_MESSAGE_BYTES: u64 = 64 * 1024 * 1024;
+
+/// 默认 IPC 地址
+pub fn default_endpoint() -> String {
+    if let Ok(endpoint) = std::env::var(ENDPOINT_ENV) {
+        if !endpoint.is_empty() {
+            return endpoint;
+        }
+    }
+
+    #[cfg(unix)]
+    {
+        "/run/port-detection/daemon.sock".to_string()
+    }
+
+    #[cfg(windows)]
+    {
+        r"\\.\pipe\port-detection-daemon".to_string()
+    }
+}
+
+/// 监听选项
+#[derive(Debug, Clone)]
+pub struct ListenOptions {
+    /// 套接字文件权限（仅 Unix）
+    pub mode: u32,
+    /// 套接字文件所属组，允许该组用户连接（仅 Unix）
+    pub group: Option<String>,
+    /// 允许修改守护进程状态的组，为 None 时所有能连接的用户都允许（仅 Linux）
+    pub admin_group: Option<String>,
+}
+
+impl Default for ListenOptions {
+    fn default() -> Self {
+        Self {
+            mode: 0o660,
+            group: None,
+            admin_group: None,
+        }
+    }
+}
+
+/// 连接对端信息
+#[derive(Debug, Clone, Default)]
+pub struct PeerInfo {
+    /// 对端用户 ID（仅 Unix）
+    pub uid: Option<u32>,
+    /// 对端用户 SID（仅 Windows，取自模拟令牌）
+    pub sid: Option<String>,
+    /// 对端进程 ID（仅 Unix）
+    pub pid: Option<u32>,
+    /// 对端是否为 root / 管理员
+    pub privileged: bool,
+    /// 是否允许修改守护进程状态（跟踪端口、保存快照、基线审批等）
+    pub admin: bool,
+}
+
+pub type BoxReader = Box<dyn AsyncRead + Send + Unpin>;
+pub type BoxWriter = Box<dyn AsyncWrite + Send + Unpin>;
+
+/// 一条 IPC 连接
+pub struct Connection {
+    pub reader: BufReader<BoxReader>,
+    pub writer: BoxWriter,
+    pub peer: PeerInfo,
+    /// 尚未确定身份的命名管道客户端，句柄随 `reader` / `writer` 一起有效
+    #[cfg(windows)]
+    pipe: Option<isize>,
+}
+
+impl Connection {
+    fn new<S>(stream: S, peer: PeerInfo) -> Self
+    where
+        S: AsyncRead + AsyncWrite + Send + 'static,
+    {
+        let (reader, writer) = tokio::io::split(stream);
+        Self {
+            reader: BufReader::new(Box::new(reader)),
+            writer: Box::new(writer),
+            peer,
+            #[cfg(windows)]
+            pipe: None,
+        }
+    }
+
+    /// 读取第一条消息后确定对端身份
+    ///
+    /// Unix 上身份在连接时已从套接字凭据取得；Windows 上只有从管道读到数据后才能模拟客户端
+    pub fn resolve_peer(&mut self) {
+        #[cfg(windows)]
+        if let Some(handle) = self.pipe.take() {
+            self.peer = pipe_peer(handle);
+        }
+    }
+}
+
+/// 读取一条消息，连接关闭时返回 None
+pub async fn read_message<T, R>(reader: &mut BufReader<R>) -> AppResult<Option<T>>
+where
+    T: DeserializeOwned,
+    R: AsyncRead + Unpin,
+{
+    read_message_limited(reader, MAX_MESSAGE_BYTES).await
+}
+
+/// 读取一条消息，超过 `limit` 字节时返回错误
+pub async fn read_message_limited<T, R>(
+    reader: &mut BufReader<R>,
+    limit: u64,
+) -> AppResult<Option<T>>
+where
+    T: DeserializeOwned,
+    R: AsyncRead + Unpin,
+{
+    let mut line = String::new();
+    let read = reader
+        .take(limit)
+        .read_line(&mut line)
+        .await
+        .map_err(|e| AppError::Ipc(e.to_string()))?;
+
+    if read == 0 {
+        return Ok(None);
+    }
+    if !line.ends_with('\n') && read as u64 == limit {
+        return Err(AppError::Ipc("消息过长".to_string()));
+    }
+
+    serde_json::from_str(&line)
+        .map(Some)
+        .map_err(|e| AppError::InvalidArgument(format!("无法解析消息: {}", e)))
+}
+
+/// 写入一条消息
+pub async fn write_message<T, W>(writer: &mut W, message: &T) -> AppResult<()>
+where
+    T: Serialize,
+    W: AsyncWrite + Unpin,
+{
+    let mut line = serde_json::to_string(message).map_err(|e| AppError::Internal(e.to_string()))?;
+    line.push('\n');
+    writer
+        .write_all(line.as_bytes())
+        .await
+        .map_err(|e| AppError::Ipc(e.to_string()))
+}
+
+/// 检查守护进程是否在监听（同步，可在运行时之外调用）
+pub fn is_listening(endpoint: &str) -> bool {
+    #[cfg(unix)]
+    {
+        std::os::unix::net::UnixStream::connect(endpoint).is_ok()
+    }
+
+    #[cfg(windows)]
+    {
+        std::fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(endpoint)
+            .is_ok()
+    }
+}
+
+/// 连接守护进程
+pub async fn connect(endpoint: &str) -> AppResult<Connection> {
+    #[cfg(unix)]
+    {
+        let stream = tokio::net::UnixStream::connect(endpoint)
+            .await
+            .map_err(|e| AppError::Ipc(format!("{}: {}", endpoint, e)))?;
+        Ok(Connection::new(stream, PeerInfo::default()))
+    }
+
+    #[cfg(windows)]
+    {
+        use tokio::net::windows::named_pipe::ClientOptions;
+
+        let client = ClientOptions::new()
+            .open(endpoint)
+            .map_err(|e| AppError::Ipc(format!("{}: {}", endpoint, e)))?;
+        Ok(Connection::new(client, PeerInfo::default()))
+    }
+}
+
+/// IPC 监听器
+pub struct IpcListener {
+    endpoint: String,
+    #[cfg(unix)]
+    listener: tokio::net::UnixListener,
+    /// 管理组 ID，为 None 时所有能连接的用户都可以修改守护进程状态
+    #[cfg(unix)]
+    admin_gid: Option<u32>,
+    #[cfg(windows)]
+    server: tokio::net::windows::named_pipe::NamedPipeServer,
+}
+
+impl IpcListener {
+    /// 开始监听，已有守护进程在监听时返回错误
+    #[cfg(unix)]
+    pub fn bind(endpoint: &str, options: &ListenOptions) -> AppResult<Self> {
+        use std::os::unix::fs::{FileTypeExt, PermissionsExt};
+        use std::path::Path;
+
+        let admin_gid = match &options.admin_group {
+            #[cfg(target_os = "linux")]
+            Some(group) => Some(crate::platform::linux::group_id(group).ok_or_else(|| {
+                AppError::InvalidArgument(format!("组不存在: {}", group))
+            })?),
+            #[cfg(not(target_os = "linux"))]
+            Some(_) => {
+                return Err(AppError::InvalidArgument(
+                    "管理组仅在 Linux 上支持".to_string(),
+                ))
+            }
+            None => None,
+        };
+
+        if let Ok(meta) = std::fs::symlink_metadata(endpoint) {
+            if !meta.file_type().is_socket() {
+                return Err(AppError::InvalidArgument(format!("{} 已存在且不是套接字", endpoint)));
+            }
+            if is_listening(endpoint) {
+                return Err(AppError::InvalidArgument(format!("守护进程已在运行: {}", endpoint)));
+            }
+            let _ = std::fs::remove_file(endpoint);
+        }
+
+        if let Some(parent) = Path::new(endpoint).parent() {
+            std::fs::create_dir_all(parent).map_err(|e| AppError::Io(e.to_string()))?;
+        }
+
+        let listener = tokio::net::UnixListener::bind(endpoint)
+            .map_err(|e| AppError::Io(format!("{}: {}", endpoint, e)))?;
+
+        std::fs::set_permissions(endpoint, std::fs::Permissions::from_mode(options.mode))
+            .map_err(|e| AppError::Io(format!("{}: {}", endpoint, e)))?;
+
+        #[cfg(target_os = "linux")]
+        if let Some(group) = &options.group {
+            crate::platform::linux::chown_group(endpoint, group)?;
+        }
+
+        Ok(Self {
+            endpoint: endpoint.to_string(),
+            listener,
+            admin_gid,
+        })
+    }
+
+    /// 开始监听，已有守护进程在监听时返回错误
+    #[cfg(windows)]
+    pub fn bind(endpoint: &str, _options: &ListenOptions) -> AppResult<Self> {
+        let server = create_pipe(endpoint, true)
+            .map_err(|e| AppError::Io(format!("{}: {}", endpoint, e)))?;
+        Ok(Self {
+            endpoint: endpoint.to_string(),
+            server,
+        })
+    }
+
+    /// 监听地址
+    pub fn endpoint(&self) -> &str {
+        &self.endpoint
+    }
+
+    /// 等待下一个连接
+    #[cfg(unix)]
+    pub async fn accept(&mut self) -> AppResult<Connection> {
+        let (stream, _) = self
+            .listener
+            .accept()
+            .await
+            .map_err(|e| AppError::Ipc(e.to_string()))?;
+
+        let peer = match stream.peer_cred() {
+            Ok(cred) => PeerInfo {
+                uid: Some(cred.uid()),
+                sid: None,
+                pid: cred.pid().map(|p| p as u32),
+                privileged: cred.uid() == 0,
+                admin: cred.uid() == 0 || self.is_admin(cred.uid(), cred.gid()),
+            },
+            Err(e) => {
+                tracing::warn!("Failed to read IPC peer credentials: {}", e);
+                PeerInfo::default()
+            }
+        };
+
+        Ok(Connection::new(stream, peer))
+    }
+
+    /// 能连接套接字的用户是否还属于管理组
+    #[cfg(unix)]
+    fn is_admin(&self, uid: u32, gid: u32) -> bool {
+        match self.admin_gid {
+            None => true,
+            #[cfg(target_os = "linux")]
+            Some(admin) => crate::platform::linux::user_in_group(uid, gid, admin),
+            #[cfg(not(target_os = "linux"))]
+            Some(_) => {
+                let _ = (uid, gid);
+                false
+            }
+        }
+    }
+
+    /// 等待下一个连接
+    #[cfg(windows)]
+    pub async fn accept(&mut self) -> AppResult<Connection> {
+        use std::os::windows::io::AsRawHandle;
+
+        self.server
+            .connect()
+            .await
+            .map_err(|e| AppError::Ipc(e.to_string()))?;
+
+        // 已连接的实例交给调用方，再创建新实例等待下一个连接
+        let next = create_pipe(&self.endpoint, false)
+            .map_err(|e| AppError::Io(format!("{}: {}", self.endpoint, e)))?;
+        let server = std::mem::replace(&mut self.server, next);
+
+        // 客户端身份在读到第一条消息后由 `Connection::resolve_peer` 确定
+        let handle = server.as_raw_handle() as isize;
+        let mut conn = Connection::new(server, PeerInfo::default());
+        conn.pipe = Some(handle);
+        Ok(conn)
+    }
+}
+
+#[cfg(unix)]
+impl Drop for IpcListener {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.endpoint);
+    }
+}
+
+/// 守护进程管道的访问控制：SYSTEM 和管理员完全控制，已登录用户可读写
+#[cfg(windows)]
+const PIPE_SDDL: &str = "D:(A;;GA;;;SY)(A;;GA;;;BA)(A;;GRGW;;;AU)";
+
+/// 创建守护进程管道实例
+#[cfg(windows)]
+fn create_pipe(
+    endpoint: &str,
+    first: bool,
+) -> std::io::Result<tokio::net::windows::named_pipe::NamedPipeServer> {
+    crate::platform::windows::create_pipe(endpoint, first, PIPE_SDDL)
+}
+
+/// 通过模拟令牌获取命名管道客户端身份
+///
+/// 能打开管道即通过了 `PIPE_SDDL` 的访问控制，允许修改守护进程状态
+#[cfg(windows)]
+fn pipe_peer(handle: isize) -> PeerInfo {
+    use windows::Win32::Foundation::HANDLE;
+
+    match crate::platform::windows::pipe_client_identity(HANDLE(handle as *mut std::ffi::c_void)) {
+        Ok(client) => PeerInfo {
+            uid: None,
+            sid: Some(client.sid),
+            pid: None,
+            privileged: client.elevated,
+            admin: true,
+        },
+        Err(e) => {
+            tracing::warn!("Failed to identify named pipe client: {}", e);
+            PeerInfo::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_read_message_limit() {
+        let input: &[u8] = b"{\"a\":1}\n{\"b\":\"0123456789\"}\n";
+        let mut reader = BufReader::new(input);
+
+        let first: Option<serde_json::Value> = read_message_limited(&mut reader, 16).await.unwrap();
+        assert_eq!(first, Some(serde_json::json!({ "a": 1 })));
+
+        let second = read_message_limited::<serde_json::Value, _>(&mut reader, 16).await;
+        assert!(matches!(second, Err(AppError::Ipc(_))));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn test_peer_identity_and_admin_group() {
+        let dir = std::env::temp_dir().join(format!("port-detection-ipc-{}", std::process::id()));
+        let endpoint = dir.join("daemon.sock").display().to_string();
+
+        let missing = ListenOptions {
+            admin_group: Some("port-detection-no-such-group".to_string()),
+            ..ListenOptions::default()
+        };
+        assert!(matches!(
+            IpcListener::bind(&endpoint, &missing),
+            Err(AppError::InvalidArgument(_))
+        ));
+
+        // 未配置管理组时，能连接套接字即可修改守护进程状态
+        let mut listener = IpcListener::bind(&endpoint, &ListenOptions::default()).unwrap();
+        let _client = connect(&endpoint).await.unwrap();
+        let mut conn = listener.accept().await.unwrap();
+        conn.resolve_peer();
+
+        let uid = unsafe { libc::getuid() };
+        assert_eq!(conn.peer.uid, Some(uid));
+        assert_eq!(conn.peer.pid, Some(std::process::id()));
+        assert_eq!(conn.peer.privileged, uid == 0);
+        assert!(conn.peer.admin);
+
+        drop(listener);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_user_in_group() {
+        use crate::platform::linux::user_in_group;
+
+        let uid = unsafe { libc::getuid() };
+        let gid = unsafe { libc::getgid() };
+        assert!(user_in_group(uid, gid, gid));
+        assert!(!user_in_group(uid, gid, 4_000_000_000));
+    }
+}
@@ -4,8 +4,11 @@
 
 pub mod commands;
 pub mod core;
+pub mod daemon;
 pub mod models;
 pub mod platform;
+#[cfg(feature = "api-server")]
+pub mod server;
 pub mod utils;
 
 pub use models::*;
@@ -1,7 +1,6 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 use port_detection_lib::commands;
-#[cfg(debug_assertions)]
 use tauri::Manager;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
@@ -78,10 +77,10 @@ fn restart_as_admin() -> bool {
 }
 
 fn main() {
-    // Windows: 检查并请求管理员权限
+    // Windows: 检查并请求管理员权限（守护进程运行时由守护进程代为执行特权操作）
     #[cfg(windows)]
     {
-        if !is_elevated() {
+        if !is_elevated() && !port_detection_lib::daemon::is_running() {
             // 尝试以管理员权限重新启动
             if restart_as_admin() {
                 // 成功启动新进程，退出当前进程
@@ -100,27 +99,90 @@ fn main() {
 
     tracing::info!("Starting Port Detection application");
 
-    tauri::Builder::default()
+    let builder = tauri::Builder::default()
         .manage(commands::monitor::MonitorState::default())
+        .manage(port_detection_lib::core::query::ScanCache::default())
+        .manage(port_detection_lib::core::probe::HttpProber::default())
+        .manage(port_detection_lib::core::probe::TlsInspector::default());
+
+    #[cfg(feature = "api-server")]
+    let builder = builder.manage(commands::server::ApiServerState::default());
+
+    builder
         .plugin(tauri_plugin_notification::init())
         .plugin(tauri_plugin_shell::init())
-        .setup(|_app| {
+        .setup(|app| {
+            // 监控事件通过输出端发送到前端
+            app.state::<commands::monitor::MonitorState>()
+                .attach_tauri_sink(app.handle().clone())?;
+
+            if let Err(e) = commands::service::load_service_overrides(app.handle()) {
+                tracing::warn!("Failed to load service overrides: {}", e);
+            }
+
+            if let Err(e) = commands::baseline::load_baseline(app.handle()) {
+                tracing::warn!("Failed to load baseline: {}", e);
+            }
+
             #[cfg(debug_assertions)]
             {
-                let window = _app.get_webview_window("main").unwrap();
+                let window = app.get_webview_window("main").unwrap();
                 window.open_devtools();
             }
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
             commands::port::scan_all_ports,
+            commands::port::scan_ports,
+            commands::port::scan_ports_page,
+            commands::probe::probe_services,
+            commands::probe::probe_http,
+            commands::probe::inspect_tls,
+            commands::exposure::get_exposure_report,
+            commands::firewall::get_firewall_status,
+            commands::compliance::check_compliance,
+            commands::service::get_service_overrides,
+            commands::service::set_service_overrides,
             commands::port::query_port,
             commands::port::get_reserved_ports,
             commands::port::check_port_reserved,
             commands::process::get_process_info,
+            commands::process::get_process_executable,
             commands::process::kill_process,
             commands::monitor::start_monitor,
             commands::monitor::stop_monitor,
+            commands::monitor::resync,
+            commands::daemon::get_daemon_info,
+            commands::watch::watch_port,
+            commands::watch::unwatch_port,
+            commands::watch::list_watches,
+            commands::watch::get_port_timeline,
+            commands::watch::export_port_timeline,
+            commands::baseline::start_baseline_training,
+            commands::baseline::get_baseline_status,
+            commands::baseline::get_baseline,
+            commands::baseline::list_drifts,
+            commands::baseline::approve_drifts,
+            commands::baseline::dismiss_drifts,
+            commands::detection::detect_suspicious_activity,
+            commands::analytics::get_connection_summary,
+            commands::resolver::set_reverse_dns,
+            commands::resolver::resolve_hostname,
+            commands::export::export_ports,
+            commands::snapshot::save_snapshot,
+            commands::snapshot::list_snapshots,
+            commands::snapshot::load_snapshot,
+            commands::snapshot::delete_snapshot,
+            commands::snapshot::diff_snapshots,
+            commands::sink::attach_event_sink,
+            commands::sink::detach_event_sink,
+            commands::sink::list_event_sinks,
+            #[cfg(feature = "api-server")]
+            commands::server::start_api_server,
+            #[cfg(feature = "api-server")]
+            commands::server::stop_api_server,
+            #[cfg(feature = "api-server")]
+            commands::server::get_api_server_info,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
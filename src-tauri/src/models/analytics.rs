@@ -0,0 +1,37 @@
+//! 连接汇总模型
+
+use serde::{Deserialize, Serialize};
+
+/// 按某一维度汇总的已建立连接
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConnectionGroup {
+    /// 分组键：远程地址、远程端口、网段或进程名
+    pub key: String,
+    /// 累计观察到的连接数
+    pub connections: u64,
+    /// 当前仍在建立状态的连接数
+    pub active: usize,
+    /// 首次看到的时间（Unix 时间戳，毫秒）
+    pub first_seen: u64,
+    /// 最后看到的时间，有活动连接时为生成汇总的时间
+    pub last_seen: u64,
+    /// 参与的进程名（按进程分组时为空）
+    pub processes: Vec<String>,
+}
+
+/// 已建立连接的汇总，各维度按累计连接数从多到少排序
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConnectionSummary {
+    /// 开始统计的时间，未收到监控事件时为 None（仅包含当前扫描结果）
+    pub tracking_since: Option<u64>,
+    pub generated_at: u64,
+    /// 累计连接数
+    pub total: u64,
+    /// 当前连接数
+    pub active: usize,
+    pub by_host: Vec<ConnectionGroup>,
+    pub by_port: Vec<ConnectionGroup>,
+    /// IPv4 按 /24、IPv6 按 /64 汇总
+    pub by_subnet: Vec<ConnectionGroup>,
+    pub by_process: Vec<ConnectionGroup>,
+}
@@ -0,0 +1,134 @@
+//! 行为基线与偏离事件模型
+
+use serde::{Deserialize, Serialize};
+
+use super::Protocol;
+
+/// 基线所处阶段
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BaselineMode {
+    /// 尚未建立基线
+    Empty,
+    /// 学习中，不检测偏离
+    Training,
+    /// 已建立基线，检测偏离
+    Active,
+}
+
+impl BaselineMode {
+    /// 阶段名（小写）
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Empty => "empty",
+            Self::Training => "training",
+            Self::Active => "active",
+        }
+    }
+}
+
+/// 基线中的监听端口
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BaselineListener {
+    pub port: u16,
+    pub protocol: Protocol,
+    /// 进程名，无法获取进程信息时为 None
+    pub process: Option<String>,
+    /// 学习期内的在线比例（0~1），审批加入的为 1
+    pub presence: f64,
+}
+
+/// 进程的出站目标
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BaselineDestination {
+    pub addr: String,
+    pub port: u16,
+    /// 观察到的连接次数
+    pub seen: u32,
+}
+
+/// 有出站连接的进程
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BaselineProcess {
+    pub name: String,
+    pub destinations: Vec<BaselineDestination>,
+}
+
+/// 学习得到的基线
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Baseline {
+    /// 学习开始时间（Unix 时间戳，毫秒）
+    pub trained_from: u64,
+    /// 学习结束时间
+    pub trained_until: u64,
+    pub listeners: Vec<BaselineListener>,
+    pub processes: Vec<BaselineProcess>,
+}
+
+/// 偏离类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DriftKind {
+    /// 基线中没有的监听端口
+    NewListener,
+    /// 监听端口的进程与基线不同
+    OwnerChanged,
+    /// 基线中没有出站连接的进程开始连接外部
+    NewOutboundProcess,
+    /// 进程连接了基线之外的目标
+    NewDestination,
+}
+
+impl DriftKind {
+    /// 类型名（小写下划线形式）
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::NewListener => "new_listener",
+            Self::OwnerChanged => "owner_changed",
+            Self::NewOutboundProcess => "new_outbound_process",
+            Self::NewDestination => "new_destination",
+        }
+    }
+}
+
+/// 偏离事件，同一对象重复出现时只更新次数和时间
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DriftEvent {
+    pub id: u64,
+    pub kind: DriftKind,
+    pub protocol: Protocol,
+    /// 监听类为本地端口，出站类为远程端口
+    pub port: u16,
+    /// 监听类为本地地址，出站类为远程地址
+    pub addr: String,
+    pub pid: u32,
+    pub process: Option<String>,
+    /// 基线中该端口的进程（`owner_changed`）
+    #[serde(default)]
+    pub expected: Vec<String>,
+    /// 置信度（0~1），基线学习时间越长、越稳定越高
+    pub confidence: f64,
+    pub message: String,
+    /// 首次出现时间（Unix 时间戳，毫秒）
+    pub first_seen: u64,
+    pub last_seen: u64,
+    pub occurrences: u32,
+}
+
+/// 基线状态
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BaselineStatus {
+    pub mode: BaselineMode,
+    /// 学习开始时间，学习中时有效
+    pub training_started_at: Option<u64>,
+    /// 计划的学习结束时间，学习中时有效
+    pub training_ends_at: Option<u64>,
+    /// 当前基线的学习结束时间
+    pub trained_until: Option<u64>,
+    /// 基线中的监听端口数
+    pub listeners: usize,
+    /// 基线中有出站连接的进程数
+    pub processes: usize,
+    /// 待处理的偏离事件数
+    pub pending_drifts: usize,
+}
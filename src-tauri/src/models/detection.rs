@@ -0,0 +1,23 @@
+//! 可疑行为检测模型
+
+use serde::{Deserialize, Serialize};
+
+use super::{Protocol, RiskLevel};
+
+/// 检测器发现的可疑行为
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Finding {
+    /// 检测器标识
+    pub detector: String,
+    pub severity: RiskLevel,
+    pub pid: u32,
+    pub process: Option<String>,
+    /// 相关端口，与单个端口无关时为 None
+    pub port: Option<u16>,
+    pub protocol: Option<Protocol>,
+    pub message: String,
+    /// 判断依据（路径、地址、用户等）
+    pub evidence: Vec<String>,
+    /// 检测时间（Unix 时间戳，毫秒）
+    pub detected_at: u64,
+}
@@ -29,6 +29,25 @@ pub enum AppError {
     #[error("监控未运行")]
     MonitorNotRunning,
 
+    #[error("快照不存在: {0}")]
+    SnapshotNotFound(String),
+
+    #[error("扫描结果已过期，请重新扫描: {0}")]
+    ScanExpired(String),
+
+    #[error("参数无效: {0}")]
+    InvalidArgument(String),
+
+    #[error("文件读写失败: {0}")]
+    Io(String),
+
+    #[error("守护进程通信失败: {0}")]
+    Ipc(String),
+
+    /// 守护进程返回的错误，消息原样保留
+    #[error("{0}")]
+    Daemon(String),
+
     #[error("内部错误: {0}")]
     Internal(String),
 }
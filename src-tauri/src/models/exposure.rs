@@ -0,0 +1,131 @@
+//! 网络暴露面报告模型
+
+use serde::{Deserialize, Serialize};
+
+use super::Protocol;
+
+/// 本机网络接口地址
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NetworkInterface {
+    /// 接口名（Windows 为友好名称）
+    pub name: String,
+    pub addr: String,
+    /// 前缀长度
+    pub prefix_len: u8,
+}
+
+/// 监听端口的可达范围，按范围从小到大排序
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExposureLevel {
+    /// 仅本机
+    Loopback,
+    /// 同一链路
+    LinkLocal,
+    /// 局域网（私有地址）
+    PrivateLan,
+    /// 公网地址，或通配地址且本机有公网接口
+    Public,
+}
+
+impl ExposureLevel {
+    /// 级别名（小写下划线形式）
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Loopback => "loopback",
+            Self::LinkLocal => "link_local",
+            Self::PrivateLan => "private_lan",
+            Self::Public => "public",
+        }
+    }
+}
+
+/// 风险等级
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RiskLevel {
+    Info,
+    Low,
+    Medium,
+    High,
+}
+
+impl RiskLevel {
+    /// 等级名（小写）
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Info => "info",
+            Self::Low => "low",
+            Self::Medium => "medium",
+            Self::High => "high",
+        }
+    }
+}
+
+/// 风险说明
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RiskNote {
+    pub level: RiskLevel,
+    pub message: String,
+}
+
+/// 单个监听端口的暴露情况
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExposedService {
+    pub port: u16,
+    pub protocol: Protocol,
+    pub local_addr: String,
+    pub service_name: Option<String>,
+    pub exposure: ExposureLevel,
+    /// 监听在通配地址（0.0.0.0 / ::）
+    pub wildcard: bool,
+    /// 可通过的非回环接口地址（`接口名 地址`）
+    pub reachable_via: Vec<String>,
+    /// 风险说明，按等级降序
+    pub risks: Vec<RiskNote>,
+}
+
+impl ExposedService {
+    /// 最高风险等级
+    pub fn risk(&self) -> RiskLevel {
+        self.risks
+            .iter()
+            .map(|r| r.level)
+            .max()
+            .unwrap_or(RiskLevel::Info)
+    }
+}
+
+/// 按进程分组的暴露情况
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProcessExposure {
+    pub pid: u32,
+    pub process_name: Option<String>,
+    pub exe_path: Option<String>,
+    /// 各端口中最大的可达范围
+    pub exposure: ExposureLevel,
+    /// 各端口中最高的风险等级
+    pub risk: RiskLevel,
+    pub services: Vec<ExposedService>,
+}
+
+/// 各可达范围的监听端口数
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ExposureSummary {
+    pub loopback: usize,
+    pub link_local: usize,
+    pub private_lan: usize,
+    pub public: usize,
+}
+
+/// 网络暴露面报告
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExposureReport {
+    /// 生成时间（Unix 时间戳，毫秒）
+    pub generated_at: u64,
+    /// 本机接口地址
+    pub interfaces: Vec<NetworkInterface>,
+    pub summary: ExposureSummary,
+    /// 按风险降序排列的进程
+    pub processes: Vec<ProcessExposure>,
+}
@@ -0,0 +1,70 @@
+//! 服务指纹模型
+
+use serde::{Deserialize, Serialize};
+
+/// 探测识别出的应用层协议
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProbedProtocol {
+    Http,
+    Tls,
+    Ssh,
+    Redis,
+    Postgresql,
+    Mysql,
+    Smtp,
+    Amqp,
+    /// 有响应但无法识别
+    Unknown,
+}
+
+impl ProbedProtocol {
+    /// 协议名（小写）
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Http => "http",
+            Self::Tls => "tls",
+            Self::Ssh => "ssh",
+            Self::Redis => "redis",
+            Self::Postgresql => "postgresql",
+            Self::Mysql => "mysql",
+            Self::Smtp => "smtp",
+            Self::Amqp => "amqp",
+            Self::Unknown => "unknown",
+        }
+    }
+}
+
+/// 服务指纹
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ServiceFingerprint {
+    /// 识别出的协议
+    pub protocol: ProbedProtocol,
+    /// 产品与版本（如 `OpenSSH_9.6`、HTTP `Server` 头）
+    pub product: Option<String>,
+    /// 响应首行（已去除控制字符并截断）
+    pub banner: Option<String>,
+    /// 探测时间（Unix 时间戳，毫秒）
+    pub probed_at: u64,
+}
+
+/// HTTP 探测结果
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HttpProbe {
+    /// 最终请求的 URL（跟随重定向后）
+    pub url: String,
+    /// 是否通过 HTTPS 访问
+    pub tls: bool,
+    /// 最终响应的状态码
+    pub status: u16,
+    /// `Server` 响应头
+    pub server: Option<String>,
+    /// HTML 页面标题
+    pub title: Option<String>,
+    /// 首个请求从建立连接到收到响应头的耗时（毫秒）
+    pub latency_ms: u64,
+    /// 跟随的重定向次数
+    pub redirects: u8,
+    /// 探测时间（Unix 时间戳，毫秒）
+    pub probed_at: u64,
+}
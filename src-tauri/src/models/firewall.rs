@@ -0,0 +1,54 @@
+//! 主机防火墙关联结果模型
+
+use serde::{Deserialize, Serialize};
+
+/// 防火墙规则来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FirewallBackend {
+    /// `nft list ruleset -j`
+    Nftables,
+    /// `iptables-save` / `ip6tables-save`
+    Iptables,
+}
+
+/// 入站新连接的处理结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FirewallVerdict {
+    Accept,
+    /// 丢弃或拒绝
+    Drop,
+    /// 默认丢弃但对部分来源（地址、接口等无法静态判断的条件）放行，或无法读取规则
+    Unknown,
+}
+
+impl FirewallVerdict {
+    /// 结果名（小写）
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Accept => "accept",
+            Self::Drop => "drop",
+            Self::Unknown => "unknown",
+        }
+    }
+}
+
+/// 决定结果的规则
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FirewallRule {
+    /// 所在链（如 `inet filter input`、`iptables filter INPUT`）
+    pub chain: String,
+    /// 规则文本，由链的默认策略决定时为 `policy drop` 等
+    pub rule: String,
+}
+
+/// 监听端口的防火墙状态
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FirewallStatus {
+    /// 无法读取规则时为 None
+    pub backend: Option<FirewallBackend>,
+    pub verdict: FirewallVerdict,
+    /// 匹配的规则，没有入站过滤链时为 None
+    pub rule: Option<FirewallRule>,
+}
@@ -3,7 +3,25 @@
 mod port_info;
 mod process_info;
 mod error;
+mod analytics;
+mod baseline;
+mod detection;
+mod exposure;
+mod firewall;
+mod fingerprint;
+mod policy;
+mod service;
+mod tls;
 
 pub use port_info::*;
 pub use process_info::*;
 pub use error::*;
+pub use analytics::*;
+pub use baseline::*;
+pub use detection::*;
+pub use exposure::*;
+pub use firewall::*;
+pub use fingerprint::*;
+pub use policy::*;
+pub use service::*;
+pub use tls::*;
@@ -0,0 +1,218 @@
+//! 监听策略与合规检查结果模型
+
+use std::path::Path;
+
+use serde::{Deserialize, Deserializer, Serialize};
+
+use super::{AppError, AppResult, ExposureLevel, Protocol};
+
+/// 端口范围（闭区间），策略文件中写作 `22` 或 `"8000-8099"`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "PortSpec", into = "PortSpec")]
+pub struct PortRange {
+    pub start: u16,
+    pub end: u16,
+}
+
+impl PortRange {
+    pub fn contains(&self, port: u16) -> bool {
+        (self.start..=self.end).contains(&port)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum PortSpec {
+    Single(u16),
+    Range(String),
+}
+
+impl TryFrom<PortSpec> for PortRange {
+    type Error = String;
+
+    fn try_from(spec: PortSpec) -> Result<Self, Self::Error> {
+        let (start, end) = match spec {
+            PortSpec::Single(port) => (port, port),
+            PortSpec::Range(text) => {
+                let parse = |s: &str| {
+                    s.trim()
+                        .parse::<u16>()
+                        .map_err(|_| format!("无效的端口范围: {}", text))
+                };
+                match text.split_once('-') {
+                    Some((start, end)) => (parse(start)?, parse(end)?),
+                    None => {
+                        let port = parse(&text)?;
+                        (port, port)
+                    }
+                }
+            }
+        };
+        if start > end {
+            return Err(format!("无效的端口范围: {}-{}", start, end));
+        }
+        Ok(Self { start, end })
+    }
+}
+
+impl From<PortRange> for PortSpec {
+    fn from(range: PortRange) -> Self {
+        if range.start == range.end {
+            Self::Single(range.start)
+        } else {
+            Self::Range(format!("{}-{}", range.start, range.end))
+        }
+    }
+}
+
+/// 允许的监听，未填写的字段不检查
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ListenerRule {
+    /// 规则名，用于报告
+    #[serde(default)]
+    pub name: Option<String>,
+    pub port: PortRange,
+    /// 为空时匹配 TCP 和 UDP（不区分大小写）
+    #[serde(default, deserialize_with = "deserialize_protocol")]
+    pub protocol: Option<Protocol>,
+    /// 进程名（不区分大小写，忽略 `.exe` 后缀）
+    #[serde(default)]
+    pub process: Option<String>,
+    /// 可执行文件路径
+    #[serde(default)]
+    pub exe: Option<String>,
+    /// 进程所属用户名
+    #[serde(default)]
+    pub user: Option<String>,
+    /// 允许的最大可达范围（`loopback`、`link_local`、`private_lan`、`public`）
+    #[serde(default)]
+    pub bind: Option<ExposureLevel>,
+    /// 必须有进程在该端口监听
+    #[serde(default)]
+    pub required: bool,
+}
+
+impl ListenerRule {
+    /// 规则名，未命名时为 `端口/协议`
+    pub fn label(&self) -> String {
+        if let Some(name) = &self.name {
+            return name.clone();
+        }
+        let port = if self.port.start == self.port.end {
+            self.port.start.to_string()
+        } else {
+            format!("{}-{}", self.port.start, self.port.end)
+        };
+        match self.protocol {
+            Some(protocol) => format!("{}/{:?}", port, protocol),
+            None => port,
+        }
+    }
+
+    /// 端口和协议是否与规则一致
+    pub fn covers(&self, port: u16, protocol: Protocol) -> bool {
+        self.port.contains(port)
+            && match self.protocol {
+                Some(expected) => expected == protocol,
+                None => true,
+            }
+    }
+}
+
+fn deserialize_protocol<'de, D>(deserializer: D) -> Result<Option<Protocol>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    match Option::<String>::deserialize(deserializer)? {
+        Some(name) => Protocol::from_name(&name)
+            .map(Some)
+            .ok_or_else(|| serde::de::Error::custom(format!("未知协议: {}", name))),
+        None => Ok(None),
+    }
+}
+
+/// 监听策略（TOML 文件）
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ListenerPolicy {
+    /// 允许规则之外的监听端口
+    pub allow_unlisted: bool,
+    /// 不检查只能从本机访问的监听端口
+    pub ignore_loopback: bool,
+    #[serde(rename = "listener")]
+    pub listeners: Vec<ListenerRule>,
+}
+
+impl ListenerPolicy {
+    /// 解析 TOML 文本
+    pub fn from_toml(content: &str) -> AppResult<Self> {
+        toml::from_str(content).map_err(|e| AppError::InvalidArgument(e.to_string()))
+    }
+
+    /// 从 TOML 文件加载
+    pub fn load(path: &Path) -> AppResult<Self> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| AppError::Io(format!("{}: {}", path.display(), e)))?;
+        toml::from_str(&content)
+            .map_err(|e| AppError::InvalidArgument(format!("{}: {}", path.display(), e)))
+    }
+}
+
+/// 违规类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ViolationKind {
+    /// 策略未声明的监听端口
+    Unexpected,
+    /// 策略要求但未在监听的服务
+    Missing,
+    /// 端口由非预期的进程、程序或用户监听
+    WrongOwner,
+    /// 可达范围超出策略允许的范围
+    TooBroad,
+}
+
+impl ViolationKind {
+    /// 类型名（小写下划线形式）
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Unexpected => "unexpected",
+            Self::Missing => "missing",
+            Self::WrongOwner => "wrong_owner",
+            Self::TooBroad => "too_broad",
+        }
+    }
+}
+
+/// 一项违规
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PolicyViolation {
+    pub kind: ViolationKind,
+    /// 相关规则名，`unexpected` 时为 None
+    pub rule: Option<String>,
+    pub port: u16,
+    pub protocol: Option<Protocol>,
+    /// 监听地址，`missing` 时为 None
+    pub local_addr: Option<String>,
+    pub pid: Option<u32>,
+    pub process_name: Option<String>,
+    pub message: String,
+}
+
+/// 合规检查结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComplianceReport {
+    /// 检查时间（Unix 时间戳，毫秒）
+    pub checked_at: u64,
+    /// 检查的监听端口数
+    pub checked: usize,
+    pub violations: Vec<PolicyViolation>,
+}
+
+impl ComplianceReport {
+    /// 没有违规
+    pub fn is_compliant(&self) -> bool {
+        self.violations.is_empty()
+    }
+}
@@ -1,15 +1,26 @@
 //! 端口信息模型
 
 use serde::{Deserialize, Serialize};
-use super::ProcessInfo;
+use super::{FirewallStatus, HttpProbe, ProcessInfo, ServiceFingerprint, TlsInfo};
 
 /// 协议类型
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub enum Protocol {
     TCP,
     UDP,
 }
 
+impl Protocol {
+    /// 从名称解析协议（不区分大小写）
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_uppercase().as_str() {
+            "TCP" => Some(Self::TCP),
+            "UDP" => Some(Self::UDP),
+            _ => None,
+        }
+    }
+}
+
 /// 连接状态
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ConnectionState {
@@ -52,12 +63,68 @@ impl ConnectionState {
         }
     }
 
+    /// 从 Linux 内核 TCP 状态（/proc/net/tcp 的 st 列）转换
+    pub fn from_linux_tcp_state(state: u32) -> Self {
+        match state {
+            0x01 => Self::Established,
+            0x02 => Self::SynSent,
+            0x03 => Self::SynReceived,
+            0x04 => Self::FinWait1,
+            0x05 => Self::FinWait2,
+            0x06 => Self::TimeWait,
+            0x07 => Self::Closed,
+            0x08 => Self::CloseWait,
+            0x09 => Self::LastAck,
+            0x0A => Self::Listen,
+            0x0B => Self::Closing,
+            _ => Self::Unknown(state),
+        }
+    }
+
     /// 是否为监听状态
     pub fn is_listening(&self) -> bool {
         matches!(self, Self::Listen | Self::Bound)
     }
+
+    /// 状态名（小写下划线形式）
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Closed => "closed",
+            Self::Listen => "listen",
+            Self::SynSent => "syn_sent",
+            Self::SynReceived => "syn_received",
+            Self::Established => "established",
+            Self::FinWait1 => "fin_wait1",
+            Self::FinWait2 => "fin_wait2",
+            Self::CloseWait => "close_wait",
+            Self::Closing => "closing",
+            Self::LastAck => "last_ack",
+            Self::TimeWait => "time_wait",
+            Self::DeleteTcb => "delete_tcb",
+            Self::Bound => "bound",
+            Self::Unknown(_) => "unknown",
+        }
+    }
 }
 
+/// 所有可用的状态名
+pub const CONNECTION_STATE_NAMES: &[&str] = &[
+    "closed",
+    "listen",
+    "syn_sent",
+    "syn_received",
+    "established",
+    "fin_wait1",
+    "fin_wait2",
+    "close_wait",
+    "closing",
+    "last_ack",
+    "time_wait",
+    "delete_tcb",
+    "bound",
+    "unknown",
+];
+
 /// 端口信息
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PortInfo {
@@ -71,12 +138,30 @@ pub struct PortInfo {
     pub remote_addr: Option<String>,
     /// 远程端口（TCP 连接时有效）
     pub remote_port: Option<u16>,
+    /// 远程地址的主机名（hosts 文件或反向 DNS），解析完成前为 None
+    #[serde(default)]
+    pub remote_hostname: Option<String>,
     /// 连接状态
     pub state: ConnectionState,
     /// 进程 ID
     pub pid: u32,
     /// 进程信息
     pub process: Option<ProcessInfo>,
+    /// 服务名（如 http、amqp），由服务名注册表填充
+    #[serde(default)]
+    pub service_name: Option<String>,
+    /// 服务指纹，仅在主动探测后填充
+    #[serde(default)]
+    pub fingerprint: Option<ServiceFingerprint>,
+    /// HTTP 探测结果，仅在主动探测后填充
+    #[serde(default)]
+    pub http: Option<HttpProbe>,
+    /// TLS 证书检查结果，仅在主动检查后填充
+    #[serde(default)]
+    pub tls: Option<TlsInfo>,
+    /// 入站防火墙状态，仅在读取防火墙规则后填充
+    #[serde(default)]
+    pub firewall: Option<FirewallStatus>,
 }
 
 impl PortInfo {
@@ -88,26 +173,136 @@ impl PortInfo {
             local_addr,
             remote_addr: None,
             remote_port: None,
+            remote_hostname: None,
             state: ConnectionState::Unknown(0),
             pid,
             process: None,
+            service_name: None,
+            fingerprint: None,
+            http: None,
+            tls: None,
+            firewall: None,
+        }
+    }
+}
+
+/// 变化类型
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum ChangeKind {
+    /// 新增端口
+    Added,
+    /// 端口被释放
+    Removed,
+    /// 连接状态变化
+    StateChanged {
+        from: ConnectionState,
+        to: ConnectionState,
+    },
+    /// 占用进程变化
+    OwnerChanged { old_pid: u32, new_pid: u32 },
+}
+
+/// 单个端口的变化
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChangeEntry {
+    /// 变化类型
+    pub kind: ChangeKind,
+    /// 端口信息（移除时为移除前的信息，其余为最新信息）
+    pub info: PortInfo,
+}
+
+impl ChangeEntry {
+    pub fn added(info: PortInfo) -> Self {
+        Self {
+            kind: ChangeKind::Added,
+            info,
+        }
+    }
+
+    pub fn removed(info: PortInfo) -> Self {
+        Self {
+            kind: ChangeKind::Removed,
+            info,
+        }
+    }
+
+    /// 比较同一端口的新旧信息，状态和进程同时变化时返回两条记录
+    pub fn between(old: &PortInfo, new: &PortInfo) -> Vec<Self> {
+        let mut entries = Vec::new();
+
+        if old.state != new.state {
+            entries.push(Self {
+                kind: ChangeKind::StateChanged {
+                    from: old.state,
+                    to: new.state,
+                },
+                info: new.clone(),
+            });
+        }
+
+        if old.pid != new.pid {
+            entries.push(Self {
+                kind: ChangeKind::OwnerChanged {
+                    old_pid: old.pid,
+                    new_pid: new.pid,
+                },
+                info: new.clone(),
+            });
         }
+
+        entries
     }
 }
 
 /// 端口变化事件
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PortChange {
-    /// 新增的端口
-    pub added: Vec<PortInfo>,
-    /// 移除的端口
-    pub removed: Vec<PortInfo>,
-    /// 状态变化的端口
-    pub changed: Vec<PortInfo>,
+    /// 事件序号，单调递增，用于检测丢失的事件
+    pub seq: u64,
+    /// 事件时间（Unix 时间戳，毫秒）
+    pub timestamp: u64,
+    /// 应用本次变化后的快照版本
+    pub snapshot_version: u64,
+    /// 变化列表
+    pub entries: Vec<ChangeEntry>,
 }
 
 impl PortChange {
     pub fn is_empty(&self) -> bool {
-        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+        self.entries.is_empty()
+    }
+}
+
+/// 端口全量快照事件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PortSnapshot {
+    /// 事件序号，与 `PortChange` 共用序列
+    pub seq: u64,
+    /// 事件时间（Unix 时间戳，毫秒）
+    pub timestamp: u64,
+    /// 快照版本
+    pub snapshot_version: u64,
+    /// 全部端口
+    pub ports: Vec<PortInfo>,
+}
+
+/// 监控事件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", content = "data", rename_all = "snake_case")]
+pub enum MonitorEvent {
+    /// 基线快照，订阅者应以此替换本地状态
+    Snapshot(PortSnapshot),
+    /// 增量变化
+    Change(PortChange),
+}
+
+impl MonitorEvent {
+    /// 事件序号
+    pub fn seq(&self) -> u64 {
+        match self {
+            Self::Snapshot(s) => s.seq,
+            Self::Change(c) => c.seq,
+        }
     }
 }
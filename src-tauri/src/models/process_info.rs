@@ -17,6 +17,9 @@ pub struct ProcessInfo {
     pub create_time: u64,
     /// 内存使用量（字节）
     pub memory_usage: u64,
+    /// 可执行文件信息，仅在按需检查后填充
+    #[serde(default)]
+    pub executable: Option<ExecutableInfo>,
 }
 
 impl ProcessInfo {
@@ -29,10 +32,67 @@ impl ProcessInfo {
             cmd_line: None,
             create_time: 0,
             memory_usage: 0,
+            executable: None,
         }
     }
 }
 
+/// 可执行文件信息
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExecutableInfo {
+    pub path: String,
+    /// 文件大小（字节）
+    pub size: u64,
+    /// 修改时间（Unix 时间戳，毫秒）
+    pub modified: u64,
+    /// SHA-256（小写十六进制），只在需要时计算
+    pub sha256: Option<String>,
+    pub package: PackageStatus,
+}
+
+/// 软件包管理器
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PackageManager {
+    Dpkg,
+    Rpm,
+}
+
+/// 可执行文件所属的软件包
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum PackageStatus {
+    /// 属于软件包
+    Owned {
+        manager: PackageManager,
+        name: String,
+        version: Option<String>,
+    },
+    /// 不属于任何软件包
+    Unowned { manager: PackageManager },
+    /// 进程启动后可执行文件已被删除或替换，无法与软件包核对
+    Deleted,
+    /// 无法读取可执行文件
+    Uninspectable { reason: String },
+    /// 无法判断（没有可用的包管理器，或非 Linux 平台）
+    Unknown,
+}
+
+impl PackageStatus {
+    /// 确定不属于任何软件包
+    pub fn is_unowned(&self) -> bool {
+        matches!(self, Self::Unowned { .. })
+    }
+
+    /// 不属于软件包，或可执行文件已删除、无法读取
+    pub fn is_unverified(&self) -> bool {
+        matches!(
+            self,
+            Self::Unowned { .. } | Self::Deleted | Self::Uninspectable { .. }
+        )
+    }
+}
+
 /// 受保护的系统进程列表
 pub const PROTECTED_PROCESSES: &[&str] = &[
     "System",
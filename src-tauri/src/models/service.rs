@@ -0,0 +1,343 @@
+//! 端口服务名注册表
+//!
+//! 查找顺序：用户覆盖 > 系统 services 文件 > 内置 IANA 表
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{OnceLock, RwLock};
+
+use serde::{Deserialize, Serialize};
+
+use super::{AppError, AppResult, PortInfo, Protocol};
+
+/// 内置服务名表
+const BUILTIN_SERVICES: &str = include_str!("services.txt");
+
+/// 服务名条目
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ServiceEntry {
+    pub port: u16,
+    pub protocol: Protocol,
+    pub name: String,
+}
+
+/// 端口到服务名的映射
+#[derive(Debug, Default)]
+pub struct ServiceRegistry {
+    names: HashMap<(u16, Protocol), String>,
+    overrides: HashMap<(u16, Protocol), String>,
+}
+
+impl ServiceRegistry {
+    /// 只包含内置表
+    pub fn builtin() -> Self {
+        let mut registry = Self::default();
+        registry.extend(parse_services(BUILTIN_SERVICES));
+        registry
+    }
+
+    /// 内置表加上系统 services 文件（读取失败时忽略）
+    pub fn system() -> Self {
+        let mut registry = Self::builtin();
+        let content = system_services_path().and_then(|p| std::fs::read_to_string(p).ok());
+        if let Some(content) = content {
+            registry.extend(parse_services(&content));
+        }
+        registry
+    }
+
+    /// 添加条目，已有的端口会被覆盖
+    pub fn extend(&mut self, entries: impl IntoIterator<Item = ServiceEntry>) {
+        for entry in entries {
+            self.names.insert((entry.port, entry.protocol), entry.name);
+        }
+    }
+
+    /// 查找服务名
+    pub fn lookup(&self, port: u16, protocol: Protocol) -> Option<&str> {
+        let key = (port, protocol);
+        self.overrides
+            .get(&key)
+            .or_else(|| self.names.get(&key))
+            .map(String::as_str)
+    }
+
+    /// 端口对应的服务名：监听端口及本地端口上有监听的连接（入站）取本地端口，
+    /// 其余连接（出站）取远程端口。`listening` 为处于监听状态的本地端口
+    pub fn service_for(
+        &self,
+        port: &PortInfo,
+        listening: &HashSet<(u16, Protocol)>,
+    ) -> Option<&str> {
+        let service_port = match port.remote_port {
+            Some(remote)
+                if remote != 0
+                    && !port.state.is_listening()
+                    && !listening.contains(&(port.port, port.protocol)) =>
+            {
+                remote
+            }
+            _ => port.port,
+        };
+        self.lookup(service_port, port.protocol)
+    }
+
+    /// 用户覆盖，按端口排序
+    pub fn overrides(&self) -> Vec<ServiceEntry> {
+        let mut entries: Vec<ServiceEntry> = self
+            .overrides
+            .iter()
+            .map(|(&(port, protocol), name)| ServiceEntry {
+                port,
+                protocol,
+                name: name.clone(),
+            })
+            .collect();
+        entries.sort_by_key(|e| (e.port, e.protocol == Protocol::UDP));
+        entries
+    }
+
+    /// 替换全部用户覆盖，名称为空的条目被忽略
+    pub fn set_overrides(&mut self, entries: Vec<ServiceEntry>) {
+        self.overrides = entries
+            .into_iter()
+            .filter(|e| !e.name.trim().is_empty())
+            .map(|e| ((e.port, e.protocol), e.name.trim().to_string()))
+            .collect();
+    }
+
+    /// 从 JSON 文件加载用户覆盖，文件不存在时视为空
+    pub fn load_overrides(&mut self, path: &Path) -> AppResult<()> {
+        let entries = match std::fs::read_to_string(path) {
+            Ok(content) => serde_json::from_str(&content)
+                .map_err(|e| AppError::InvalidArgument(format!("{}: {}", path.display(), e)))?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(AppError::Io(format!("{}: {}", path.display(), e))),
+        };
+        self.set_overrides(entries);
+        Ok(())
+    }
+
+    /// 将用户覆盖保存为 JSON 文件
+    pub fn save_overrides(&self, path: &Path) -> AppResult<()> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| AppError::Io(e.to_string()))?;
+        }
+        let json = serde_json::to_string_pretty(&self.overrides())
+            .map_err(|e| AppError::Internal(e.to_string()))?;
+        std::fs::write(path, json).map_err(|e| AppError::Io(format!("{}: {}", path.display(), e)))
+    }
+}
+
+/// 全局注册表，首次访问时加载内置表和系统 services 文件
+pub fn service_registry() -> &'static RwLock<ServiceRegistry> {
+    static REGISTRY: OnceLock<RwLock<ServiceRegistry>> = OnceLock::new();
+    REGISTRY.get_or_init(|| RwLock::new(ServiceRegistry::system()))
+}
+
+/// 为端口列表填充 `service_name`
+pub fn annotate_services(ports: &mut [PortInfo]) {
+    let listening: HashSet<(u16, Protocol)> = ports
+        .iter()
+        .filter(|p| p.state.is_listening())
+        .map(|p| (p.port, p.protocol))
+        .collect();
+
+    let registry = service_registry().read().unwrap_or_else(|e| e.into_inner());
+    for port in ports {
+        port.service_name = registry.service_for(port, &listening).map(str::to_string);
+    }
+}
+
+/// 系统 services 文件路径
+pub fn system_services_path() -> Option<PathBuf> {
+    #[cfg(windows)]
+    {
+        std::env::var_os("SystemRoot")
+            .map(PathBuf::from)
+            .map(|root| root.join(r"System32\drivers\etc\services"))
+    }
+
+    #[cfg(not(windows))]
+    {
+        Some(PathBuf::from("/etc/services"))
+    }
+}
+
+/// 解析 services 文件（Unix `/etc/services` 与 Windows `drivers\etc\services` 格式相同）
+///
+/// 每行为 `名称 端口/协议 [别名...] [# 注释]`，无法识别的行被跳过；同一端口出现多次时保留第一个
+pub fn parse_services(content: &str) -> Vec<ServiceEntry> {
+    let mut seen = HashSet::new();
+    let mut entries = Vec::new();
+
+    for line in content.lines() {
+        let line = line.split('#').next().unwrap_or("");
+        let mut fields = line.split_whitespace();
+        let (Some(name), Some(port_proto)) = (fields.next(), fields.next()) else {
+            continue;
+        };
+        let Some((port, protocol)) = port_proto.split_once('/') else {
+            continue;
+        };
+        let (Ok(port), Some(protocol)) = (port.parse::<u16>(), Protocol::from_name(protocol))
+        else {
+            continue;
+        };
+
+        if seen.insert((port, protocol)) {
+            entries.push(ServiceEntry {
+                port,
+                protocol,
+                name: name.to_string(),
+            });
+        }
+    }
+
+    entries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::ConnectionState;
+
+    fn entry(port: u16, protocol: Protocol, name: &str) -> ServiceEntry {
+        ServiceEntry {
+            port,
+            protocol,
+            name: name.to_string(),
+        }
+    }
+
+    fn connection(local: u16, remote: u16) -> PortInfo {
+        let mut port = PortInfo::new(local, Protocol::TCP, "10.0.0.2".to_string(), 1);
+        port.state = ConnectionState::Established;
+        port.remote_addr = Some("10.0.0.1".to_string());
+        port.remote_port = Some(remote);
+        port
+    }
+
+    #[test]
+    fn test_parse_services() {
+        let content = "\
+# comment line
+http        80/tcp   www www-http   # World Wide Web
+http-alt    80/tcp
+domain      53/udp
+quic        443/UDP
+broken      70000/tcp
+nothing
+bad         22/sctp
+ssh         22
+";
+        assert_eq!(
+            parse_services(content),
+            vec![
+                entry(80, Protocol::TCP, "http"),
+                entry(53, Protocol::UDP, "domain"),
+                entry(443, Protocol::UDP, "quic"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_overrides_take_precedence() {
+        let mut registry = ServiceRegistry::builtin();
+        assert_eq!(registry.lookup(80, Protocol::TCP), Some("http"));
+        assert_eq!(registry.lookup(80, Protocol::UDP), None);
+
+        registry.extend([
+            entry(80, Protocol::TCP, "web"),
+            entry(9, Protocol::UDP, "x"),
+        ]);
+        assert_eq!(registry.lookup(80, Protocol::TCP), Some("web"));
+
+        registry.set_overrides(vec![
+            entry(3000, Protocol::UDP, "dev-udp"),
+            entry(80, Protocol::TCP, "  proxy "),
+            entry(3000, Protocol::TCP, "dev"),
+            entry(443, Protocol::TCP, "   "),
+        ]);
+        assert_eq!(registry.lookup(80, Protocol::TCP), Some("proxy"));
+        assert_eq!(registry.lookup(443, Protocol::TCP), Some("https"));
+        assert_eq!(
+            registry.overrides(),
+            vec![
+                entry(80, Protocol::TCP, "proxy"),
+                entry(3000, Protocol::TCP, "dev"),
+                entry(3000, Protocol::UDP, "dev-udp"),
+            ]
+        );
+
+        // 清空覆盖后回到已有条目
+        registry.set_overrides(Vec::new());
+        assert_eq!(registry.lookup(80, Protocol::TCP), Some("web"));
+    }
+
+    #[test]
+    fn test_save_and_load_overrides() {
+        let dir = std::env::temp_dir().join(format!(
+            "port-detection-services-test-{}",
+            std::process::id()
+        ));
+        let path = dir.join("nested").join("services.json");
+        let _ = std::fs::remove_dir_all(&dir);
+
+        // 文件不存在时视为空
+        let mut registry = ServiceRegistry::builtin();
+        registry.load_overrides(&path).unwrap();
+        assert!(registry.overrides().is_empty());
+
+        registry.set_overrides(vec![entry(8080, Protocol::TCP, "api")]);
+        registry.save_overrides(&path).unwrap();
+
+        let mut loaded = ServiceRegistry::default();
+        loaded.load_overrides(&path).unwrap();
+        assert_eq!(loaded.overrides(), registry.overrides());
+        assert_eq!(loaded.lookup(8080, Protocol::TCP), Some("api"));
+
+        std::fs::write(&path, "not json").unwrap();
+        assert!(matches!(
+            loaded.load_overrides(&path),
+            Err(AppError::InvalidArgument(_))
+        ));
+        // 加载失败时保留原有覆盖
+        assert_eq!(loaded.lookup(8080, Protocol::TCP), Some("api"));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_service_for_uses_listener_side() {
+        let registry = ServiceRegistry::builtin();
+        let listening: HashSet<(u16, Protocol)> = [(5432, Protocol::TCP)].into();
+
+        let mut listener = PortInfo::new(5432, Protocol::TCP, "0.0.0.0".to_string(), 1);
+        listener.state = ConnectionState::Listen;
+        assert_eq!(
+            registry.service_for(&listener, &listening),
+            Some("postgresql")
+        );
+
+        // 入站连接：本地端口有监听，即使远程端口更小也取本地端口
+        let inbound = connection(5432, 80);
+        assert_eq!(
+            registry.service_for(&inbound, &listening),
+            Some("postgresql")
+        );
+
+        // 出站连接：本地端口没有监听，即使本地端口更小也取远程端口
+        let outbound = connection(111, 5432);
+        assert_eq!(
+            registry.service_for(&outbound, &listening),
+            Some("postgresql")
+        );
+        let outbound = connection(50000, 443);
+        assert_eq!(registry.service_for(&outbound, &listening), Some("https"));
+
+        // 远程端口未知时取本地端口
+        let unknown = connection(22, 0);
+        assert_eq!(registry.service_for(&unknown, &HashSet::new()), Some("ssh"));
+    }
+}
@@ -0,0 +1,75 @@
+//! TLS 证书检查结果模型
+
+use serde::{Deserialize, Serialize};
+
+/// 证书有效期状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CertStatus {
+    Valid,
+    /// 将在告警天数内过期
+    ExpiringSoon,
+    Expired,
+    /// 尚未生效
+    NotYetValid,
+    /// 未收到或无法解析证书
+    Unknown,
+}
+
+impl CertStatus {
+    /// 状态名（小写下划线形式）
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Valid => "valid",
+            Self::ExpiringSoon => "expiring_soon",
+            Self::Expired => "expired",
+            Self::NotYetValid => "not_yet_valid",
+            Self::Unknown => "unknown",
+        }
+    }
+}
+
+/// 单张证书
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CertificateInfo {
+    pub subject: String,
+    pub issuer: String,
+    /// 使用者可选名称（如 `DNS:localhost`、`IP:127.0.0.1`）
+    pub sans: Vec<String>,
+    /// 序列号（十六进制）
+    pub serial: String,
+    /// 生效时间（Unix 时间戳，秒）
+    pub not_before: i64,
+    /// 过期时间（Unix 时间戳，秒）
+    pub not_after: i64,
+    /// 公钥类型（如 `RSA 2048`、`EC P-256`、`Ed25519`）
+    pub key_type: String,
+    /// 签发者与使用者相同
+    pub self_signed: bool,
+}
+
+/// TLS 握手与证书链信息
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TlsInfo {
+    /// 协商的协议版本（如 `TLSv1.3`）
+    pub protocol_version: String,
+    /// 协商的密码套件
+    pub cipher_suite: String,
+    /// 协商的 ALPN 协议
+    pub alpn: Option<String>,
+    /// 服务端发送的证书链，第一张为叶子证书
+    pub chain: Vec<CertificateInfo>,
+    /// 证书链中最早过期的证书的剩余天数，已过期时为负数；无可用证书时为 None
+    pub expires_in_days: Option<i64>,
+    /// 按最早过期的证书计算的状态
+    pub status: CertStatus,
+    /// 检查时间（Unix 时间戳，毫秒）
+    pub inspected_at: u64,
+}
+
+impl TlsInfo {
+    /// 叶子证书
+    pub fn leaf(&self) -> Option<&CertificateInfo> {
+        self.chain.first()
+    }
+}
@@ -0,0 +1,89 @@
+//! 测试用的内存平台实现
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+use crate::models::{is_protected_process, AppError, AppResult, PortInfo, ProcessInfo, Protocol};
+use crate::platform::traits::{PortProvider, ProcessProvider};
+
+/// 端口和进程都由测试设置的平台实现
+#[derive(Default)]
+pub struct FakePlatform {
+    ports: Mutex<Vec<PortInfo>>,
+    processes: Mutex<HashMap<u32, ProcessInfo>>,
+    killed: Mutex<Vec<u32>>,
+    scans: AtomicUsize,
+}
+
+impl FakePlatform {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 替换当前端口列表
+    pub fn set_ports(&self, ports: Vec<PortInfo>) {
+        *self.ports.lock().unwrap() = ports;
+    }
+
+    pub fn add_process(&self, process: ProcessInfo) {
+        self.processes.lock().unwrap().insert(process.pid, process);
+    }
+
+    /// 已终止的进程
+    pub fn killed(&self) -> Vec<u32> {
+        self.killed.lock().unwrap().clone()
+    }
+
+    /// 端口表被读取的次数
+    pub fn scans(&self) -> usize {
+        self.scans.load(Ordering::SeqCst)
+    }
+
+    fn ports_of(&self, protocol: Protocol) -> Vec<PortInfo> {
+        self.ports
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|p| p.protocol == protocol)
+            .cloned()
+            .collect()
+    }
+}
+
+impl PortProvider for FakePlatform {
+    fn get_tcp_connections(&self) -> AppResult<Vec<PortInfo>> {
+        self.scans.fetch_add(1, Ordering::SeqCst);
+        Ok(self.ports_of(Protocol::TCP))
+    }
+
+    fn get_udp_endpoints(&self) -> AppResult<Vec<PortInfo>> {
+        Ok(self.ports_of(Protocol::UDP))
+    }
+
+    fn get_port_info(&self, port: u16, protocol: Protocol) -> AppResult<Option<PortInfo>> {
+        Ok(self.ports_of(protocol).into_iter().find(|p| p.port == port))
+    }
+}
+
+impl ProcessProvider for FakePlatform {
+    fn get_process_info(&self, pid: u32) -> AppResult<Option<ProcessInfo>> {
+        Ok(self.processes.lock().unwrap().get(&pid).cloned())
+    }
+
+    fn kill_process(&self, pid: u32, _expected_create_time: Option<u64>) -> AppResult<()> {
+        let mut processes = self.processes.lock().unwrap();
+        let process = processes.get(&pid).ok_or(AppError::ProcessNotFound(pid))?;
+        if self.is_protected(&process.name) {
+            return Err(AppError::ProtectedProcess(process.name.clone()));
+        }
+        processes.remove(&pid);
+        self.ports.lock().unwrap().retain(|p| p.pid != pid);
+        self.killed.lock().unwrap().push(pid);
+        Ok(())
+    }
+
+    fn is_protected(&self, name: &str) -> bool {
+        is_protected_process(name)
+    }
+}
@@ -0,0 +1,248 @@
+//! 解析 `iptables-save` / `ip6tables-save` 输出（只读取 filter 表）
+
+use super::{Action, Chain, Cond, Family, InputHook, Rule, Ruleset};
+use crate::models::{FirewallBackend, FirewallVerdict};
+
+/// 解析规则，`family` 决定 INPUT 链适用的地址族
+pub(super) fn parse(output: &str, family: Family) -> Ruleset {
+    let prefix = match family {
+        Family::V4 => "iptables filter",
+        Family::V6 => "ip6tables filter",
+    };
+    let chain_id = |name: &str| format!("{} {}", prefix, name);
+
+    let mut ruleset = Ruleset::new(FirewallBackend::Iptables);
+    let mut in_filter = false;
+
+    for line in output.lines().map(str::trim) {
+        if let Some(table) = line.strip_prefix('*') {
+            in_filter = table == "filter";
+            continue;
+        }
+        if !in_filter || line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if line == "COMMIT" {
+            in_filter = false;
+            continue;
+        }
+
+        // `:INPUT DROP [0:0]`，自定义链的策略为 `-`
+        if let Some(decl) = line.strip_prefix(':') {
+            let mut parts = decl.split_whitespace();
+            let (Some(name), Some(policy)) = (parts.next(), parts.next()) else {
+                continue;
+            };
+            let policy = match policy {
+                "DROP" => Some(FirewallVerdict::Drop),
+                "-" => None,
+                _ => Some(FirewallVerdict::Accept),
+            };
+            if name == "INPUT" {
+                ruleset.inputs.push(InputHook {
+                    chain: chain_id(name),
+                    families: vec![family],
+                    priority: 0,
+                });
+            }
+            ruleset.chains.insert(
+                chain_id(name),
+                Chain {
+                    name: chain_id(name),
+                    policy,
+                    rules: Vec::new(),
+                },
+            );
+            continue;
+        }
+
+        let tokens = tokenize(line);
+        let (Some("-A"), Some(chain)) = (tokens.first().map(String::as_str), tokens.get(1)) else {
+            continue;
+        };
+        let (conds, action) = parse_args(&tokens[2..], |target| {
+            let id = chain_id(target);
+            ruleset.chains.contains_key(&id).then_some(id)
+        });
+        if let Some(chain) = ruleset.chains.get_mut(&chain_id(chain)) {
+            chain.rules.push(Rule {
+                text: line.to_string(),
+                conds,
+                action,
+            });
+        }
+    }
+
+    ruleset
+}
+
+/// 解析规则参数，`resolve_chain` 将目标名解析为自定义链 ID
+fn parse_args(
+    args: &[String],
+    resolve_chain: impl Fn(&str) -> Option<String>,
+) -> (Vec<Cond>, Action) {
+    let mut conds = Vec::new();
+    let mut action = Action::Continue;
+    let mut negate = false;
+    let mut i = 0;
+
+    let value = |i: usize| args.get(i + 1).map(String::as_str).unwrap_or("");
+
+    while i < args.len() {
+        let arg = args[i].as_str();
+        if arg == "!" {
+            negate = true;
+            i += 1;
+            continue;
+        }
+
+        match arg {
+            "-p" | "--protocol" => {
+                let protocol = value(i).to_lowercase();
+                if protocol == "all" {
+                    if negate {
+                        conds.push(Cond::Never);
+                    }
+                } else {
+                    let protocol = match protocol.as_str() {
+                        "6" => "tcp".to_string(),
+                        "17" => "udp".to_string(),
+                        _ => protocol,
+                    };
+                    conds.push(Cond::Protocol {
+                        negate,
+                        protocols: vec![protocol],
+                    });
+                }
+                i += 2;
+            }
+            "-i" | "--in-interface" => {
+                conds.push(if value(i) == "lo" {
+                    Cond::Loopback { negate }
+                } else {
+                    Cond::Opaque
+                });
+                i += 2;
+            }
+            "--dport" | "--destination-port" | "--dports" | "--destination-ports" => {
+                conds.push(match parse_ports(value(i)) {
+                    Some(ranges) => Cond::Dport { negate, ranges },
+                    None => Cond::Opaque,
+                });
+                i += 2;
+            }
+            "--state" | "--ctstate" => {
+                conds.push(Cond::CtState {
+                    negate,
+                    states: value(i).split(',').map(str::to_lowercase).collect(),
+                });
+                i += 2;
+            }
+            "--dst-type" => {
+                match (value(i), negate) {
+                    ("LOCAL", false) => {}
+                    ("LOCAL", true) => conds.push(Cond::Never),
+                    _ => conds.push(Cond::Opaque),
+                }
+                i += 2;
+            }
+            // 新连接的第一个包总是 SYN
+            "--syn" => {
+                if negate {
+                    conds.push(Cond::Never);
+                }
+                i += 1;
+            }
+            "--icmp-type" | "--icmpv6-type" => {
+                conds.push(Cond::Never);
+                i += 2;
+            }
+            // 模块名和注释不构成条件
+            "-m" | "--match" | "--comment" => i += 2,
+            // 目标参数（如 `--reject-with`）在 `-j` 之后，不再解析
+            "-j" | "--jump" | "-g" | "--goto" => {
+                let target = value(i);
+                action = match target {
+                    "ACCEPT" => Action::Accept,
+                    "DROP" | "REJECT" => Action::Drop,
+                    "RETURN" => Action::Return,
+                    _ => match resolve_chain(target) {
+                        Some(id) if arg == "-g" || arg == "--goto" => Action::Goto(id),
+                        Some(id) => Action::Jump(id),
+                        // LOG、MARK 等扩展目标
+                        None => Action::Continue,
+                    },
+                };
+                break;
+            }
+            _ => {
+                // 来源地址、速率限制等：跳过参数值
+                conds.push(Cond::Opaque);
+                i += 1;
+                while i < args.len() && !args[i].starts_with('-') && args[i] != "!" {
+                    i += 1;
+                }
+            }
+        }
+        negate = false;
+    }
+
+    (conds, action)
+}
+
+/// `22`、`8000:8099` 或 multiport 的 `80,443,8000:8099`
+fn parse_ports(value: &str) -> Option<Vec<(u16, u16)>> {
+    value
+        .split(',')
+        .map(|part| match part.split_once(':') {
+            Some((lo, hi)) => {
+                let lo = if lo.is_empty() { 0 } else { lo.parse().ok()? };
+                let hi = if hi.is_empty() {
+                    u16::MAX
+                } else {
+                    hi.parse().ok()?
+                };
+                Some((lo, hi))
+            }
+            None => part.parse().ok().map(|port| (port, port)),
+        })
+        .collect()
+}
+
+/// 按空白分割，支持双引号和反斜杠转义（`--comment "allow ssh"`）
+fn tokenize(line: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut in_token = false;
+    let mut quoted = false;
+    let mut chars = line.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                if let Some(next) = chars.next() {
+                    current.push(next);
+                }
+                in_token = true;
+            }
+            '"' => {
+                quoted = !quoted;
+                in_token = true;
+            }
+            c if c.is_whitespace() && !quoted => {
+                if in_token {
+                    tokens.push(std::mem::take(&mut current));
+                    in_token = false;
+                }
+            }
+            c => {
+                current.push(c);
+                in_token = true;
+            }
+        }
+    }
+    if in_token {
+        tokens.push(current);
+    }
+    tokens
+}
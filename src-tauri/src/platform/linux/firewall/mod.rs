@@ -0,0 +1,577 @@
+//! 主机防火墙规则读取与入站判断
+//!
+//! 读取 nftables（`nft -j list ruleset`）或 iptables（`iptables-save`）规则，
+//! 判断从外部发往监听端口的新连接会被接受还是丢弃。来源地址、速率限制等条件无法静态判断，
+//! 只对部分来源放行的端口记为 `Unknown`；只对部分来源丢弃的端口仍记为 `Accept`
+
+mod iptables;
+mod nft;
+
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::process::Command;
+
+use crate::models::{
+    AppError, AppResult, FirewallBackend, FirewallRule, FirewallStatus, FirewallVerdict, PortInfo,
+    Protocol,
+};
+use crate::utils::net::parse_addr;
+
+/// 同一钩子上的链跳转深度上限
+const MAX_DEPTH: usize = 32;
+
+/// 地址族
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Family {
+    V4,
+    V6,
+}
+
+/// 从外部发往本机的新连接
+#[derive(Debug, Clone, Copy)]
+pub struct Packet {
+    pub family: Family,
+    pub protocol: Protocol,
+    pub port: u16,
+}
+
+/// 条件与数据包的匹配结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Match {
+    Yes,
+    No,
+    Maybe,
+}
+
+impl Match {
+    fn from_bool(matched: bool) -> Self {
+        if matched {
+            Self::Yes
+        } else {
+            Self::No
+        }
+    }
+}
+
+/// 规则条件
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Cond {
+    Family(Family),
+    /// 传输层协议（小写名称）
+    Protocol {
+        negate: bool,
+        protocols: Vec<String>,
+    },
+    /// 目的端口范围（闭区间）
+    Dport {
+        negate: bool,
+        ranges: Vec<(u16, u16)>,
+    },
+    /// 连接跟踪状态（小写名称）
+    CtState {
+        negate: bool,
+        states: Vec<String>,
+    },
+    /// 入接口为回环接口
+    Loopback {
+        negate: bool,
+    },
+    /// 来源地址、速率限制等无法静态判断的条件
+    Opaque,
+    /// 不会匹配入站 TCP/UDP 新连接（如 ICMP）
+    Never,
+}
+
+impl Cond {
+    fn matches(&self, packet: &Packet) -> Match {
+        match self {
+            Self::Family(family) => Match::from_bool(*family == packet.family),
+            Self::Protocol { negate, protocols } => {
+                let name = match packet.protocol {
+                    Protocol::TCP => "tcp",
+                    Protocol::UDP => "udp",
+                };
+                Match::from_bool(protocols.iter().any(|p| p == name) != *negate)
+            }
+            Self::Dport { negate, ranges } => Match::from_bool(
+                ranges
+                    .iter()
+                    .any(|(lo, hi)| (*lo..=*hi).contains(&packet.port))
+                    != *negate,
+            ),
+            Self::CtState { negate, states } => {
+                Match::from_bool(states.iter().any(|s| s == "new") != *negate)
+            }
+            // 外部流量不会从回环接口进入
+            Self::Loopback { negate } => Match::from_bool(*negate),
+            Self::Opaque => Match::Maybe,
+            Self::Never => Match::No,
+        }
+    }
+}
+
+/// 规则动作
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Action {
+    Accept,
+    /// 丢弃或拒绝
+    Drop,
+    /// 跳转到链（链 ID），返回后继续
+    Jump(String),
+    /// 转到链（链 ID），返回后不再回到当前链
+    Goto(String),
+    Return,
+    /// 计数、日志等不终止匹配的动作
+    Continue,
+}
+
+#[derive(Debug, Clone)]
+struct Rule {
+    text: String,
+    conds: Vec<Cond>,
+    action: Action,
+}
+
+impl Rule {
+    fn matches(&self, packet: &Packet) -> Match {
+        let mut result = Match::Yes;
+        for cond in &self.conds {
+            match cond.matches(packet) {
+                Match::No => return Match::No,
+                Match::Maybe => result = Match::Maybe,
+                Match::Yes => {}
+            }
+        }
+        result
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Chain {
+    /// 显示名（如 `inet filter input`、`iptables filter INPUT`）
+    name: String,
+    /// 默认策略，只有基础链有
+    policy: Option<FirewallVerdict>,
+    rules: Vec<Rule>,
+}
+
+/// 入站基础链
+#[derive(Debug, Clone)]
+struct InputHook {
+    chain: String,
+    families: Vec<Family>,
+    priority: i64,
+}
+
+/// 链遍历结果
+enum Outcome {
+    Verdict(FirewallVerdict, FirewallRule),
+    Return,
+}
+
+/// 解析后的防火墙规则
+#[derive(Debug, Clone)]
+pub struct Ruleset {
+    backend: FirewallBackend,
+    /// 入站基础链，按优先级排序
+    inputs: Vec<InputHook>,
+    /// 以链 ID 为键
+    chains: HashMap<String, Chain>,
+}
+
+impl Ruleset {
+    fn new(backend: FirewallBackend) -> Self {
+        Self {
+            backend,
+            inputs: Vec::new(),
+            chains: HashMap::new(),
+        }
+    }
+
+    /// 没有入站过滤链（所有入站连接都被接受）
+    pub fn is_empty(&self) -> bool {
+        self.inputs.is_empty()
+    }
+
+    /// 合并另一组规则（如 `ip6tables-save` 的输出）
+    fn merge(&mut self, other: Ruleset) {
+        self.inputs.extend(other.inputs);
+        self.inputs.sort_by_key(|hook| hook.priority);
+        self.chains.extend(other.chains);
+    }
+
+    /// 判断监听在 `addr` 上的端口能否接受外部新连接
+    ///
+    /// `::` 在 Linux 上默认同时接受 IPv4，取两个地址族中较宽松的结果
+    pub fn evaluate_listener(&self, addr: IpAddr, protocol: Protocol, port: u16) -> FirewallStatus {
+        let families: &[Family] = match addr {
+            IpAddr::V4(_) => &[Family::V4],
+            IpAddr::V6(v6) if v6.is_unspecified() => &[Family::V6, Family::V4],
+            IpAddr::V6(v6) if v6.to_ipv4_mapped().is_some() => &[Family::V4],
+            IpAddr::V6(_) => &[Family::V6],
+        };
+
+        let rank = |verdict: FirewallVerdict| match verdict {
+            FirewallVerdict::Drop => 0,
+            FirewallVerdict::Unknown => 1,
+            FirewallVerdict::Accept => 2,
+        };
+        families
+            .iter()
+            .map(|&family| {
+                self.evaluate(&Packet {
+                    family,
+                    protocol,
+                    port,
+                })
+            })
+            .max_by_key(|status| rank(status.verdict))
+            .expect("至少有一个地址族")
+    }
+
+    /// 依次经过各入站基础链，任一链丢弃即丢弃
+    pub fn evaluate(&self, packet: &Packet) -> FirewallStatus {
+        let mut status = FirewallStatus {
+            backend: Some(self.backend),
+            verdict: FirewallVerdict::Accept,
+            rule: None,
+        };
+
+        for hook in &self.inputs {
+            if !hook.families.contains(&packet.family) {
+                continue;
+            }
+            let Some(chain) = self.chains.get(&hook.chain) else {
+                continue;
+            };
+            let (verdict, rule) = self.evaluate_base(chain, packet);
+            match verdict {
+                FirewallVerdict::Drop => {
+                    status.verdict = verdict;
+                    status.rule = Some(rule);
+                    return status;
+                }
+                FirewallVerdict::Unknown => {
+                    if status.verdict == FirewallVerdict::Accept {
+                        status.verdict = verdict;
+                        status.rule = Some(rule);
+                    }
+                }
+                FirewallVerdict::Accept => {
+                    if status.verdict == FirewallVerdict::Accept {
+                        status.rule = Some(rule);
+                    }
+                }
+            }
+        }
+
+        status
+    }
+
+    fn evaluate_base(&self, chain: &Chain, packet: &Packet) -> (FirewallVerdict, FirewallRule) {
+        let mut conditional = None;
+        let (verdict, rule) = match self.walk(chain, packet, &mut conditional, 0) {
+            Outcome::Verdict(verdict, rule) => (verdict, rule),
+            Outcome::Return => {
+                let policy = chain.policy.unwrap_or(FirewallVerdict::Accept);
+                let rule = FirewallRule {
+                    chain: chain.name.clone(),
+                    rule: format!("policy {}", policy.name()),
+                };
+                (policy, rule)
+            }
+        };
+
+        // 最终丢弃，但之前有只对部分来源放行的规则
+        match conditional {
+            Some(rule) if verdict == FirewallVerdict::Drop => (FirewallVerdict::Unknown, rule),
+            _ => (verdict, rule),
+        }
+    }
+
+    /// 遍历链，`conditional` 记录第一条可能匹配且可能放行的规则
+    fn walk(
+        &self,
+        chain: &Chain,
+        packet: &Packet,
+        conditional: &mut Option<FirewallRule>,
+        depth: usize,
+    ) -> Outcome {
+        if depth > MAX_DEPTH {
+            return Outcome::Return;
+        }
+
+        for rule in &chain.rules {
+            let here = || FirewallRule {
+                chain: chain.name.clone(),
+                rule: rule.text.clone(),
+            };
+            match rule.matches(packet) {
+                Match::No => continue,
+                Match::Maybe => {
+                    if conditional.is_none()
+                        && !matches!(rule.action, Action::Drop | Action::Continue)
+                    {
+                        *conditional = Some(here());
+                    }
+                    continue;
+                }
+                Match::Yes => {}
+            }
+
+            match &rule.action {
+                Action::Accept => return Outcome::Verdict(FirewallVerdict::Accept, here()),
+                Action::Drop => return Outcome::Verdict(FirewallVerdict::Drop, here()),
+                Action::Return => return Outcome::Return,
+                Action::Continue => {}
+                Action::Jump(target) => {
+                    if let Some(target) = self.chains.get(target) {
+                        if let verdict @ Outcome::Verdict(..) =
+                            self.walk(target, packet, conditional, depth + 1)
+                        {
+                            return verdict;
+                        }
+                    }
+                }
+                Action::Goto(target) => {
+                    return match self.chains.get(target) {
+                        Some(target) => self.walk(target, packet, conditional, depth + 1),
+                        None => Outcome::Return,
+                    };
+                }
+            }
+        }
+
+        Outcome::Return
+    }
+}
+
+/// 读取当前规则：优先使用 nftables，规则为空或含 iptables 兼容表达式时改用 `iptables-save`
+pub fn read_ruleset() -> AppResult<Ruleset> {
+    let nft = run("nft", &["-j", "list", "ruleset"]).and_then(|output| nft::parse(&output));
+    if let Ok((ruleset, compat)) = &nft {
+        if !ruleset.is_empty() && !compat {
+            return Ok(ruleset.clone());
+        }
+    }
+
+    match run("iptables-save", &[]) {
+        Ok(output) => {
+            let mut ruleset = iptables::parse(&output, Family::V4);
+            if let Ok(output) = run("ip6tables-save", &[]) {
+                ruleset.merge(iptables::parse(&output, Family::V6));
+            }
+            Ok(ruleset)
+        }
+        Err(e) => nft.map(|(ruleset, _)| ruleset).map_err(|_| e),
+    }
+}
+
+/// 为非回环监听端口填充 `firewall`，无法读取规则时记为 `Unknown`
+pub fn annotate_firewall(ports: &mut [PortInfo]) {
+    let targets: Vec<usize> = ports
+        .iter()
+        .enumerate()
+        .filter(|(_, p)| {
+            p.state.is_listening() && parse_addr(&p.local_addr).is_some_and(|ip| !ip.is_loopback())
+        })
+        .map(|(i, _)| i)
+        .collect();
+    if targets.is_empty() {
+        return;
+    }
+
+    let ruleset = read_ruleset()
+        .map_err(|e| tracing::warn!("Failed to read firewall rules: {}", e))
+        .ok();
+
+    for i in targets {
+        let port = &mut ports[i];
+        let Some(addr) = parse_addr(&port.local_addr) else {
+            continue;
+        };
+        port.firewall = Some(match &ruleset {
+            Some(ruleset) => ruleset.evaluate_listener(addr, port.protocol, port.port),
+            None => FirewallStatus {
+                backend: None,
+                verdict: FirewallVerdict::Unknown,
+                rule: None,
+            },
+        });
+    }
+}
+
+fn run(program: &str, args: &[&str]) -> AppResult<String> {
+    let output = Command::new(program)
+        .args(args)
+        .output()
+        .map_err(|e| AppError::Internal(format!("执行 {} 失败: {}", program, e)))?;
+
+    if !output.status.success() {
+        return Err(AppError::Internal(format!(
+            "{} 命令失败: {}",
+            program,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NFT_RULESET: &str = include_str!("fixtures/nft_ruleset.json");
+    const IPTABLES_SAVE: &str = include_str!("fixtures/iptables_save.txt");
+    const IP6TABLES_SAVE: &str = include_str!("fixtures/ip6tables_save.txt");
+
+    fn check(
+        ruleset: &Ruleset,
+        family: Family,
+        protocol: Protocol,
+        port: u16,
+    ) -> (FirewallVerdict, String) {
+        let status = ruleset.evaluate(&Packet {
+            family,
+            protocol,
+            port,
+        });
+        let rule = status
+            .rule
+            .map(|r| format!("{}: {}", r.chain, r.rule))
+            .unwrap_or_default();
+        (status.verdict, rule)
+    }
+
+    #[test]
+    fn test_nft_ruleset() {
+        let (ruleset, compat) = nft::parse(NFT_RULESET).unwrap();
+        assert!(!compat);
+        assert_eq!(ruleset.backend, FirewallBackend::Nftables);
+
+        let cases = [
+            (
+                Family::V4,
+                Protocol::TCP,
+                22,
+                FirewallVerdict::Accept,
+                "inet filter input: tcp dport 22 counter accept",
+            ),
+            (
+                Family::V4,
+                Protocol::TCP,
+                8080,
+                FirewallVerdict::Accept,
+                "inet filter input: tcp dport @web_ports accept",
+            ),
+            (
+                Family::V4,
+                Protocol::TCP,
+                5432,
+                FirewallVerdict::Unknown,
+                "inet filter input: ip saddr 192.168.0.0/16 tcp dport 5432 accept",
+            ),
+            (
+                Family::V4,
+                Protocol::UDP,
+                5353,
+                FirewallVerdict::Accept,
+                "inet filter services: udp dport 5353 accept",
+            ),
+            (
+                Family::V4,
+                Protocol::TCP,
+                9005,
+                FirewallVerdict::Drop,
+                "inet filter services: tcp dport 9000-9010 drop",
+            ),
+            (
+                Family::V4,
+                Protocol::TCP,
+                3306,
+                FirewallVerdict::Drop,
+                "inet filter input: tcp dport 3306 reject",
+            ),
+            (
+                Family::V4,
+                Protocol::TCP,
+                6379,
+                FirewallVerdict::Drop,
+                "inet filter input: policy drop",
+            ),
+            (
+                Family::V6,
+                Protocol::UDP,
+                546,
+                FirewallVerdict::Accept,
+                "inet filter input: meta nfproto ipv6 udp dport 546 accept",
+            ),
+            (
+                Family::V4,
+                Protocol::UDP,
+                546,
+                FirewallVerdict::Drop,
+                "inet filter input: policy drop",
+            ),
+        ];
+        for (family, protocol, port, verdict, rule) in cases {
+            assert_eq!(
+                check(&ruleset, family, protocol, port),
+                (verdict, rule.to_string()),
+                "{:?} {:?} {}",
+                family,
+                protocol,
+                port
+            );
+        }
+    }
+
+    #[test]
+    fn test_iptables_save() {
+        let mut ruleset = iptables::parse(IPTABLES_SAVE, Family::V4);
+        ruleset.merge(iptables::parse(IP6TABLES_SAVE, Family::V6));
+        assert_eq!(ruleset.backend, FirewallBackend::Iptables);
+
+        let cases = [
+            (Family::V4, Protocol::TCP, 22, FirewallVerdict::Accept, "iptables filter user-input: -A user-input -p tcp -m tcp --dport 22 -m comment --comment \"allow ssh\" -j ACCEPT"),
+            (Family::V4, Protocol::TCP, 8080, FirewallVerdict::Accept, "iptables filter user-input: -A user-input -p tcp -m multiport --dports 80,443,8000:8099 -j ACCEPT"),
+            (Family::V4, Protocol::TCP, 5432, FirewallVerdict::Unknown, "iptables filter user-input: -A user-input -s 192.168.0.0/16 -p tcp -m tcp --dport 5432 -j ACCEPT"),
+            (Family::V4, Protocol::UDP, 5353, FirewallVerdict::Accept, "iptables filter user-input: -A user-input -p udp -m udp --dport 5353 -j ACCEPT"),
+            (Family::V4, Protocol::TCP, 9005, FirewallVerdict::Drop, "iptables filter user-input: -A user-input -p tcp -m tcp --dport 9000:9010 -j DROP"),
+            (Family::V4, Protocol::TCP, 3306, FirewallVerdict::Drop, "iptables filter INPUT: -A INPUT -p tcp -m tcp --dport 3306 -j REJECT --reject-with icmp-port-unreachable"),
+            (Family::V4, Protocol::TCP, 6379, FirewallVerdict::Drop, "iptables filter INPUT: policy drop"),
+            (Family::V6, Protocol::TCP, 6379, FirewallVerdict::Drop, "ip6tables filter INPUT: -A INPUT -p tcp -m tcp --dport 6379 -j DROP"),
+            (Family::V6, Protocol::TCP, 5432, FirewallVerdict::Accept, "ip6tables filter INPUT: policy accept"),
+        ];
+        for (family, protocol, port, verdict, rule) in cases {
+            assert_eq!(
+                check(&ruleset, family, protocol, port),
+                (verdict, rule.to_string()),
+                "{:?} {:?} {}",
+                family,
+                protocol,
+                port
+            );
+        }
+
+        // `::` 同时接受 IPv4，取较宽松的结果
+        let status = ruleset.evaluate_listener("::".parse().unwrap(), Protocol::TCP, 5432);
+        assert_eq!(status.verdict, FirewallVerdict::Accept);
+        let status = ruleset.evaluate_listener("0.0.0.0".parse().unwrap(), Protocol::TCP, 5432);
+        assert_eq!(status.verdict, FirewallVerdict::Unknown);
+    }
+
+    #[test]
+    fn test_empty_ruleset() {
+        let (ruleset, _) =
+            nft::parse(r#"{"nftables": [{"metainfo": {"json_schema_version": 1}}]}"#).unwrap();
+        assert!(ruleset.is_empty());
+        let (verdict, rule) = check(&ruleset, Family::V4, Protocol::TCP, 22);
+        assert_eq!(verdict, FirewallVerdict::Accept);
+        assert!(rule.is_empty());
+
+        let ruleset = iptables::parse("", Family::V4);
+        assert!(ruleset.is_empty());
+    }
+}
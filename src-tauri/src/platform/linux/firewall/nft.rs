@@ -0,0 +1,417 @@
+//! 解析 `nft -j list ruleset` 输出
+
+use std::collections::HashMap;
+
+use serde_json::Value;
+
+use super::{Action, Chain, Cond, Family, InputHook, Rule, Ruleset};
+use crate::models::{AppError, AppResult, FirewallBackend, FirewallVerdict};
+
+/// 不影响匹配结果的语句
+const NOOP_STATEMENTS: &[&str] = &["counter", "log", "mangle", "notrack", "comment"];
+
+/// 解析规则，第二个返回值表示是否含 iptables 兼容表达式（`xt`）
+pub(super) fn parse(json: &str) -> AppResult<(Ruleset, bool)> {
+    let root: Value = serde_json::from_str(json)
+        .map_err(|e| AppError::Internal(format!("解析 nft 输出失败: {}", e)))?;
+    let items = root
+        .get("nftables")
+        .and_then(Value::as_array)
+        .ok_or_else(|| AppError::Internal("nft 输出缺少 nftables 字段".to_string()))?;
+
+    let mut ruleset = Ruleset::new(FirewallBackend::Nftables);
+    let mut sets: HashMap<String, Vec<Value>> = HashMap::new();
+
+    for item in items {
+        if let Some(set) = item.get("set").or_else(|| item.get("map")) {
+            let elems = set
+                .get("elem")
+                .and_then(Value::as_array)
+                .cloned()
+                .unwrap_or_default();
+            sets.insert(object_id(set, "name"), elems);
+        }
+
+        let Some(chain) = item.get("chain") else {
+            continue;
+        };
+        let id = object_id(chain, "name");
+        let families = match str_field(chain, "family") {
+            "ip" => vec![Family::V4],
+            "ip6" => vec![Family::V6],
+            "inet" => vec![Family::V4, Family::V6],
+            _ => Vec::new(),
+        };
+        let is_input = chain.get("hook").and_then(Value::as_str) == Some("input")
+            && matches!(
+                chain.get("type").and_then(Value::as_str),
+                Some("filter") | None
+            )
+            && !families.is_empty();
+        let policy = is_input.then(|| match chain.get("policy").and_then(Value::as_str) {
+            Some("drop") => FirewallVerdict::Drop,
+            _ => FirewallVerdict::Accept,
+        });
+
+        if is_input {
+            ruleset.inputs.push(InputHook {
+                chain: id.clone(),
+                families,
+                priority: chain.get("prio").and_then(Value::as_i64).unwrap_or(0),
+            });
+        }
+        ruleset.chains.insert(
+            id.clone(),
+            Chain {
+                name: id,
+                policy,
+                rules: Vec::new(),
+            },
+        );
+    }
+    ruleset.inputs.sort_by_key(|hook| hook.priority);
+
+    let mut compat = false;
+    for rule in items.iter().filter_map(|item| item.get("rule")) {
+        let table = format!("{} {}", str_field(rule, "family"), str_field(rule, "table"));
+        let Some(chain) =
+            ruleset
+                .chains
+                .get_mut(&format!("{} {}", table, str_field(rule, "chain")))
+        else {
+            continue;
+        };
+        let exprs = rule
+            .get("expr")
+            .and_then(Value::as_array)
+            .cloned()
+            .unwrap_or_default();
+        compat |= exprs.iter().any(|e| e.get("xt").is_some());
+        chain.rules.extend(parse_rule(&exprs, &table, &sets));
+    }
+
+    Ok((ruleset, compat))
+}
+
+/// `族 表 名称`
+fn object_id(object: &Value, name_field: &str) -> String {
+    format!(
+        "{} {} {}",
+        str_field(object, "family"),
+        str_field(object, "table"),
+        str_field(object, name_field)
+    )
+}
+
+fn str_field<'a>(object: &'a Value, field: &str) -> &'a str {
+    object.get(field).and_then(Value::as_str).unwrap_or("")
+}
+
+/// 解析一条规则；含 `vmap` 时按映射的每个元素展开为多条
+fn parse_rule(exprs: &[Value], table: &str, sets: &HashMap<String, Vec<Value>>) -> Vec<Rule> {
+    let text = exprs
+        .iter()
+        .map(render_statement)
+        .collect::<Vec<_>>()
+        .join(" ");
+    let mut conds = Vec::new();
+    let mut action = Action::Continue;
+    let mut vmap = None;
+
+    for expr in exprs {
+        let Some((key, body)) = expr.as_object().and_then(|o| o.iter().next()) else {
+            continue;
+        };
+        match key.as_str() {
+            "match" => conds.extend(parse_match(
+                str_field(body, "op"),
+                &body["left"],
+                &body["right"],
+                table,
+                sets,
+            )),
+            "vmap" => vmap = Some(body),
+            _ => match parse_verdict(key, body, table) {
+                Some(verdict) => action = verdict,
+                None if NOOP_STATEMENTS.contains(&key.as_str()) => {}
+                None => conds.push(Cond::Opaque),
+            },
+        }
+    }
+
+    let Some(vmap) = vmap else {
+        return vec![Rule {
+            text,
+            conds,
+            action,
+        }];
+    };
+
+    resolve(&vmap["data"], table, sets)
+        .iter()
+        .filter_map(|elem| {
+            let pair = elem.as_array()?;
+            let (key, verdict) = (pair.first()?, pair.get(1)?);
+            let (name, body) = verdict.as_object()?.iter().next()?;
+            let mut conds = conds.clone();
+            conds.extend(parse_match(
+                "==",
+                &vmap["key"],
+                unwrap_elem(key),
+                table,
+                sets,
+            ));
+            Some(Rule {
+                text: text.clone(),
+                conds,
+                action: parse_verdict(name, body, table)?,
+            })
+        })
+        .collect()
+}
+
+fn parse_verdict(key: &str, body: &Value, table: &str) -> Option<Action> {
+    let target = || format!("{} {}", table, str_field(body, "target"));
+    Some(match key {
+        "accept" => Action::Accept,
+        "drop" | "reject" => Action::Drop,
+        "return" => Action::Return,
+        "continue" => Action::Continue,
+        "jump" => Action::Jump(target()),
+        "goto" => Action::Goto(target()),
+        _ => return None,
+    })
+}
+
+fn parse_match(
+    op: &str,
+    left: &Value,
+    right: &Value,
+    table: &str,
+    sets: &HashMap<String, Vec<Value>>,
+) -> Vec<Cond> {
+    let negate = op == "!=";
+    let values = || resolve(right, table, sets);
+
+    if let Some(payload) = left.get("payload") {
+        let protocol = str_field(payload, "protocol");
+        let field = str_field(payload, "field");
+        return match protocol {
+            "tcp" | "udp" | "udplite" | "sctp" | "dccp" | "th" => {
+                let mut conds = Vec::new();
+                if protocol != "th" {
+                    conds.push(Cond::Protocol {
+                        negate: false,
+                        protocols: vec![protocol.to_string()],
+                    });
+                }
+                conds.push(match field {
+                    "dport" => port_cond(op, &values()),
+                    _ => Cond::Opaque,
+                });
+                conds
+            }
+            "ip" | "ip6" => {
+                let family = if protocol == "ip" {
+                    Family::V4
+                } else {
+                    Family::V6
+                };
+                let cond = match field {
+                    "protocol" | "nexthdr" => protocol_cond(negate, &values()),
+                    _ => Cond::Opaque,
+                };
+                vec![Cond::Family(family), cond]
+            }
+            "icmp" | "icmpv6" | "igmp" => vec![Cond::Never],
+            _ => vec![Cond::Opaque],
+        };
+    }
+
+    if let Some(meta) = left.get("meta") {
+        return vec![match str_field(meta, "key") {
+            "l4proto" => protocol_cond(negate, &values()),
+            "nfproto" => match (right.as_str(), negate) {
+                (Some("ipv4"), false) | (Some("ipv6"), true) => Cond::Family(Family::V4),
+                (Some("ipv6"), false) | (Some("ipv4"), true) => Cond::Family(Family::V6),
+                _ => Cond::Opaque,
+            },
+            "iif" | "iifname" if right.as_str() == Some("lo") => Cond::Loopback { negate },
+            _ => Cond::Opaque,
+        }];
+    }
+
+    if left
+        .get("ct")
+        .is_some_and(|ct| str_field(ct, "key") == "state")
+    {
+        return vec![Cond::CtState {
+            negate,
+            states: values()
+                .iter()
+                .filter_map(Value::as_str)
+                .map(str::to_lowercase)
+                .collect(),
+        }];
+    }
+
+    vec![Cond::Opaque]
+}
+
+/// 目的端口条件，支持单值、集合、范围和比较运算
+fn port_cond(op: &str, values: &[Value]) -> Cond {
+    let ranges: Option<Vec<(u16, u16)>> = values.iter().map(port_range).collect();
+    let Some(ranges) = ranges else {
+        return Cond::Opaque;
+    };
+
+    match (op, ranges.as_slice()) {
+        ("==" | "in" | "!=", _) => Cond::Dport {
+            negate: op == "!=",
+            ranges,
+        },
+        ("<", [(port, _)]) if *port > 0 => dport(0, port - 1),
+        ("<=", [(port, _)]) => dport(0, *port),
+        (">", [(port, _)]) if *port < u16::MAX => dport(port + 1, u16::MAX),
+        (">=", [(port, _)]) => dport(*port, u16::MAX),
+        _ => Cond::Opaque,
+    }
+}
+
+fn dport(lo: u16, hi: u16) -> Cond {
+    Cond::Dport {
+        negate: false,
+        ranges: vec![(lo, hi)],
+    }
+}
+
+fn port_range(value: &Value) -> Option<(u16, u16)> {
+    if let Some(range) = value.get("range").and_then(Value::as_array) {
+        let lo = u16::try_from(range.first()?.as_u64()?).ok()?;
+        let hi = u16::try_from(range.get(1)?.as_u64()?).ok()?;
+        return Some((lo, hi));
+    }
+    let port = match value {
+        Value::Number(n) => u16::try_from(n.as_u64()?).ok()?,
+        Value::String(s) => s.parse().ok()?,
+        _ => return None,
+    };
+    Some((port, port))
+}
+
+fn protocol_cond(negate: bool, values: &[Value]) -> Cond {
+    let protocols: Option<Vec<String>> = values
+        .iter()
+        .map(|value| match value {
+            Value::String(s) => Some(s.to_lowercase()),
+            Value::Number(n) => match n.as_u64()? {
+                6 => Some("tcp".to_string()),
+                17 => Some("udp".to_string()),
+                other => Some(other.to_string()),
+            },
+            _ => None,
+        })
+        .collect();
+    match protocols {
+        Some(protocols) => Cond::Protocol { negate, protocols },
+        None => Cond::Opaque,
+    }
+}
+
+/// 展开匿名集合、列表和命名集合（`@name`）
+fn resolve(value: &Value, table: &str, sets: &HashMap<String, Vec<Value>>) -> Vec<Value> {
+    let elems = match value {
+        Value::String(s) if s.starts_with('@') => sets
+            .get(&format!("{} {}", table, &s[1..]))
+            .cloned()
+            .unwrap_or_default(),
+        Value::Array(items) => items.clone(),
+        Value::Object(o) if o.contains_key("set") => {
+            o["set"].as_array().cloned().unwrap_or_default()
+        }
+        other => vec![other.clone()],
+    };
+    elems.iter().map(|e| unwrap_elem(e).clone()).collect()
+}
+
+/// 带超时、计数器的集合元素为 `{"elem": {"val": ...}}`
+fn unwrap_elem(value: &Value) -> &Value {
+    value
+        .get("elem")
+        .and_then(|elem| elem.get("val"))
+        .unwrap_or(value)
+}
+
+/// 将 JSON 语句还原为接近 `nft list ruleset` 的文本
+fn render_statement(stmt: &Value) -> String {
+    let Some((key, body)) = stmt.as_object().and_then(|o| o.iter().next()) else {
+        return stmt.to_string();
+    };
+    match key.as_str() {
+        "match" => {
+            let op = match str_field(body, "op") {
+                "==" | "in" => String::new(),
+                op => format!("{} ", op),
+            };
+            format!(
+                "{} {}{}",
+                render_expr(&body["left"]),
+                op,
+                render_expr(&body["right"])
+            )
+        }
+        "vmap" => format!(
+            "{} vmap {}",
+            render_expr(&body["key"]),
+            render_expr(&body["data"])
+        ),
+        "jump" | "goto" => format!("{} {}", key, str_field(body, "target")),
+        _ => key.clone(),
+    }
+}
+
+fn render_expr(expr: &Value) -> String {
+    match expr {
+        Value::String(s) => s.clone(),
+        Value::Number(n) => n.to_string(),
+        Value::Array(items) => items.iter().map(render_expr).collect::<Vec<_>>().join(","),
+        Value::Object(o) => {
+            if let Some(payload) = o.get("payload") {
+                format!(
+                    "{} {}",
+                    str_field(payload, "protocol"),
+                    str_field(payload, "field")
+                )
+            } else if let Some(meta) = o.get("meta") {
+                match str_field(meta, "key") {
+                    key @ ("iif" | "iifname" | "oif" | "oifname") => key.to_string(),
+                    key => format!("meta {}", key),
+                }
+            } else if let Some(ct) = o.get("ct") {
+                format!("ct {}", str_field(ct, "key"))
+            } else if let Some(set) = o.get("set").and_then(Value::as_array) {
+                let elems: Vec<String> = set
+                    .iter()
+                    .map(|elem| match elem.as_array() {
+                        Some(pair) if pair.len() == 2 => {
+                            format!("{} : {}", render_expr(&pair[0]), render_statement(&pair[1]))
+                        }
+                        _ => render_expr(elem),
+                    })
+                    .collect();
+                format!("{{ {} }}", elems.join(", "))
+            } else if let Some(range) = o.get("range").and_then(Value::as_array) {
+                range.iter().map(render_expr).collect::<Vec<_>>().join("-")
+            } else if let Some(prefix) = o.get("prefix") {
+                format!(
+                    "{}/{}",
+                    render_expr(&prefix["addr"]),
+                    render_expr(&prefix["len"])
+                )
+            } else {
+                expr.to_string()
+            }
+        }
+        other => other.to_string(),
+    }
+}
@@ -0,0 +1,72 @@
+//! 网络接口地址
+
+use std::ffi::CStr;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+use crate::models::{AppError, AppResult, NetworkInterface};
+
+/// 列出已启用接口的 IPv4 / IPv6 地址
+pub fn list_interfaces() -> AppResult<Vec<NetworkInterface>> {
+    let mut head: *mut libc::ifaddrs = std::ptr::null_mut();
+    if unsafe { libc::getifaddrs(&mut head) } != 0 {
+        return Err(AppError::Io(format!(
+            "getifaddrs: {}",
+            std::io::Error::last_os_error()
+        )));
+    }
+
+    let mut interfaces = Vec::new();
+    let mut cursor = head;
+    while !cursor.is_null() {
+        let ifa = unsafe { &*cursor };
+        cursor = ifa.ifa_next;
+
+        if ifa.ifa_addr.is_null() || ifa.ifa_flags & libc::IFF_UP as u32 == 0 {
+            continue;
+        }
+        let Some(addr) = (unsafe { sockaddr_ip(ifa.ifa_addr) }) else {
+            continue;
+        };
+        let prefix_len = if ifa.ifa_netmask.is_null() {
+            0
+        } else {
+            unsafe { sockaddr_ip(ifa.ifa_netmask) }
+                .map(prefix_len)
+                .unwrap_or(0)
+        };
+        let name = unsafe { CStr::from_ptr(ifa.ifa_name) }
+            .to_string_lossy()
+            .into_owned();
+
+        interfaces.push(NetworkInterface {
+            name,
+            addr: addr.to_string(),
+            prefix_len,
+        });
+    }
+
+    unsafe { libc::freeifaddrs(head) };
+    Ok(interfaces)
+}
+
+/// 读取 sockaddr 中的地址，非 IP 地址族返回 None
+unsafe fn sockaddr_ip(addr: *const libc::sockaddr) -> Option<IpAddr> {
+    match (*addr).sa_family as i32 {
+        libc::AF_INET => {
+            let addr = &*(addr as *const libc::sockaddr_in);
+            Some(Ipv4Addr::from(u32::from_be(addr.sin_addr.s_addr)).into())
+        }
+        libc::AF_INET6 => {
+            let addr = &*(addr as *const libc::sockaddr_in6);
+            Some(Ipv6Addr::from(addr.sin6_addr.s6_addr).into())
+        }
+        _ => None,
+    }
+}
+
+fn prefix_len(mask: IpAddr) -> u8 {
+    match mask {
+        IpAddr::V4(v4) => u32::from(v4).count_ones() as u8,
+        IpAddr::V6(v6) => u128::from(v6).count_ones() as u8,
+    }
+}
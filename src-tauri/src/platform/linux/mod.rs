@@ -0,0 +1,68 @@
+//! Linux 平台实现
+
+mod firewall;
+mod interfaces;
+mod netlink;
+mod packages;
+mod proc_net;
+mod process;
+mod users;
+
+use crate::models::{AppResult, PortInfo, ProcessInfo, Protocol, is_protected_process};
+use crate::platform::traits::{PortProvider, ProcessProvider};
+
+pub use firewall::{annotate_firewall, read_ruleset, Family, Packet, Ruleset};
+pub use interfaces::list_interfaces;
+pub use netlink::{ProcConnectorTrigger, SockDiagTrigger};
+pub use packages::package_owner;
+pub use proc_net::{get_tcp_table, get_udp_table};
+pub use process::{get_process_info as linux_get_process_info, kill_process as linux_kill_process, process_uid};
+pub use users::{chown_group, group_id, user_in_group, user_name};
+
+/// Linux 平台实现（基于 procfs）
+pub struct LinuxPlatform;
+
+impl LinuxPlatform {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for LinuxPlatform {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PortProvider for LinuxPlatform {
+    fn get_tcp_connections(&self) -> AppResult<Vec<PortInfo>> {
+        get_tcp_table()
+    }
+
+    fn get_udp_endpoints(&self) -> AppResult<Vec<PortInfo>> {
+        get_udp_table()
+    }
+
+    fn get_port_info(&self, port: u16, protocol: Protocol) -> AppResult<Option<PortInfo>> {
+        let ports = match protocol {
+            Protocol::TCP => self.get_tcp_connections()?,
+            Protocol::UDP => self.get_udp_endpoints()?,
+        };
+
+        Ok(ports.into_iter().find(|p| p.port == port))
+    }
+}
+
+impl ProcessProvider for LinuxPlatform {
+    fn get_process_info(&self, pid: u32) -> AppResult<Option<ProcessInfo>> {
+        linux_get_process_info(pid)
+    }
+
+    fn kill_process(&self, pid: u32, expected_create_time: Option<u64>) -> AppResult<()> {
+        linux_kill_process(pid, expected_create_time)
+    }
+
+    fn is_protected(&self, name: &str) -> bool {
+        is_protected_process(name)
+    }
+}
@@ -0,0 +1,241 @@
+//! 基于 netlink 的事件触发源
+//!
+//! - `SockDiagTrigger`: 订阅 SOCK_DIAG 的套接字销毁多播组
+//! - `ProcConnectorTrigger`: 订阅进程连接器的 exec/exit 事件（需要 CAP_NET_ADMIN）
+//!
+//! 内核只通知套接字销毁，不通知新建；新监听端口主要依靠进程 exec 事件和定时采样发现
+
+use std::io;
+use std::mem;
+use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+use std::thread;
+
+use tokio::sync::mpsc;
+
+use crate::core::trigger::{TriggerReason, TriggerSource};
+use crate::models::{AppError, AppResult};
+
+/// SOCK_DIAG 多播组
+const SKNLGRP_INET_TCP_DESTROY: u32 = 1;
+const SKNLGRP_INET_UDP_DESTROY: u32 = 2;
+const SKNLGRP_INET6_TCP_DESTROY: u32 = 3;
+const SKNLGRP_INET6_UDP_DESTROY: u32 = 4;
+
+/// 进程连接器常量
+const CN_IDX_PROC: u32 = 1;
+const CN_VAL_PROC: u32 = 1;
+const PROC_CN_MCAST_LISTEN: u32 = 1;
+const PROC_EVENT_EXEC: u32 = 0x0000_0002;
+const PROC_EVENT_EXIT: u32 = 0x8000_0000;
+
+/// nlmsghdr 与 cn_msg 的长度
+const NLMSG_HDR_LEN: usize = 16;
+const CN_MSG_LEN: usize = 20;
+
+/// 接收超时，用于定期检查监控是否已停止
+const RECV_TIMEOUT_SECS: libc::time_t = 1;
+
+/// 套接字销毁事件触发源
+pub struct SockDiagTrigger;
+
+impl SockDiagTrigger {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for SockDiagTrigger {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TriggerSource for SockDiagTrigger {
+    fn name(&self) -> &'static str {
+        "sock_diag"
+    }
+
+    fn start(self: Box<Self>, tx: mpsc::Sender<TriggerReason>) -> AppResult<()> {
+        let groups = [
+            SKNLGRP_INET_TCP_DESTROY,
+            SKNLGRP_INET_UDP_DESTROY,
+            SKNLGRP_INET6_TCP_DESTROY,
+            SKNLGRP_INET6_UDP_DESTROY,
+        ]
+        .iter()
+        .fold(0u32, |mask, g| mask | (1 << (g - 1)));
+
+        let fd = open_netlink(libc::NETLINK_SOCK_DIAG, groups, 0)?;
+
+        spawn_reader("sock_diag", fd, tx, |_| Some(TriggerReason::SocketEvent))
+    }
+}
+
+/// 进程 exec/exit 事件触发源
+pub struct ProcConnectorTrigger;
+
+impl ProcConnectorTrigger {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for ProcConnectorTrigger {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TriggerSource for ProcConnectorTrigger {
+    fn name(&self) -> &'static str {
+        "proc_connector"
+    }
+
+    fn start(self: Box<Self>, tx: mpsc::Sender<TriggerReason>) -> AppResult<()> {
+        let pid = std::process::id();
+        let fd = open_netlink(libc::NETLINK_CONNECTOR, CN_IDX_PROC, pid)?;
+
+        // 发送订阅请求: nlmsghdr + cn_msg + op
+        let total_len = NLMSG_HDR_LEN + CN_MSG_LEN + 4;
+        let mut msg = Vec::with_capacity(total_len);
+        msg.extend_from_slice(&(total_len as u32).to_ne_bytes()); // nlmsg_len
+        msg.extend_from_slice(&(libc::NLMSG_DONE as u16).to_ne_bytes()); // nlmsg_type
+        msg.extend_from_slice(&0u16.to_ne_bytes()); // nlmsg_flags
+        msg.extend_from_slice(&0u32.to_ne_bytes()); // nlmsg_seq
+        msg.extend_from_slice(&pid.to_ne_bytes()); // nlmsg_pid
+        msg.extend_from_slice(&CN_IDX_PROC.to_ne_bytes()); // id.idx
+        msg.extend_from_slice(&CN_VAL_PROC.to_ne_bytes()); // id.val
+        msg.extend_from_slice(&0u32.to_ne_bytes()); // seq
+        msg.extend_from_slice(&0u32.to_ne_bytes()); // ack
+        msg.extend_from_slice(&4u16.to_ne_bytes()); // len
+        msg.extend_from_slice(&0u16.to_ne_bytes()); // flags
+        msg.extend_from_slice(&PROC_CN_MCAST_LISTEN.to_ne_bytes());
+
+        let ret = unsafe {
+            libc::send(
+                fd.as_raw_fd(),
+                msg.as_ptr() as *const libc::c_void,
+                msg.len(),
+                0,
+            )
+        };
+        if ret < 0 {
+            return Err(netlink_error("send(PROC_CN_MCAST_LISTEN)"));
+        }
+
+        spawn_reader("proc_connector", fd, tx, |buf| {
+            let offset = NLMSG_HDR_LEN + CN_MSG_LEN;
+            let what = u32::from_ne_bytes(buf.get(offset..offset + 4)?.try_into().ok()?);
+            match what {
+                PROC_EVENT_EXEC | PROC_EVENT_EXIT => Some(TriggerReason::ProcessEvent),
+                _ => None,
+            }
+        })
+    }
+}
+
+/// 创建并绑定 netlink 套接字
+fn open_netlink(protocol: libc::c_int, groups: u32, pid: u32) -> AppResult<OwnedFd> {
+    let raw = unsafe {
+        libc::socket(
+            libc::AF_NETLINK,
+            libc::SOCK_DGRAM | libc::SOCK_CLOEXEC,
+            protocol,
+        )
+    };
+    if raw < 0 {
+        return Err(netlink_error("socket(AF_NETLINK)"));
+    }
+    let fd = unsafe { OwnedFd::from_raw_fd(raw) };
+
+    let mut addr: libc::sockaddr_nl = unsafe { mem::zeroed() };
+    addr.nl_family = libc::AF_NETLINK as libc::sa_family_t;
+    addr.nl_pid = pid;
+    addr.nl_groups = groups;
+
+    let ret = unsafe {
+        libc::bind(
+            fd.as_raw_fd(),
+            &addr as *const libc::sockaddr_nl as *const libc::sockaddr,
+            mem::size_of::<libc::sockaddr_nl>() as libc::socklen_t,
+        )
+    };
+    if ret < 0 {
+        return Err(netlink_error("bind(AF_NETLINK)"));
+    }
+
+    let timeout = libc::timeval {
+        tv_sec: RECV_TIMEOUT_SECS,
+        tv_usec: 0,
+    };
+    unsafe {
+        libc::setsockopt(
+            fd.as_raw_fd(),
+            libc::SOL_SOCKET,
+            libc::SO_RCVTIMEO,
+            &timeout as *const libc::timeval as *const libc::c_void,
+            mem::size_of::<libc::timeval>() as libc::socklen_t,
+        );
+    }
+
+    Ok(fd)
+}
+
+/// 在后台线程中读取 netlink 消息并转换为触发事件
+fn spawn_reader<F>(
+    name: &'static str,
+    fd: OwnedFd,
+    tx: mpsc::Sender<TriggerReason>,
+    classify: F,
+) -> AppResult<()>
+where
+    F: Fn(&[u8]) -> Option<TriggerReason> + Send + 'static,
+{
+    thread::Builder::new()
+        .name(format!("trigger-{}", name))
+        .spawn(move || {
+            let mut buf = vec![0u8; 8192];
+
+            while !tx.is_closed() {
+                let n = unsafe {
+                    libc::recv(
+                        fd.as_raw_fd(),
+                        buf.as_mut_ptr() as *mut libc::c_void,
+                        buf.len(),
+                        0,
+                    )
+                };
+
+                if n < 0 {
+                    let err = io::Error::last_os_error();
+                    match err.kind() {
+                        io::ErrorKind::WouldBlock | io::ErrorKind::Interrupted => continue,
+                        _ => {
+                            tracing::warn!("{} trigger stopped: {}", name, err);
+                            break;
+                        }
+                    }
+                }
+
+                if let Some(reason) = classify(&buf[..n as usize]) {
+                    // 已有未处理的触发时直接丢弃，下一次扫描会覆盖本次变化
+                    if let Err(mpsc::error::TrySendError::Closed(_)) = tx.try_send(reason) {
+                        break;
+                    }
+                }
+            }
+
+            tracing::debug!("{} trigger thread exited", name);
+        })
+        .map_err(|e| AppError::Internal(format!("启动 {} 触发线程失败: {}", name, e)))?;
+
+    Ok(())
+}
+
+fn netlink_error(call: &str) -> AppError {
+    let err = io::Error::last_os_error();
+    match err.raw_os_error() {
+        Some(libc::EPERM) | Some(libc::EACCES) => AppError::AccessDenied,
+        _ => AppError::Internal(format!("{}: {}", call, err)),
+    }
+}
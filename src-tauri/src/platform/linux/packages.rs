@@ -0,0 +1,133 @@
+//! 查询文件所属的软件包（dpkg / rpm）
+
+use std::path::Path;
+use std::process::Command;
+
+use crate::models::{PackageManager, PackageStatus};
+
+/// 单个路径的查询结果
+enum Lookup {
+    Owned {
+        name: String,
+        version: Option<String>,
+    },
+    NotFound,
+    Failed,
+}
+
+/// 查询文件所属的软件包，系统没有 dpkg / rpm 数据库或查询失败时为 Unknown
+pub fn package_owner(path: &str) -> PackageStatus {
+    let Some(manager) = detect_manager() else {
+        return PackageStatus::Unknown;
+    };
+
+    // usrmerge 后 /bin 等目录是 /usr 下的链接，数据库中可能记录的是任一路径
+    let candidates = std::iter::once(path.to_string()).chain(merged_alias(path));
+    for candidate in candidates {
+        let lookup = match manager {
+            PackageManager::Dpkg => dpkg_lookup(&candidate),
+            PackageManager::Rpm => rpm_lookup(&candidate),
+        };
+        match lookup {
+            Lookup::Owned { name, version } => {
+                return PackageStatus::Owned {
+                    manager,
+                    name,
+                    version,
+                }
+            }
+            Lookup::NotFound => continue,
+            Lookup::Failed => return PackageStatus::Unknown,
+        }
+    }
+    PackageStatus::Unowned { manager }
+}
+
+fn detect_manager() -> Option<PackageManager> {
+    if Path::new("/var/lib/dpkg/status").exists() {
+        Some(PackageManager::Dpkg)
+    } else if Path::new("/var/lib/rpm").exists() || Path::new("/usr/lib/sysimage/rpm").exists() {
+        Some(PackageManager::Rpm)
+    } else {
+        None
+    }
+}
+
+/// `/usr/bin/x` 与 `/bin/x` 互为别名
+fn merged_alias(path: &str) -> Option<String> {
+    const MERGED: &[&str] = &["bin", "sbin", "lib", "lib32", "lib64"];
+    let top = |p: &str| {
+        p.strip_prefix('/')
+            .and_then(|rest| rest.split('/').next())
+            .is_some_and(|dir| MERGED.contains(&dir))
+    };
+
+    match path.strip_prefix("/usr") {
+        Some(rest) if top(rest) => Some(rest.to_string()),
+        Some(_) => None,
+        None if top(path) => Some(format!("/usr{}", path)),
+        None => None,
+    }
+}
+
+/// `dpkg-query -S` 输出 `pkg[:arch][, pkg2]: /path`，找不到时退出码为 1
+fn dpkg_lookup(path: &str) -> Lookup {
+    let Ok(output) = Command::new("dpkg-query").args(["-S", path]).output() else {
+        return Lookup::Failed;
+    };
+    match output.status.code() {
+        Some(0) => {}
+        Some(1) => return Lookup::NotFound,
+        _ => return Lookup::Failed,
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let owner = stdout
+        .lines()
+        .filter(|line| !line.starts_with("diversion by"))
+        .filter_map(|line| line.split_once(": "))
+        .find(|(_, file)| *file == path)
+        .and_then(|(packages, _)| packages.split(", ").next())
+        .map(|package| package.split(':').next().unwrap_or(package).to_string());
+
+    match owner {
+        Some(name) => {
+            let version = Command::new("dpkg-query")
+                .args(["-W", "-f=${Version}", &name])
+                .output()
+                .ok()
+                .filter(|output| output.status.success())
+                .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+                .filter(|version| !version.is_empty());
+            Lookup::Owned { name, version }
+        }
+        None => Lookup::NotFound,
+    }
+}
+
+/// `rpm -qf`，不属于任何包时退出码非 0 并输出 `... is not owned by any package`
+fn rpm_lookup(path: &str) -> Lookup {
+    let Ok(output) = Command::new("rpm")
+        .args(["-qf", "--qf", "%{NAME}\\t%{VERSION}-%{RELEASE}\\n", path])
+        .output()
+    else {
+        return Lookup::Failed;
+    };
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    if !output.status.success() {
+        return if stdout.contains("not owned by any package") {
+            Lookup::NotFound
+        } else {
+            Lookup::Failed
+        };
+    }
+
+    match stdout.lines().next().and_then(|line| line.split_once('\t')) {
+        Some((name, version)) => Lookup::Owned {
+            name: name.to_string(),
+            version: Some(version.to_string()),
+        },
+        None => Lookup::Failed,
+    }
+}
@@ -0,0 +1,145 @@
+//! /proc/net 套接字表解析
+
+use std::collections::HashMap;
+use std::fs;
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+use crate::models::{AppError, AppResult, ConnectionState, PortInfo, Protocol};
+
+/// 获取所有 TCP 连接（IPv4 + IPv6）
+pub fn get_tcp_table() -> AppResult<Vec<PortInfo>> {
+    let inodes = socket_inode_owners();
+    let mut results = read_table("/proc/net/tcp", Protocol::TCP, &inodes)?;
+
+    // IPv6 可能被禁用，读取失败时忽略
+    if let Ok(v6) = read_table("/proc/net/tcp6", Protocol::TCP, &inodes) {
+        results.extend(v6);
+    }
+
+    Ok(results)
+}
+
+/// 获取所有 UDP 端点（IPv4 + IPv6）
+pub fn get_udp_table() -> AppResult<Vec<PortInfo>> {
+    let inodes = socket_inode_owners();
+    let mut results = read_table("/proc/net/udp", Protocol::UDP, &inodes)?;
+
+    if let Ok(v6) = read_table("/proc/net/udp6", Protocol::UDP, &inodes) {
+        results.extend(v6);
+    }
+
+    Ok(results)
+}
+
+/// 读取并解析一个套接字表文件
+fn read_table(
+    path: &str,
+    protocol: Protocol,
+    inodes: &HashMap<u64, u32>,
+) -> AppResult<Vec<PortInfo>> {
+    let content = fs::read_to_string(path).map_err(|e| AppError::Io(format!("{}: {}", path, e)))?;
+
+    Ok(content
+        .lines()
+        .skip(1) // 标题行
+        .filter_map(|line| parse_line(line, protocol, inodes))
+        .collect())
+}
+
+/// 解析一行套接字记录
+///
+/// 格式: `sl local_address rem_address st tx_queue:rx_queue tr:tm->when retrnsmt uid timeout inode ...`
+fn parse_line(line: &str, protocol: Protocol, inodes: &HashMap<u64, u32>) -> Option<PortInfo> {
+    let fields: Vec<&str> = line.split_whitespace().collect();
+    if fields.len() < 10 {
+        return None;
+    }
+
+    let (local_addr, local_port) = parse_endpoint(fields[1])?;
+    let (remote_addr, remote_port) = parse_endpoint(fields[2])?;
+    let state = u32::from_str_radix(fields[3], 16).ok()?;
+    let inode: u64 = fields[9].parse().ok()?;
+
+    // 无法访问的进程（非 root 运行时）PID 记为 0
+    let pid = inodes.get(&inode).copied().unwrap_or(0);
+
+    let mut port_info = PortInfo::new(local_port, protocol, local_addr, pid);
+
+    match protocol {
+        Protocol::TCP => {
+            port_info.state = ConnectionState::from_linux_tcp_state(state);
+            port_info.remote_addr = Some(remote_addr);
+            port_info.remote_port = Some(remote_port);
+        }
+        Protocol::UDP => {
+            port_info.state = ConnectionState::Bound;
+        }
+    }
+
+    Some(port_info)
+}
+
+/// 解析 `地址:端口` 形式的十六进制端点
+///
+/// 地址按内核字（主机字节序）输出，端口已是主机字节序
+fn parse_endpoint(s: &str) -> Option<(String, u16)> {
+    let (addr_hex, port_hex) = s.split_once(':')?;
+    let port = u16::from_str_radix(port_hex, 16).ok()?;
+
+    let addr = match addr_hex.len() {
+        8 => {
+            let word = u32::from_str_radix(addr_hex, 16).ok()?;
+            Ipv4Addr::from(word.to_ne_bytes()).to_string()
+        }
+        32 => {
+            let mut octets = [0u8; 16];
+            for (i, chunk) in octets.chunks_mut(4).enumerate() {
+                let word = u32::from_str_radix(&addr_hex[i * 8..i * 8 + 8], 16).ok()?;
+                chunk.copy_from_slice(&word.to_ne_bytes());
+            }
+            format!("[{}]", Ipv6Addr::from(octets))
+        }
+        _ => return None,
+    };
+
+    Some((addr, port))
+}
+
+/// 建立套接字 inode 到进程 ID 的映射
+fn socket_inode_owners() -> HashMap<u64, u32> {
+    let mut owners = HashMap::new();
+
+    let Ok(entries) = fs::read_dir("/proc") else {
+        return owners;
+    };
+
+    for entry in entries.flatten() {
+        let Some(pid) = entry
+            .file_name()
+            .to_str()
+            .and_then(|n| n.parse::<u32>().ok())
+        else {
+            continue;
+        };
+
+        let Ok(fds) = fs::read_dir(entry.path().join("fd")) else {
+            continue;
+        };
+
+        for fd in fds.flatten() {
+            let Ok(target) = fs::read_link(fd.path()) else {
+                continue;
+            };
+            let target = target.to_string_lossy();
+            if let Some(inode) = target
+                .strip_prefix("socket:[")
+                .and_then(|rest| rest.strip_suffix(']'))
+                .and_then(|n| n.parse::<u64>().ok())
+            {
+                owners.entry(inode).or_insert(pid);
+            }
+        }
+    }
+
+    owners
+}
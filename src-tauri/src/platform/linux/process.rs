@@ -0,0 +1,139 @@
+//! 进程操作
+
+use std::fs;
+use std::path::Path;
+
+use crate::models::{is_protected_process, AppError, AppResult, ProcessInfo};
+
+/// 获取进程信息
+pub fn get_process_info(pid: u32) -> AppResult<Option<ProcessInfo>> {
+    // PID 0 表示无法确定占用进程
+    if pid == 0 {
+        return Ok(None);
+    }
+
+    let proc_dir = format!("/proc/{}", pid);
+    if !Path::new(&proc_dir).exists() {
+        return Ok(None);
+    }
+
+    // 可执行文件路径，非本用户进程需要 root 权限
+    let exe_path = fs::read_link(format!("{}/exe", proc_dir))
+        .ok()
+        .map(|p| p.to_string_lossy().into_owned());
+
+    let name = fs::read_to_string(format!("{}/comm", proc_dir))
+        .map(|s| s.trim_end().to_string())
+        .ok()
+        .or_else(|| {
+            exe_path
+                .as_ref()
+                .and_then(|p| Path::new(p).file_name())
+                .map(|n| n.to_string_lossy().into_owned())
+        })
+        .unwrap_or_else(|| format!("<unknown: {}>", pid));
+
+    // 命令行参数以 NUL 分隔
+    let cmd_line = fs::read(format!("{}/cmdline", proc_dir))
+        .ok()
+        .filter(|raw| !raw.is_empty())
+        .map(|raw| {
+            raw.split(|&b| b == 0)
+                .filter(|arg| !arg.is_empty())
+                .map(|arg| String::from_utf8_lossy(arg).into_owned())
+                .collect::<Vec<_>>()
+                .join(" ")
+        });
+
+    Ok(Some(ProcessInfo {
+        pid,
+        name,
+        exe_path,
+        cmd_line,
+        create_time: process_start_time_ms(pid).unwrap_or(0),
+        memory_usage: process_rss_bytes(pid).unwrap_or(0),
+        executable: None,
+    }))
+}
+
+/// 终止进程（带 PID 复用保护）
+pub fn kill_process(pid: u32, expected_create_time: Option<u64>) -> AppResult<()> {
+    let info = get_process_info(pid)?.ok_or(AppError::ProcessNotFound(pid))?;
+
+    // init 进程和名单内的进程不允许终止
+    if pid == 1 || is_protected_process(&info.name) {
+        return Err(AppError::ProtectedProcess(info.name));
+    }
+
+    // PID 复用校验
+    if let Some(expected) = expected_create_time {
+        if info.create_time != 0 && info.create_time != expected {
+            return Err(AppError::PidReused);
+        }
+    }
+
+    let ret = unsafe { libc::kill(pid as libc::pid_t, libc::SIGKILL) };
+    if ret == 0 {
+        return Ok(());
+    }
+
+    match std::io::Error::last_os_error().raw_os_error() {
+        Some(libc::EPERM) => Err(AppError::AccessDenied),
+        Some(libc::ESRCH) => Err(AppError::ProcessNotFound(pid)),
+        code => Err(AppError::Internal(format!(
+            "kill({}) failed: errno {}",
+            pid,
+            code.unwrap_or(0)
+        ))),
+    }
+}
+
+/// 进程的真实用户 ID
+pub fn process_uid(pid: u32) -> Option<u32> {
+    fs::read_to_string(format!("/proc/{}/status", pid))
+        .ok()?
+        .lines()
+        .find_map(|line| line.strip_prefix("Uid:"))
+        .and_then(|v| v.split_whitespace().next())
+        .and_then(|v| v.parse().ok())
+}
+
+/// 进程启动时间（Unix 时间戳，毫秒）
+///
+/// /proc/<pid>/stat 第 22 个字段为自系统启动以来的时钟滴答数
+fn process_start_time_ms(pid: u32) -> Option<u64> {
+    let stat = fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+
+    // 进程名可能包含空格和括号，从最后一个 ')' 之后开始解析
+    let rest = &stat[stat.rfind(')')? + 1..];
+    let start_ticks: u64 = rest.split_whitespace().nth(19)?.parse().ok()?;
+
+    let ticks_per_sec = unsafe { libc::sysconf(libc::_SC_CLK_TCK) };
+    if ticks_per_sec <= 0 {
+        return None;
+    }
+
+    Some(boot_time_secs()? * 1000 + start_ticks * 1000 / ticks_per_sec as u64)
+}
+
+/// 系统启动时间（Unix 时间戳，秒）
+fn boot_time_secs() -> Option<u64> {
+    fs::read_to_string("/proc/stat")
+        .ok()?
+        .lines()
+        .find_map(|line| line.strip_prefix("btime "))
+        .and_then(|v| v.trim().parse().ok())
+}
+
+/// 常驻内存（字节）
+fn process_rss_bytes(pid: u32) -> Option<u64> {
+    let statm = fs::read_to_string(format!("/proc/{}/statm", pid)).ok()?;
+    let resident_pages: u64 = statm.split_whitespace().nth(1)?.parse().ok()?;
+
+    let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) };
+    if page_size <= 0 {
+        return None;
+    }
+
+    Some(resident_pages * page_size as u64)
+}
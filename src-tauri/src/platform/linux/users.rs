@@ -0,0 +1,69 @@
+//! 用户与组
+
+use std::ffi::{CStr, CString};
+
+use crate::models::{AppError, AppResult};
+
+/// 按组名查询组 ID
+pub fn group_id(name: &str) -> Option<u32> {
+    let name = CString::new(name).ok()?;
+    let group = unsafe { libc::getgrnam(name.as_ptr()) };
+    if group.is_null() {
+        return None;
+    }
+    Some(unsafe { (*group).gr_gid })
+}
+
+/// 按用户 ID 查询用户名
+pub fn user_name(uid: u32) -> Option<String> {
+    let user = unsafe { libc::getpwuid(uid) };
+    if user.is_null() {
+        return None;
+    }
+    let name = unsafe { CStr::from_ptr((*user).pw_name) };
+    Some(name.to_string_lossy().into_owned())
+}
+
+/// 用户是否属于指定组（主组或附加组）
+pub fn user_in_group(uid: u32, primary_gid: u32, gid: u32) -> bool {
+    if primary_gid == gid {
+        return true;
+    }
+    let Some(name) = user_name(uid).and_then(|n| CString::new(n).ok()) else {
+        return false;
+    };
+
+    let mut count: libc::c_int = 32;
+    loop {
+        let mut groups = vec![0 as libc::gid_t; count as usize];
+        let capacity = count;
+        let ret = unsafe {
+            libc::getgrouplist(name.as_ptr(), primary_gid, groups.as_mut_ptr(), &mut count)
+        };
+        if ret >= 0 {
+            groups.truncate(count as usize);
+            return groups.contains(&gid);
+        }
+        // 缓冲区不足时 count 被设为所需长度
+        if count <= capacity {
+            return false;
+        }
+    }
+}
+
+/// 修改文件所属组，所有者不变
+pub fn chown_group(path: &str, group: &str) -> AppResult<()> {
+    let gid = group_id(group).ok_or_else(|| AppError::InvalidArgument(format!("组不存在: {}", group)))?;
+    let c_path = CString::new(path).map_err(|e| AppError::InvalidArgument(e.to_string()))?;
+
+    // uid 传 -1 表示不修改所有者
+    let ret = unsafe { libc::chown(c_path.as_ptr(), libc::uid_t::MAX, gid) };
+    if ret != 0 {
+        return Err(AppError::Io(format!(
+            "{}: {}",
+            path,
+            std::io::Error::last_os_error()
+        )));
+    }
+    Ok(())
+}
@@ -2,8 +2,25 @@
 
 pub mod traits;
 
+#[cfg(test)]
+pub mod fake;
+
 #[cfg(windows)]
 pub mod windows;
 
+#[cfg(target_os = "linux")]
+pub mod linux;
+
 #[cfg(windows)]
 pub use windows::WindowsPlatform;
+
+#[cfg(target_os = "linux")]
+pub use linux::LinuxPlatform;
+
+/// 当前平台的实现
+#[cfg(windows)]
+pub type NativePlatform = WindowsPlatform;
+
+/// 当前平台的实现
+#[cfg(target_os = "linux")]
+pub type NativePlatform = LinuxPlatform;
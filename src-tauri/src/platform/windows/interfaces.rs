@@ -0,0 +1,138 @@
+//! 网络接口地址和 DNS 服务器
+
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+use windows::Win32::Foundation::{ERROR_BUFFER_OVERFLOW, NO_ERROR};
+use windows::Win32::NetworkManagement::IpHelper::{
+    GetAdaptersAddresses, GAA_FLAG_SKIP_ANYCAST, GAA_FLAG_SKIP_DNS_SERVER, GAA_FLAG_SKIP_MULTICAST,
+    GAA_FLAG_SKIP_UNICAST, GET_ADAPTERS_ADDRESSES_FLAGS, IP_ADAPTER_ADDRESSES_LH,
+};
+use windows::Win32::NetworkManagement::Ndis::IfOperStatusUp;
+use windows::Win32::Networking::WinSock::{
+    AF_INET, AF_INET6, AF_UNSPEC, SOCKADDR, SOCKADDR_IN, SOCKADDR_IN6,
+};
+
+use crate::models::{AppError, AppResult, NetworkInterface};
+
+/// 列出已启用适配器的 IPv4 / IPv6 单播地址
+pub fn list_interfaces() -> AppResult<Vec<NetworkInterface>> {
+    let flags = GAA_FLAG_SKIP_ANYCAST | GAA_FLAG_SKIP_MULTICAST | GAA_FLAG_SKIP_DNS_SERVER;
+    with_adapters(flags, |head| unsafe { parse_adapters(head) })
+}
+
+/// 已启用适配器配置的 DNS 服务器，去重并保持顺序
+pub fn dns_servers() -> AppResult<Vec<IpAddr>> {
+    let flags = GAA_FLAG_SKIP_ANYCAST | GAA_FLAG_SKIP_MULTICAST | GAA_FLAG_SKIP_UNICAST;
+    with_adapters(flags, |head| unsafe { parse_dns_servers(head) })
+}
+
+/// 获取适配器链表并交给 `parse` 处理
+fn with_adapters<T>(
+    flags: GET_ADAPTERS_ADDRESSES_FLAGS,
+    parse: impl Fn(*const IP_ADAPTER_ADDRESSES_LH) -> T,
+) -> AppResult<T> {
+    // 以 u64 分配保证结构体对齐，首次按 16 KB 尝试
+    let mut size: u32 = 16 * 1024;
+
+    loop {
+        let mut buffer = vec![0u64; (size as usize).div_ceil(8)];
+        let head = buffer.as_mut_ptr() as *mut IP_ADAPTER_ADDRESSES_LH;
+        let ret =
+            unsafe { GetAdaptersAddresses(AF_UNSPEC.0 as u32, flags, None, Some(head), &mut size) };
+
+        if ret == ERROR_BUFFER_OVERFLOW.0 {
+            continue; // 按返回的大小重试
+        }
+        if ret != NO_ERROR.0 {
+            return Err(AppError::WindowsApi(
+                "GetAdaptersAddresses".to_string(),
+                ret,
+            ));
+        }
+
+        return Ok(parse(head));
+    }
+}
+
+unsafe fn parse_dns_servers(head: *const IP_ADAPTER_ADDRESSES_LH) -> Vec<IpAddr> {
+    let mut servers = Vec::new();
+    let mut adapter = head;
+
+    while !adapter.is_null() {
+        let current = &*adapter;
+        adapter = current.Next;
+
+        if current.OperStatus != IfOperStatusUp {
+            continue;
+        }
+
+        let mut dns = current.FirstDnsServerAddress;
+        while !dns.is_null() {
+            let address = &*dns;
+            dns = address.Next;
+
+            let Some(ip) = sockaddr_ip(address.Address.lpSockaddr) else {
+                continue;
+            };
+            // 未配置 IPv6 DNS 时系统会列出已废弃的站点本地地址 fec0:0:0:ffff::1~3
+            if let IpAddr::V6(v6) = ip {
+                if v6.segments()[0] & 0xffc0 == 0xfec0 {
+                    continue;
+                }
+            }
+            if !servers.contains(&ip) {
+                servers.push(ip);
+            }
+        }
+    }
+
+    servers
+}
+
+unsafe fn parse_adapters(head: *const IP_ADAPTER_ADDRESSES_LH) -> Vec<NetworkInterface> {
+    let mut interfaces = Vec::new();
+    let mut adapter = head;
+
+    while !adapter.is_null() {
+        let current = &*adapter;
+        adapter = current.Next;
+
+        if current.OperStatus != IfOperStatusUp {
+            continue;
+        }
+        let name = current.FriendlyName.to_string().unwrap_or_default();
+
+        let mut unicast = current.FirstUnicastAddress;
+        while !unicast.is_null() {
+            let address = &*unicast;
+            unicast = address.Next;
+
+            if let Some(ip) = sockaddr_ip(address.Address.lpSockaddr) {
+                interfaces.push(NetworkInterface {
+                    name: name.clone(),
+                    addr: ip.to_string(),
+                    prefix_len: address.OnLinkPrefixLength,
+                });
+            }
+        }
+    }
+
+    interfaces
+}
+
+unsafe fn sockaddr_ip(addr: *const SOCKADDR) -> Option<IpAddr> {
+    if addr.is_null() {
+        return None;
+    }
+    match (*addr).sa_family {
+        AF_INET => {
+            let addr = &*(addr as *const SOCKADDR_IN);
+            Some(Ipv4Addr::from(u32::from_be(addr.sin_addr.S_un.S_addr)).into())
+        }
+        AF_INET6 => {
+            let addr = &*(addr as *const SOCKADDR_IN6);
+            Some(Ipv6Addr::from(addr.sin6_addr.u.Byte).into())
+        }
+        _ => None,
+    }
+}
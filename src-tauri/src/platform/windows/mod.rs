@@ -3,14 +3,20 @@
 mod tcp_table;
 mod udp_table;
 mod process;
+mod interfaces;
+mod pipe;
+mod token;
 pub mod reserved_ports;
 
 use crate::models::{AppResult, PortInfo, ProcessInfo, Protocol, is_protected_process};
 use crate::platform::traits::{PortProvider, ProcessProvider};
 
+pub use interfaces::{dns_servers, list_interfaces};
+pub use pipe::{create_pipe, current_user_sid};
+pub use token::{pipe_client_identity, process_user_sid, PipeClient};
 pub use tcp_table::get_tcp_table;
 pub use udp_table::get_udp_table;
-pub use process::{get_process_info as win_get_process_info, kill_process as win_kill_process};
+pub use process::{get_process_info as win_get_process_info, kill_process as win_kill_process, is_process_elevated};
 pub use reserved_ports::{ReservedPortRange, get_reserved_tcp_ports, get_reserved_udp_ports, is_tcp_port_reserved};
 
 /// Windows 平台实现
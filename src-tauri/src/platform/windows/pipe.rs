@@ -0,0 +1,59 @@
+//! 带访问控制的命名管道
+
+use std::io;
+
+use tokio::net::windows::named_pipe::{NamedPipeServer, ServerOptions};
+use windows::core::PCWSTR;
+use windows::Win32::Foundation::{CloseHandle, LocalFree, HANDLE, HLOCAL};
+use windows::Win32::Security::Authorization::{
+    ConvertStringSecurityDescriptorToSecurityDescriptorW, SDDL_REVISION_1,
+};
+use windows::Win32::Security::{PSECURITY_DESCRIPTOR, SECURITY_ATTRIBUTES, TOKEN_QUERY};
+use windows::Win32::System::Threading::{GetCurrentProcess, OpenProcessToken};
+
+use super::token::token_user_sid;
+
+/// 使用 SDDL 描述的安全描述符创建命名管道实例
+pub fn create_pipe(endpoint: &str, first: bool, sddl: &str) -> io::Result<NamedPipeServer> {
+    let sddl: Vec<u16> = sddl.encode_utf16().chain(std::iter::once(0)).collect();
+
+    unsafe {
+        let mut descriptor = PSECURITY_DESCRIPTOR::default();
+        ConvertStringSecurityDescriptorToSecurityDescriptorW(
+            PCWSTR(sddl.as_ptr()),
+            SDDL_REVISION_1,
+            &mut descriptor,
+            None,
+        )
+        .map_err(|e| io::Error::other(e.to_string()))?;
+
+        let mut attributes = SECURITY_ATTRIBUTES {
+            nLength: std::mem::size_of::<SECURITY_ATTRIBUTES>() as u32,
+            lpSecurityDescriptor: descriptor.0,
+            bInheritHandle: false.into(),
+        };
+
+        let result = ServerOptions::new()
+            .first_pipe_instance(first)
+            .create_with_security_attributes_raw(
+                endpoint,
+                &mut attributes as *mut SECURITY_ATTRIBUTES as *mut std::ffi::c_void,
+            );
+
+        let _ = LocalFree(HLOCAL(descriptor.0));
+        result
+    }
+}
+
+/// 当前进程用户的 SID 字符串（如 `S-1-5-21-...`）
+pub fn current_user_sid() -> io::Result<String> {
+    unsafe {
+        let mut token = HANDLE::default();
+        OpenProcessToken(GetCurrentProcess(), TOKEN_QUERY, &mut token)
+            .map_err(|e| io::Error::other(e.to_string()))?;
+
+        let sid = token_user_sid(token);
+        let _ = CloseHandle(token);
+        sid
+    }
+}
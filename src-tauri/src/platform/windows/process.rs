@@ -26,6 +26,7 @@ pub fn get_process_info(pid: u32) -> AppResult<Option<ProcessInfo>> {
             cmd_line: None,
             create_time: 0,
             memory_usage: 0,
+            executable: None,
         }));
     }
 
@@ -38,6 +39,7 @@ pub fn get_process_info(pid: u32) -> AppResult<Option<ProcessInfo>> {
             cmd_line: None,
             create_time: 0,
             memory_usage: 0,
+            executable: None,
         }));
     }
 
@@ -59,6 +61,7 @@ pub fn get_process_info(pid: u32) -> AppResult<Option<ProcessInfo>> {
                         cmd_line: None,
                         create_time: 0,
                         memory_usage: 0,
+                        executable: None,
                     }));
                 }
                 if code == 87 {
@@ -145,6 +148,7 @@ unsafe fn get_process_info_from_handle(pid: u32, handle: HANDLE) -> AppResult<Pr
         cmd_line: None, // 需要额外权限，暂不实现
         create_time: create_time_ms,
         memory_usage: memory_usage as u64,
+        executable: None,
     })
 }
 
@@ -193,6 +197,41 @@ pub fn kill_process(pid: u32, expected_create_time: Option<u64>) -> AppResult<()
     }
 }
 
+/// 检查进程是否以管理员身份（提升权限）运行
+pub fn is_process_elevated(pid: u32) -> bool {
+    use windows::Win32::Security::{
+        GetTokenInformation, TokenElevation, TOKEN_ELEVATION, TOKEN_QUERY,
+    };
+    use windows::Win32::System::Threading::OpenProcessToken;
+
+    unsafe {
+        let process = match OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid) {
+            Ok(h) => h,
+            Err(_) => return false,
+        };
+
+        let mut token = HANDLE::default();
+        let opened = OpenProcessToken(process, TOKEN_QUERY, &mut token);
+        let _ = CloseHandle(process);
+        if opened.is_err() {
+            return false;
+        }
+
+        let mut elevation = TOKEN_ELEVATION::default();
+        let mut size = std::mem::size_of::<TOKEN_ELEVATION>() as u32;
+        let result = GetTokenInformation(
+            token,
+            TokenElevation,
+            Some(&mut elevation as *mut _ as *mut _),
+            size,
+            &mut size,
+        );
+        let _ = CloseHandle(token);
+
+        result.is_ok() && elevation.TokenIsElevated != 0
+    }
+}
+
 /// FILETIME 转 Unix 时间戳（毫秒）
 fn filetime_to_unix_ms(ft: FILETIME) -> u64 {
     let ticks = ((ft.dwHighDateTime as u64) << 32) | (ft.dwLowDateTime as u64);
@@ -0,0 +1,112 @@
+//! 访问令牌与命名管道客户端身份
+
+use std::io;
+
+use windows::core::PWSTR;
+use windows::Win32::Foundation::{CloseHandle, LocalFree, HANDLE, HLOCAL};
+use windows::Win32::Security::Authorization::ConvertSidToStringSidW;
+use windows::Win32::Security::{
+    GetTokenInformation, RevertToSelf, TokenElevation, TokenUser, TOKEN_ELEVATION, TOKEN_QUERY,
+    TOKEN_USER,
+};
+use windows::Win32::System::Pipes::ImpersonateNamedPipeClient;
+use windows::Win32::System::Threading::{
+    GetCurrentThread, OpenProcess, OpenProcessToken, OpenThreadToken,
+    PROCESS_QUERY_LIMITED_INFORMATION,
+};
+
+/// 命名管道客户端身份，取自模拟令牌
+#[derive(Debug, Clone)]
+pub struct PipeClient {
+    /// 客户端用户 SID
+    pub sid: String,
+    /// 客户端令牌是否已提升（管理员）
+    pub elevated: bool,
+}
+
+/// 令牌用户的 SID 字符串（如 `S-1-5-21-...`）
+pub fn token_user_sid(token: HANDLE) -> io::Result<String> {
+    unsafe {
+        // 先获取所需长度，再读取 TOKEN_USER（按 u64 分配以满足对齐）
+        let mut size = 0u32;
+        let _ = GetTokenInformation(token, TokenUser, None, 0, &mut size);
+        let mut buffer = vec![0u64; (size as usize).div_ceil(8)];
+        GetTokenInformation(
+            token,
+            TokenUser,
+            Some(buffer.as_mut_ptr() as *mut _),
+            size,
+            &mut size,
+        )
+        .map_err(|e| io::Error::other(e.to_string()))?;
+
+        let user = &*(buffer.as_ptr() as *const TOKEN_USER);
+        let mut sid = PWSTR::null();
+        ConvertSidToStringSidW(user.User.Sid, &mut sid)
+            .map_err(|e| io::Error::other(e.to_string()))?;
+        let text = sid.to_string();
+        let _ = LocalFree(HLOCAL(sid.0 as *mut std::ffi::c_void));
+
+        text.map_err(|e| io::Error::other(e.to_string()))
+    }
+}
+
+/// 令牌是否已提升
+pub fn token_is_elevated(token: HANDLE) -> bool {
+    unsafe {
+        let mut elevation = TOKEN_ELEVATION::default();
+        let mut size = std::mem::size_of::<TOKEN_ELEVATION>() as u32;
+        let result = GetTokenInformation(
+            token,
+            TokenElevation,
+            Some(&mut elevation as *mut _ as *mut _),
+            size,
+            &mut size,
+        );
+        result.is_ok() && elevation.TokenIsElevated != 0
+    }
+}
+
+/// 进程所属用户的 SID
+pub fn process_user_sid(pid: u32) -> io::Result<String> {
+    unsafe {
+        let process = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid)
+            .map_err(|e| io::Error::other(e.to_string()))?;
+
+        let mut token = HANDLE::default();
+        let opened = OpenProcessToken(process, TOKEN_QUERY, &mut token);
+        let _ = CloseHandle(process);
+        opened.map_err(|e| io::Error::other(e.to_string()))?;
+
+        let sid = token_user_sid(token);
+        let _ = CloseHandle(token);
+        sid
+    }
+}
+
+/// 通过模拟客户端获取命名管道客户端的身份
+///
+/// 身份来自客户端的访问令牌而不是进程 ID，不受 PID 复用影响。
+/// 管道中读到客户端写入的数据之后才能模拟，否则返回 ERROR_CANNOT_IMPERSONATE
+pub fn pipe_client_identity(pipe: HANDLE) -> io::Result<PipeClient> {
+    unsafe {
+        ImpersonateNamedPipeClient(pipe).map_err(|e| io::Error::other(e.to_string()))?;
+
+        // 以服务进程自身身份打开线程令牌，之后立即恢复
+        let mut token = HANDLE::default();
+        let opened = OpenThreadToken(GetCurrentThread(), TOKEN_QUERY, true, &mut token);
+        if let Err(e) = RevertToSelf() {
+            // 线程仍以客户端身份运行，继续执行会以错误的权限处理后续请求
+            tracing::error!("RevertToSelf failed after pipe impersonation: {}", e);
+            std::process::abort();
+        }
+        opened.map_err(|e| io::Error::other(e.to_string()))?;
+
+        let client = token_user_sid(token).map(|sid| PipeClient {
+            sid,
+            elevated: token_is_elevated(token),
+        });
+        let _ = CloseHandle(token);
+        client
+    }
+}
@@ -0,0 +1,49 @@
+//! 令牌认证
+
+use axum::extract::{Request, State};
+use axum::http::{header, StatusCode};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+
+use super::ApiContext;
+
+/// 校验请求令牌
+pub async fn require_token(
+    State(ctx): State<ApiContext>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if !is_authorized(&request, &ctx.token) {
+        return (StatusCode::UNAUTHORIZED, "invalid or missing token").into_response();
+    }
+
+    next.run(request).await
+}
+
+/// 检查请求是否携带正确的令牌（Bearer 请求头或 `token` 查询参数）
+pub(crate) fn is_authorized(request: &Request, token: &str) -> bool {
+    let from_header = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "));
+
+    let from_query = request.uri().query().and_then(|q| {
+        q.split('&')
+            .filter_map(|pair| pair.split_once('='))
+            .find(|(k, _)| *k == "token")
+            .map(|(_, v)| v)
+    });
+
+    from_header
+        .or(from_query)
+        .is_some_and(|provided| constant_time_eq(provided.as_bytes(), token.as_bytes()))
+}
+
+/// 长度相同时按字节比较全部内容，避免计时差异泄露令牌
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
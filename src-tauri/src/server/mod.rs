@@ -0,0 +1,160 @@
+//! 本地 REST API 服务
+//!
+//! 仅监听回环地址，所有请求需携带令牌：`Authorization: Bearer <token>`，
+//! 无法设置请求头的客户端（如浏览器 EventSource）可使用 `?token=` 查询参数
+
+mod auth;
+mod routes;
+#[cfg(test)]
+mod tests;
+#[cfg(feature = "websocket")]
+mod ws;
+
+use std::net::{Ipv4Addr, SocketAddr};
+use std::sync::Arc;
+
+use axum::Router;
+use serde::{Deserialize, Serialize};
+use tokio::net::TcpListener;
+use tokio::sync::{broadcast, oneshot};
+
+use crate::core::PortScanner;
+use crate::models::{AppError, AppResult, MonitorEvent};
+
+pub use routes::ApiError;
+#[cfg(feature = "websocket")]
+pub use ws::WebSocketSink;
+
+/// 路由共享状态
+#[derive(Clone)]
+pub struct ApiContext {
+    /// 端口扫描器（测试时可替换为假平台）
+    pub scanner: PortScanner,
+    /// 监控事件广播
+    pub events: broadcast::Sender<MonitorEvent>,
+    /// 守护进程端点，设置后扫描和进程操作优先转发给守护进程
+    pub daemon: Option<Arc<str>>,
+    /// 访问令牌
+    token: Arc<str>,
+}
+
+impl ApiContext {
+    pub fn new(scanner: PortScanner, events: broadcast::Sender<MonitorEvent>, token: &str) -> Self {
+        Self {
+            scanner,
+            events,
+            daemon: None,
+            token: Arc::from(token),
+        }
+    }
+
+    /// 优先使用指定端点的守护进程，守护进程未运行时回退到本进程扫描
+    pub fn with_daemon(mut self, endpoint: &str) -> Self {
+        self.daemon = Some(Arc::from(endpoint));
+        self
+    }
+}
+
+/// API 服务信息
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiServerInfo {
+    /// 监听地址
+    pub addr: String,
+    /// 访问令牌
+    pub token: String,
+}
+
+/// 运行中的 API 服务
+pub struct ApiServer {
+    addr: SocketAddr,
+    token: String,
+    shutdown_tx: oneshot::Sender<()>,
+}
+
+impl ApiServer {
+    /// 在 127.0.0.1 上启动服务，`port` 为 0 时由系统分配端口，未指定令牌时随机生成
+    ///
+    /// 指定 `daemon` 端点时，请求优先转发给守护进程，与 GUI 命令的处理方式一致
+    pub async fn start(
+        scanner: PortScanner,
+        events: broadcast::Sender<MonitorEvent>,
+        daemon: Option<&str>,
+        port: u16,
+        token: Option<String>,
+    ) -> AppResult<Self> {
+        let token = token
+            .filter(|t| !t.is_empty())
+            .unwrap_or_else(generate_token);
+
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, port))
+            .await
+            .map_err(|e| AppError::Internal(format!("API 服务绑定端口失败: {}", e)))?;
+        let addr = listener
+            .local_addr()
+            .map_err(|e| AppError::Internal(e.to_string()))?;
+
+        let mut ctx = ApiContext::new(scanner, events, &token);
+        if let Some(endpoint) = daemon {
+            ctx = ctx.with_daemon(endpoint);
+        }
+        let app = router(ctx);
+        let (shutdown_tx, shutdown_rx) = oneshot::channel::<()>();
+
+        tokio::spawn(async move {
+            let result = axum::serve(listener, app)
+                .with_graceful_shutdown(async {
+                    let _ = shutdown_rx.await;
+                })
+                .await;
+            if let Err(e) = result {
+                tracing::error!("API server error: {}", e);
+            }
+            tracing::info!("API server stopped");
+        });
+
+        tracing::info!("API server listening on {}", addr);
+
+        Ok(Self {
+            addr,
+            token,
+            shutdown_tx,
+        })
+    }
+
+    /// 监听地址
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// 服务信息
+    pub fn info(&self) -> ApiServerInfo {
+        ApiServerInfo {
+            addr: self.addr.to_string(),
+            token: self.token.clone(),
+        }
+    }
+
+    /// 停止服务（正在进行的 SSE 连接会被断开）
+    pub fn stop(self) {
+        let _ = self.shutdown_tx.send(());
+    }
+}
+
+/// 构建路由
+pub fn router(ctx: ApiContext) -> Router {
+    routes::routes()
+        .layer(axum::middleware::from_fn_with_state(
+            ctx.clone(),
+            auth::require_token,
+        ))
+        .with_state(ctx)
+}
+
+/// 生成随机访问令牌（32 位十六进制）
+pub fn generate_token() -> String {
+    use rand::RngCore;
+
+    let mut bytes = [0u8; 16];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
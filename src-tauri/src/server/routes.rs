@@ -0,0 +1,203 @@
+//! API 路由
+
+use std::convert::Infallible;
+use std::time::Duration;
+
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde::Deserialize;
+use tokio_stream::wrappers::BroadcastStream;
+use tokio_stream::{Stream, StreamExt};
+
+use super::ApiContext;
+use crate::core::{metrics, PortFilter};
+use crate::daemon::DaemonClient;
+use crate::models::{AppError, MonitorEvent, PortInfo, ProcessInfo, Protocol};
+
+/// HTTP 错误响应
+pub struct ApiError(pub AppError);
+
+impl From<AppError> for ApiError {
+    fn from(e: AppError) -> Self {
+        Self(e)
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let status = match &self.0 {
+            AppError::ProcessNotFound(_)
+            | AppError::PortNotFound(_)
+            | AppError::SnapshotNotFound(_) => StatusCode::NOT_FOUND,
+            AppError::AccessDenied => StatusCode::FORBIDDEN,
+            AppError::ProtectedProcess(_) | AppError::PidReused => StatusCode::CONFLICT,
+            AppError::InvalidArgument(_) => StatusCode::BAD_REQUEST,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+
+        let body = serde_json::json!({ "error": self.0.to_string() });
+        (status, Json(body)).into_response()
+    }
+}
+
+pub(super) fn routes() -> Router<ApiContext> {
+    let router = Router::new()
+        .route("/ports", get(list_ports))
+        .route("/ports/{port}", get(get_port))
+        .route("/processes/{pid}", get(get_process))
+        .route("/processes/{pid}/kill", post(kill_process))
+        .route("/events", get(events));
+
+    #[cfg(feature = "metrics")]
+    let router = router.route("/metrics", get(metrics_text));
+
+    router
+}
+
+/// 连接配置的守护进程，未配置、未运行或无法连接时返回 None，由本进程处理
+async fn daemon_client(ctx: &ApiContext) -> Option<DaemonClient> {
+    let endpoint = ctx.daemon.as_deref()?;
+    match DaemonClient::connect(endpoint).await {
+        Ok(client) => Some(client),
+        Err(e) => {
+            tracing::debug!("Daemon unavailable, API using in-process backend: {}", e);
+            None
+        }
+    }
+}
+
+/// GET /ports
+async fn list_ports(
+    State(ctx): State<ApiContext>,
+    Query(filter): Query<PortFilter>,
+) -> Result<Json<Vec<PortInfo>>, ApiError> {
+    if let Some(mut client) = daemon_client(&ctx).await {
+        return Ok(Json(client.scan_ports(filter).await?));
+    }
+    Ok(Json(ctx.scanner.scan_filtered(&filter)?))
+}
+
+#[derive(Debug, Deserialize)]
+struct PortQuery {
+    protocol: Option<String>,
+}
+
+/// GET /ports/{port}
+async fn get_port(
+    State(ctx): State<ApiContext>,
+    Path(port): Path<u16>,
+    Query(query): Query<PortQuery>,
+) -> Result<Json<Vec<PortInfo>>, ApiError> {
+    let protocol = match query.protocol {
+        Some(name) => Some(
+            Protocol::from_name(&name)
+                .ok_or_else(|| AppError::InvalidArgument(format!("未知协议: {}", name)))?,
+        ),
+        None => None,
+    };
+
+    let ports = match daemon_client(&ctx).await {
+        Some(mut client) => client.query_port(port, protocol).await?,
+        None => ctx.scanner.query_port(port, protocol)?,
+    };
+    if ports.is_empty() {
+        return Err(AppError::PortNotFound(port).into());
+    }
+    Ok(Json(ports))
+}
+
+/// GET /processes/{pid}
+async fn get_process(
+    State(ctx): State<ApiContext>,
+    Path(pid): Path<u32>,
+) -> Result<Json<ProcessInfo>, ApiError> {
+    let info = match daemon_client(&ctx).await {
+        Some(mut client) => client.get_process_info(pid).await?,
+        None => ctx.scanner.processes().get_process_info(pid)?,
+    };
+    info.map(Json)
+        .ok_or_else(|| AppError::ProcessNotFound(pid).into())
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct KillRequest {
+    /// 进程创建时间，用于 PID 复用保护
+    create_time: Option<u64>,
+}
+
+/// POST /processes/{pid}/kill
+async fn kill_process(
+    State(ctx): State<ApiContext>,
+    Path(pid): Path<u32>,
+    body: Option<Json<KillRequest>>,
+) -> Result<StatusCode, ApiError> {
+    let request = body.map(|Json(b)| b).unwrap_or_default();
+    // 经守护进程结束时由守护进程校验调用方是否有权结束该进程
+    let result = match daemon_client(&ctx).await {
+        Some(mut client) => client.kill_process(pid, request.create_time).await,
+        None => ctx
+            .scanner
+            .processes()
+            .kill_process(pid, request.create_time),
+    };
+    metrics::counters().record_kill(result.is_ok());
+    result?;
+
+    tracing::info!("Process {} killed via API", pid);
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// GET /events，以 SSE 推送监控事件
+///
+/// 事件名与 Tauri 事件一致：`port-snapshot` 和 `port-change`
+async fn events(
+    State(ctx): State<ApiContext>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let stream = BroadcastStream::new(ctx.events.subscribe()).filter_map(|item| {
+        let event = match item {
+            Ok(event) => event,
+            // 客户端过慢导致丢失事件时，由客户端根据 seq 判断是否需要 resync
+            Err(e) => {
+                tracing::warn!("SSE client lagged: {}", e);
+                return None;
+            }
+        };
+
+        let (name, data) = match &event {
+            MonitorEvent::Snapshot(s) => ("port-snapshot", serde_json::to_string(s)),
+            MonitorEvent::Change(c) => ("port-change", serde_json::to_string(c)),
+        };
+
+        data.ok().map(|data| {
+            Ok(Event::default()
+                .event(name)
+                .id(event.seq().to_string())
+                .data(data))
+        })
+    });
+
+    Sse::new(stream).keep_alive(KeepAlive::new().interval(Duration::from_secs(15)))
+}
+
+/// GET /metrics，Prometheus 文本格式
+#[cfg(feature = "metrics")]
+async fn metrics_text(State(ctx): State<ApiContext>) -> Result<Response, ApiError> {
+    let ports = match daemon_client(&ctx).await {
+        Some(mut client) => client.scan_ports(PortFilter::default()).await?,
+        None => ctx.scanner.scan_all()?,
+    };
+    let body = metrics::render(&ports, &metrics::LabelLimits::default());
+
+    Ok((
+        [(
+            axum::http::header::CONTENT_TYPE,
+            "text/plain; version=0.0.4; charset=utf-8",
+        )],
+        body,
+    )
+        .into_response())
+}
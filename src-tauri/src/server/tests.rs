@@ -0,0 +1,270 @@
+//! API 服务集成测试，数据来自假平台
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde_json::Value;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::sync::broadcast;
+use tokio::time::timeout;
+
+use super::*;
+use crate::models::{ChangeEntry, ConnectionState, PortChange, PortInfo, ProcessInfo, Protocol};
+use crate::platform::fake::FakePlatform;
+
+const TOKEN: &str = "test-token";
+
+struct TestServer {
+    server: ApiServer,
+    platform: Arc<FakePlatform>,
+    events: broadcast::Sender<MonitorEvent>,
+}
+
+fn port(port: u16, protocol: Protocol, pid: u32, state: ConnectionState) -> PortInfo {
+    let mut info = PortInfo::new(port, protocol, "127.0.0.1".to_string(), pid);
+    info.state = state;
+    info
+}
+
+async fn start() -> TestServer {
+    let platform = Arc::new(FakePlatform::new());
+    platform.set_ports(vec![
+        port(3000, Protocol::TCP, 42, ConnectionState::Listen),
+        port(5353, Protocol::UDP, 43, ConnectionState::Bound),
+        port(50000, Protocol::TCP, 42, ConnectionState::Established),
+    ]);
+    platform.add_process(ProcessInfo::new(42, "node".to_string()));
+    platform.add_process(ProcessInfo::new(43, "avahi-daemon".to_string()));
+    platform.add_process(ProcessInfo::new(4, "System".to_string()));
+
+    let (events, _) = broadcast::channel(8);
+    let scanner = PortScanner::with_providers(platform.clone(), platform.clone());
+    let server = ApiServer::start(scanner, events.clone(), None, 0, Some(TOKEN.to_string()))
+        .await
+        .unwrap();
+
+    TestServer {
+        server,
+        platform,
+        events,
+    }
+}
+
+/// 发送请求并读取完整响应，返回状态码和响应体
+async fn request(addr: SocketAddr, method: &str, path: &str, token: Option<&str>) -> (u16, String) {
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    let auth = token
+        .map(|t| format!("Authorization: Bearer {}\r\n", t))
+        .unwrap_or_default();
+    let raw = format!(
+        "{} {} HTTP/1.1\r\nHost: localhost\r\n{}Content-Length: 0\r\nConnection: close\r\n\r\n",
+        method, path, auth
+    );
+    stream.write_all(raw.as_bytes()).await.unwrap();
+
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await.unwrap();
+    parse_response(&response)
+}
+
+fn parse_response(response: &str) -> (u16, String) {
+    let (head, body) = response.split_once("\r\n\r\n").unwrap();
+    let status = head
+        .split_whitespace()
+        .nth(1)
+        .and_then(|s| s.parse().ok())
+        .unwrap();
+    (status, body.to_string())
+}
+
+fn json(body: &str) -> Value {
+    serde_json::from_str(body).unwrap()
+}
+
+#[tokio::test]
+async fn test_rejects_missing_or_wrong_token() {
+    let t = start().await;
+    let addr = t.server.addr();
+
+    assert_eq!(request(addr, "GET", "/ports", None).await.0, 401);
+    assert_eq!(request(addr, "GET", "/ports", Some("wrong")).await.0, 401);
+    assert_eq!(
+        request(addr, "GET", "/ports?token=wrong", None).await.0,
+        401
+    );
+    assert_eq!(
+        request(addr, "POST", "/processes/42/kill", None).await.0,
+        401
+    );
+    assert!(t.platform.killed().is_empty());
+
+    let path = format!("/ports?token={}", TOKEN);
+    assert_eq!(request(addr, "GET", &path, None).await.0, 200);
+    t.server.stop();
+}
+
+#[tokio::test]
+async fn test_list_ports() {
+    let t = start().await;
+    let addr = t.server.addr();
+
+    let (status, body) = request(addr, "GET", "/ports", Some(TOKEN)).await;
+    assert_eq!(status, 200);
+    let ports = json(&body);
+    let ports = ports.as_array().unwrap();
+    assert_eq!(ports.len(), 3);
+    assert_eq!(ports[0]["port"], 3000);
+    assert_eq!(ports[0]["process"]["name"], "node");
+
+    let (status, body) = request(addr, "GET", "/ports?listen_only=true", Some(TOKEN)).await;
+    assert_eq!(status, 200);
+    let ports: Vec<u64> = json(&body)
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|p| p["port"].as_u64().unwrap())
+        .collect();
+    assert_eq!(ports, vec![3000, 5353]);
+    t.server.stop();
+}
+
+#[tokio::test]
+async fn test_get_port() {
+    let t = start().await;
+    let addr = t.server.addr();
+
+    let (status, body) = request(addr, "GET", "/ports/3000", Some(TOKEN)).await;
+    assert_eq!(status, 200);
+    let ports = json(&body);
+    assert_eq!(ports.as_array().unwrap().len(), 1);
+    assert_eq!(ports[0]["pid"], 42);
+
+    let (status, _) = request(addr, "GET", "/ports/3000?protocol=udp", Some(TOKEN)).await;
+    assert_eq!(status, 404);
+    let (status, _) = request(addr, "GET", "/ports/3000?protocol=icmp", Some(TOKEN)).await;
+    assert_eq!(status, 400);
+    let (status, body) = request(addr, "GET", "/ports/3001", Some(TOKEN)).await;
+    assert_eq!(status, 404);
+    assert!(json(&body)["error"].is_string());
+    t.server.stop();
+}
+
+#[tokio::test]
+async fn test_get_process() {
+    let t = start().await;
+    let addr = t.server.addr();
+
+    let (status, body) = request(addr, "GET", "/processes/42", Some(TOKEN)).await;
+    assert_eq!(status, 200);
+    assert_eq!(json(&body)["name"], "node");
+
+    let (status, _) = request(addr, "GET", "/processes/7", Some(TOKEN)).await;
+    assert_eq!(status, 404);
+    t.server.stop();
+}
+
+#[tokio::test]
+async fn test_kill_process() {
+    let t = start().await;
+    let addr = t.server.addr();
+
+    let (status, _) = request(addr, "POST", "/processes/42/kill", Some(TOKEN)).await;
+    assert_eq!(status, 204);
+    assert_eq!(t.platform.killed(), vec![42]);
+
+    let (status, _) = request(addr, "GET", "/processes/42", Some(TOKEN)).await;
+    assert_eq!(status, 404);
+    let (status, _) = request(addr, "POST", "/processes/42/kill", Some(TOKEN)).await;
+    assert_eq!(status, 404);
+
+    // 受保护的进程
+    let (status, _) = request(addr, "POST", "/processes/4/kill", Some(TOKEN)).await;
+    assert_eq!(status, 409);
+    assert_eq!(t.platform.killed(), vec![42]);
+    t.server.stop();
+}
+
+#[tokio::test]
+async fn test_unavailable_daemon_falls_back_to_scanner() {
+    let platform = Arc::new(FakePlatform::new());
+    platform.set_ports(vec![port(3000, Protocol::TCP, 42, ConnectionState::Listen)]);
+    platform.add_process(ProcessInfo::new(42, "node".to_string()));
+
+    let endpoint = std::env::temp_dir().join(format!(
+        "port-detection-api-daemon-{}.sock",
+        std::process::id()
+    ));
+    let (events, _) = broadcast::channel(8);
+    let scanner = PortScanner::with_providers(platform.clone(), platform.clone());
+    let server = ApiServer::start(
+        scanner,
+        events,
+        Some(&endpoint.to_string_lossy()),
+        0,
+        Some(TOKEN.to_string()),
+    )
+    .await
+    .unwrap();
+    let addr = server.addr();
+
+    let (status, body) = request(addr, "GET", "/ports", Some(TOKEN)).await;
+    assert_eq!(status, 200);
+    assert_eq!(json(&body)[0]["port"], 3000);
+
+    let (status, _) = request(addr, "POST", "/processes/42/kill", Some(TOKEN)).await;
+    assert_eq!(status, 204);
+    assert_eq!(platform.killed(), vec![42]);
+    server.stop();
+}
+
+#[tokio::test]
+async fn test_events_stream() {
+    let t = start().await;
+    let mut stream = TcpStream::connect(t.server.addr()).await.unwrap();
+    let raw = format!(
+        "GET /events HTTP/1.1\r\nHost: localhost\r\nAuthorization: Bearer {}\r\n\r\n",
+        TOKEN
+    );
+    stream.write_all(raw.as_bytes()).await.unwrap();
+
+    // 响应头返回时已完成订阅
+    let mut received = String::new();
+    let mut buf = [0u8; 4096];
+    while !received.contains("\r\n\r\n") {
+        let n = timeout(Duration::from_secs(2), stream.read(&mut buf))
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(n > 0, "connection closed");
+        received.push_str(&String::from_utf8_lossy(&buf[..n]));
+    }
+    assert!(received.starts_with("HTTP/1.1 200"));
+    assert!(received.contains("text/event-stream"));
+
+    let change = PortChange {
+        seq: 7,
+        timestamp: 1,
+        snapshot_version: 2,
+        entries: vec![ChangeEntry::added(port(
+            8080,
+            Protocol::TCP,
+            42,
+            ConnectionState::Listen,
+        ))],
+    };
+    t.events.send(MonitorEvent::Change(change)).unwrap();
+
+    while !received.contains("\"port\":8080") {
+        let n = timeout(Duration::from_secs(2), stream.read(&mut buf))
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(n > 0, "connection closed");
+        received.push_str(&String::from_utf8_lossy(&buf[..n]));
+    }
+    assert!(received.contains("event: port-change"));
+    assert!(received.contains("id: 7"));
+    t.server.stop();
+}
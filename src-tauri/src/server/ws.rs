@@ -0,0 +1,149 @@
+//! WebSocket 事件输出
+//!
+//! 独立于 REST API 服务，在回环地址上监听，连接时需携带令牌。
+//! 每个监控事件作为一条文本消息发送，内容与 NDJSON 输出的单行相同
+
+use std::net::{Ipv4Addr, SocketAddr};
+use std::sync::Arc;
+
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::{Request, State};
+use axum::http::StatusCode;
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::Router;
+use tokio::sync::{mpsc, oneshot};
+
+use super::auth::is_authorized;
+use super::generate_token;
+use crate::core::sink::{ClientFanout, ClientSubscription, EventSink};
+use crate::models::{AppError, AppResult, MonitorEvent};
+
+#[derive(Clone)]
+struct WsContext {
+    fanout: ClientFanout,
+    token: Arc<str>,
+}
+
+/// WebSocket 输出端
+pub struct WebSocketSink {
+    listener: std::net::TcpListener,
+    addr: SocketAddr,
+    token: String,
+}
+
+impl WebSocketSink {
+    /// 在 127.0.0.1 上绑定端口，`port` 为 0 时由系统分配，未指定令牌时随机生成
+    pub fn bind(port: u16, token: Option<String>) -> AppResult<Self> {
+        let token = token
+            .filter(|t| !t.is_empty())
+            .unwrap_or_else(generate_token);
+
+        let listener = std::net::TcpListener::bind((Ipv4Addr::LOCALHOST, port))
+            .map_err(|e| AppError::Internal(format!("WebSocket 服务绑定端口失败: {}", e)))?;
+        listener
+            .set_nonblocking(true)
+            .map_err(|e| AppError::Internal(e.to_string()))?;
+        let addr = listener
+            .local_addr()
+            .map_err(|e| AppError::Internal(e.to_string()))?;
+
+        Ok(Self {
+            listener,
+            addr,
+            token,
+        })
+    }
+}
+
+impl EventSink for WebSocketSink {
+    fn kind(&self) -> &'static str {
+        "websocket"
+    }
+
+    fn describe(&self) -> String {
+        format!("ws://{}/?token={}", self.addr, self.token)
+    }
+
+    fn start(self: Box<Self>, mut rx: mpsc::Receiver<MonitorEvent>) -> AppResult<()> {
+        let listener = tokio::net::TcpListener::from_std(self.listener)
+            .map_err(|e| AppError::Internal(e.to_string()))?;
+
+        let ctx = WsContext {
+            fanout: ClientFanout::new(),
+            token: Arc::from(self.token.as_str()),
+        };
+        let app = Router::new()
+            .route("/", get(upgrade))
+            .layer(axum::middleware::from_fn_with_state(ctx.clone(), require_token))
+            .with_state(ctx.clone());
+
+        let (shutdown_tx, shutdown_rx) = oneshot::channel::<()>();
+        tokio::spawn(async move {
+            let result = axum::serve(listener, app)
+                .with_graceful_shutdown(async {
+                    let _ = shutdown_rx.await;
+                })
+                .await;
+            if let Err(e) = result {
+                tracing::error!("WebSocket server error: {}", e);
+            }
+        });
+
+        let fanout = ctx.fanout;
+        let addr = self.addr;
+        tokio::spawn(async move {
+            while let Some(event) = rx.recv().await {
+                fanout.publish(event);
+            }
+
+            // 关闭分发后已建立的连接随之结束
+            fanout.close();
+            let _ = shutdown_tx.send(());
+            tracing::info!("WebSocket sink on {} stopped", addr);
+        });
+
+        Ok(())
+    }
+}
+
+async fn require_token(State(ctx): State<WsContext>, request: Request, next: Next) -> Response {
+    if !is_authorized(&request, &ctx.token) {
+        return (StatusCode::UNAUTHORIZED, "invalid or missing token").into_response();
+    }
+    next.run(request).await
+}
+
+async fn upgrade(State(ctx): State<WsContext>, ws: WebSocketUpgrade) -> Response {
+    let subscription = ctx.fanout.subscribe();
+    ws.on_upgrade(move |socket| serve_client(socket, subscription))
+}
+
+/// 向客户端推送事件，客户端发送的消息被忽略，断开或写入失败时退出
+async fn serve_client(mut socket: WebSocket, mut subscription: ClientSubscription) {
+    loop {
+        tokio::select! {
+            event = subscription.next() => {
+                let Some(event) = event else {
+                    let _ = socket.send(Message::Close(None)).await;
+                    break;
+                };
+                let text = match serde_json::to_string(&event) {
+                    Ok(text) => text,
+                    Err(e) => {
+                        tracing::error!("Failed to serialize monitor event: {}", e);
+                        continue;
+                    }
+                };
+                if socket.send(Message::Text(text.into())).await.is_err() {
+                    break;
+                }
+            }
+            incoming = socket.recv() => match incoming {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => {}
+            },
+        }
+    }
+}
@@ -1,3 +1,5 @@
 //! 工具函数
 
 pub mod error;
+pub mod net;
+pub mod time;
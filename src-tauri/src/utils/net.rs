@@ -0,0 +1,10 @@
+//! 地址工具
+
+use std::net::IpAddr;
+
+/// 解析地址，兼容 `[::1]` 和 IPv6 区域标识 `fe80::1%eth0`
+pub fn parse_addr(addr: &str) -> Option<IpAddr> {
+    let addr = addr.trim_start_matches('[').trim_end_matches(']');
+    let addr = addr.split('%').next().unwrap_or(addr);
+    addr.parse().ok()
+}
@@ -21,6 +21,7 @@ export interface PortInfo {
   local_addr: string;
   remote_addr: string | null;
   remote_port: number | null;
+  remote_hostname?: string | null;
   state: string;
   pid: number;
   process: ProcessInfo | null;
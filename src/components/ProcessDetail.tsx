@@ -94,6 +94,17 @@ export function ProcessDetail({ port, onKill, onClose }: ProcessDetailProps) {
                 value={<code className="text-xs font-mono bg-slate-100 px-2 py-0.5 rounded">{port.remote_addr}:{port.remote_port}</code>}
               />
             )}
+            {port.remote_hostname && (
+              <InfoRow
+                icon={
+                  <svg className="w-4 h-4" fill="none" stroke="currentColor" viewBox="0 0 24 24">
+                    <path strokeLinecap="round" strokeLinejoin="round" strokeWidth={2} d="M7 7h.01M7 3h5c.512 0 1.024.195 1.414.586l7 7a2 2 0 010 2.828l-7 7a2 2 0 01-2.828 0l-7-7A1.994 1.994 0 013 12V7a4 4 0 014-4z" />
+                  </svg>
+                }
+                label="远程主机名"
+                value={<span className="font-mono text-xs break-all">{port.remote_hostname}</span>}
+              />
+            )}
             <InfoRow
               icon={
                 <svg className="w-4 h-4" fill="none" stroke="currentColor" viewBox="0 0 24 24">